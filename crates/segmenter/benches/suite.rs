@@ -0,0 +1,144 @@
+//! The shared yardstick for performance work: tokenization throughput on short, medium
+//! and long generated texts and on an article-length one, on kana-only texts with and
+//! without the lookup miss cache, on a sample of real sentences and on a long English
+//! prefix before Japanese text, dictionary load time, and path finding on a prebuilt
+//! lattice.
+//!
+//! Run with `cargo bench -p segmenter --bench suite`. The texts come from
+//! `tests/common/corpus.rs` with fixed seeds, and the dictionary is the small one
+//! checked in under `benches/data`, so runs are comparable and need no JMdict download.
+
+#[path = "../tests/common/corpus.rs"]
+mod corpus;
+
+use segmenter::dictionary::Dictionary;
+use segmenter::lattice::Lattice;
+use segmenter::tokenizer::{Tokenizer, TokenizerOptions};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+const SEED: u64 = 0x9E37_79B9_7F4A_7C15;
+const RUNS: usize = 5;
+
+/// Fastest of [`RUNS`] timings of `f`.
+fn fastest(mut f: impl FnMut()) -> Duration {
+    (0..RUNS)
+        .map(|_| {
+            let start = Instant::now();
+            f();
+            start.elapsed()
+        })
+        .min()
+        .unwrap()
+}
+
+/// Prints the time to tokenize every text of `texts`, and the throughput.
+fn throughput(name: &str, tokenizer: &Tokenizer, texts: &[String]) {
+    let bytes: usize = texts.iter().map(String::len).sum();
+    let elapsed = fastest(|| {
+        for text in texts {
+            std::hint::black_box(tokenizer.tokenize(text));
+        }
+    });
+    println!(
+        "tokenize {name}: {} texts in {:.2} ms, {:.2} MB/s",
+        texts.len(),
+        elapsed.as_secs_f64() * 1e3,
+        bytes as f64 / elapsed.as_secs_f64() / 1e6
+    );
+}
+
+fn main() {
+    let dictionary = corpus::bench_dictionary();
+    let bytes = {
+        let mut bytes = Vec::new();
+        dictionary.write_to(&mut bytes).unwrap();
+        bytes
+    };
+    let elapsed = fastest(|| {
+        std::hint::black_box(Dictionary::from_bytes(&bytes).unwrap());
+    });
+    println!(
+        "dictionary load: {} bytes in {:.3} ms",
+        bytes.len(),
+        elapsed.as_secs_f64() * 1e3
+    );
+
+    let tokenizer = Tokenizer::new(Arc::new(dictionary));
+    for (name, length, count) in [
+        ("short", 20, 5000),
+        ("medium", 200, 500),
+        ("long", 2000, 50),
+    ] {
+        let texts: Vec<String> = (0..count as u64)
+            .map(|i| corpus::generate_text(SEED + i, length))
+            .collect();
+        throughput(&format!("{name} ({length} chars)"), &tokenizer, &texts);
+    }
+    let sample: Vec<String> = corpus::sample_sentences()
+        .into_iter()
+        .map(str::to_string)
+        .collect();
+    throughput("sample sentences", &tokenizer, &sample);
+
+    // A news article as one paragraph, for the costs that grow faster than the text, also
+    // through the iterator and the checked tokenization, which make their own tokens.
+    let article = corpus::generate_text(SEED, 5000);
+    throughput(
+        "article (5000 chars)",
+        &tokenizer,
+        std::slice::from_ref(&article),
+    );
+    let elapsed = fastest(|| {
+        std::hint::black_box(tokenizer.tokenize_iter(&article).rev().count());
+        std::hint::black_box(tokenizer.tokenize_checked(&article).unwrap());
+    });
+    println!(
+        "tokenize_iter and tokenize_checked article: {} bytes in {:.2} ms",
+        article.len(),
+        elapsed.as_secs_f64() * 1e3
+    );
+
+    // Kana-only text misses the dictionary at most spans, and the same misses come back
+    // from every start position.
+    let kana_texts: Vec<String> = (0..500)
+        .map(|i| corpus::generate_kana_text(SEED + i, 200))
+        .collect();
+    for cache_lookup_misses in [true, false] {
+        let options = TokenizerOptions {
+            cache_lookup_misses,
+            ..TokenizerOptions::default()
+        };
+        let tokenizer = Tokenizer::with_options(tokenizer.dictionary(), options);
+        let name = if cache_lookup_misses {
+            "kana (200 chars)"
+        } else {
+            "kana (200 chars, misses not cached)"
+        };
+        throughput(name, &tokenizer, &kana_texts);
+    }
+
+    // Kilobytes of English before the Japanese, as in scraped pages, which the
+    // candidate generation scans past instead of looking up every position.
+    let english = "The quick brown fox jumps over the lazy dog, again and again. ";
+    let prefixed = format!(
+        "{}{}",
+        english.repeat(50_000 / english.len()),
+        corpus::generate_text(SEED, 500)
+    );
+    throughput("50KB English prefix", &tokenizer, &[prefixed]);
+
+    let snapshot = tokenizer.export_lattice(&corpus::generate_text(SEED, 2000));
+    let mut lattice = Lattice::new(snapshot.nodes.len(), snapshot.text.chars().count());
+    for snapshot_node in snapshot.nodes.iter() {
+        lattice.add_node(snapshot_node.node);
+    }
+    let elapsed = fastest(|| {
+        std::hint::black_box(lattice.find_path().score());
+    });
+    println!(
+        "find_path: {} nodes over 2000 chars in {:.3} ms",
+        snapshot.nodes.len(),
+        elapsed.as_secs_f64() * 1e3
+    );
+}
@@ -0,0 +1,173 @@
+//! Tokenization throughput over a generated 10k-sentence corpus.
+//!
+//! Run with `cargo bench -p segmenter --bench tokenize`, and again with
+//! `--features fast-hash` to compare the dictionary map hashers. The corpus and the
+//! dictionary are generated from a fixed seed, so runs are comparable.
+
+use segmenter::dictionary::{
+    Dictionary, DictionaryBuilder, FxHasher, PartOfSpeech, SurfaceTrie, Tag,
+};
+use segmenter::tokenizer::Tokenizer;
+use std::collections::hash_map::RandomState;
+use std::collections::HashMap;
+use std::hash::BuildHasherDefault;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+const SENTENCES: usize = 10_000;
+const WORDS: usize = 50_000;
+const PARTICLES: &[&str] = &["は", "が", "を", "に", "で", "と", "の", "も"];
+
+/// Deterministic xorshift generator, so every run gets the same corpus.
+struct Rng(u64);
+
+impl Rng {
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    fn below(&mut self, bound: usize) -> usize {
+        (self.next() % bound as u64) as usize
+    }
+}
+
+/// Words of one to four kanji followed by up to two hiragana, like 勉強する.
+fn words(rng: &mut Rng) -> Vec<String> {
+    (0..WORDS)
+        .map(|_| {
+            let mut word = String::new();
+            for _ in 0..1 + rng.below(4) {
+                word.push(char::from_u32(0x4E00 + rng.below(3000) as u32).unwrap());
+            }
+            for _ in 0..rng.below(3) {
+                word.push(char::from_u32(0x3042 + rng.below(80) as u32).unwrap());
+            }
+            word
+        })
+        .collect()
+}
+
+fn dictionary(words: &[String]) -> Dictionary {
+    let mut builder = DictionaryBuilder::new();
+    for word in words {
+        builder.add(Some(word), "よみ", PartOfSpeech::NOUN, Tag::NONE);
+    }
+    for particle in PARTICLES {
+        builder.add(None, particle, PartOfSpeech::PARTICLE, Tag::NONE);
+    }
+    builder.build()
+}
+
+/// Sentences of four to twelve words, each followed by a particle, ending in 。.
+fn corpus(rng: &mut Rng, words: &[String]) -> Vec<String> {
+    (0..SENTENCES)
+        .map(|_| {
+            let mut sentence = String::new();
+            for _ in 0..4 + rng.below(9) {
+                sentence.push_str(&words[rng.below(words.len())]);
+                sentence.push_str(PARTICLES[rng.below(PARTICLES.len())]);
+            }
+            sentence.push('。');
+            sentence
+        })
+        .collect()
+}
+
+/// Fastest of `runs` timings of `f`.
+fn fastest(runs: usize, mut f: impl FnMut()) -> Duration {
+    (0..runs)
+        .map(|_| {
+            let start = Instant::now();
+            f();
+            start.elapsed()
+        })
+        .min()
+        .unwrap()
+}
+
+/// Time to look up every substring of up to eight characters of the corpus in a map
+/// with the given hasher.
+fn substring_lookups<S: std::hash::BuildHasher + Default>(
+    words: &[String],
+    corpus: &[String],
+) -> Duration {
+    let map: HashMap<String, usize, S> = words
+        .iter()
+        .enumerate()
+        .map(|(index, word)| (word.clone(), index))
+        .collect();
+    let offsets: Vec<Vec<usize>> = corpus
+        .iter()
+        .map(|sentence| {
+            let offsets = sentence.char_indices().map(|(offset, _)| offset);
+            offsets.chain(std::iter::once(sentence.len())).collect()
+        })
+        .collect();
+
+    fastest(5, || {
+        let mut hits = 0;
+        for (sentence, offsets) in corpus.iter().zip(offsets.iter()) {
+            for (i, start) in offsets.iter().enumerate() {
+                for end in offsets.iter().skip(i + 1).take(8) {
+                    hits += map.contains_key(&sentence[*start..*end]) as usize;
+                }
+            }
+        }
+        std::hint::black_box(hits);
+    })
+}
+
+/// Time to find the same words as [`substring_lookups`] with one walk of a
+/// [`SurfaceTrie`] per start.
+fn prefix_searches(words: &[String], corpus: &[String]) -> Duration {
+    let trie = SurfaceTrie::new(words);
+
+    fastest(5, || {
+        let mut hits = 0;
+        for sentence in corpus.iter() {
+            for (start, _) in sentence.char_indices() {
+                hits += trie.common_prefixes(&sentence[start..]).count();
+            }
+        }
+        std::hint::black_box(hits);
+    })
+}
+
+fn main() {
+    let mut rng = Rng(0x9E37_79B9_7F4A_7C15);
+    let words = words(&mut rng);
+    let corpus = corpus(&mut rng, &words);
+    let bytes: usize = corpus.iter().map(String::len).sum();
+    let tokenizer = Tokenizer::new(Arc::new(dictionary(&words)));
+
+    let hasher = if cfg!(feature = "fast-hash") {
+        "FxHash"
+    } else {
+        "SipHash"
+    };
+    let elapsed = fastest(5, || {
+        for sentence in corpus.iter() {
+            std::hint::black_box(tokenizer.tokenize(sentence));
+        }
+    });
+    println!(
+        "tokenize ({hasher} maps): {SENTENCES} sentences in {:.1} ms, {:.0} sentences/s, \
+         {:.2} MB/s",
+        elapsed.as_secs_f64() * 1e3,
+        SENTENCES as f64 / elapsed.as_secs_f64(),
+        bytes as f64 / elapsed.as_secs_f64() / 1e6
+    );
+
+    let sip = substring_lookups::<RandomState>(&words, &corpus);
+    let fx = substring_lookups::<BuildHasherDefault<FxHasher>>(&words, &corpus);
+    let trie = prefix_searches(&words, &corpus);
+    println!(
+        "substring lookups: SipHash {:.1} ms, FxHash {:.1} ms, surface trie {:.1} ms",
+        sip.as_secs_f64() * 1e3,
+        fx.as_secs_f64() * 1e3,
+        trie.as_secs_f64() * 1e3
+    );
+}
@@ -0,0 +1,52 @@
+//! Prints a sentence as HTML with `<ruby>` readings above its kanji.
+//!
+//! ```text
+//! cargo run --example furigana -- 今日は朝から雨が降っている。
+//! cargo run --example furigana -- --dict dictionary_test_sg_jp.bin 東京に行く
+//! ```
+//!
+//! Without `--dict` the small dictionary checked in under `benches/data` is used.
+
+#[path = "../tests/common/corpus.rs"]
+mod corpus;
+
+use segmenter::dictionary::Dictionary;
+use segmenter::ruby::RubyRenderer;
+use segmenter::tokenizer::Tokenizer;
+use std::sync::Arc;
+
+/// The dictionary saved at `path`, or the checked-in one without a path.
+fn dictionary(path: Option<&str>) -> Dictionary {
+    let Some(path) = path else {
+        return corpus::bench_dictionary();
+    };
+    Dictionary::load(path).unwrap_or_else(|error| {
+        eprintln!("{path}: {error}");
+        std::process::exit(1);
+    })
+}
+
+fn main() {
+    let mut args: Vec<String> = std::env::args().skip(1).collect();
+    let path = match args.iter().position(|arg| arg == "--dict") {
+        Some(index) if index + 1 < args.len() => {
+            Some(args.drain(index..=index + 1).nth(1))
+        }
+        Some(_) => {
+            eprintln!("--dict expects a dictionary file");
+            std::process::exit(2);
+        }
+        None => None,
+    };
+    if args.is_empty() {
+        eprintln!("usage: furigana [--dict <dictionary.bin>] <text>...");
+        std::process::exit(2);
+    }
+
+    let dictionary = Arc::new(dictionary(path.flatten().as_deref()));
+    let tokenizer = Tokenizer::new(dictionary.clone());
+    let renderer = RubyRenderer::new(&dictionary);
+    for text in args {
+        println!("{}", renderer.html(&tokenizer.tokenize(&text)));
+    }
+}
@@ -0,0 +1,64 @@
+//! Prints the lattice of a sentence as a Graphviz DOT graph: a vertex per character
+//! boundary and an edge per candidate, labeled with its surface and score, with the
+//! chosen path drawn bold.
+//!
+//! ```text
+//! cargo run --example inspect_lattice -- 今日は朝から雨が降っている。 | dot -Tsvg > lattice.svg
+//! ```
+//!
+//! The small dictionary checked in under `benches/data` is used.
+
+#[path = "../tests/common/corpus.rs"]
+mod corpus;
+
+use segmenter::candidate::CandidateSource;
+use segmenter::lattice::LatticeSnapshot;
+use segmenter::tokenizer::Tokenizer;
+
+/// `text` quoted for a DOT string.
+fn quote(text: &str) -> String {
+    format!("\"{}\"", text.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+/// The DOT graph of `snapshot`.
+fn dot(snapshot: &LatticeSnapshot) -> String {
+    let mut output = String::from("digraph lattice {\n    rankdir=LR;\n");
+    let chars: Vec<char> = snapshot.text.chars().collect();
+    for position in 0..=chars.len() {
+        output.push_str(&format!("    {position} [shape=circle];\n"));
+    }
+    for (id, snapshot_node) in snapshot.nodes.iter().enumerate() {
+        let node = &snapshot_node.node;
+        let source = match snapshot_node.source {
+            CandidateSource::Dictionary(_) => "dictionary",
+            CandidateSource::Unknown => "unknown",
+            CandidateSource::FunctionWord => "function word",
+            CandidateSource::Emphasis => "emphasis",
+        };
+        let label = format!("{} ({source}, {:.1})", snapshot_node.surface, node.score);
+        let style = if snapshot.path.contains(&id) {
+            ", style=bold, color=red"
+        } else {
+            ""
+        };
+        output.push_str(&format!(
+            "    {} -> {} [label={}{style}];\n",
+            node.start,
+            node.end,
+            quote(&label)
+        ));
+    }
+    output.push_str("}\n");
+
+    output
+}
+
+fn main() {
+    let Some(text) = std::env::args().nth(1) else {
+        eprintln!("usage: inspect_lattice <text>");
+        std::process::exit(2);
+    };
+
+    let tokenizer = Tokenizer::new(corpus::bench_dictionary().into());
+    print!("{}", dot(&tokenizer.export_lattice(&text)));
+}
@@ -0,0 +1,48 @@
+//! Segments standard input line by line and prints every line in wakati-gaki, its
+//! tokens separated by spaces.
+//!
+//! ```text
+//! echo 今日は朝から雨が降っている。 | cargo run --example segment_stdin
+//! cargo run --example segment_stdin -- dictionary_test_sg_jp.bin < input.txt
+//! ```
+//!
+//! Without a dictionary file the small dictionary checked in under `benches/data` is
+//! used, so the example runs without a JMdict build.
+
+#[path = "../tests/common/corpus.rs"]
+mod corpus;
+
+use segmenter::dictionary::Dictionary;
+use segmenter::format;
+use segmenter::tokenizer::Tokenizer;
+use std::io::{BufRead, Write};
+
+/// The dictionary saved at `path`, or the checked-in one without a path.
+fn dictionary(path: Option<&str>) -> Dictionary {
+    let Some(path) = path else {
+        return corpus::bench_dictionary();
+    };
+    Dictionary::load(path).unwrap_or_else(|error| {
+        eprintln!("{path}: {error}");
+        std::process::exit(1);
+    })
+}
+
+fn main() {
+    let path = std::env::args().nth(1);
+    let tokenizer = Tokenizer::new(dictionary(path.as_deref()).into());
+
+    let stdout = std::io::stdout();
+    let mut output = stdout.lock();
+    for line in std::io::stdin().lock().lines() {
+        let line = line.unwrap_or_else(|error| {
+            eprintln!("stdin: {error}");
+            std::process::exit(1);
+        });
+        let tokens = tokenizer.tokenize(&line);
+        if writeln!(output, "{}", format::wakati(&tokens)).is_err() {
+            // The reader went away, like `head` does.
+            return;
+        }
+    }
+}
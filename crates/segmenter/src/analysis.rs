@@ -0,0 +1,202 @@
+use crate::{
+    dictionary::{
+        Dialect, Dictionary, DictionaryEntry, DictionaryMetadata, Domain, Fnv1a,
+        InflectionType, PartOfSpeech, Tag, TermEntry,
+    },
+    tokenizer::{Token, TokenKind},
+};
+
+/// Version of the [`OwnedToken`] and [`AnalysisResult`] layout. Bumped whenever a field
+/// is added, removed or changes meaning, so stored results from an older layout are
+/// recognized instead of misread.
+pub const SCHEMA_VERSION: u32 = 4;
+
+/// A [`Token`] that owns its surface and carries its dictionary information, for
+/// results that outlive the input text or get stored.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct OwnedToken {
+    pub surface: String,
+    /// Character range of the token in the analyzed text.
+    pub char_start: usize,
+    pub char_end: usize,
+    /// Byte range of the token in the analyzed text.
+    pub byte_start: usize,
+    pub byte_end: usize,
+    pub term_id: Option<u32>,
+    pub kind: TokenKind,
+    pub pos: PartOfSpeech,
+    /// Tags of the dictionary entry, empty for unknown tokens.
+    pub tag: Tag,
+    /// Dialects of the dictionary entry, empty for standard words and unknown tokens.
+    pub dialect: Dialect,
+    /// Fields the dictionary entry is used in, empty for general words and unknown
+    /// tokens.
+    pub domain: Domain,
+    /// How the surface inflects the dictionary entry, `None` for unknown tokens.
+    pub inflection: Option<InflectionType>,
+    /// See [`Token::repeat_count`].
+    pub repeat_count: Option<usize>,
+    pub components: Option<Vec<OwnedToken>>,
+}
+
+/// Converts a token, looking up its entry's tags in the dictionary it was produced with.
+impl From<(&Token<'_>, &Dictionary)> for OwnedToken {
+    fn from((token, dictionary): (&Token<'_>, &Dictionary)) -> Self {
+        Self::resolved(token, &|term_entry| {
+            dictionary.entries.get(term_entry.entry_index as usize)
+        })
+    }
+}
+
+impl OwnedToken {
+    /// Converts a token, looking up the entries of it and its components with `entry`,
+    /// for tokens of several dictionaries.
+    pub(crate) fn resolved<'d>(
+        token: &Token<'_>,
+        entry: &dyn Fn(&TermEntry) -> Option<&'d DictionaryEntry>,
+    ) -> Self {
+        let entry_of_token = token.term_entry.as_ref().and_then(entry);
+
+        Self {
+            surface: token.token.to_string(),
+            char_start: token.char_start,
+            char_end: token.char_start + token.token.chars().count(),
+            byte_start: token.byte_start,
+            byte_end: token.byte_start + token.token.len(),
+            term_id: token.term_id,
+            kind: token.kind,
+            pos: token.pos,
+            tag: entry_of_token.map_or(Tag::NONE, |entry| entry.tag),
+            dialect: entry_of_token.map_or(Dialect::NONE, |entry| entry.dialect),
+            domain: entry_of_token.map_or(Domain::NONE, |entry| entry.domain),
+            inflection: token
+                .term_entry
+                .map(|term_entry| term_entry.inflection_type),
+            repeat_count: token.repeat_count,
+            components: token.components.as_ref().map(|components| {
+                components
+                    .iter()
+                    .map(|component| Self::resolved(component, entry))
+                    .collect()
+            }),
+        }
+    }
+}
+
+/// `tokens` as a table with a line per token: its surface, part of speech, tags, dialects
+/// and domains, separated by tabs. Flag sets are written as the pipe-separated names
+/// their `FromStr` reads back, and left empty when none is set.
+pub fn tsv(tokens: &[OwnedToken]) -> String {
+    let mut output = String::new();
+    for token in tokens {
+        let columns = [
+            token.surface.clone(),
+            token.pos.names().join("|"),
+            token.tag.names().join("|"),
+            token.dialect.names().join("|"),
+            token.domain.names().join("|"),
+        ];
+        output.push_str(&columns.join("\t"));
+        output.push('\n');
+    }
+
+    output
+}
+
+/// Hash identifying the analyzed text of an [`AnalysisResult`].
+pub fn text_hash(text: &str) -> u64 {
+    let mut hash = Fnv1a::new();
+    hash.write(text.as_bytes());
+    hash.finish()
+}
+
+/// Tokens of one text together with what is needed to tell whether they are still valid.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AnalysisResult {
+    /// [`SCHEMA_VERSION`] at the time the result was produced.
+    pub schema_version: u32,
+    /// [`text_hash`] of the analyzed text.
+    pub text_hash: u64,
+    pub tokens: Vec<OwnedToken>,
+    /// [`Dictionary::metadata`] of the dictionary the tokens came from.
+    pub dictionary_metadata: DictionaryMetadata,
+}
+
+impl AnalysisResult {
+    /// Pass the same `dictionary_metadata` for every result of one dictionary.
+    pub fn new(
+        text: &str,
+        tokens: &[Token],
+        dictionary: &Dictionary,
+        dictionary_metadata: DictionaryMetadata,
+    ) -> Self {
+        Self {
+            schema_version: SCHEMA_VERSION,
+            text_hash: text_hash(text),
+            tokens: tokens
+                .iter()
+                .map(|token| OwnedToken::from((token, dictionary)))
+                .collect(),
+            dictionary_metadata,
+        }
+    }
+
+    /// Checks that the result has the current layout and was produced from `text` with
+    /// the dictionary described by `dictionary_metadata`.
+    pub fn validate(
+        &self,
+        text: &str,
+        dictionary_metadata: &DictionaryMetadata,
+    ) -> Result<(), AnalysisMismatch> {
+        if self.schema_version != SCHEMA_VERSION {
+            return Err(AnalysisMismatch::SchemaVersion(self.schema_version));
+        }
+        if self.text_hash != text_hash(text) {
+            return Err(AnalysisMismatch::Text);
+        }
+        if self.dictionary_metadata != *dictionary_metadata {
+            return Err(AnalysisMismatch::Dictionary {
+                stored: self.dictionary_metadata,
+                current: *dictionary_metadata,
+            });
+        }
+
+        Ok(())
+    }
+}
+
+/// Why a stored [`AnalysisResult`] no longer applies.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum AnalysisMismatch {
+    /// The result was stored with another [`SCHEMA_VERSION`].
+    SchemaVersion(u32),
+    /// The result belongs to a different text.
+    Text,
+    /// The result was produced with a different dictionary.
+    Dictionary {
+        stored: DictionaryMetadata,
+        current: DictionaryMetadata,
+    },
+}
+
+impl std::fmt::Display for AnalysisMismatch {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::SchemaVersion(version) => write!(
+                f,
+                "analysis schema version {version} is not the supported version {SCHEMA_VERSION}"
+            ),
+            Self::Text => write!(f, "analysis belongs to a different text"),
+            Self::Dictionary { stored, current } => write!(
+                f,
+                "analysis was produced with dictionary {:016x} ({} entries), not {:016x} ({} entries)",
+                stored.fingerprint, stored.entry_count, current.fingerprint, current.entry_count
+            ),
+        }
+    }
+}
+
+impl std::error::Error for AnalysisMismatch {}
@@ -0,0 +1,246 @@
+//! A cache of tokenization results, for services that tokenize the same strings over
+//! and over, like menu labels and canned phrases.
+
+use crate::analysis::OwnedToken;
+use crate::dictionary::{Dictionary, DictionaryId, Fnv1a};
+use crate::tokenizer::Tokenizer;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+
+/// Number of shards of a cache holding at least [`MIN_SHARDED_CAPACITY`] results.
+const SHARDS: usize = 16;
+
+/// Smallest capacity split into [`SHARDS`] shards. Smaller caches keep one, so their
+/// least recently used order is exact.
+const MIN_SHARDED_CAPACITY: usize = 256;
+
+/// What a result is cached under: hashes of the text, of the tokenizer options and of
+/// the dictionaries it was tokenized with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+struct Key {
+    text: u64,
+    options: u64,
+    dictionaries: u64,
+}
+
+///
+struct Entry {
+    /// The text itself, as two texts may share a hash.
+    text: String,
+    tokens: Arc<[OwnedToken]>,
+    /// When the entry was last used, its key in [`Shard::recency`].
+    used: u64,
+}
+
+/// The dictionaries last tokenized with, and their hash. Holding on to them keeps new
+/// dictionaries from being mistaken for these.
+#[derive(Default)]
+struct Fingerprint {
+    dictionaries: Vec<(DictionaryId, Arc<Dictionary>)>,
+    hash: u64,
+}
+
+/// One part of the cache, with its own lock and least recently used order.
+struct Shard {
+    entries: HashMap<Key, Entry>,
+    /// Keys by the time they were last used, oldest first.
+    recency: BTreeMap<u64, Key>,
+    capacity: usize,
+    /// Time of the next use.
+    clock: u64,
+}
+
+impl Shard {
+    ///
+    fn get(&mut self, key: &Key, text: &str) -> Option<Arc<[OwnedToken]>> {
+        let entry = self
+            .entries
+            .get_mut(key)
+            .filter(|entry| entry.text == text)?;
+        self.recency.remove(&entry.used);
+        entry.used = self.clock;
+        self.recency.insert(self.clock, *key);
+        self.clock += 1;
+        Some(entry.tokens.clone())
+    }
+
+    /// Caches `tokens`, evicting the least recently used entry when full.
+    fn insert(&mut self, key: Key, text: &str, tokens: Arc<[OwnedToken]>) {
+        if let Some(old) = self.entries.remove(&key) {
+            self.recency.remove(&old.used);
+        } else if self.entries.len() >= self.capacity {
+            if let Some((_, oldest)) = self.recency.pop_first() {
+                self.entries.remove(&oldest);
+            }
+        }
+
+        let entry = Entry {
+            text: text.to_string(),
+            tokens,
+            used: self.clock,
+        };
+        self.entries.insert(key, entry);
+        self.recency.insert(self.clock, key);
+        self.clock += 1;
+    }
+}
+
+/// A [`Tokenizer`] remembering the tokens of the last `capacity` distinct texts it
+/// tokenized, evicting the least recently used ones first. It can be shared between
+/// threads; large caches are split into shards with a lock each, so threads seldom
+/// wait on one another.
+///
+/// Results are cached under hashes of the text, of the tokenizer options and of the
+/// fingerprints of its dictionaries (see [`Dictionary::metadata`]), so after
+/// [`Tokenizer::swap_dictionary`] nothing tokenized with the old dictionary is handed
+/// out again. Fingerprinting reads the whole dictionary, which is done once per
+/// dictionary swapped in.
+pub struct CachedTokenizer {
+    tokenizer: Arc<Tokenizer>,
+    shards: Vec<Mutex<Shard>>,
+    /// Hash of the tokenizer's options.
+    options: u64,
+    dictionaries: Mutex<Fingerprint>,
+    hits: AtomicU64,
+    misses: AtomicU64,
+}
+
+impl CachedTokenizer {
+    /// `capacity` is clamped to at least one result.
+    pub fn new(tokenizer: Arc<Tokenizer>, capacity: usize) -> Self {
+        let capacity = capacity.max(1);
+        let shard_count = if capacity >= MIN_SHARDED_CAPACITY {
+            SHARDS
+        } else {
+            1
+        };
+        let shards = (0..shard_count)
+            .map(|index| {
+                // The first shards take the remainder.
+                let share =
+                    capacity / shard_count + (index < capacity % shard_count) as usize;
+                Mutex::new(Shard {
+                    entries: HashMap::with_capacity(share),
+                    recency: BTreeMap::new(),
+                    capacity: share,
+                    clock: 0,
+                })
+            })
+            .collect();
+
+        let mut options = Fnv1a::new();
+        options.write_str(&format!("{:?}", tokenizer.options()));
+        Self {
+            tokenizer,
+            shards,
+            options: options.finish(),
+            dictionaries: Mutex::new(Fingerprint::default()),
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+        }
+    }
+
+    /// The tokens of `text`, from the cache if it has them.
+    pub fn tokenize(&self, text: &str) -> Arc<[OwnedToken]> {
+        // Tokens and the fingerprint from the same dictionaries, even while they are
+        // swapped.
+        let tokenizer = self.tokenizer.current();
+        let mut hash = Fnv1a::new();
+        hash.write_str(text);
+        let key = Key {
+            text: hash.finish(),
+            options: self.options,
+            dictionaries: self.fingerprint(&tokenizer.dictionaries()),
+        };
+        let shard = &self.shards[key.text as usize % self.shards.len()];
+
+        if let Some(tokens) = shard.lock().unwrap().get(&key, text) {
+            self.hits.fetch_add(1, Ordering::Relaxed);
+            return tokens;
+        }
+        self.misses.fetch_add(1, Ordering::Relaxed);
+
+        let tokens: Arc<[OwnedToken]> = tokenizer
+            .tokenize(text)
+            .iter()
+            .map(|token| {
+                OwnedToken::resolved(token, &|term_entry| {
+                    Some(tokenizer.entry(term_entry))
+                })
+            })
+            .collect();
+        shard.lock().unwrap().insert(key, text, tokens.clone());
+        tokens
+    }
+
+    /// The wrapped tokenizer.
+    pub fn tokenizer(&self) -> &Arc<Tokenizer> {
+        &self.tokenizer
+    }
+
+    /// Number of [`CachedTokenizer::tokenize`] calls answered from the cache.
+    pub fn hits(&self) -> u64 {
+        self.hits.load(Ordering::Relaxed)
+    }
+
+    /// Number of [`CachedTokenizer::tokenize`] calls that had to tokenize.
+    pub fn misses(&self) -> u64 {
+        self.misses.load(Ordering::Relaxed)
+    }
+
+    /// Share of the calls answered from the cache, `0.0` before the first one.
+    pub fn hit_rate(&self) -> f64 {
+        let (hits, misses) = (self.hits(), self.misses());
+        if hits + misses == 0 {
+            return 0.0;
+        }
+        hits as f64 / (hits + misses) as f64
+    }
+
+    /// Number of results cached.
+    pub fn len(&self) -> usize {
+        self.shards
+            .iter()
+            .map(|shard| shard.lock().unwrap().entries.len())
+            .sum()
+    }
+
+    ///
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Forgets every cached result. The hit and miss counts are kept.
+    pub fn clear(&self) {
+        for shard in self.shards.iter() {
+            let mut shard = shard.lock().unwrap();
+            shard.entries.clear();
+            shard.recency.clear();
+        }
+    }
+
+    /// Hash of the fingerprints of `dictionaries`, computed again only when they aren't
+    /// the ones of the last call.
+    fn fingerprint(&self, dictionaries: &[(DictionaryId, Arc<Dictionary>)]) -> u64 {
+        let mut last = self.dictionaries.lock().unwrap();
+        let same = last.dictionaries.len() == dictionaries.len()
+            && last
+                .dictionaries
+                .iter()
+                .zip(dictionaries.iter())
+                .all(|(a, b)| a.0 == b.0 && Arc::ptr_eq(&a.1, &b.1));
+        if !same {
+            let mut hash = Fnv1a::new();
+            for (id, dictionary) in dictionaries {
+                hash.write_u64(id.0 as u64);
+                hash.write_u64(dictionary.metadata().fingerprint);
+            }
+            *last = Fingerprint {
+                dictionaries: dictionaries.to_vec(),
+                hash: hash.finish(),
+            };
+        }
+        last.hash
+    }
+}
@@ -0,0 +1,128 @@
+use crate::dictionary::{PartOfSpeech, TermEntry};
+
+/// Where a candidate span came from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum CandidateSource {
+    /// The surface matched this dictionary term entry.
+    Dictionary(TermEntry),
+    /// Grouped by the unknown-term category rules.
+    Unknown,
+    /// One of the built-in hiragana function words, for spans without a dictionary
+    /// match.
+    FunctionWord,
+    /// A run of one repeated character, see [`TokenKind::Emphasis`].
+    ///
+    /// [`TokenKind::Emphasis`]: crate::tokenizer::TokenKind::Emphasis
+    Emphasis,
+}
+
+/// Receives candidate spans from [`Tokenizer::generate_candidates`].
+///
+/// `start` and `end` are character positions in the text, `surface` the matching slice.
+///
+/// [`Tokenizer::generate_candidates`]: crate::tokenizer::Tokenizer::generate_candidates
+pub trait CandidateSink {
+    fn accept(
+        &mut self,
+        surface: &str,
+        start: usize,
+        end: usize,
+        source: CandidateSource,
+    );
+}
+
+///
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Candidate {
+    pub surface: String,
+    pub start: usize,
+    pub end: usize,
+    pub source: CandidateSource,
+}
+
+/// Sink that just keeps every candidate, in generation order.
+#[derive(Debug, Default)]
+pub struct RecordingSink {
+    pub candidates: Vec<Candidate>,
+}
+
+impl CandidateSink for RecordingSink {
+    fn accept(
+        &mut self,
+        surface: &str,
+        start: usize,
+        end: usize,
+        source: CandidateSource,
+    ) {
+        self.candidates.push(Candidate {
+            surface: surface.to_string(),
+            start,
+            end,
+            source,
+        });
+    }
+}
+
+/// A candidate span with the score its lattice node would get.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ScoredCandidate {
+    pub surface: String,
+    pub score: f32,
+    pub source: CandidateSource,
+    pub pos: PartOfSpeech,
+}
+
+/// The best candidates starting at one character position, highest score first.
+///
+/// Returned by [`Tokenizer::debug_candidates`]. Scores are the node scores before the
+/// lattice-wide adjustments such as the single-character discount.
+///
+/// [`Tokenizer::debug_candidates`]: crate::tokenizer::Tokenizer::debug_candidates
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PositionCandidates {
+    /// Character position of the candidates' start.
+    pub position: usize,
+    pub candidates: Vec<ScoredCandidate>,
+}
+
+/// Columns a surface takes up in a monospace terminal, counting CJK as two.
+fn display_width(text: &str) -> usize {
+    text.chars()
+        .map(|c| if (c as u32) >= 0x1100 { 2 } else { 1 })
+        .sum()
+}
+
+/// One line per candidate: position, surface, score, source and part of speech.
+impl std::fmt::Display for PositionCandidates {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        const SURFACE_WIDTH: usize = 16;
+
+        for candidate in self.candidates.iter() {
+            let padding = SURFACE_WIDTH.saturating_sub(display_width(&candidate.surface));
+            let source = match candidate.source {
+                CandidateSource::Dictionary(_) => "dictionary",
+                CandidateSource::Unknown => "unknown",
+                CandidateSource::FunctionWord => "function",
+                CandidateSource::Emphasis => "emphasis",
+            };
+            let pos = candidate.pos.names();
+            let pos = if pos.is_empty() {
+                "-".to_string()
+            } else {
+                pos.join("|")
+            };
+
+            writeln!(
+                f,
+                "{:>4}  {}{:padding$}  {:>9.2}  {:<10}  {}",
+                self.position, candidate.surface, "", candidate.score, source, pos
+            )?;
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,266 @@
+//! Readings of numbers followed by a counter, whose sounds change where they join: 一本
+//! is いっぽん, 三本 さんぼん and 六匹 ろっぴき.
+
+/// A counter and how its reading joins the number before it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Counter {
+    pub surface: &'static str,
+    /// Reading after numbers that don't change it.
+    pub reading: &'static str,
+    /// The last word of a number together with the counter, where they change, like
+    /// いっぽん for いち and 本.
+    pub joined: &'static [(&'static str, &'static str)],
+    /// Whole numbers with the counter that follow no rule, like ひとり for 一人.
+    pub exceptions: &'static [(u64, &'static str)],
+}
+
+/// The common counters.
+#[rustfmt::skip]
+pub const COUNTERS: &[Counter] = &[
+    Counter {
+        surface: "本",
+        reading: "ほん",
+        joined: &[
+            ("いち", "いっぽん"), ("ろく", "ろっぽん"), ("はち", "はっぽん"),
+            ("じゅう", "じゅっぽん"), ("ひゃく", "ひゃっぽん"), ("びゃく", "びゃっぽん"),
+            ("ぴゃく", "ぴゃっぽん"), ("さん", "さんぼん"), ("せん", "せんぼん"),
+            ("ぜん", "ぜんぼん"), ("まん", "まんぼん"), ("なん", "なんぼん"),
+        ],
+        exceptions: &[],
+    },
+    Counter {
+        surface: "匹",
+        reading: "ひき",
+        joined: &[
+            ("いち", "いっぴき"), ("ろく", "ろっぴき"), ("はち", "はっぴき"),
+            ("じゅう", "じゅっぴき"), ("ひゃく", "ひゃっぴき"), ("びゃく", "びゃっぴき"),
+            ("ぴゃく", "ぴゃっぴき"), ("さん", "さんびき"), ("せん", "せんびき"),
+            ("ぜん", "ぜんびき"), ("まん", "まんびき"), ("なん", "なんびき"),
+        ],
+        exceptions: &[],
+    },
+    Counter {
+        surface: "杯",
+        reading: "はい",
+        joined: &[
+            ("いち", "いっぱい"), ("ろく", "ろっぱい"), ("はち", "はっぱい"),
+            ("じゅう", "じゅっぱい"), ("ひゃく", "ひゃっぱい"), ("びゃく", "びゃっぱい"),
+            ("ぴゃく", "ぴゃっぱい"), ("さん", "さんばい"), ("せん", "せんばい"),
+            ("ぜん", "ぜんばい"), ("まん", "まんばい"), ("なん", "なんばい"),
+        ],
+        exceptions: &[],
+    },
+    Counter {
+        surface: "分",
+        reading: "ふん",
+        joined: &[
+            ("いち", "いっぷん"), ("ろく", "ろっぷん"), ("はち", "はっぷん"),
+            ("じゅう", "じゅっぷん"), ("ひゃく", "ひゃっぷん"), ("びゃく", "びゃっぷん"),
+            ("ぴゃく", "ぴゃっぷん"), ("さん", "さんぷん"), ("よん", "よんぷん"),
+            ("せん", "せんぷん"), ("ぜん", "ぜんぷん"), ("まん", "まんぷん"),
+            ("なん", "なんぷん"),
+        ],
+        exceptions: &[],
+    },
+    Counter {
+        surface: "冊",
+        reading: "さつ",
+        joined: &[("いち", "いっさつ"), ("はち", "はっさつ"), ("じゅう", "じゅっさつ")],
+        exceptions: &[],
+    },
+    Counter {
+        surface: "階",
+        reading: "かい",
+        joined: &[
+            ("いち", "いっかい"), ("ろく", "ろっかい"), ("はち", "はっかい"),
+            ("じゅう", "じゅっかい"), ("ひゃく", "ひゃっかい"), ("びゃく", "びゃっかい"),
+            ("ぴゃく", "ぴゃっかい"), ("さん", "さんがい"), ("なん", "なんがい"),
+        ],
+        exceptions: &[],
+    },
+    Counter {
+        surface: "人",
+        reading: "にん",
+        joined: &[("よん", "よにん")],
+        exceptions: &[(1, "ひとり"), (2, "ふたり")],
+    },
+    // Days of the month.
+    Counter {
+        surface: "日",
+        reading: "にち",
+        joined: &[("よん", "よっか"), ("なな", "しちにち"), ("きゅう", "くにち")],
+        exceptions: &[
+            (1, "ついたち"), (2, "ふつか"), (3, "みっか"), (4, "よっか"), (5, "いつか"),
+            (6, "むいか"), (7, "なのか"), (8, "ようか"), (9, "ここのか"), (10, "とおか"),
+            (20, "はつか"),
+        ],
+    },
+];
+
+/// The counter of [`COUNTERS`] spelled `surface`.
+pub fn counter(surface: &str) -> Option<&'static Counter> {
+    COUNTERS.iter().find(|counter| counter.surface == surface)
+}
+
+/// A number as it is read: its value, `None` for 何, and its words, like さん and
+/// びゃく for 三百.
+struct Number {
+    value: Option<u64>,
+    words: Vec<&'static str>,
+}
+
+/// Readings of the digits one to nine.
+const DIGITS: [&str; 9] = [
+    "いち",
+    "に",
+    "さん",
+    "よん",
+    "ご",
+    "ろく",
+    "なな",
+    "はち",
+    "きゅう",
+];
+
+/// Value of `c` as a digit, in ASCII, full width or kanji.
+fn digit_value(c: char) -> Option<u64> {
+    let value = match c {
+        '0'..='9' => c as u32 - '0' as u32,
+        '０'..='９' => c as u32 - '０' as u32,
+        '〇' => 0,
+        '一' => 1,
+        '二' => 2,
+        '三' => 3,
+        '四' => 4,
+        '五' => 5,
+        '六' => 6,
+        '七' => 7,
+        '八' => 8,
+        '九' => 9,
+        _ => return None,
+    };
+    Some(value as u64)
+}
+
+/// Value of a number written in digits (12, 一二) or with kanji units (十二, 三千五百),
+/// from one to below a hundred million.
+fn number_value(surface: &str) -> Option<u64> {
+    // Value of the groups of ten thousand so far, of the units below them, and of the
+    // digits not yet followed by a unit.
+    let mut total: u64 = 0;
+    let mut section: u64 = 0;
+    let mut digits: Option<u64> = None;
+    for c in surface.chars() {
+        if let Some(value) = digit_value(c) {
+            digits = Some(digits.unwrap_or(0).checked_mul(10)?.checked_add(value)?);
+            continue;
+        }
+
+        let unit = match c {
+            '十' => 10,
+            '百' => 100,
+            '千' => 1000,
+            '万' => {
+                let ones = digits.take().unwrap_or(u64::from(section == 0));
+                let group = section.checked_add(ones)?;
+                total = total.checked_add(group.checked_mul(10_000)?)?;
+                section = 0;
+                continue;
+            }
+            _ => return None,
+        };
+        section = section.checked_add(digits.take().unwrap_or(1).checked_mul(unit)?)?;
+    }
+
+    let value = total
+        .checked_add(section)?
+        .checked_add(digits.unwrap_or(0))?;
+    (1..100_000_000).contains(&value).then_some(value)
+}
+
+/// Words of a number below ten thousand, with the sound changes of 三百, 六百, 八百,
+/// 三千 and 八千.
+fn push_words_below_10_000(value: u64, words: &mut Vec<&'static str>) {
+    let digit = |place: u64| (value / place % 10) as usize;
+    match digit(1000) {
+        0 => {}
+        1 => words.push("せん"),
+        3 => words.extend(["さん", "ぜん"]),
+        8 => words.extend(["はっ", "せん"]),
+        d => words.extend([DIGITS[d - 1], "せん"]),
+    }
+    match digit(100) {
+        0 => {}
+        1 => words.push("ひゃく"),
+        3 => words.extend(["さん", "びゃく"]),
+        6 => words.extend(["ろっ", "ぴゃく"]),
+        8 => words.extend(["はっ", "ぴゃく"]),
+        d => words.extend([DIGITS[d - 1], "ひゃく"]),
+    }
+    match digit(10) {
+        0 => {}
+        1 => words.push("じゅう"),
+        d => words.extend([DIGITS[d - 1], "じゅう"]),
+    }
+    if digit(1) != 0 {
+        words.push(DIGITS[digit(1) - 1]);
+    }
+}
+
+/// `surface` read as a number, or as 何.
+fn parse_number(surface: &str) -> Option<Number> {
+    if surface == "何" {
+        return Some(Number {
+            value: None,
+            words: vec!["なん"],
+        });
+    }
+
+    let value = number_value(surface)?;
+    let mut words = Vec::new();
+    if value >= 10_000 {
+        push_words_below_10_000(value / 10_000, &mut words);
+        words.push("まん");
+    }
+    push_words_below_10_000(value % 10_000, &mut words);
+    Some(Number {
+        value: Some(value),
+        words,
+    })
+}
+
+/// Whether `surface` is a number [`counted_reading`] can read: digits, kanji numerals
+/// or 何.
+pub fn is_number(surface: &str) -> bool {
+    parse_number(surface).is_some()
+}
+
+/// Reading of the number `surface` on its own, like さんびゃく for 三百 or 300.
+pub fn number_reading(surface: &str) -> Option<String> {
+    Some(parse_number(surface)?.words.concat())
+}
+
+/// Reading of the number `number` followed by `counter`, `None` when `number` isn't a
+/// number or `counter` isn't one of [`COUNTERS`].
+pub fn counted_reading(number: &str, counter: &str) -> Option<String> {
+    let counter = self::counter(counter)?;
+    let number = parse_number(number)?;
+    let exception = counter
+        .exceptions
+        .iter()
+        .find(|(value, _)| Some(*value) == number.value);
+    if let Some((_, reading)) = exception {
+        return Some(reading.to_string());
+    }
+
+    let (last, rest) = number.words.split_last()?;
+    let joined = counter
+        .joined
+        .iter()
+        .find(|(word, _)| word == last)
+        .map_or_else(
+            || format!("{last}{}", counter.reading),
+            |(_, joined)| joined.to_string(),
+        );
+    Some(rest.concat() + &joined)
+}
@@ -0,0 +1,258 @@
+use crate::dictionary::{InflectionType, PartOfSpeech};
+
+/// Rules are applied at most this many times in a row (e.g. させられなかった).
+const MAX_DEPTH: usize = 4;
+
+/// Rewrites an inflected ending into the ending of a less inflected form.
+#[derive(Debug, Clone)]
+pub struct Rule {
+    pub from: String,
+    pub to: &'static str,
+    pub inflection: InflectionType,
+    /// Word class the inflected surface itself must inflect as for the rule to apply
+    /// after another rule, e.g. 食べない inflects like an adjective.
+    /// [`PartOfSpeech::NONE`] marks final forms, which only match the original surface.
+    pub input: PartOfSpeech,
+    /// Word classes the rewritten form can belong to.
+    pub output: PartOfSpeech,
+}
+
+/// A candidate base form of an inflected surface.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Deinflection {
+    pub term: String,
+    pub inflection: InflectionType,
+    /// Word classes a dictionary entry for `term` must have to be a valid match.
+    pub pos: PartOfSpeech,
+}
+
+impl Deinflection {
+    /// Whether an entry with `pos` for [`Deinflection::term`] is a valid match. Entries
+    /// without part-of-speech information can't be ruled out.
+    pub fn accepts(&self, pos: PartOfSpeech) -> bool {
+        pos.is_empty() || pos.intersects(self.pos)
+    }
+}
+
+const ICHIDAN: PartOfSpeech = PartOfSpeech::ICHIDAN_VERB;
+const GODAN: PartOfSpeech = PartOfSpeech::GODAN_VERB;
+const ADJECTIVE: PartOfSpeech = PartOfSpeech::ADJECTIVE;
+const SURU: PartOfSpeech = PartOfSpeech::SURU_VERB;
+const KURU: PartOfSpeech = PartOfSpeech::KURU_VERB;
+const FINAL: PartOfSpeech = PartOfSpeech::NONE;
+
+/// Godan rows: dictionary ending, a-, i- and e-stem endings, te and ta forms.
+const GODAN_ROWS: [(&str, &str, &str, &str, &str, &str); 9] = [
+    ("う", "わ", "い", "え", "って", "った"),
+    ("く", "か", "き", "け", "いて", "いた"),
+    ("ぐ", "が", "ぎ", "げ", "いで", "いだ"),
+    ("す", "さ", "し", "せ", "して", "した"),
+    ("つ", "た", "ち", "て", "って", "った"),
+    ("ぬ", "な", "に", "ね", "んで", "んだ"),
+    ("ぶ", "ば", "び", "べ", "んで", "んだ"),
+    ("む", "ま", "み", "め", "んで", "んだ"),
+    ("る", "ら", "り", "れ", "って", "った"),
+];
+
+/// `(from, to, inflection, input, output)` for everything that isn't a regular godan row.
+const RULES: &[(&str, &str, InflectionType, PartOfSpeech, PartOfSpeech)] = {
+    use InflectionType::*;
+    &[
+        // Ichidan
+        ("ない", "る", Negative, ADJECTIVE, ICHIDAN),
+        ("ません", "る", Negative, FINAL, ICHIDAN),
+        ("て", "る", Te, FINAL, ICHIDAN),
+        ("た", "る", Past, FINAL, ICHIDAN),
+        ("ました", "る", Past, FINAL, ICHIDAN),
+        ("ませんでした", "る", NegativePast, FINAL, ICHIDAN),
+        ("られる", "る", Potential, ICHIDAN, ICHIDAN),
+        ("ろ", "る", Imperative, FINAL, ICHIDAN),
+        ("るな", "る", ImperativeNegative, FINAL, ICHIDAN),
+        ("させる", "る", Causative, ICHIDAN, ICHIDAN),
+        ("させられる", "る", CausativePassive, ICHIDAN, ICHIDAN),
+        ("れば", "る", Conditional, FINAL, ICHIDAN),
+        // 行く has an irregular te/ta form.
+        ("行って", "行く", Te, FINAL, GODAN),
+        ("行った", "行く", Past, FINAL, GODAN),
+        ("いって", "いく", Te, FINAL, GODAN),
+        ("いった", "いく", Past, FINAL, GODAN),
+        // Adjectives
+        ("かった", "い", Past, FINAL, ADJECTIVE),
+        ("くない", "い", Negative, ADJECTIVE, ADJECTIVE),
+        ("くて", "い", Te, FINAL, ADJECTIVE),
+        ("ければ", "い", Conditional, FINAL, ADJECTIVE),
+        // する
+        ("しない", "する", Negative, ADJECTIVE, SURU),
+        ("しません", "する", Negative, FINAL, SURU),
+        ("して", "する", Te, FINAL, SURU),
+        ("した", "する", Past, FINAL, SURU),
+        ("しました", "する", Past, FINAL, SURU),
+        ("しませんでした", "する", NegativePast, FINAL, SURU),
+        ("できる", "する", Potential, ICHIDAN, SURU),
+        ("させる", "する", Causative, ICHIDAN, SURU),
+        ("される", "する", Passive, ICHIDAN, SURU),
+        ("させられる", "する", CausativePassive, ICHIDAN, SURU),
+        ("しろ", "する", Imperative, FINAL, SURU),
+        ("せよ", "する", Imperative, FINAL, SURU),
+        ("するな", "する", ImperativeNegative, FINAL, SURU),
+        ("すれば", "する", Conditional, FINAL, SURU),
+        // 来る, in kana and kanji
+        ("こない", "くる", Negative, ADJECTIVE, KURU),
+        ("来ない", "来る", Negative, ADJECTIVE, KURU),
+        ("きません", "くる", Negative, FINAL, KURU),
+        ("来ません", "来る", Negative, FINAL, KURU),
+        ("きて", "くる", Te, FINAL, KURU),
+        ("来て", "来る", Te, FINAL, KURU),
+        ("きた", "くる", Past, FINAL, KURU),
+        ("来た", "来る", Past, FINAL, KURU),
+        ("きました", "くる", Past, FINAL, KURU),
+        ("来ました", "来る", Past, FINAL, KURU),
+        ("こられる", "くる", Potential, ICHIDAN, KURU),
+        ("来られる", "来る", Potential, ICHIDAN, KURU),
+        ("こさせる", "くる", Causative, ICHIDAN, KURU),
+        ("来させる", "来る", Causative, ICHIDAN, KURU),
+        ("こい", "くる", Imperative, FINAL, KURU),
+        ("来い", "来る", Imperative, FINAL, KURU),
+        ("くれば", "くる", Conditional, FINAL, KURU),
+        ("来れば", "来る", Conditional, FINAL, KURU),
+    ]
+};
+
+/// All deinflection rules, longest `from` first.
+pub fn rules() -> &'static [Rule] {
+    lazy_static::lazy_static! {
+        static ref RULE_TABLE: Vec<Rule> = {
+            crate::tokenizer::count_initialization();
+            build_rules()
+        };
+    }
+
+    &RULE_TABLE
+}
+
+///
+fn build_rules() -> Vec<Rule> {
+    use InflectionType::*;
+
+    let mut rules: Vec<Rule> = RULES
+        .iter()
+        .map(|&(from, to, inflection, input, output)| Rule {
+            from: from.to_string(),
+            to,
+            inflection,
+            input,
+            output,
+        })
+        .collect();
+
+    for (u, a, i, e, te, ta) in GODAN_ROWS {
+        let forms = [
+            (format!("{a}ない"), Negative, ADJECTIVE),
+            (format!("{i}ません"), Negative, FINAL),
+            (te.to_string(), Te, FINAL),
+            (ta.to_string(), Past, FINAL),
+            (format!("{i}ました"), Past, FINAL),
+            (format!("{i}ませんでした"), NegativePast, FINAL),
+            (format!("{e}る"), Potential, ICHIDAN),
+            (e.to_string(), Imperative, FINAL),
+            (format!("{u}な"), ImperativeNegative, FINAL),
+            (format!("{a}せる"), Causative, ICHIDAN),
+            (format!("{a}れる"), Passive, ICHIDAN),
+            (format!("{a}される"), CausativePassive, ICHIDAN),
+            (format!("{e}ば"), Conditional, FINAL),
+        ];
+
+        for (from, inflection, input) in forms {
+            rules.push(Rule {
+                from,
+                to: u,
+                inflection,
+                input,
+                output: GODAN,
+            });
+        }
+    }
+
+    rules.sort_by_key(|rule| std::cmp::Reverse(rule.from.chars().count()));
+    rules
+}
+
+/// Most characters [`deinflect`] can strip from a surface, so an inflected surface is
+/// never longer than its base form plus this.
+pub fn max_growth() -> usize {
+    let longest = rules()
+        .iter()
+        .map(|rule| {
+            rule.from
+                .chars()
+                .count()
+                .saturating_sub(rule.to.chars().count())
+        })
+        .max()
+        .unwrap_or(0);
+    longest * MAX_DEPTH
+}
+
+/// Inflection of a form reached by applying `inner` after `outer`, when a single
+/// [`InflectionType`] describes both. Otherwise the outer one, nearest to the surface,
+/// is kept.
+fn combine(outer: InflectionType, inner: InflectionType) -> InflectionType {
+    use InflectionType::*;
+    match (outer, inner) {
+        (DictionaryForm, inner) => inner,
+        (Past, Negative) => NegativePast,
+        (Te, Negative) => NegativeTe,
+        (Negative, Potential) => NegativePotential,
+        (Negative, Causative) => NegativeCausative,
+        (Negative, Passive) => NegativePassive,
+        (Negative, CausativePassive)
+        | (NegativePassive | NegativePotential, Causative) => NegativeCausativePassive,
+        (Passive | Potential, Causative) => CausativePassive,
+        (outer, _) => outer,
+    }
+}
+
+/// Every form `surface` could be an inflection of, nearest to the surface first.
+///
+/// The surface itself is not included. Candidates are not checked against a
+/// dictionary; a match is only valid if its entry has one of [`Deinflection::pos`].
+pub fn deinflect(surface: &str) -> Vec<Deinflection> {
+    let mut results: Vec<Deinflection> = Vec::new();
+    let mut frontier = vec![Deinflection {
+        term: surface.to_string(),
+        inflection: InflectionType::DictionaryForm,
+        pos: PartOfSpeech::NONE,
+    }];
+
+    for depth in 0..MAX_DEPTH {
+        let mut next = Vec::new();
+        for current in frontier.iter() {
+            for rule in rules() {
+                if depth > 0 && !rule.input.intersects(current.pos) {
+                    continue;
+                }
+                let Some(stem) = current.term.strip_suffix(rule.from.as_str()) else {
+                    continue;
+                };
+
+                let candidate = Deinflection {
+                    term: format!("{stem}{}", rule.to),
+                    inflection: combine(current.inflection, rule.inflection),
+                    pos: rule.output,
+                };
+                if candidate.term != surface && !results.contains(&candidate) {
+                    results.push(candidate.clone());
+                    next.push(candidate);
+                }
+            }
+        }
+
+        if next.is_empty() {
+            break;
+        }
+        frontier = next;
+    }
+
+    results
+}
@@ -0,0 +1,254 @@
+//! Telling Japanese sentences apart from other languages, so mixed documents only run
+//! the lattice over the Japanese parts.
+
+use std::ops::Range;
+
+/// How much of a text is written in Japanese scripts, from [`is_japanese`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct JapaneseScore {
+    /// Letters counted: alphabetic characters of any script, so no digits, spaces,
+    /// punctuation or symbols.
+    pub letters: usize,
+    /// Share of the letters that are hiragana or katakana, `0.0` without letters.
+    pub kana: f32,
+    /// Share of the letters that are kanji, `0.0` without letters.
+    pub kanji: f32,
+}
+
+/// Limits under which [`JapaneseScore::passes`] rejects a text.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct JapaneseThresholds {
+    /// Texts with fewer letters always pass, since a few letters say little about the
+    /// language.
+    pub min_letters: usize,
+    /// Minimum share of kana and kanji together.
+    pub min_japanese: f32,
+    /// Minimum share of kana, which tells Japanese from Chinese.
+    pub min_kana: f32,
+}
+
+impl Default for JapaneseThresholds {
+    fn default() -> Self {
+        Self {
+            min_letters: 4,
+            min_japanese: 0.5,
+            min_kana: 0.1,
+        }
+    }
+}
+
+impl JapaneseScore {
+    /// Whether the text looks Japanese by `thresholds`. Sentences written in kanji
+    /// alone, like some headlines, fail the kana threshold like Chinese does.
+    pub fn passes(&self, thresholds: &JapaneseThresholds) -> bool {
+        self.letters < thresholds.min_letters
+            || (self.kana + self.kanji >= thresholds.min_japanese
+                && self.kana >= thresholds.min_kana)
+    }
+}
+
+///
+fn is_kana(c: char) -> bool {
+    matches!(c as u32, 0x3041..=0x309F | 0x30A0..=0x30FF | 0x31F0..=0x31FF | 0xFF66..=0xFF9F)
+}
+
+///
+fn is_kanji(c: char) -> bool {
+    matches!(
+        c as u32,
+        0x3005 | 0x3400..=0x4DBF | 0x4E00..=0x9FFF | 0xF900..=0xFAFF | 0x20000..=0x2FFFF
+    )
+}
+
+/// Counts the kana and kanji among the letters of `text`.
+pub fn is_japanese(text: &str) -> JapaneseScore {
+    let (mut letters, mut kana, mut kanji) = (0, 0, 0);
+    for c in text.chars() {
+        if is_kana(c) {
+            kana += 1;
+        } else if is_kanji(c) {
+            kanji += 1;
+        } else if !c.is_alphabetic() {
+            continue;
+        }
+        letters += 1;
+    }
+
+    let share = |count: usize| {
+        if letters == 0 {
+            0.0
+        } else {
+            count as f32 / letters as f32
+        }
+    };
+    JapaneseScore {
+        letters,
+        kana: share(kana),
+        kanji: share(kanji),
+    }
+}
+
+/// Closing quotes and brackets that belong to the sentence they follow.
+const CLOSERS: &[char] = &['」', '』', '）', ')', '"', '\'', '”', '’'];
+
+/// How [`sentences_with`] splits a text into sentences. The default suits Japanese
+/// text with some English mixed in.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct SentenceRules {
+    /// Characters ending a sentence. A full stop `.` only does when followed by
+    /// whitespace or the end of the text, so 3.5 and example.com stay whole, and not
+    /// after another full stop, so an ellipsis ... doesn't either, like …… never does.
+    pub terminators: Vec<char>,
+    /// Words that a full stop `.` followed by whitespace doesn't end a sentence after,
+    /// spelled with their last period, like `e.g.` or `U.S.A.`. Compared with the run
+    /// of ASCII letters, digits and periods before the full stop, case-sensitively.
+    pub abbreviations: Vec<String>,
+    /// Keep a run of terminators such as ！？ or ?! in the sentence of the first one,
+    /// rather than making each of them a sentence of its own.
+    pub merge_terminator_runs: bool,
+    /// Don't end a sentence inside any of the `brackets`, so 「行く。」と聞いた。 is one
+    /// sentence. Only a line break does, and brackets left open are closed with it.
+    pub keep_brackets_whole: bool,
+    /// Opening and closing characters of the brackets and quotes of
+    /// `keep_brackets_whole`.
+    pub brackets: Vec<(char, char)>,
+    /// Don't end a sentence at a full stop, `.` or `．`, after nothing but a number, so
+    /// the 1. of a list like 1. 項目 stays with its item.
+    pub numbered_lists: bool,
+}
+
+impl Default for SentenceRules {
+    fn default() -> Self {
+        Self {
+            terminators: vec!['。', '！', '？', '!', '?', '．', '.', '\n'],
+            abbreviations: [
+                "e.g.", "i.e.", "etc.", "vs.", "cf.", "Mr.", "Mrs.", "Ms.", "Dr.", "St.",
+                "No.", "Vol.", "p.", "pp.", "a.m.", "p.m.", "U.S.", "U.S.A.", "U.K.",
+            ]
+            .map(String::from)
+            .to_vec(),
+            merge_terminator_runs: true,
+            keep_brackets_whole: true,
+            brackets: vec![('「', '」'), ('『', '』'), ('（', '）'), ('(', ')')],
+            numbered_lists: true,
+        }
+    }
+}
+
+impl SentenceRules {
+    /// Whether the terminator `c` at byte `i` ends the sentence starting at `start`.
+    fn ends_sentence(&self, text: &str, start: usize, i: usize, c: char) -> bool {
+        if !self.terminators.contains(&c) {
+            return false;
+        }
+        if c != '.' && c != '．' {
+            return true;
+        }
+
+        let after = &text[i + c.len_utf8()..];
+        if c == '.' && !after.chars().next().is_none_or(char::is_whitespace) {
+            return false;
+        }
+
+        let before = &text[start..i];
+        if c == '.' && before.ends_with('.') {
+            return false;
+        }
+
+        let word_start = before
+            .trim_end_matches(|c: char| c.is_ascii_alphanumeric() || c == '.')
+            .len();
+        let word = &before[word_start..];
+        if c == '.'
+            && self
+                .abbreviations
+                .iter()
+                .any(|abbreviation| abbreviation.strip_suffix('.') == Some(word))
+        {
+            return false;
+        }
+
+        let number = before.trim_start();
+        !(self.numbered_lists
+            && !number.is_empty()
+            && number
+                .chars()
+                .all(|c| c.is_ascii_digit() || ('０'..='９').contains(&c)))
+    }
+}
+
+/// [`sentences_with`] the default [`SentenceRules`].
+pub fn sentences(text: &str) -> Vec<Range<usize>> {
+    sentences_with(text, &SentenceRules::default())
+}
+
+/// Byte ranges of the sentences of `text`, which tile it. A sentence keeps its
+/// terminators and the closing quotes after them; whitespace after it starts the next
+/// one.
+pub fn sentences_with(text: &str, rules: &SentenceRules) -> Vec<Range<usize>> {
+    let mut sentences = Vec::new();
+    let mut start = 0;
+    // Closing brackets still to come, innermost last.
+    let mut open_brackets: Vec<char> = Vec::new();
+    let mut chars = text.char_indices().peekable();
+    while let Some((i, c)) = chars.next() {
+        if c == '\n' {
+            open_brackets.clear();
+        } else if rules.keep_brackets_whole {
+            if let Some(depth) = open_brackets.iter().rposition(|&closer| closer == c) {
+                open_brackets.truncate(depth);
+            } else if let Some(&(_, closer)) =
+                rules.brackets.iter().find(|(opener, _)| *opener == c)
+            {
+                open_brackets.push(closer);
+            }
+        }
+        if !open_brackets.is_empty() || !rules.ends_sentence(text, start, i, c) {
+            continue;
+        }
+
+        let mut end = i + c.len_utf8();
+        while let Some((j, next)) = chars.peek().copied() {
+            let absorbed = CLOSERS.contains(&next)
+                || (rules.merge_terminator_runs && rules.terminators.contains(&next));
+            if next == '\n' || !absorbed {
+                break;
+            }
+            end = j + next.len_utf8();
+            chars.next();
+        }
+        sentences.push(start..end);
+        start = end;
+    }
+    if start < text.len() {
+        sentences.push(start..text.len());
+    }
+
+    sentences
+}
+
+/// Byte ranges of the paragraphs of `text`, which are separated by blank lines, lines
+/// of whitespace alone. A paragraph runs from the start of its first line to the end of
+/// its last line, without the line break, so the blank lines are in no paragraph.
+pub fn paragraphs(text: &str) -> Vec<Range<usize>> {
+    let mut paragraphs = Vec::new();
+    let mut current: Option<Range<usize>> = None;
+    let mut line_start = 0;
+    for line in text.split_inclusive('\n') {
+        let content = line.trim_end_matches(['\n', '\r']);
+        if content.trim().is_empty() {
+            paragraphs.extend(current.take());
+        } else {
+            let end = line_start + content.len();
+            current.get_or_insert(line_start..end).end = end;
+        }
+        line_start += line.len();
+    }
+    paragraphs.extend(current);
+
+    paragraphs
+}
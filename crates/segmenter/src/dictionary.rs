@@ -1,8 +1,14 @@
 use std::collections::HashMap;
+use std::io::Read;
+#[cfg(feature = "serde")]
+use std::io::Write;
+#[cfg(feature = "serde")]
+use std::path::Path;
 
 bitflags::bitflags! {
     ///
-    #[derive(serde::Serialize, serde::Deserialize, Clone, Copy, Debug, PartialEq, Eq, Hash)]
+    #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
     pub struct PartOfSpeech: u32 {
         const NONE = 0;
         /// Noun or verb acting prenominally
@@ -67,6 +73,8 @@ bitflags::bitflags! {
         const TRANSITIVE_VERB = 1 << 29;
         /// pre-noun adjectival
         const PRE_NOUN_ADJECTIVAL = 1 << 30;
+        /// Punctuation, brackets and other symbols (built-in, not from JMdict)
+        const SYMBOL = 1 << 31;
     }
 }
 
@@ -74,11 +82,88 @@ impl PartOfSpeech {
     pub fn is_particle(&self) -> bool {
         self.contains(Self::PARTICLE)
     }
+
+    pub fn is_symbol(&self) -> bool {
+        self.contains(Self::SYMBOL)
+    }
+}
+
+/// Every flag of a bitflags table but the leading `NONE`, with its name.
+const fn named_flags<F: Copy, const N: usize>(
+    flags: &[bitflags::Flag<F>],
+) -> [(F, &'static str); N] {
+    let mut named = [(*flags[1].value(), flags[1].name()); N];
+    let mut i = 0;
+    while i < N {
+        named[i] = (*flags[i + 1].value(), flags[i + 1].name());
+        i += 1;
+    }
+    named
+}
+
+/// A name in a pipe-separated flag list that isn't a flag.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnknownFlagName(pub String);
+
+impl std::fmt::Display for UnknownFlagName {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "unknown flag name `{}`", self.0)
+    }
+}
+
+impl std::error::Error for UnknownFlagName {}
+
+/// `ALL_NAMED`, `names` and pipe-separated `FromStr` for a bitflags type, all derived
+/// from the names in its declaration.
+macro_rules! impl_named_flags {
+    ($flags:ident) => {
+        impl $flags {
+            /// Every flag except `NONE` with its name, in declaration order.
+            pub const ALL_NAMED: &'static [($flags, &'static str)] =
+                &named_flags::<$flags, { <$flags as bitflags::Flags>::FLAGS.len() - 1 }>(
+                    <$flags as bitflags::Flags>::FLAGS,
+                );
+
+            /// Names of the flags that are set, in declaration order.
+            pub fn names(&self) -> Vec<&'static str> {
+                Self::ALL_NAMED
+                    .iter()
+                    .filter(|(flag, _)| self.contains(*flag))
+                    .map(|(_, name)| *name)
+                    .collect()
+            }
+        }
+
+        /// Parses pipe-separated names such as `NOUN|SURU_VERB`; the empty string (or
+        /// `NONE`) is the empty set.
+        impl std::str::FromStr for $flags {
+            type Err = UnknownFlagName;
+
+            fn from_str(text: &str) -> Result<Self, Self::Err> {
+                let mut flags = Self::NONE;
+                for name in text
+                    .split('|')
+                    .map(str::trim)
+                    .filter(|name| !name.is_empty())
+                {
+                    flags |= Self::from_name(name)
+                        .ok_or_else(|| UnknownFlagName(name.to_string()))?;
+                }
+                Ok(flags)
+            }
+        }
+    };
 }
 
+impl_named_flags!(PartOfSpeech);
+impl_named_flags!(Tag);
+impl_named_flags!(Dialect);
+impl_named_flags!(Domain);
+
 bitflags::bitflags! {
     ///
-    #[derive(serde::Serialize, serde::Deserialize, Clone, Copy, Debug, PartialEq, Eq, Hash)]
+    #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
     pub struct Tag: u16 {
         const NONE = 0;
         /// Word usually written using kana alone
@@ -105,13 +190,115 @@ bitflags::bitflags! {
         const RARE = 1 << 10;
         /// yojijukugo
         const YOJIJUKUGO = 1 << 11;
+        /// Spelled with ateji or gikun: kanji chosen for their sound or meaning, whose
+        /// readings don't split up per character, like 流石 for さすが
+        const ATEJI = 1 << 12;
+    }
+}
+
+bitflags::bitflags! {
+    /// Regional dialects a word belongs to, from the `dial` markers of JMdict senses.
+    /// Empty for standard Japanese.
+    #[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+    pub struct Dialect: u16 {
+        const NONE = 0;
+        /// Brazilian
+        const BRAZILIAN = 1 << 0;
+        /// Hokkaido-ben
+        const HOKKAIDO = 1 << 1;
+        /// Kansai-ben
+        const KANSAI = 1 << 2;
+        /// Kantou-ben
+        const KANTOU = 1 << 3;
+        /// Kyoto-ben
+        const KYOTO = 1 << 4;
+        /// Kyuushuu-ben
+        const KYUUSHUU = 1 << 5;
+        /// Nagano-ben
+        const NAGANO = 1 << 6;
+        /// Osaka-ben
+        const OSAKA = 1 << 7;
+        /// Ryuukyuu-ben
+        const RYUUKYUU = 1 << 8;
+        /// Touhoku-ben
+        const TOUHOKU = 1 << 9;
+        /// Tosa-ben
+        const TOSA = 1 << 10;
+        /// Tsugaru-ben
+        const TSUGARU = 1 << 11;
+    }
+}
+
+bitflags::bitflags! {
+    /// Fields of knowledge a word is used in, from the `field` markers of JMdict senses.
+    /// Empty for general vocabulary.
+    #[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+    pub struct Domain: u32 {
+        const NONE = 0;
+        /// Agriculture
+        const AGRICULTURE = 1 << 0;
+        /// Anatomy
+        const ANATOMY = 1 << 1;
+        /// Architecture
+        const ARCHITECTURE = 1 << 2;
+        /// Astronomy
+        const ASTRONOMY = 1 << 3;
+        /// Baseball
+        const BASEBALL = 1 << 4;
+        /// Biology
+        const BIOLOGY = 1 << 5;
+        /// Botany
+        const BOTANY = 1 << 6;
+        /// Buddhism
+        const BUDDHISM = 1 << 7;
+        /// Business
+        const BUSINESS = 1 << 8;
+        /// Chemistry
+        const CHEMISTRY = 1 << 9;
+        /// Computing
+        const COMPUTING = 1 << 10;
+        /// Economics
+        const ECONOMICS = 1 << 11;
+        /// Electricity, electrical engineering
+        const ELECTRICITY = 1 << 12;
+        /// Engineering
+        const ENGINEERING = 1 << 13;
+        /// Finance
+        const FINANCE = 1 << 14;
+        /// Food, cooking
+        const FOOD = 1 << 15;
+        /// Geology
+        const GEOLOGY = 1 << 16;
+        /// Grammar
+        const GRAMMAR = 1 << 17;
+        /// Law
+        const LAW = 1 << 18;
+        /// Linguistics
+        const LINGUISTICS = 1 << 19;
+        /// Mathematics
+        const MATHEMATICS = 1 << 20;
+        /// Medicine
+        const MEDICINE = 1 << 21;
+        /// Military
+        const MILITARY = 1 << 22;
+        /// Music
+        const MUSIC = 1 << 23;
+        /// Physics
+        const PHYSICS = 1 << 24;
+        /// Sports
+        const SPORTS = 1 << 25;
+        /// Sumo
+        const SUMO = 1 << 26;
+        /// Zoology
+        const ZOOLOGY = 1 << 27;
     }
 }
 
 ///
-#[derive(
-    Debug, Clone, Copy, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize,
-)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum InflectionType {
     DictionaryForm,
     Negative,
@@ -129,36 +316,1647 @@ pub enum InflectionType {
     NegativeCausative,
     Passive,
     NegativePassive,
+    Conditional,
+}
+
+/// External identifier of a dictionary entry, stored as [`DictionaryEntry::term_id`].
+pub type TermId = u32;
+
+/// Version of the rules term ids are assigned by, recorded in
+/// [`DictionaryMetadata::term_id_scheme`]. Under these rules an entry keeps its term id
+/// when the dictionary is rebuilt from a newer source:
+///
+/// - JMdict entries use their `ent_seq`, which JMdict never reuses.
+/// - Imported entries use the first free id of [`imported_term_ids`], in
+///   [`IMPORTED_TERM_IDS`].
+/// - User dictionary rows keep the ids they were written with.
+///
+/// Two entries with the same id fail the build rather than replacing one another.
+pub const TERM_ID_SCHEME: u32 = 1;
+
+/// Term ids reserved for imported entries. JMdict sequence numbers stay far below.
+pub const IMPORTED_TERM_IDS: std::ops::RangeInclusive<TermId> = 0x8000_0000..=TermId::MAX;
+
+/// Candidate term ids of an entry imported from `source` (e.g. `mecab`), all in
+/// [`IMPORTED_TERM_IDS`]. The entry takes the first one no other entry has, so its id
+/// only moves when a new entry hashes to the same id and is placed before it.
+pub fn imported_term_ids<'a>(
+    source: &'a str,
+    surface: &'a str,
+    reading: &'a str,
+) -> impl Iterator<Item = TermId> + 'a {
+    (0u64..).map(move |attempt| {
+        let mut hash = Fnv1a::new();
+        hash.write_str(source);
+        hash.write_str(surface);
+        hash.write_str(reading);
+        if attempt > 0 {
+            hash.write_u64(attempt);
+        }
+        let hash = hash.finish();
+        // Folding keeps the high bits in play.
+        let folded = (hash ^ (hash >> 32)) as u32;
+        *IMPORTED_TERM_IDS.start() | (folded & !*IMPORTED_TERM_IDS.start())
+    })
 }
 
 ///
-#[derive(Debug, serde::Serialize, serde::Deserialize, Copy, Clone)]
+#[derive(Debug, Copy, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct DictionaryEntry {
     pub term_id: u32,
     pub pos: PartOfSpeech,
     pub tag: Tag,
+    /// Term id of the transitive verb for an intransitive one and the other way around,
+    /// like 開ける and 開く.
+    pub pair_term_id: Option<TermId>,
+    /// Dialects any sense of the word belongs to.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub dialect: Dialect,
+    /// Fields any sense of the word is used in.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub domain: Domain,
+}
+
+impl DictionaryEntry {
+    /// Whether the entry is a transitive or intransitive verb, `None` when its part of
+    /// speech says neither or both.
+    pub fn transitivity(&self) -> Option<Transitivity> {
+        match (
+            self.pos.contains(PartOfSpeech::TRANSITIVE_VERB),
+            self.pos.contains(PartOfSpeech::INTRANSITIVE_VERB),
+        ) {
+            (true, false) => Some(Transitivity::Transitive),
+            (false, true) => Some(Transitivity::Intransitive),
+            _ => None,
+        }
+    }
+}
+
+/// Whether a verb takes a direct object.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Transitivity {
+    Transitive,
+    Intransitive,
+}
+
+/// Names one of the dictionaries of a tokenizer, see [`Tokenizer::with_dictionaries`].
+/// Term ids only need to be unique within a dictionary, the id tells them apart.
+///
+/// [`Tokenizer::with_dictionaries`]: crate::tokenizer::Tokenizer::with_dictionaries
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DictionaryId(pub u16);
+
+impl DictionaryId {
+    /// The dictionary of [`Tokenizer::new`], and the one entries of a dictionary on its
+    /// own belong to.
+    ///
+    /// [`Tokenizer::new`]: crate::tokenizer::Tokenizer::new
+    pub const MAIN: Self = Self(0);
 }
 
 ///
-#[derive(Debug, serde::Serialize, serde::Deserialize, Copy, Clone)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct TermEntry {
     pub entry_index: u32,
     pub inflection_type: InflectionType,
+    /// Set where the surface is the entry's kana in the other script than the one it is
+    /// listed with, see [`Dictionary::index_both_kana_scripts`]. Lattice snapshots
+    /// written before it existed read as unset.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub other_script: bool,
+    /// How irregular the spelling of the surface is for its entry, `0` for a standard
+    /// one. Scores drop by [`TokenizerOptions::irregular_form_penalty`] per point, so a
+    /// rarely used kanji form still matches but loses close calls. Lattice snapshots
+    /// written before it existed read as `0`.
+    ///
+    /// [`TokenizerOptions::irregular_form_penalty`]: crate::tokenizer::TokenizerOptions::irregular_form_penalty
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub form_penalty: u8,
+    /// Set where the surface only matched once respelled in modern kana, see
+    /// [`TokenizerOptions::fold_historical_kana`]. Not stored, so dictionary files and
+    /// lattice snapshots read back as unset.
+    ///
+    /// [`TokenizerOptions::fold_historical_kana`]: crate::tokenizer::TokenizerOptions::fold_historical_kana
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub folded: bool,
+    /// Which of a tokenizer's dictionaries the entry was found in. Not stored, so
+    /// dictionary files and lattice snapshots read back as [`DictionaryId::MAIN`].
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub dictionary: DictionaryId,
 }
 
-#[derive(serde::Serialize, serde::Deserialize, Clone)]
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Dictionary {
     pub entries: Vec<DictionaryEntry>,
-    pub kanji: HashMap<String, Vec<TermEntry>>,
-    pub kana: HashMap<String, Vec<TermEntry>>,
+    /// Kana readings of each entry (indexed like `entries`), highest priority first.
+    /// The first one is the kana base form.
+    pub readings: Vec<Vec<String>>,
+    /// Term entries of every kanji surface, in homograph order, see
+    /// [`Dictionary::sort_term_entries`].
+    pub kanji: SurfaceMap<Vec<TermEntry>>,
+    /// Term entries of every kana surface, in homograph order.
+    pub kana: SurfaceMap<Vec<TermEntry>>,
+    /// Kanji base forms of each entry (indexed like `entries`), highest priority first,
+    /// empty for kana-only words.
+    pub kanji_forms: Vec<Vec<String>>,
+    /// Kana surfaces by folded romanization, for [`Dictionary::lookup_romaji`]. Empty
+    /// unless the index was built, and then saved as an empty section.
+    pub romaji: SurfaceMap<Vec<String>>,
+    /// Term ids each entry (indexed like `entries`) refers to as "see also", for
+    /// [`Dictionary::xrefs`]. Empty unless cross-references were resolved, and then saved
+    /// as an empty section.
+    pub xrefs: Vec<Vec<TermId>>,
+}
+
+/// `surface` spelled in the other kana script, `None` unless it is all hiragana or all
+/// katakana. The long vowel mark ー is kept either way.
+fn other_kana_script(surface: &str) -> Option<String> {
+    // Distance between a hiragana and the katakana for the same sound.
+    const OFFSET: u32 = 0x60;
+    let is_hiragana = |c: char| matches!(c, 'ぁ'..='ゖ' | 'ゝ' | 'ゞ');
+    let is_katakana = |c: char| matches!(c, 'ァ'..='ヶ' | 'ヽ' | 'ヾ');
+
+    if surface.chars().all(|c| c == 'ー') {
+        return None;
+    }
+    let shift = |forward: bool| {
+        surface
+            .chars()
+            .map(|c| match c {
+                'ー' => c,
+                _ if forward => char::from_u32(c as u32 + OFFSET).unwrap_or(c),
+                _ => char::from_u32(c as u32 - OFFSET).unwrap_or(c),
+            })
+            .collect()
+    };
+    if surface.chars().all(|c| is_hiragana(c) || c == 'ー') {
+        Some(shift(true))
+    } else if surface.chars().all(|c| is_katakana(c) || c == 'ー') {
+        Some(shift(false))
+    } else {
+        None
+    }
+}
+
+/// Sort key of `term_entry` among the term entries of its surface, lowest first, see
+/// [`Dictionary::sort_term_entries`].
+fn homograph_rank(
+    entries: &[DictionaryEntry],
+    term_entry: &TermEntry,
+) -> (u8, bool, bool, u8, TermId, u32) {
+    // Term entries of missing entries, which `validate` rejects, go last.
+    let (class, term_id) = match entries.get(term_entry.entry_index as usize) {
+        None => (u8::MAX, TermId::MAX),
+        Some(entry) => {
+            let class = if entry.tag.intersects(Tag::ARCHAIC | Tag::OBSOLETE_TERM) {
+                3
+            } else if entry.pos.is_particle() {
+                0
+            } else if entry.pos.contains(PartOfSpeech::NOUN_PROPER) {
+                2
+            } else {
+                1
+            };
+            (class, entry.term_id)
+        }
+    };
+
+    (
+        term_entry.form_penalty,
+        term_entry.other_script,
+        term_entry.inflection_type != InflectionType::DictionaryForm,
+        class,
+        term_id,
+        term_entry.entry_index,
+    )
+}
+
+/// Inserts `term_entry` into the term entries of a surface after those ranking as high
+/// or higher, keeping them in homograph order.
+fn insert_ranked(
+    entries: &[DictionaryEntry],
+    term_entries: &mut Vec<TermEntry>,
+    term_entry: TermEntry,
+) {
+    let rank = homograph_rank(entries, &term_entry);
+    let index =
+        term_entries.partition_point(|other| homograph_rank(entries, other) <= rank);
+    term_entries.insert(index, term_entry);
+}
+
+///
+fn is_kanji(c: char) -> bool {
+    matches!(c as u32, 0x3005 | 0x3400..=0x4DBF | 0x4E00..=0x9FFF | 0xF900..=0xFAFF)
 }
 
 impl Dictionary {
     pub fn new() -> Self {
         Self {
             entries: Vec::new(),
-            kanji: HashMap::new(),
-            kana: HashMap::new(),
+            readings: Vec::new(),
+            kanji: SurfaceMap::default(),
+            kana: SurfaceMap::default(),
+            kanji_forms: Vec::new(),
+            romaji: SurfaceMap::default(),
+            xrefs: Vec::new(),
+        }
+    }
+
+    /// Releases the excess capacity left by building the dictionary: of the entry and
+    /// form lists, both surface maps and every [`TermEntry`] list in them.
+    pub fn shrink_to_fit(&mut self) {
+        self.entries.shrink_to_fit();
+        for lists in [&mut self.readings, &mut self.kanji_forms] {
+            lists.shrink_to_fit();
+            lists.iter_mut().for_each(Vec::shrink_to_fit);
+        }
+        self.romaji.shrink_to_fit();
+        self.romaji.values_mut().for_each(Vec::shrink_to_fit);
+        self.xrefs.shrink_to_fit();
+        self.xrefs.iter_mut().for_each(Vec::shrink_to_fit);
+        for map in [&mut self.kanji, &mut self.kana] {
+            map.shrink_to_fit();
+            map.values_mut().for_each(Vec::shrink_to_fit);
+        }
+    }
+
+    /// Keeps at most `max_per_surface` term entries under every kanji and kana surface,
+    /// those of the highest-priority entries, and returns how many were removed.
+    ///
+    /// Entries are ranked by their index, lower being more likely as in
+    /// [`Dictionary::prefix_search`]. The best entry of every distinct part of speech is
+    /// kept whatever the limit, so a particle never loses out to a pile of archaic
+    /// nouns, and a surface can keep more than `max_per_surface` entries that way.
+    /// Kept entries stay in their order.
+    ///
+    /// Pruning is lossy: lookups no longer find the removed entries under the surface,
+    /// though the entries themselves, their readings and kanji forms are kept.
+    pub fn prune(&mut self, max_per_surface: usize) -> usize {
+        let mut removed = 0;
+        for map in [&mut self.kanji, &mut self.kana] {
+            for term_entries in map.values_mut() {
+                if term_entries.len() <= max_per_surface {
+                    continue;
+                }
+
+                let mut ranked: Vec<usize> = (0..term_entries.len()).collect();
+                ranked.sort_by_key(|index| term_entries[*index].entry_index);
+                let mut keep = vec![false; term_entries.len()];
+                let mut kept = 0;
+                let mut parts_of_speech = Vec::new();
+                for index in ranked.iter() {
+                    let pos = self.entries[term_entries[*index].entry_index as usize].pos;
+                    if !parts_of_speech.contains(&pos) {
+                        parts_of_speech.push(pos);
+                        keep[*index] = true;
+                        kept += 1;
+                    }
+                }
+                for index in ranked.iter() {
+                    if kept >= max_per_surface {
+                        break;
+                    }
+                    if !keep[*index] {
+                        keep[*index] = true;
+                        kept += 1;
+                    }
+                }
+
+                removed += term_entries.len() - kept;
+                let mut keep = keep.into_iter();
+                term_entries.retain(|_| keep.next().unwrap_or(false));
+            }
+        }
+
+        removed
+    }
+
+    /// Drops the entries no surface refers to any more, like those [`Dictionary::prune`]
+    /// removed from every surface they had, along with the surfaces left without term
+    /// entries, and reports how many of each were removed.
+    ///
+    /// Kept entries stay in their order and keep their term ids, but move to lower entry
+    /// indices, which every [`TermEntry`] is rewritten to. Romaji index entries of the
+    /// removed kana surfaces are dropped too, and so are the cross-references of the
+    /// removed entries.
+    pub fn compact(&mut self) -> CompactReport {
+        let mut report = CompactReport::default();
+        for map in [&mut self.kanji, &mut self.kana] {
+            let before = map.len();
+            map.retain(|_, term_entries| !term_entries.is_empty());
+            report.keys += before - map.len();
+        }
+        let kana = &self.kana;
+        self.romaji.retain(|_, surfaces| {
+            surfaces.retain(|surface| kana.contains_key(surface));
+            !surfaces.is_empty()
+        });
+
+        let mut referenced = vec![false; self.entries.len()];
+        for term_entry in self.kanji.values().chain(self.kana.values()).flatten() {
+            referenced[term_entry.entry_index as usize] = true;
+        }
+        // New index of every kept entry.
+        let mut new_indices = Vec::with_capacity(self.entries.len());
+        let mut kept = 0;
+        for referenced in referenced.iter() {
+            new_indices.push(kept);
+            kept += *referenced as u32;
+        }
+        report.entries = self.entries.len() - kept as usize;
+        if report.entries == 0 {
+            return report;
+        }
+
+        let mut keep = referenced.iter();
+        self.entries.retain(|_| *keep.next().unwrap());
+        for lists in [&mut self.readings, &mut self.kanji_forms] {
+            let mut keep = referenced.iter();
+            lists.retain(|_| *keep.next().unwrap());
+        }
+        if !self.xrefs.is_empty() {
+            let mut keep = referenced.iter();
+            self.xrefs.retain(|_| *keep.next().unwrap());
+        }
+        for map in [&mut self.kanji, &mut self.kana] {
+            for term_entry in map.values_mut().flatten() {
+                term_entry.entry_index = new_indices[term_entry.entry_index as usize];
+            }
+        }
+
+        report
+    }
+
+    /// Also indexes every kana surface under its spelling in the other kana script, so
+    /// あるばいと finds アルバイト and フワフワ finds ふわふわ, and returns how many term
+    /// entries were added. This roughly doubles the number of kana surfaces.
+    ///
+    /// The copies are marked as [`TermEntry::other_script`], which the tokenizer scores a
+    /// little lower, see
+    /// [`TokenizerOptions::other_script_penalty`](crate::tokenizer::TokenizerOptions::other_script_penalty).
+    /// Surfaces mixing both scripts are left out, and so are entries the other spelling
+    /// already has.
+    pub fn index_both_kana_scripts(&mut self) -> usize {
+        let mut copies: Vec<(String, TermEntry)> = Vec::new();
+        for (surface, term_entries) in self.kana.iter() {
+            let Some(other) = other_kana_script(surface) else {
+                continue;
+            };
+            for term_entry in term_entries.iter().filter(|entry| !entry.other_script) {
+                let copy = TermEntry {
+                    other_script: true,
+                    ..*term_entry
+                };
+                copies.push((other.clone(), copy));
+            }
+        }
+
+        let mut added = 0;
+        for (surface, copy) in copies {
+            let term_entries = self.kana.entry(surface).or_default();
+            let indexed = term_entries.iter().any(|term_entry| {
+                term_entry.entry_index == copy.entry_index
+                    && term_entry.inflection_type == copy.inflection_type
+            });
+            if !indexed {
+                insert_ranked(&self.entries, term_entries, copy);
+                added += 1;
+            }
+        }
+
+        added
+    }
+
+    /// Appends an entry indexed under `surface_kanji` (if any) and `surface_kana`, which
+    /// also becomes its first reading, and returns its entry index.
+    pub fn add_word(
+        &mut self,
+        term_id: TermId,
+        surface_kanji: Option<&str>,
+        surface_kana: &str,
+        pos: PartOfSpeech,
+        tag: Tag,
+    ) -> u32 {
+        let entry_index = self.entries.len() as u32;
+        self.entries.push(DictionaryEntry {
+            term_id,
+            pos,
+            tag,
+            pair_term_id: None,
+            dialect: Dialect::NONE,
+            domain: Domain::NONE,
+        });
+        self.readings.push(Vec::new());
+        self.kanji_forms
+            .push(surface_kanji.into_iter().map(str::to_string).collect());
+        if !self.xrefs.is_empty() {
+            self.xrefs.push(Vec::new());
+        }
+
+        if let Some(surface_kanji) = surface_kanji {
+            Self::insert_term(
+                &self.entries,
+                &mut self.kanji,
+                surface_kanji,
+                entry_index,
+                0,
+            );
+        }
+        self.add_reading(entry_index, surface_kana);
+
+        entry_index
+    }
+
+    /// Indexes another kanji spelling, also recorded as a kanji form, for an existing
+    /// entry, with the [`TermEntry::form_penalty`] of an irregular one.
+    pub fn add_kanji_form(
+        &mut self,
+        entry_index: u32,
+        surface_kanji: &str,
+        form_penalty: u8,
+    ) {
+        Self::insert_term(
+            &self.entries,
+            &mut self.kanji,
+            surface_kanji,
+            entry_index,
+            form_penalty,
+        );
+        self.kanji_forms[entry_index as usize].push(surface_kanji.to_string());
+    }
+
+    /// Indexes another kana surface, also recorded as a reading, for an existing entry.
+    pub fn add_reading(&mut self, entry_index: u32, surface_kana: &str) {
+        Self::insert_term(&self.entries, &mut self.kana, surface_kana, entry_index, 0);
+        self.readings[entry_index as usize].push(surface_kana.to_string());
+    }
+
+    /// Indexes entry `entry_index` under `surface` in `map`, in homograph order.
+    fn insert_term(
+        entries: &[DictionaryEntry],
+        map: &mut SurfaceMap<Vec<TermEntry>>,
+        surface: &str,
+        entry_index: u32,
+        form_penalty: u8,
+    ) {
+        let term_entry = TermEntry {
+            entry_index,
+            inflection_type: InflectionType::DictionaryForm,
+            other_script: false,
+            form_penalty,
+            folded: false,
+            dictionary: DictionaryId::MAIN,
+        };
+        insert_ranked(
+            entries,
+            map.entry(surface.to_string()).or_default(),
+            term_entry,
+        );
+    }
+
+    /// Puts the term entries of every surface in homograph order, the order lookups and
+    /// the tokenizer go by when nothing else tells homographs apart:
+    ///
+    /// 1. standard spellings first, by [`TermEntry::form_penalty`], then entries in the
+    ///    kana script they are listed with before [`TermEntry::other_script`] copies;
+    /// 2. dictionary forms before conjugated forms spelled the same;
+    /// 3. particles, then other words, then proper nouns, then words tagged
+    ///    [`Tag::ARCHAIC`] or [`Tag::OBSOLETE_TERM`];
+    /// 4. lower term ids, then lower entry indices.
+    ///
+    /// Entries added through [`Dictionary::add_word`] and the like are inserted in this
+    /// order, and loading a dictionary sorts it, so this is only needed after filling the
+    /// maps directly. The sort is stable, so the forms of one entry keep their order.
+    /// [`Dictionary::best_entry_for`] gives the first entry of a surface.
+    pub fn sort_term_entries(&mut self) {
+        let entries = &self.entries;
+        for map in [&mut self.kanji, &mut self.kana] {
+            for term_entries in map.values_mut() {
+                term_entries
+                    .sort_by_key(|term_entry| homograph_rank(entries, term_entry));
+            }
+        }
+    }
+
+    /// The entry ranked first under `surface` by [`Dictionary::sort_term_entries`], the
+    /// one the tokenizer picks among homographs scoring alike. Kanji surfaces win ties
+    /// against kana ones. No deinflection is tried.
+    pub fn best_entry_for(&self, surface: &str) -> Option<LookupHit> {
+        let (term_entry, matched_via) = [
+            (&self.kanji, MatchSource::Kanji),
+            (&self.kana, MatchSource::Kana),
+        ]
+        .into_iter()
+        .filter_map(|(map, matched_via)| Some((map.get(surface)?.first()?, matched_via)))
+        .min_by_key(|(term_entry, _)| homograph_rank(&self.entries, term_entry))?;
+        let entry = self.entries.get(term_entry.entry_index as usize)?;
+
+        Some(LookupHit {
+            entry_index: term_entry.entry_index,
+            term_id: entry.term_id,
+            inflection_type: term_entry.inflection_type,
+            pos: entry.pos,
+            tag: entry.tag,
+            matched_via,
+        })
+    }
+
+    /// Checks that every index refers to an existing entry.
+    pub fn validate(&self) -> Result<(), DictionaryError> {
+        if self.readings.len() != self.entries.len() {
+            return Err(DictionaryError::Invalid(format!(
+                "{} readings lists for {} entries",
+                self.readings.len(),
+                self.entries.len()
+            )));
+        }
+        if self.kanji_forms.len() != self.entries.len() {
+            return Err(DictionaryError::Invalid(format!(
+                "{} kanji form lists for {} entries",
+                self.kanji_forms.len(),
+                self.entries.len()
+            )));
+        }
+        if !self.xrefs.is_empty() && self.xrefs.len() != self.entries.len() {
+            return Err(DictionaryError::Invalid(format!(
+                "{} cross-reference lists for {} entries",
+                self.xrefs.len(),
+                self.entries.len()
+            )));
+        }
+
+        for (surface, term_entries) in self.kanji.iter().chain(self.kana.iter()) {
+            for term_entry in term_entries.iter() {
+                if term_entry.entry_index as usize >= self.entries.len() {
+                    return Err(DictionaryError::Invalid(format!(
+                        "`{surface}` refers to missing entry {}",
+                        term_entry.entry_index
+                    )));
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Term ids used by more than one entry, sorted, each listed once.
+    pub fn duplicate_term_ids(&self) -> Vec<TermId> {
+        let mut term_ids: Vec<TermId> =
+            self.entries.iter().map(|entry| entry.term_id).collect();
+        term_ids.sort_unstable();
+        let mut duplicates: Vec<TermId> = term_ids
+            .windows(2)
+            .filter(|pair| pair[0] == pair[1])
+            .map(|pair| pair[0])
+            .collect();
+        duplicates.dedup();
+        duplicates
+    }
+
+    /// Identification of this dictionary's content, for checking stored results
+    /// against the dictionary they were produced with. Hashes the whole dictionary, so
+    /// compute it once rather than per result.
+    pub fn metadata(&self) -> DictionaryMetadata {
+        let mut hash = Fnv1a::new();
+        for (entry, readings) in self.entries.iter().zip(self.readings.iter()) {
+            hash.write_u64(entry.term_id as u64);
+            hash.write_u64(entry.pos.bits() as u64);
+            hash.write_u64(entry.tag.bits() as u64);
+            hash.write_u64(entry.pair_term_id.map_or(0, |term_id| term_id as u64 + 1));
+            hash.write_u64(entry.dialect.bits() as u64);
+            hash.write_u64(entry.domain.bits() as u64);
+            hash.write_u64(readings.len() as u64);
+            for reading in readings {
+                hash.write_str(reading);
+            }
+        }
+        for kanji_forms in self.kanji_forms.iter() {
+            hash.write_u64(kanji_forms.len() as u64);
+            for kanji_form in kanji_forms {
+                hash.write_str(kanji_form);
+            }
+        }
+        for xrefs in self.xrefs.iter() {
+            hash.write_u64(xrefs.len() as u64);
+            for term_id in xrefs {
+                hash.write_u64(*term_id as u64);
+            }
+        }
+
+        for map in [&self.kanji, &self.kana] {
+            let mut surfaces: Vec<_> = map.iter().collect();
+            surfaces.sort_unstable_by_key(|(surface, _)| surface.as_str());
+            hash.write_u64(surfaces.len() as u64);
+            for (surface, term_entries) in surfaces {
+                hash.write_str(surface);
+                hash.write_u64(term_entries.len() as u64);
+                for term_entry in term_entries {
+                    hash.write_u64(term_entry.entry_index as u64);
+                    hash.write_u64(term_entry.inflection_type as u64);
+                    hash.write_u64(term_entry.other_script as u64);
+                    hash.write_u64(term_entry.form_penalty as u64);
+                }
+            }
+        }
+
+        DictionaryMetadata {
+            format_version: DictionaryHeader::VERSION,
+            entry_count: self.entries.len() as u64,
+            fingerprint: hash.finish(),
+            term_id_scheme: TERM_ID_SCHEME,
+        }
+    }
+
+    /// Estimated heap usage per section. The numbers follow capacities rather than
+    /// allocator internals, so they are only approximate but grow with the dictionary.
+    pub fn memory_report(&self) -> MemoryReport {
+        use std::mem::size_of;
+
+        // Buckets of the std HashMap hold the key/value pair plus one control byte.
+        let bucket = size_of::<String>() + size_of::<Vec<TermEntry>>() + 1;
+        let keys = |map: &SurfaceMap<Vec<TermEntry>>| {
+            map.capacity() * bucket + map.keys().map(String::capacity).sum::<usize>()
+        };
+        let term_entries = |map: &SurfaceMap<Vec<TermEntry>>| {
+            map.values().map(Vec::capacity).sum::<usize>() * size_of::<TermEntry>()
+        };
+
+        let strings = |lists: &Vec<Vec<String>>| {
+            lists.capacity() * size_of::<Vec<String>>()
+                + lists
+                    .iter()
+                    .flatten()
+                    .map(|string| size_of::<String>() + string.capacity())
+                    .sum::<usize>()
+        };
+
+        let romaji_bucket = size_of::<String>() + size_of::<Vec<String>>() + 1;
+        let romaji_index = self.romaji.capacity() * romaji_bucket
+            + self
+                .romaji
+                .iter()
+                .map(|(romaji, kana)| {
+                    romaji.capacity()
+                        + kana.capacity() * size_of::<String>()
+                        + kana.iter().map(String::capacity).sum::<usize>()
+                })
+                .sum::<usize>();
+
+        let xrefs = self.xrefs.capacity() * size_of::<Vec<TermId>>()
+            + self.xrefs.iter().map(Vec::capacity).sum::<usize>() * size_of::<TermId>();
+
+        MemoryReport {
+            entries: self.entries.capacity() * size_of::<DictionaryEntry>(),
+            readings: strings(&self.readings),
+            kanji_forms: strings(&self.kanji_forms),
+            romaji_index,
+            xrefs,
+            kanji_keys: keys(&self.kanji),
+            kana_keys: keys(&self.kana),
+            term_entries: term_entries(&self.kanji) + term_entries(&self.kana),
+        }
+    }
+
+    /// Writes the dictionary to `path` in the versioned binary format, whose sections
+    /// are bincode and so need the `serde` feature like every method reading them.
+    /// Surfaces are written in order, so equal dictionaries make identical files.
+    #[cfg(feature = "serde")]
+    pub fn save(
+        &self,
+        path: impl AsRef<Path>,
+    ) -> Result<DictionaryHeader, DictionaryError> {
+        let mut writer = std::io::BufWriter::new(std::fs::File::create(path)?);
+        let header = self.write_to(&mut writer)?;
+        writer.flush()?;
+
+        Ok(header)
+    }
+
+    /// Writes the same format as [`Dictionary::save`] to any writer.
+    #[cfg(feature = "serde")]
+    pub fn write_to(
+        &self,
+        writer: &mut impl Write,
+    ) -> Result<DictionaryHeader, DictionaryError> {
+        let sections = [
+            bincode::serialize(&self.entries)?,
+            bincode::serialize(&self.readings)?,
+            serialize_sorted(&self.kanji)?,
+            serialize_sorted(&self.kana)?,
+            bincode::serialize(&self.kanji_forms)?,
+            // Left empty rather than holding an empty map, so the index costs nothing
+            // when it wasn't built.
+            if self.romaji.is_empty() {
+                Vec::new()
+            } else {
+                serialize_sorted(&self.romaji)?
+            },
+            if self.xrefs.is_empty() {
+                Vec::new()
+            } else {
+                bincode::serialize(&self.xrefs)?
+            },
+        ];
+        let header = DictionaryHeader {
+            version: DictionaryHeader::VERSION,
+            section_lengths: sections.each_ref().map(|section| section.len() as u64),
+        };
+
+        header.write(writer)?;
+        for section in sections.iter() {
+            writer.write_all(section)?;
+        }
+
+        Ok(header)
+    }
+
+    /// The bytes [`Dictionary::save`] writes to a file, for [`Dictionary::from_bytes`].
+    #[cfg(feature = "serde")]
+    pub fn to_bytes(&self) -> Result<Vec<u8>, DictionaryError> {
+        let mut bytes = Vec::new();
+        self.write_to(&mut bytes)?;
+        Ok(bytes)
+    }
+
+    /// Reads a dictionary written by [`Dictionary::save`], with its term entries in
+    /// homograph order, see [`Dictionary::sort_term_entries`].
+    #[cfg(feature = "serde")]
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, DictionaryError> {
+        let file = std::fs::File::open(path)?;
+        Self::from_reader(std::io::BufReader::new(file))
+    }
+
+    /// Reads a dictionary from an in-memory copy of a saved file, e.g. an embedded asset.
+    ///
+    /// Sections are deserialized straight from `bytes` without copying them first. The
+    /// dictionary owns all of its strings, so nothing can borrow from `bytes`.
+    #[cfg(feature = "serde")]
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, DictionaryError> {
+        let mut reader = bytes;
+        let header = DictionaryHeader::read(&mut reader)?;
+
+        let mut sections = [&[][..]; DictionaryHeader::SECTION_COUNT];
+        for (section, length) in sections.iter_mut().zip(header.section_lengths) {
+            let length =
+                usize::try_from(length).map_err(|_| DictionaryError::Truncated)?;
+            if reader.len() < length {
+                return Err(DictionaryError::Truncated);
+            }
+            (*section, reader) = reader.split_at(length);
+        }
+
+        let mut dictionary = Self {
+            entries: bincode::deserialize(sections[0])?,
+            readings: bincode::deserialize(sections[1])?,
+            kanji: bincode::deserialize(sections[2])?,
+            kana: bincode::deserialize(sections[3])?,
+            kanji_forms: bincode::deserialize(sections[4])?,
+            romaji: match sections[5] {
+                [] => SurfaceMap::default(),
+                section => bincode::deserialize(section)?,
+            },
+            xrefs: match sections[6] {
+                [] => Vec::new(),
+                section => bincode::deserialize(section)?,
+            },
+        };
+        dictionary.sort_term_entries();
+
+        Ok(dictionary)
+    }
+
+    /// Reads a dictionary from a stream, one section at a time.
+    ///
+    /// Each section is deserialized while it is being read, so the file is never
+    /// buffered as a whole.
+    #[cfg(feature = "serde")]
+    pub fn from_reader(mut reader: impl Read) -> Result<Self, DictionaryError> {
+        let header = DictionaryHeader::read(&mut reader)?;
+        let [entries, readings, kanji, kana, kanji_forms, romaji, xrefs] =
+            header.section_lengths;
+
+        let mut dictionary = Self {
+            entries: read_section(&mut reader, entries)?,
+            readings: read_section(&mut reader, readings)?,
+            kanji: read_section(&mut reader, kanji)?,
+            kana: read_section(&mut reader, kana)?,
+            kanji_forms: read_section(&mut reader, kanji_forms)?,
+            romaji: match romaji {
+                0 => SurfaceMap::default(),
+                romaji => read_section(&mut reader, romaji)?,
+            },
+            xrefs: match xrefs {
+                0 => Vec::new(),
+                xrefs => read_section(&mut reader, xrefs)?,
+            },
+        };
+        dictionary.sort_term_entries();
+
+        Ok(dictionary)
+    }
+
+    /// Every entry `surface` can stand for: exact matches in the kanji and kana maps,
+    /// then entries of the base forms [`deinflect`] suggests.
+    ///
+    /// Hits are ordered by [`MatchSource`], deinflected ones nearest to the surface
+    /// first, then by entry index. An entry appears once per match source.
+    ///
+    /// [`deinflect`]: crate::deinflect::deinflect
+    pub fn lookup_detailed(&self, surface: &str) -> Vec<LookupHit> {
+        let mut hits: Vec<LookupHit> = Vec::new();
+        let mut push = |term_entry: &TermEntry, matched_via: MatchSource| {
+            let Some(entry) = self.entries.get(term_entry.entry_index as usize) else {
+                return;
+            };
+            let seen = hits.iter().any(|hit| {
+                hit.entry_index == term_entry.entry_index
+                    && hit.matched_via == matched_via
+            });
+            if !seen {
+                hits.push(LookupHit {
+                    entry_index: term_entry.entry_index,
+                    term_id: entry.term_id,
+                    inflection_type: term_entry.inflection_type,
+                    pos: entry.pos,
+                    tag: entry.tag,
+                    matched_via,
+                });
+            }
+        };
+
+        for (map, matched_via) in [
+            (&self.kanji, MatchSource::Kanji),
+            (&self.kana, MatchSource::Kana),
+        ] {
+            for term_entry in map.get(surface).into_iter().flatten() {
+                push(term_entry, matched_via.clone());
+            }
+        }
+
+        // Deinflected hits are kept in deinflection order and only sorted by entry
+        // index within one base form.
+        for deinflection in crate::deinflect::deinflect(surface) {
+            let candidates = self.kanji.get(&deinflection.term).into_iter().flatten();
+            let mut candidates: Vec<&TermEntry> = candidates
+                .chain(self.kana.get(&deinflection.term).into_iter().flatten())
+                .collect();
+            candidates.sort_by_key(|term_entry| term_entry.entry_index);
+
+            for candidate in candidates {
+                let Some(entry) = self.entries.get(candidate.entry_index as usize) else {
+                    continue;
+                };
+                if deinflection.accepts(entry.pos) {
+                    let term_entry = TermEntry {
+                        entry_index: candidate.entry_index,
+                        inflection_type: deinflection.inflection,
+                        ..*candidate
+                    };
+                    let base = deinflection.term.clone();
+                    push(&term_entry, MatchSource::Deinflected { base });
+                }
+            }
         }
+
+        hits.sort_by_key(|hit| match hit.matched_via {
+            MatchSource::Kanji => (0, hit.entry_index),
+            MatchSource::Kana => (1, hit.entry_index),
+            MatchSource::Deinflected { .. } => (2, 0),
+            MatchSource::Romaji { .. } => (3, hit.entry_index),
+        });
+        hits
+    }
+
+    /// Indexes the folded romanization of every kana surface, see
+    /// [`fold_romaji`](crate::romaji::fold_romaji), replacing any previous index.
+    #[cfg(feature = "romaji")]
+    pub fn build_romaji_index(&mut self) {
+        use crate::romaji::{fold_romaji, kana_to_romaji};
+
+        self.romaji.clear();
+        for kana in self.kana.keys() {
+            let romaji = fold_romaji(&kana_to_romaji(kana));
+            if !romaji.is_empty() {
+                self.romaji.entry(romaji).or_default().push(kana.clone());
+            }
+        }
+        // Key order of the kana map isn't stable.
+        self.romaji
+            .values_mut()
+            .for_each(|kana| kana.sort_unstable());
+    }
+
+    /// Entries with a kana surface romanized like `romaji`, typed without an IME.
+    ///
+    /// Both sides are folded by [`fold_romaji`](crate::romaji::fold_romaji), so
+    /// "toukyou", "tōkyō" and "tokyo" all find とうきょう. Hits are ordered by entry
+    /// index, one per entry. Empty unless [`Dictionary::build_romaji_index`] ran.
+    #[cfg(feature = "romaji")]
+    pub fn lookup_romaji(&self, romaji: &str) -> Vec<LookupHit> {
+        let romaji = crate::romaji::fold_romaji(romaji);
+        let mut hits: Vec<LookupHit> = Vec::new();
+        for kana in self.romaji.get(&romaji).into_iter().flatten() {
+            for term_entry in self.kana.get(kana).into_iter().flatten() {
+                let Some(entry) = self.entries.get(term_entry.entry_index as usize)
+                else {
+                    continue;
+                };
+                if hits
+                    .iter()
+                    .any(|hit| hit.entry_index == term_entry.entry_index)
+                {
+                    continue;
+                }
+                hits.push(LookupHit {
+                    entry_index: term_entry.entry_index,
+                    term_id: entry.term_id,
+                    inflection_type: term_entry.inflection_type,
+                    pos: entry.pos,
+                    tag: entry.tag,
+                    matched_via: MatchSource::Romaji { kana: kana.clone() },
+                });
+            }
+        }
+
+        hits.sort_by_key(|hit| hit.entry_index);
+        hits
+    }
+
+    /// Up to `limit` kanji and kana surfaces starting with `prefix`, highest priority
+    /// first, for autocompletion.
+    ///
+    /// A surface's priority is that of its earliest entry, since entries keep dictionary
+    /// order; ties are ordered by surface. The maps have no ordered index, so this scans
+    /// every key and takes time linear in the size of the dictionary.
+    pub fn prefix_search(&self, prefix: &str, limit: usize) -> Vec<PrefixHit<'_>> {
+        // The same surface can be a key of both maps.
+        let mut priorities: HashMap<&str, u32> = HashMap::new();
+        for (surface, term_entries) in self.kanji.iter().chain(self.kana.iter()) {
+            if !surface.starts_with(prefix) {
+                continue;
+            }
+            let entry_indices =
+                term_entries.iter().map(|term_entry| term_entry.entry_index);
+            let Some(priority) = entry_indices.min() else {
+                continue;
+            };
+
+            let best = priorities.entry(surface).or_insert(priority);
+            *best = (*best).min(priority);
+        }
+
+        let mut hits: Vec<PrefixHit> = priorities
+            .into_iter()
+            .map(|(surface, priority)| PrefixHit { surface, priority })
+            .collect();
+        hits.sort_unstable_by(|a, b| {
+            (a.priority, a.surface).cmp(&(b.priority, b.surface))
+        });
+        hits.truncate(limit);
+        hits
+    }
+
+    /// A [`SurfaceTrie`] of the kanji and kana surfaces as they are now, for
+    /// [`Dictionary::common_prefix_search`].
+    pub fn surface_trie(&self) -> SurfaceTrie {
+        SurfaceTrie::new(self.kanji.keys().chain(self.kana.keys()))
+    }
+
+    /// Every kanji and kana surface `text` starts with, shortest first, as its length in
+    /// bytes and its term entries. A surface that is a key of both maps comes up twice,
+    /// its kanji entries first.
+    ///
+    /// `trie` has to be the [`Dictionary::surface_trie`] of this dictionary. The maps
+    /// are public and can change after it is built, so the dictionary doesn't keep one.
+    pub fn common_prefix_search<'a>(
+        &'a self,
+        trie: &'a SurfaceTrie,
+        text: &'a str,
+    ) -> impl Iterator<Item = (usize, &'a [TermEntry])> + 'a {
+        self.common_prefix_matches(trie, text)
+            .map(|(length, _, term_entries)| (length, term_entries))
+    }
+
+    /// [`Dictionary::common_prefix_search`], along with whether each match is from the
+    /// kana map.
+    pub(crate) fn common_prefix_matches<'a>(
+        &'a self,
+        trie: &'a SurfaceTrie,
+        text: &'a str,
+    ) -> impl Iterator<Item = (usize, bool, &'a [TermEntry])> + 'a {
+        trie.common_prefixes(text).flat_map(move |length| {
+            let surface = &text[..length];
+            [
+                (false, self.kanji.get(surface)),
+                (true, self.kana.get(surface)),
+            ]
+            .into_iter()
+            .filter_map(move |(kana, term_entries)| {
+                Some((length, kana, term_entries?.as_slice()))
+            })
+        })
+    }
+
+    /// Readings of every entry indexed under the kanji `surface`, without duplicates.
+    ///
+    /// Readings are ordered by priority within each entry, and entries keep their
+    /// dictionary order, so the first candidate is the most likely one.
+    pub fn readings_for(&self, surface: &str) -> Vec<&str> {
+        let mut readings: Vec<&str> = Vec::new();
+        for term_entry in self.kanji.get(surface).into_iter().flatten() {
+            let entry_readings = self.readings.get(term_entry.entry_index as usize);
+            for reading in entry_readings.into_iter().flatten() {
+                if !readings.contains(&reading.as_str()) {
+                    readings.push(reading);
+                }
+            }
+        }
+
+        readings
+    }
+
+    /// Base form of the entry with `term_id` in the script of `sample_surface`, the
+    /// surface it was matched as: its first kanji form when the sample has kanji, so
+    /// 食べた gives 食べる, and its kana base form otherwise, so たべた gives たべる.
+    ///
+    /// Entries lacking a form in that script give the other one. `None` when no entry
+    /// has `term_id`; if several do, the first one is used.
+    pub fn lemma_matching_script(
+        &self,
+        term_id: TermId,
+        sample_surface: &str,
+    ) -> Option<&str> {
+        let entry_index = self
+            .entries
+            .iter()
+            .position(|entry| entry.term_id == term_id)?;
+        let kanji = self
+            .kanji_forms
+            .get(entry_index)
+            .and_then(|forms| forms.first());
+        let kana = self
+            .readings
+            .get(entry_index)
+            .and_then(|forms| forms.first());
+
+        let (preferred, other) = if sample_surface.chars().any(is_kanji) {
+            (kanji, kana)
+        } else {
+            (kana, kanji)
+        };
+        preferred.or(other).map(String::as_str)
+    }
+
+    /// Term ids of the entries the entry with `term_id` refers to as "see also", in
+    /// JMdict order. Empty unless cross-references were resolved when the dictionary was
+    /// built, or when no entry has `term_id`; if several do, the first one is used.
+    pub fn xrefs(&self, term_id: TermId) -> &[TermId] {
+        self.entries
+            .iter()
+            .position(|entry| entry.term_id == term_id)
+            .and_then(|entry_index| self.xrefs.get(entry_index))
+            .map_or(&[], Vec::as_slice)
+    }
+}
+
+impl Default for Dictionary {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Only the size of each part, since a full dictionary is far too large to print.
+impl std::fmt::Debug for Dictionary {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Dictionary")
+            .field("entries", &self.entries.len())
+            .field(
+                "readings",
+                &self.readings.iter().map(Vec::len).sum::<usize>(),
+            )
+            .field(
+                "kanji_forms",
+                &self.kanji_forms.iter().map(Vec::len).sum::<usize>(),
+            )
+            .field("kanji", &self.kanji.len())
+            .field("kana", &self.kana.len())
+            .finish()
+    }
+}
+
+/// Deserializes one section of `length` bytes, leaving `reader` at the next one.
+#[cfg(feature = "serde")]
+fn read_section<T: serde::de::DeserializeOwned>(
+    reader: &mut impl Read,
+    length: u64,
+) -> Result<T, DictionaryError> {
+    let mut section = reader.take(length);
+    let value =
+        bincode::deserialize_from(&mut section).map_err(|error| match *error {
+            bincode::ErrorKind::Io(error)
+                if error.kind() == std::io::ErrorKind::UnexpectedEof =>
+            {
+                DictionaryError::Truncated
+            }
+            _ => DictionaryError::Serialization(error),
+        })?;
+
+    // Skip whatever the section holds beyond the value, as `from_bytes` does.
+    std::io::copy(&mut section, &mut std::io::sink())?;
+    if section.limit() > 0 {
+        return Err(DictionaryError::Truncated);
+    }
+
+    Ok(value)
+}
+
+/// What [`Dictionary::compact`] removed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CompactReport {
+    /// Entries no surface referred to.
+    pub entries: usize,
+    /// Kanji and kana surfaces without term entries.
+    pub keys: usize,
+}
+
+/// Approximate heap usage of a [`Dictionary`] in bytes, per section.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MemoryReport {
+    pub entries: usize,
+    pub readings: usize,
+    pub kanji_forms: usize,
+    /// Kanji map buckets plus key bytes.
+    pub kanji_keys: usize,
+    /// Kana map buckets plus key bytes.
+    pub kana_keys: usize,
+    /// TermEntry vectors of both maps.
+    pub term_entries: usize,
+    /// Romaji map buckets plus key and kana bytes.
+    pub romaji_index: usize,
+    /// Cross-reference lists.
+    pub xrefs: usize,
+}
+
+impl MemoryReport {
+    ///
+    pub fn total(&self) -> usize {
+        self.entries
+            + self.readings
+            + self.kanji_forms
+            + self.kanji_keys
+            + self.kana_keys
+            + self.term_entries
+            + self.romaji_index
+            + self.xrefs
+    }
+}
+
+impl std::fmt::Display for MemoryReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let rows = [
+            ("entries", self.entries),
+            ("readings", self.readings),
+            ("kanji forms", self.kanji_forms),
+            ("kanji keys", self.kanji_keys),
+            ("kana keys", self.kana_keys),
+            ("term entries", self.term_entries),
+            ("romaji index", self.romaji_index),
+            ("xrefs", self.xrefs),
+            ("total", self.total()),
+        ];
+        for (name, bytes) in rows {
+            writeln!(
+                f,
+                "{name:>12}: {:>10.2} MiB",
+                bytes as f64 / (1024.0 * 1024.0)
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// One entry found by [`Dictionary::lookup_detailed`] or `Dictionary::lookup_romaji`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct LookupHit {
+    pub entry_index: u32,
+    pub term_id: TermId,
+    /// How the looked-up surface inflects the entry.
+    pub inflection_type: InflectionType,
+    pub pos: PartOfSpeech,
+    pub tag: Tag,
+    pub matched_via: MatchSource,
+}
+
+/// How a [`LookupHit`] was found, in order of priority.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum MatchSource {
+    /// The surface is one of the entry's kanji surfaces.
+    Kanji,
+    /// The surface is one of the entry's kana surfaces.
+    Kana,
+    /// The surface deinflects to `base`, a surface of the entry.
+    Deinflected { base: String },
+    /// The romaji folds like the romanization of `kana`, a kana surface of the entry.
+    Romaji { kana: String },
+}
+
+/// One surface found by [`Dictionary::prefix_search`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PrefixHit<'a> {
+    pub surface: &'a str,
+    /// Index of the earliest entry with this surface; lower is more likely.
+    pub priority: u32,
+}
+
+/// The kanji and kana surfaces of a [`Dictionary`] as a trie over their characters,
+/// finding every surface a text starts with in one walk, where probing the maps takes
+/// one lookup per prefix.
+///
+/// Nodes are stored breadth first, the children of a node next to each other and sorted
+/// by character, so a step down is a binary search.
+#[derive(Debug, Clone)]
+pub struct SurfaceTrie {
+    /// The root, which has no character, first.
+    nodes: Vec<TrieNode>,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct TrieNode {
+    label: char,
+    first_child: u32,
+    child_count: u32,
+    /// Whether the characters down to this node are a surface.
+    terminal: bool,
+}
+
+impl SurfaceTrie {
+    /// Trie of `surfaces`, in any order and with any repeats.
+    pub fn new<'s>(surfaces: impl IntoIterator<Item = &'s String>) -> Self {
+        let mut surfaces: Vec<&str> = surfaces.into_iter().map(String::as_str).collect();
+        surfaces.sort_unstable();
+        surfaces.dedup();
+
+        let root = TrieNode {
+            label: '\0',
+            first_child: 0,
+            child_count: 0,
+            terminal: false,
+        };
+        let mut nodes = vec![root];
+        // Nodes still without children, with the surfaces below them and the length in
+        // bytes of the prefix they stand for. The surfaces sharing a prefix are next to
+        // each other, the prefix itself first.
+        let mut pending = std::collections::VecDeque::from([(0, &surfaces[..], 0)]);
+        while let Some((node, mut below, depth)) = pending.pop_front() {
+            if below.first().is_some_and(|surface| surface.len() == depth) {
+                nodes[node].terminal = true;
+                below = &below[1..];
+            }
+
+            let first_child = nodes.len();
+            while let Some(surface) = below.first() {
+                let label = surface[depth..].chars().next().unwrap_or_default();
+                let end =
+                    below.partition_point(|other| other[depth..].starts_with(label));
+                nodes.push(TrieNode { label, ..root });
+                pending.push_back((
+                    nodes.len() - 1,
+                    &below[..end],
+                    depth + label.len_utf8(),
+                ));
+                below = &below[end..];
+            }
+            nodes[node].first_child = first_child as u32;
+            nodes[node].child_count = (nodes.len() - first_child) as u32;
+        }
+
+        Self { nodes }
+    }
+
+    /// Length in bytes of every surface `text` starts with, shortest first.
+    pub fn common_prefixes<'t>(
+        &'t self,
+        text: &'t str,
+    ) -> impl Iterator<Item = usize> + 't {
+        let mut node = 0;
+        text.char_indices()
+            .map_while(move |(offset, c)| {
+                let parent = &self.nodes[node];
+                let first_child = parent.first_child as usize;
+                let children = &self.nodes[first_child..][..parent.child_count as usize];
+                let index = children
+                    .binary_search_by_key(&c, |child| child.label)
+                    .ok()?;
+                node = first_child + index;
+                Some((offset + c.len_utf8(), self.nodes[node].terminal))
+            })
+            .filter_map(|(length, terminal)| terminal.then_some(length))
+    }
+}
+
+/// What [`Dictionary::metadata`] identifies a dictionary by.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DictionaryMetadata {
+    /// [`DictionaryHeader::VERSION`] of the library that computed the metadata.
+    pub format_version: u32,
+    pub entry_count: u64,
+    /// Hash of the entries, readings and surface maps, stable across runs and platforms.
+    pub fingerprint: u64,
+    /// [`TERM_ID_SCHEME`] of the library that computed the metadata. Term ids of
+    /// dictionaries with the same scheme can be compared across rebuilds.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub term_id_scheme: u32,
+}
+
+/// 64-bit FNV-1a, used where a hash has to stay the same between runs.
+pub(crate) struct Fnv1a(u64);
+
+impl Fnv1a {
+    pub(crate) fn new() -> Self {
+        Self(0xcbf2_9ce4_8422_2325)
+    }
+
+    pub(crate) fn write(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.0 ^= *byte as u64;
+            self.0 = self.0.wrapping_mul(0x0100_0000_01b3);
+        }
+    }
+
+    pub(crate) fn write_u64(&mut self, value: u64) {
+        self.write(&value.to_le_bytes());
+    }
+
+    /// Length-prefixed, so consecutive strings can't run into each other.
+    pub(crate) fn write_str(&mut self, value: &str) {
+        self.write_u64(value.len() as u64);
+        self.write(value.as_bytes());
+    }
+
+    pub(crate) fn finish(&self) -> u64 {
+        self.0
+    }
+}
+
+/// Builds the hashers of [`SurfaceMap`]s: FxHash with the `fast-hash` feature, the
+/// standard library's SipHash otherwise.
+///
+/// FxHash is several times faster on the short keys looked up during tokenization, but
+/// it isn't keyed, so whoever picks the keys of a map can make them all collide. The
+/// tokenizer only looks keys up; keys come from building the dictionary, so only enable
+/// the feature where untrusted input can't add words, e.g. through user dictionaries.
+#[cfg(feature = "fast-hash")]
+pub type SurfaceHasher = std::hash::BuildHasherDefault<FxHasher>;
+/// Builds the hashers of [`SurfaceMap`]s: FxHash with the `fast-hash` feature, the
+/// standard library's SipHash otherwise.
+#[cfg(not(feature = "fast-hash"))]
+pub type SurfaceHasher = std::collections::hash_map::RandomState;
+
+/// The maps of [`Dictionary`] from surfaces to what they stand for.
+pub type SurfaceMap<V> = HashMap<String, V, SurfaceHasher>;
+
+/// `map` serialized with its keys in order. Bincode writes the pairs of a map in
+/// iteration order, which for a hash map changes from run to run, and reads them back
+/// in any order, so saving the same dictionary always writes the same bytes.
+#[cfg(feature = "serde")]
+fn serialize_sorted<V: serde::Serialize>(
+    map: &SurfaceMap<V>,
+) -> Result<Vec<u8>, bincode::Error> {
+    let sorted: std::collections::BTreeMap<&String, &V> = map.iter().collect();
+    bincode::serialize(&sorted)
+}
+
+/// The multiply-rotate hash rustc uses for its own tables, fast for short keys but not
+/// resistant to chosen collisions, see [`SurfaceHasher`].
+#[derive(Debug, Clone, Copy, Default)]
+pub struct FxHasher {
+    hash: u64,
+}
+
+impl FxHasher {
+    const SEED: u64 = 0x51_7c_c1_b7_27_22_0a_95;
+
+    ///
+    fn add(&mut self, word: u64) {
+        self.hash = (self.hash.rotate_left(5) ^ word).wrapping_mul(Self::SEED);
+    }
+}
+
+impl std::hash::Hasher for FxHasher {
+    fn write(&mut self, bytes: &[u8]) {
+        let mut words = bytes.chunks_exact(8);
+        for word in &mut words {
+            self.add(u64::from_le_bytes(word.try_into().unwrap()));
+        }
+
+        let rest = words.remainder();
+        if !rest.is_empty() {
+            let mut word = [0u8; 8];
+            word[..rest.len()].copy_from_slice(rest);
+            self.add(u64::from_le_bytes(word));
+        }
+    }
+
+    fn write_u8(&mut self, value: u8) {
+        self.add(value as u64);
+    }
+
+    fn write_u64(&mut self, value: u64) {
+        self.add(value);
+    }
+
+    fn write_usize(&mut self, value: usize) {
+        self.add(value as u64);
+    }
+
+    fn finish(&self) -> u64 {
+        // The multiplication leaves the low bits, which pick the bucket, depending on the
+        // low input bits only, and UTF-8 lead bytes hardly vary there. The rotation
+        // brings the well-mixed high bits down.
+        self.hash.rotate_left(26)
+    }
+}
+
+/// Fixed-size header at the start of a saved dictionary.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DictionaryHeader {
+    pub version: u32,
+    /// Serialized byte length of the entries, readings, kanji, kana, kanji forms, romaji
+    /// index and xrefs sections. The last two are empty if they weren't built.
+    pub section_lengths: [u64; Self::SECTION_COUNT],
+}
+
+impl DictionaryHeader {
+    const MAGIC: [u8; 8] = *b"JPSEGDIC";
+    /// Bumped whenever the serialized layout changes (2: `DictionaryEntry::pair_term_id`,
+    /// 3: the kanji forms section, 4: the romaji index section, 5:
+    /// `TermEntry::other_script`, 6: `TermEntry::form_penalty`, 7:
+    /// `DictionaryEntry::dialect`, 8: `DictionaryEntry::domain`, 9: the xrefs section).
+    pub const VERSION: u32 = 9;
+    ///
+    pub const SECTION_COUNT: usize = 7;
+
+    ///
+    #[cfg(feature = "serde")]
+    fn write(&self, writer: &mut impl Write) -> std::io::Result<()> {
+        writer.write_all(&Self::MAGIC)?;
+        writer.write_all(&self.version.to_le_bytes())?;
+        for length in self.section_lengths {
+            writer.write_all(&length.to_le_bytes())?;
+        }
+        Ok(())
+    }
+
+    /// Reads and validates a header.
+    pub fn read(reader: &mut impl Read) -> Result<Self, DictionaryError> {
+        let truncated = |error: std::io::Error| match error.kind() {
+            std::io::ErrorKind::UnexpectedEof => DictionaryError::Truncated,
+            _ => DictionaryError::Io(error),
+        };
+
+        let mut magic = [0u8; 8];
+        reader.read_exact(&mut magic).map_err(truncated)?;
+        if magic != Self::MAGIC {
+            return Err(DictionaryError::NotADictionary);
+        }
+
+        let mut version = [0u8; 4];
+        reader.read_exact(&mut version).map_err(truncated)?;
+        let version = u32::from_le_bytes(version);
+        if version != Self::VERSION {
+            return Err(DictionaryError::UnsupportedVersion(version));
+        }
+
+        let mut section_lengths = [0u64; Self::SECTION_COUNT];
+        for length in section_lengths.iter_mut() {
+            let mut bytes = [0u8; 8];
+            reader.read_exact(&mut bytes).map_err(truncated)?;
+            *length = u64::from_le_bytes(bytes);
+        }
+
+        Ok(Self {
+            version,
+            section_lengths,
+        })
+    }
+}
+
+///
+#[derive(Debug)]
+pub enum DictionaryError {
+    Io(std::io::Error),
+    /// The data does not start with the dictionary magic number.
+    NotADictionary,
+    /// The file was written with a different format version.
+    UnsupportedVersion(u32),
+    /// The data ends before the sections announced in the header.
+    Truncated,
+    /// A section failed to (de)serialize.
+    #[cfg(feature = "serde")]
+    Serialization(bincode::Error),
+    /// The sections deserialized but are inconsistent with each other.
+    Invalid(String),
+}
+
+impl std::fmt::Display for DictionaryError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Io(error) => write!(f, "dictionary io error: {error}"),
+            Self::NotADictionary => write!(f, "not a dictionary file"),
+            Self::UnsupportedVersion(version) => write!(
+                f,
+                "unsupported dictionary version {version} (expected {})",
+                DictionaryHeader::VERSION
+            ),
+            Self::Truncated => write!(f, "dictionary data is truncated"),
+            #[cfg(feature = "serde")]
+            Self::Serialization(error) => {
+                write!(f, "malformed dictionary section: {error}")
+            }
+            Self::Invalid(reason) => write!(f, "invalid dictionary: {reason}"),
+        }
+    }
+}
+
+impl std::error::Error for DictionaryError {}
+
+impl From<std::io::Error> for DictionaryError {
+    fn from(error: std::io::Error) -> Self {
+        Self::Io(error)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl From<bincode::Error> for DictionaryError {
+    fn from(error: bincode::Error) -> Self {
+        Self::Serialization(error)
+    }
+}
+
+/// Builds a [`Dictionary`] entry by entry, keeping entry indices consistent.
+///
+/// Words are inserted as given: no conjugated forms are generated. Useful for user
+/// dictionaries and test fixtures.
+pub struct DictionaryBuilder {
+    dictionary: Dictionary,
+}
+
+impl DictionaryBuilder {
+    ///
+    pub fn new() -> Self {
+        Self {
+            dictionary: Dictionary::new(),
+        }
+    }
+
+    /// Adds a word indexed under `surface_kanji` (if any) and `surface_kana`, which is
+    /// also recorded as its reading. Term ids are assigned sequentially from zero.
+    pub fn add(
+        &mut self,
+        surface_kanji: Option<&str>,
+        surface_kana: &str,
+        pos: PartOfSpeech,
+        tag: Tag,
+    ) -> TermId {
+        let term_id = self.dictionary.entries.len() as TermId;
+        self.dictionary
+            .add_word(term_id, surface_kanji, surface_kana, pos, tag);
+
+        term_id
+    }
+
+    /// Adds another kana surface and reading to an already added word.
+    pub fn add_reading(&mut self, term_id: TermId, surface_kana: &str) {
+        // Term ids are assigned from entry indices.
+        self.dictionary.add_reading(term_id, surface_kana);
+    }
+
+    /// Adds another kanji spelling to an already added word, `form_penalty` points
+    /// behind its others, see [`TermEntry::form_penalty`].
+    pub fn add_kanji_form(
+        &mut self,
+        term_id: TermId,
+        surface_kanji: &str,
+        form_penalty: u8,
+    ) {
+        self.dictionary
+            .add_kanji_form(term_id, surface_kanji, form_penalty);
+    }
+
+    ///
+    pub fn build(self) -> Dictionary {
+        self.dictionary
     }
 }
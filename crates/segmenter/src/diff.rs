@@ -0,0 +1,179 @@
+//! Regression diffs between the segmentations of two tokenizers, for seeing what a
+//! dictionary or scoring change does to a corpus before shipping it.
+
+use crate::tokenizer::{Token, Tokenizer};
+use std::collections::{BTreeSet, HashMap};
+
+/// A text the two tokenizers segment differently.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ChangedText {
+    pub text: String,
+    /// Surfaces of the tokens of the first tokenizer.
+    pub before: Vec<String>,
+    /// Surfaces of the tokens of the second tokenizer.
+    pub after: Vec<String>,
+}
+
+/// One way a span of text was resplit: its tokens under the first tokenizer and under
+/// the second, with how often the change came up.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ChangePattern {
+    pub before: Vec<String>,
+    pub after: Vec<String>,
+    pub count: u64,
+}
+
+/// What [`compare`] found.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DiffReport {
+    /// Number of texts compared.
+    pub texts: usize,
+    /// The texts segmented differently, in corpus order.
+    pub changed: Vec<ChangedText>,
+    /// Share of the second tokenizer's token boundaries the first one has too, `1.0`
+    /// when it has none.
+    pub precision: f64,
+    /// Share of the first tokenizer's token boundaries the second one has too, `1.0`
+    /// when it has none.
+    pub recall: f64,
+    /// Every resplit span, most frequent first and ties in order of their tokens.
+    pub patterns: Vec<ChangePattern>,
+}
+
+impl DiffReport {
+    /// The `n` most frequent [`DiffReport::patterns`].
+    pub fn top_patterns(&self, n: usize) -> &[ChangePattern] {
+        &self.patterns[..n.min(self.patterns.len())]
+    }
+}
+
+/// Tokenizes every text of `corpus` with `a` and with `b` and reports where they
+/// disagree, taking `a` as the reference.
+///
+/// Boundaries are the byte offsets between two tokens, counted over the whole corpus.
+/// A pattern is a stretch of text between two boundaries both tokenizers share, with
+/// at least one boundary in it only one of them has.
+pub fn compare<I>(a: &Tokenizer, b: &Tokenizer, corpus: I) -> DiffReport
+where
+    I: IntoIterator,
+    I::Item: AsRef<str>,
+{
+    let mut texts = 0;
+    let mut changed = Vec::new();
+    let mut counts: HashMap<(Vec<String>, Vec<String>), u64> = HashMap::new();
+    let (mut shared, mut a_total, mut b_total) = (0, 0, 0);
+    for text in corpus {
+        let text = text.as_ref();
+        texts += 1;
+        let before = a.tokenize(text);
+        let after = b.tokenize(text);
+        let a_boundaries = boundaries(&before);
+        let b_boundaries = boundaries(&after);
+        shared += a_boundaries.intersection(&b_boundaries).count();
+        a_total += a_boundaries.len();
+        b_total += b_boundaries.len();
+        if a_boundaries == b_boundaries {
+            continue;
+        }
+
+        for pattern in resplit_spans(&before, &after) {
+            *counts.entry(pattern).or_insert(0) += 1;
+        }
+        changed.push(ChangedText {
+            text: text.to_string(),
+            before: surfaces(&before),
+            after: surfaces(&after),
+        });
+    }
+
+    let mut patterns: Vec<ChangePattern> = counts
+        .into_iter()
+        .map(|((before, after), count)| ChangePattern {
+            before,
+            after,
+            count,
+        })
+        .collect();
+    patterns.sort_by(|x, y| {
+        y.count
+            .cmp(&x.count)
+            .then_with(|| x.before.cmp(&y.before))
+            .then_with(|| x.after.cmp(&y.after))
+    });
+
+    DiffReport {
+        texts,
+        changed,
+        precision: share(shared, b_total),
+        recall: share(shared, a_total),
+        patterns,
+    }
+}
+
+///
+fn share(part: usize, whole: usize) -> f64 {
+    if whole == 0 {
+        1.0
+    } else {
+        part as f64 / whole as f64
+    }
+}
+
+///
+fn surfaces(tokens: &[Token]) -> Vec<String> {
+    tokens.iter().map(|token| token.token.to_string()).collect()
+}
+
+/// Byte offsets where a token of `tokens` starts after another.
+fn boundaries(tokens: &[Token]) -> BTreeSet<usize> {
+    tokens
+        .iter()
+        .skip(1)
+        .map(|token| token.byte_start)
+        .collect()
+}
+
+/// The tokens of `before` and of `after` over every span between two shared boundaries
+/// that the two split differently.
+fn resplit_spans(before: &[Token], after: &[Token]) -> Vec<(Vec<String>, Vec<String>)> {
+    let mut spans = Vec::new();
+    let (mut before, mut after) = (before.iter().peekable(), after.iter().peekable());
+    while before.peek().is_some() || after.peek().is_some() {
+        let mut span = (Vec::new(), Vec::new());
+        // Take tokens from whichever side lags behind until both end at the same offset.
+        let (mut a_end, mut b_end) = (None, None);
+        loop {
+            let take_before = match (a_end, b_end) {
+                (Some(a_end), Some(b_end)) if a_end == b_end => break,
+                (Some(a_end), Some(b_end)) => a_end < b_end,
+                (None, _) => true,
+                (_, None) => false,
+            };
+            // A side that ran out leaves the rest of the span to the other.
+            let take_before = match (before.peek(), after.peek()) {
+                (None, None) => break,
+                (None, _) => false,
+                (_, None) => true,
+                _ => take_before,
+            };
+            let side = if take_before {
+                (&mut before, &mut span.0, &mut a_end)
+            } else {
+                (&mut after, &mut span.1, &mut b_end)
+            };
+            let Some(token) = side.0.next() else {
+                break;
+            };
+            side.1.push(token.token.to_string());
+            *side.2 = Some(token.byte_start + token.token.len());
+        }
+        if span.0 != span.1 {
+            spans.push(span);
+        }
+    }
+
+    spans
+}
@@ -0,0 +1,40 @@
+//! The error of the operations that can fail for more than one reason, such as
+//! [`Tokenizer::self_test`].
+//!
+//! [`Tokenizer::self_test`]: crate::tokenizer::Tokenizer::self_test
+
+use crate::dictionary::DictionaryError;
+use crate::health::SelfTestError;
+
+/// Why an operation of the crate failed, by the part of it that failed.
+#[derive(Debug)]
+pub enum SegmenterError {
+    /// The dictionary couldn't be read, or its sections are inconsistent.
+    Dictionary(DictionaryError),
+    /// The dictionary is consistent but failed a check of
+    /// [`Tokenizer::self_test`](crate::tokenizer::Tokenizer::self_test).
+    SelfTest(SelfTestError),
+}
+
+impl std::fmt::Display for SegmenterError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Dictionary(error) => write!(f, "{error}"),
+            Self::SelfTest(error) => write!(f, "{error}"),
+        }
+    }
+}
+
+impl std::error::Error for SegmenterError {}
+
+impl From<DictionaryError> for SegmenterError {
+    fn from(error: DictionaryError) -> Self {
+        Self::Dictionary(error)
+    }
+}
+
+impl From<SelfTestError> for SegmenterError {
+    fn from(error: SelfTestError) -> Self {
+        Self::SelfTest(error)
+    }
+}
@@ -0,0 +1,75 @@
+use crate::dictionary::PartOfSpeech;
+use crate::tokenizer::Token;
+
+/// Parts of speech of the words [`drop_stopwords`] removes.
+const STOPWORDS: PartOfSpeech = PartOfSpeech::PARTICLE
+    .union(PartOfSpeech::AUXILIARY)
+    .union(PartOfSpeech::AUXILIARY_ADJECTIVE)
+    .union(PartOfSpeech::AUXILIARY_VERB)
+    .union(PartOfSpeech::COPULA);
+
+/// Removes punctuation and bracket tokens, keeping the remaining tokens in order.
+pub fn drop_punctuation(tokens: &mut Vec<Token>) {
+    tokens.retain(|token| !token.pos.is_symbol());
+}
+
+/// Removes the tokens that are only particles, auxiliaries or copula forms, keeping the
+/// remaining tokens in order. Tokens that can also be a content word, and unknown words,
+/// stay.
+pub fn drop_stopwords(tokens: &mut Vec<Token>) {
+    tokens.retain(|token| token.pos.is_empty() || !STOPWORDS.contains(token.pos));
+}
+
+/// `tokens` of `text` with every run of at least `min_run` consecutive tokens of the same
+/// surface merged into one token spanning the run, which keeps the term, part of speech
+/// and kind of the first of them, has their number as [`Token::repeat_count`] and them
+/// as its components. Shorter runs are left as they are, as are all runs when `min_run`
+/// is below two.
+pub fn collapse_repeats<'a>(
+    text: &'a str,
+    tokens: Vec<Token<'a>>,
+    min_run: usize,
+) -> Vec<Token<'a>> {
+    let min_run = min_run.max(2);
+    let mut collapsed = Vec::with_capacity(tokens.len());
+    let mut run: Vec<Token<'a>> = Vec::new();
+    for token in tokens {
+        if run.last().is_some_and(|last| last.token != token.token) {
+            push_run(text, &mut collapsed, std::mem::take(&mut run), min_run);
+        }
+        run.push(token);
+    }
+    push_run(text, &mut collapsed, run, min_run);
+
+    collapsed
+}
+
+/// Adds the tokens of `run`, which all have the same surface, to `collapsed`, merged
+/// into one if there are at least `min_run` of them.
+fn push_run<'a>(
+    text: &'a str,
+    collapsed: &mut Vec<Token<'a>>,
+    run: Vec<Token<'a>>,
+    min_run: usize,
+) {
+    if run.len() < min_run {
+        collapsed.extend(run);
+        return;
+    }
+
+    let first = &run[0];
+    let last = &run[run.len() - 1];
+    let end = last.byte_start + last.token.len();
+    collapsed.push(Token {
+        term_id: first.term_id,
+        token: &text[first.byte_start..end],
+        char_start: first.char_start,
+        byte_start: first.byte_start,
+        term_entry: first.term_entry,
+        pos: first.pos,
+        pos_alternatives: Vec::new(),
+        kind: first.kind,
+        repeat_count: Some(run.len()),
+        components: Some(run),
+    });
+}
@@ -0,0 +1,120 @@
+//! Plain-text output of tokens with their boundaries marked, known as wakati-gaki.
+
+use crate::tokenizer::Token;
+
+/// Marker [`wakati_preserving`] puts between tokens.
+pub const DEFAULT_MARKER: char = ' ';
+
+/// The classic wakati output: the tokens joined by single spaces.
+///
+/// Whitespace between tokens is dropped, except for line breaks, which are kept so
+/// every input line stays a line of its own.
+pub fn wakati(tokens: &[Token]) -> String {
+    let mut output = String::new();
+    let mut line_has_words = false;
+    for token in tokens {
+        if token.token.trim().is_empty() {
+            for _ in token.token.matches('\n') {
+                output.push('\n');
+                line_has_words = false;
+            }
+            continue;
+        }
+
+        if line_has_words {
+            output.push(' ');
+        }
+        output.push_str(token.token);
+        line_has_words = true;
+    }
+
+    output
+}
+
+/// `text` with a space between every two tokens that touch, leaving its own whitespace
+/// as it is, so [`unwakati`] gives `text` back.
+///
+/// `tokens` are the tokens of `text`, in order.
+pub fn wakati_preserving(text: &str, tokens: &[Token]) -> String {
+    wakati_preserving_with(text, tokens, DEFAULT_MARKER)
+}
+
+/// [`wakati_preserving`] with `marker` between the tokens instead of a space.
+///
+/// Tokens are only marked off where neither side is whitespace or `marker`. For the
+/// output to read back unambiguously, whitespace between two words that consists of
+/// nothing but markers gets one more, so a gap of `n` markers stands for `n - 1` of the
+/// original text.
+pub fn wakati_preserving_with(text: &str, tokens: &[Token], marker: char) -> String {
+    let runs = separator_runs(text, marker);
+    let mut boundaries = tokens
+        .iter()
+        .skip(1)
+        .map(|token| token.byte_start)
+        .peekable();
+    let mut output = String::with_capacity(text.len() * 2);
+    for (index, &(separator, start, end)) in runs.iter().enumerate() {
+        let run = &text[start..end];
+        if separator {
+            output.push_str(run);
+            let between_words = index > 0 && index + 1 < runs.len();
+            if between_words && run.chars().all(|c| c == marker) {
+                output.push(marker);
+            }
+            continue;
+        }
+
+        // Boundaries at the ends of the run lie next to whitespace or the text's ends.
+        let mut written = start;
+        while let Some(boundary) = boundaries.next_if(|boundary| *boundary < end) {
+            if boundary > start {
+                output.push_str(&text[written..boundary]);
+                output.push(marker);
+                written = boundary;
+            }
+        }
+        output.push_str(&text[written..end]);
+    }
+
+    output
+}
+
+/// The original text of [`wakati_preserving`]'s output.
+pub fn unwakati(text: &str) -> String {
+    unwakati_with(text, DEFAULT_MARKER)
+}
+
+/// The original text of [`wakati_preserving_with`]'s output for `marker`.
+pub fn unwakati_with(text: &str, marker: char) -> String {
+    let runs = separator_runs(text, marker);
+    let mut output = String::with_capacity(text.len());
+    for (index, &(separator, start, end)) in runs.iter().enumerate() {
+        let run = &text[start..end];
+        let between_words = index > 0 && index + 1 < runs.len();
+        if separator && between_words && run.chars().all(|c| c == marker) {
+            // One of the markers is the boundary itself.
+            output.push_str(&run[marker.len_utf8()..]);
+        } else {
+            output.push_str(run);
+        }
+    }
+
+    output
+}
+
+/// Byte ranges of the maximal runs of `text` that are whitespace or `marker`, and of the
+/// runs between them, with whether each is made of separators.
+fn separator_runs(text: &str, marker: char) -> Vec<(bool, usize, usize)> {
+    let is_separator = |c: char| c.is_whitespace() || c == marker;
+    let mut runs: Vec<(bool, usize, usize)> = Vec::new();
+    for (offset, c) in text.char_indices() {
+        let separator = is_separator(c);
+        let end = offset + c.len_utf8();
+        match runs.last_mut() {
+            Some(run) if run.0 == separator => run.2 = end,
+            _ => runs.push((separator, offset, end)),
+        }
+    }
+
+    runs
+}
@@ -0,0 +1,78 @@
+use crate::dictionary::TermId;
+use crate::unknown::UnknownWordModel;
+use std::collections::HashMap;
+#[cfg(feature = "serde")]
+use std::io::{Read, Write};
+#[cfg(feature = "serde")]
+use std::path::Path;
+
+/// How often terms and unknown surfaces occurred in a corpus, as counted by
+/// `dict_generator count`, for deriving term priorities.
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FrequencyTable {
+    /// Number of tokens counted, including those not retained in `unknown`.
+    pub total_tokens: u64,
+    /// Occurrences of each dictionary term.
+    pub terms: HashMap<TermId, u64>,
+    /// The most frequent surfaces of tokens without a dictionary term, most frequent
+    /// first. Counts are lower bounds, since rare surfaces are pruned while counting.
+    pub unknown: Vec<(String, u64)>,
+    /// Lengths of the unknown words counted, all of them rather than those retained in
+    /// `unknown`, for [`TokenizerOptions::unknown_words`].
+    ///
+    /// [`TokenizerOptions::unknown_words`]: crate::tokenizer::TokenizerOptions::unknown_words
+    pub unknown_lengths: UnknownWordModel,
+}
+
+impl FrequencyTable {
+    /// Occurrences of `term_id`, `0` for terms that were never seen.
+    pub fn term_count(&self, term_id: TermId) -> u64 {
+        self.terms.get(&term_id).copied().unwrap_or(0)
+    }
+
+    /// Share of all counted tokens that were `term_id`.
+    pub fn relative_frequency(&self, term_id: TermId) -> f64 {
+        if self.total_tokens == 0 {
+            return 0.0;
+        }
+
+        self.term_count(term_id) as f64 / self.total_tokens as f64
+    }
+
+    ///
+    #[cfg(feature = "serde")]
+    pub fn save(&self, path: impl AsRef<Path>) -> std::io::Result<()> {
+        let mut writer = std::io::BufWriter::new(std::fs::File::create(path)?);
+        self.write(&mut writer)?;
+        writer.flush()
+    }
+
+    ///
+    #[cfg(feature = "serde")]
+    pub fn load(path: impl AsRef<Path>) -> std::io::Result<Self> {
+        let file = std::fs::File::open(path)?;
+        Self::read(std::io::BufReader::new(file))
+    }
+
+    ///
+    #[cfg(feature = "serde")]
+    pub fn write(&self, writer: impl Write) -> std::io::Result<()> {
+        bincode::serialize_into(writer, self).map_err(into_io_error)
+    }
+
+    ///
+    #[cfg(feature = "serde")]
+    pub fn read(reader: impl Read) -> std::io::Result<Self> {
+        bincode::deserialize_from(reader).map_err(into_io_error)
+    }
+}
+
+///
+#[cfg(feature = "serde")]
+fn into_io_error(error: bincode::Error) -> std::io::Error {
+    match error.as_ref() {
+        bincode::ErrorKind::Io(io_error) => std::io::Error::new(io_error.kind(), error),
+        _ => std::io::Error::new(std::io::ErrorKind::InvalidData, error),
+    }
+}
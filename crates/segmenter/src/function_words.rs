@@ -0,0 +1,94 @@
+use crate::dictionary::PartOfSpeech;
+
+const PARTICLE: PartOfSpeech = PartOfSpeech::PARTICLE;
+const AUXILIARY: PartOfSpeech = PartOfSpeech::AUXILIARY_VERB;
+const COPULA: PartOfSpeech = PartOfSpeech::COPULA;
+
+/// High-frequency hiragana particles, auxiliaries and copula forms. They carry most of
+/// the structure of kana-only text, so they are known whatever the dictionary holds.
+const FUNCTION_WORDS: &[(&str, PartOfSpeech)] = &[
+    // Case and topic particles
+    ("は", PARTICLE),
+    ("が", PARTICLE),
+    ("を", PARTICLE),
+    ("に", PARTICLE),
+    ("へ", PARTICLE),
+    ("と", PARTICLE),
+    ("で", PARTICLE),
+    ("の", PARTICLE),
+    ("も", PARTICLE),
+    ("から", PARTICLE),
+    ("まで", PARTICLE),
+    ("より", PARTICLE),
+    ("には", PARTICLE),
+    ("では", PARTICLE),
+    ("とは", PARTICLE),
+    ("へは", PARTICLE),
+    ("でも", PARTICLE),
+    ("にも", PARTICLE),
+    // Adverbial and conjunctive particles
+    ("か", PARTICLE),
+    ("しか", PARTICLE),
+    ("だけ", PARTICLE),
+    ("ばかり", PARTICLE),
+    ("など", PARTICLE),
+    ("ほど", PARTICLE),
+    ("くらい", PARTICLE),
+    ("ので", PARTICLE),
+    ("のに", PARTICLE),
+    ("けど", PARTICLE),
+    ("けれど", PARTICLE),
+    ("けれども", PARTICLE),
+    ("ながら", PARTICLE),
+    // Sentence-ending particles
+    ("ね", PARTICLE),
+    ("よ", PARTICLE),
+    ("よね", PARTICLE),
+    ("かな", PARTICLE),
+    ("ぞ", PARTICLE),
+    // Auxiliaries
+    ("ます", AUXILIARY),
+    ("ました", AUXILIARY),
+    ("ません", AUXILIARY),
+    ("ましょう", AUXILIARY),
+    ("ない", AUXILIARY),
+    ("なかった", AUXILIARY),
+    ("たい", AUXILIARY),
+    ("ください", AUXILIARY),
+    // Contractions of the nominalizer の with the copula, as in 食べるんです
+    ("んだ", AUXILIARY),
+    ("んです", AUXILIARY),
+    ("んですか", AUXILIARY),
+    // Copula
+    ("だ", COPULA),
+    ("だった", COPULA),
+    ("だろう", COPULA),
+    ("です", COPULA),
+    ("でした", COPULA),
+    ("でしょう", COPULA),
+];
+
+/// Part of speech of `surface` if it is a built-in function word.
+pub fn function_word_pos(surface: &str) -> Option<PartOfSpeech> {
+    FUNCTION_WORDS
+        .iter()
+        .find(|(word, _)| *word == surface)
+        .map(|(_, pos)| *pos)
+}
+
+/// Length in characters of the longest built-in function word.
+pub(crate) fn max_function_word_length() -> usize {
+    FUNCTION_WORDS
+        .iter()
+        .map(|(word, _)| word.chars().count())
+        .max()
+        .unwrap_or(0)
+}
+
+/// Every built-in function word `text` starts with.
+pub fn function_words_at(text: &str) -> impl Iterator<Item = &'static str> + '_ {
+    FUNCTION_WORDS
+        .iter()
+        .map(|(word, _)| *word)
+        .filter(move |word| text.starts_with(word))
+}
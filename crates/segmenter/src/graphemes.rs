@@ -0,0 +1,117 @@
+//! Grapheme cluster boundaries, so tokens never separate a character from the marks
+//! that modify it.
+//!
+//! This follows the extended grapheme cluster rules of UAX #29 for the characters text
+//! mixed with Japanese is likely to hold: combining marks (including the combining
+//! voiced sound marks U+3099 and U+309A), variation selectors, emoji modifiers, tag
+//! sequences, zero-width joiner sequences, regional indicator pairs, Hangul syllables and
+//! CR LF. Prepend characters and the spacing marks of Indic scripts are not handled.
+
+/// The cluster property of a character, as far as the handled rules need it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Property {
+    Cr,
+    Lf,
+    Control,
+    Extend,
+    Zwj,
+    RegionalIndicator,
+    Pictographic,
+    L,
+    V,
+    T,
+    Lv,
+    Lvt,
+    Other,
+}
+
+///
+fn property(c: char) -> Property {
+    match c as u32 {
+        0x000D => Property::Cr,
+        0x000A => Property::Lf,
+        0x0000..=0x001F | 0x007F..=0x009F | 0x200B | 0x2028 | 0x2029 => Property::Control,
+        0x200D => Property::Zwj,
+        0x0300..=0x036F
+        | 0x0483..=0x0489
+        | 0x1AB0..=0x1AFF
+        | 0x1DC0..=0x1DFF
+        | 0x200C
+        | 0x20D0..=0x20FF
+        | 0x302A..=0x302F
+        | 0x3099..=0x309A
+        | 0xFE00..=0xFE0F
+        | 0xFE20..=0xFE2F
+        | 0x1F3FB..=0x1F3FF
+        | 0xE0020..=0xE007F
+        | 0xE0100..=0xE01EF => Property::Extend,
+        0x1F1E6..=0x1F1FF => Property::RegionalIndicator,
+        0x00A9
+        | 0x00AE
+        | 0x203C
+        | 0x2049
+        | 0x2122
+        | 0x2139
+        | 0x2194..=0x21AA
+        | 0x231A..=0x23FF
+        | 0x2600..=0x27BF
+        | 0x2B00..=0x2BFF
+        | 0x1F000..=0x1F1E5
+        | 0x1F200..=0x1F3FA
+        | 0x1F400..=0x1FAFF => Property::Pictographic,
+        0x1100..=0x115F | 0xA960..=0xA97C => Property::L,
+        0x1160..=0x11A7 | 0xD7B0..=0xD7C6 => Property::V,
+        0x11A8..=0x11FF | 0xD7CB..=0xD7FB => Property::T,
+        code @ 0xAC00..=0xD7A3 if (code - 0xAC00) % 28 == 0 => Property::Lv,
+        0xAC00..=0xD7A3 => Property::Lvt,
+        _ => Property::Other,
+    }
+}
+
+/// Whether a cluster may start at every character position of `chars`, with one more
+/// entry for the end of the text. Both ends of the text are always boundaries.
+pub fn cluster_boundaries(chars: &[char]) -> Vec<bool> {
+    let properties: Vec<Property> = chars.iter().map(|c| property(*c)).collect();
+    let mut boundaries = vec![true; chars.len() + 1];
+
+    // Whether the characters up to the one before the current one end in an emoji
+    // followed by any number of extending marks, and how many regional indicators in a
+    // row end there.
+    let mut ends_in_emoji = false;
+    let mut regional_indicators = 0;
+    for i in 1..chars.len() {
+        let (left, right) = (properties[i - 1], properties[i]);
+        let emoji_before_left = ends_in_emoji;
+        ends_in_emoji = match left {
+            Property::Pictographic => true,
+            Property::Extend => ends_in_emoji,
+            _ => false,
+        };
+        regional_indicators = if left == Property::RegionalIndicator {
+            regional_indicators + 1
+        } else {
+            0
+        };
+
+        use Property::*;
+        let joins = match (left, right) {
+            (Cr, Lf) => true,
+            (Cr | Lf | Control, _) | (_, Cr | Lf | Control) => false,
+            (L, L | V | Lv | Lvt) | (Lv | V, V | T) | (Lvt | T, T) => true,
+            (_, Extend | Zwj) => true,
+            (Zwj, Pictographic) => emoji_before_left,
+            (RegionalIndicator, RegionalIndicator) => regional_indicators % 2 == 1,
+            _ => false,
+        };
+        boundaries[i] = !joins;
+    }
+
+    boundaries
+}
+
+/// The first cluster boundary at or after character position `index`.
+pub fn next_boundary(boundaries: &[bool], index: usize) -> usize {
+    (index..boundaries.len())
+        .find(|i| boundaries[*i])
+        .unwrap_or(boundaries.len() - 1)
+}
@@ -0,0 +1,114 @@
+//! Deployment checks of a dictionary and tokenizer, see [`Tokenizer::self_test`].
+//!
+//! [`Tokenizer::self_test`]: crate::tokenizer::Tokenizer::self_test
+
+/// Everyday sentences whose words any full dictionary has. Only common words are used,
+/// so a small dictionary of everyday words resolves most of them too.
+pub const CANARY_SENTENCES: &[&str] = &[
+    "私は学生です。",
+    "今日は天気が良い。",
+    "猫を見た。",
+    "東京に行く。",
+    "お茶を飲みました。",
+    "田中さんの会社で働く。",
+];
+
+/// What [`Tokenizer::self_test_with`] expects of a sane dictionary. Dictionary files
+/// record no expectations of their own, so the thresholds are shares rather than
+/// counts, and hold for dictionaries of any size.
+///
+/// [`Tokenizer::self_test_with`]: crate::tokenizer::Tokenizer::self_test_with
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct SelfTestThresholds {
+    /// Fewest entries the dictionary may have.
+    pub min_entries: usize,
+    /// Smallest share of the entries any kanji or kana surface has to lead to. A
+    /// dictionary losing a surface map falls below it.
+    pub min_indexed_fraction: f64,
+    /// Smallest share of the tokens of [`CANARY_SENTENCES`], symbols aside, that have
+    /// to be dictionary words.
+    pub min_known_fraction: f64,
+}
+
+impl Default for SelfTestThresholds {
+    fn default() -> Self {
+        Self {
+            min_entries: 1,
+            min_indexed_fraction: 0.95,
+            min_known_fraction: 0.8,
+        }
+    }
+}
+
+/// What [`Tokenizer::self_test`] found in a dictionary that passed.
+///
+/// [`Tokenizer::self_test`]: crate::tokenizer::Tokenizer::self_test
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SelfTestReport {
+    pub entries: usize,
+    /// Share of the entries some surface leads to.
+    pub indexed_fraction: f64,
+    /// Tokens of the canary sentences, symbols aside.
+    pub canary_tokens: usize,
+    /// Share of [`SelfTestReport::canary_tokens`] that are dictionary words.
+    pub known_fraction: f64,
+    /// Time the whole check took.
+    pub elapsed: std::time::Duration,
+}
+
+/// Why a consistent dictionary failed [`Tokenizer::self_test`], by the first check it
+/// failed. Inconsistent sections fail it as a [`SegmenterError::Dictionary`].
+///
+/// [`Tokenizer::self_test`]: crate::tokenizer::Tokenizer::self_test
+/// [`SegmenterError::Dictionary`]: crate::error::SegmenterError::Dictionary
+#[derive(Debug)]
+pub enum SelfTestError {
+    TooFewEntries {
+        entries: usize,
+        min: usize,
+    },
+    /// Too many entries have no surface leading to them.
+    Unindexed {
+        fraction: f64,
+        min: f64,
+    },
+    /// Too few canary tokens are dictionary words, listed in `unknown`.
+    UnknownCanaries {
+        fraction: f64,
+        min: f64,
+        unknown: Vec<String>,
+    },
+}
+
+impl std::fmt::Display for SelfTestError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::TooFewEntries { entries, min } => {
+                write!(f, "{entries} entries, expected at least {min}")
+            }
+            Self::Unindexed { fraction, min } => write!(
+                f,
+                "{:.1}% of the entries have surfaces, expected at least {:.1}%",
+                fraction * 100.0,
+                min * 100.0
+            ),
+            Self::UnknownCanaries {
+                fraction,
+                min,
+                unknown,
+            } => write!(
+                f,
+                "{:.1}% of the canary tokens are known, expected at least {:.1}% \
+                 (unknown: {})",
+                fraction * 100.0,
+                min * 100.0,
+                unknown.join(", ")
+            ),
+        }
+    }
+}
+
+impl std::error::Error for SelfTestError {}
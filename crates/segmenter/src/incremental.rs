@@ -0,0 +1,150 @@
+//! Tokenization of text that only grows at its end, like the input of an IME.
+
+use crate::lattice::{Lattice, LatticeNode, NodeId, PathScores};
+use crate::tokenizer::{char_offsets, Current, Token, Tokenizer};
+
+/// Tokenizes a text as it is appended to, keeping what the appended text can't change.
+///
+/// Candidates starting far enough before the old end of the text are kept rather than
+/// looked up again, see [`IncrementalTokenizer::push_str`], and the best path is only
+/// searched again from the first position whose candidates changed. The tokens are
+/// always those [`Tokenizer::tokenize`] gives for the whole text.
+///
+/// Texts tokenized one sentence at a time or with invisible characters to skip are left
+/// to [`Tokenizer::tokenize`] as a whole.
+///
+/// A dictionary swapped in with [`Tokenizer::swap_dictionary`] is picked up on the next
+/// append, which then tokenizes the whole text again.
+pub struct IncrementalTokenizer<'t> {
+    tokenizer: &'t Tokenizer,
+    /// The tokenizer for the dictionary the lattice was made with.
+    current: Current<'t>,
+    text: String,
+    /// Number of characters of `text` the lattice and the path were made for.
+    length: usize,
+    /// Nodes of `lattice` as generated, before rescoring.
+    candidates: Vec<LatticeNode>,
+    lattice: Lattice,
+    scores: PathScores,
+    path: Vec<NodeId>,
+    /// Set when [`Tokenizer::tokenize`] doesn't search the lattice of the whole text,
+    /// which leaves the tokens to it.
+    delegated: bool,
+}
+
+impl<'t> IncrementalTokenizer<'t> {
+    /// An incremental tokenizer of the empty text.
+    pub fn new(tokenizer: &'t Tokenizer) -> Self {
+        Self {
+            tokenizer,
+            current: tokenizer.current(),
+            text: String::new(),
+            length: 0,
+            candidates: Vec::new(),
+            lattice: Lattice::new(0, 0),
+            scores: PathScores::default(),
+            path: Vec::new(),
+            delegated: false,
+        }
+    }
+
+    /// The text appended so far.
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    /// Appends `suffix` to the text and finds its best path again.
+    ///
+    /// Only candidates starting at most a word's length before the old end are
+    /// generated again, or further back when the old text ends in a katakana run,
+    /// repeated characters or kana that could attach to what follows.
+    pub fn push_str(&mut self, suffix: &str) {
+        self.text.push_str(suffix);
+        let current = self.tokenizer.current();
+        if !std::ptr::eq(&*current, &*self.current) {
+            // Nothing kept is known to hold for another dictionary.
+            self.clear_lattice();
+        }
+        self.current = current;
+
+        self.delegated = !self.current.searches_whole_text(&self.text);
+        if self.delegated {
+            self.clear_lattice();
+            return;
+        }
+
+        let chars: Vec<char> = self.text.chars().collect();
+        let settled = self.current.settled_length(&chars[..self.length]);
+        let mut lattice = Lattice::new(chars.len() * 4, chars.len());
+        for node in self
+            .candidates
+            .iter()
+            .take_while(|node| node.start < settled)
+        {
+            lattice.add_node(*node);
+        }
+        let mut lattice = self.current.add_candidates(&self.text, settled, lattice);
+        self.candidates = lattice.nodes().to_vec();
+        self.current.rescore(&mut lattice, &self.text);
+
+        // Rescoring looks at the whole lattice, so even settled nodes can change.
+        let from = first_change(self.lattice.nodes(), lattice.nodes());
+        self.path =
+            self.current
+                .resumed_path_ids(&lattice, &self.text, &mut self.scores, from);
+        self.lattice = lattice;
+        self.length = chars.len();
+    }
+
+    /// The tokens of the text, as [`Tokenizer::tokenize`] gives them.
+    pub fn tokens(&self) -> Vec<Token<'_>> {
+        if self.delegated {
+            return self.current.tokenize(&self.text);
+        }
+
+        let offsets = char_offsets(&self.text);
+        self.path
+            .iter()
+            .map(|node_id| {
+                self.current.path_token(
+                    &self.text,
+                    &offsets,
+                    &self.lattice.nodes()[*node_id],
+                )
+            })
+            .collect()
+    }
+
+    /// Drops the text and everything kept for it, for when the text changes other than
+    /// at its end.
+    pub fn invalidate(&mut self) {
+        self.text.clear();
+        self.clear_lattice();
+        self.delegated = false;
+    }
+
+    ///
+    fn clear_lattice(&mut self) {
+        self.length = 0;
+        self.candidates.clear();
+        self.lattice = Lattice::new(0, 0);
+        self.scores = PathScores::default();
+        self.path.clear();
+    }
+}
+
+/// The first position whose nodes differ between `old` and `new`, both in order of
+/// their start.
+fn first_change(old: &[LatticeNode], new: &[LatticeNode]) -> usize {
+    let index = old
+        .iter()
+        .zip(new)
+        .position(|(old, new)| old != new)
+        .unwrap_or(old.len().min(new.len()));
+    [old.get(index), new.get(index)]
+        .into_iter()
+        .flatten()
+        .map(|node| node.start)
+        .min()
+        .unwrap_or(usize::MAX)
+}
@@ -0,0 +1,80 @@
+//! Characters without a visible form, like byte order marks and zero-width spaces, that
+//! text pasted from word processors and web pages is sprinkled with.
+
+use crate::graphemes;
+
+/// Whether `c` is always invisible: the byte order mark (zero-width no-break space),
+/// the zero-width space, non-joiner and word joiner, and the soft hyphen. The
+/// zero-width joiner is only invisible outside emoji sequences, see [`VisibleText`].
+pub fn is_invisible(c: char) -> bool {
+    matches!(
+        c,
+        '\u{FEFF}' | '\u{200B}' | '\u{200C}' | '\u{2060}' | '\u{00AD}'
+    )
+}
+
+/// A text with its invisible characters left out, and the way back to the positions of
+/// the original text.
+///
+/// Zero-width joiners that join an emoji sequence like 👨‍👩‍👧 are kept, so clusters stay
+/// whole. Invisible characters belong to the visible character before them, or to the
+/// first one at the start of the text.
+#[derive(Debug, Clone)]
+pub struct VisibleText {
+    /// The visible characters of the original text.
+    pub text: String,
+    /// Character index and byte offset in the original text of every character of
+    /// `text`, and of its end.
+    positions: Vec<(usize, usize)>,
+}
+
+impl VisibleText {
+    /// `None` when `text` has no invisible characters, or nothing but them.
+    pub fn new(text: &str) -> Option<Self> {
+        let chars: Vec<char> = text.chars().collect();
+        let clusters = graphemes::cluster_boundaries(&chars);
+        let invisible = |i: usize| {
+            is_invisible(chars[i]) || (chars[i] == '\u{200D}' && clusters[i + 1])
+        };
+        if !(0..chars.len()).any(invisible) {
+            return None;
+        }
+
+        let mut visible = String::with_capacity(text.len());
+        let mut positions = Vec::with_capacity(chars.len() + 1);
+        for (i, (offset, c)) in text.char_indices().enumerate() {
+            if invisible(i) {
+                continue;
+            }
+            // Leading invisible characters go with the first visible one.
+            let position = if positions.is_empty() {
+                (0, 0)
+            } else {
+                (i, offset)
+            };
+            positions.push(position);
+            visible.push(c);
+        }
+        if visible.is_empty() {
+            return None;
+        }
+        positions.push((chars.len(), text.len()));
+
+        Some(Self {
+            text: visible,
+            positions,
+        })
+    }
+
+    /// Character index in the original text of character `index` of
+    /// [`VisibleText::text`], which may be its end.
+    pub fn original_char(&self, index: usize) -> usize {
+        self.positions[index].0
+    }
+
+    /// Byte offset in the original text of character `index` of [`VisibleText::text`],
+    /// which may be its end.
+    pub fn original_byte(&self, index: usize) -> usize {
+        self.positions[index].1
+    }
+}
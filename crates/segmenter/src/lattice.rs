@@ -1,29 +1,118 @@
+use crate::{
+    candidate::CandidateSource,
+    dictionary::{PartOfSpeech, TermEntry},
+    tokenizer::{self, TokenKind},
+};
+
 pub type NodeId = usize;
 
-///
-pub(crate) struct NodePath<'a> {
+/// The best path through a [`Lattice`], from [`Lattice::find_path`].
+#[derive(Debug, Clone)]
+pub struct NodePath<'a> {
     nodes: &'a [LatticeNode],
-    node_path: Vec<usize>,
+    node_path: Vec<NodeId>,
+    score: f64,
 }
 
-///
 impl<'a> NodePath<'a> {
-    pub fn path(&self) -> impl Iterator<Item = &LatticeNode> {
-        self.node_path.iter().rev().map(|i| &self.nodes[*i])
+    /// Number of nodes on the path.
+    pub fn len(&self) -> usize {
+        self.node_path.len()
+    }
+
+    /// Whether the lattice had no path, as for empty text.
+    pub fn is_empty(&self) -> bool {
+        self.node_path.is_empty()
+    }
+
+    /// The `index`th node from the start of the text.
+    pub fn get(&self, index: usize) -> Option<&'a LatticeNode> {
+        self.node_path
+            .get(index)
+            .map(|node_id| &self.nodes[*node_id])
+    }
+
+    /// Total of the node scores and connection scores along the path, `0.0` if it is
+    /// empty. Totals are summed in `f64`, so the small scores of particles still count
+    /// next to the huge ones long spans get with high length powers.
+    pub fn score(&self) -> f64 {
+        self.score
+    }
+
+    /// [`NodeId`]s of the nodes, in text order.
+    pub fn node_ids(&self) -> &[NodeId] {
+        &self.node_path
+    }
+
+    ///
+    pub fn into_node_ids(self) -> Vec<NodeId> {
+        self.node_path
+    }
+
+    /// The nodes in text order, or from the end of the text with `.rev()`.
+    pub fn iter(&self) -> NodePathIter<'a, '_> {
+        NodePathIter {
+            nodes: self.nodes,
+            node_ids: self.node_path.iter(),
+        }
+    }
+}
+
+impl<'a, 'p> IntoIterator for &'p NodePath<'a> {
+    type Item = &'a LatticeNode;
+    type IntoIter = NodePathIter<'a, 'p>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// Iterator over the nodes of a [`NodePath`], from either end.
+#[derive(Debug, Clone)]
+pub struct NodePathIter<'a, 'p> {
+    nodes: &'a [LatticeNode],
+    node_ids: std::slice::Iter<'p, NodeId>,
+}
+
+impl<'a> Iterator for NodePathIter<'a, '_> {
+    type Item = &'a LatticeNode;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.node_ids.next().map(|node_id| &self.nodes[*node_id])
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.node_ids.size_hint()
+    }
+}
+
+impl DoubleEndedIterator for NodePathIter<'_, '_> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.node_ids
+            .next_back()
+            .map(|node_id| &self.nodes[*node_id])
     }
 }
 
+impl ExactSizeIterator for NodePathIter<'_, '_> {}
+
 ///
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct LatticeNode {
     pub term_id: Option<u32>,
+    /// The dictionary match the node was created from.
+    pub term_entry: Option<TermEntry>,
+    pub pos: PartOfSpeech,
     pub start: usize,
     pub end: usize,
     pub score: f32,
+    pub kind: TokenKind,
 }
 
 ///
 #[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Lattice {
     length: usize,
     nodes: Vec<LatticeNode>,
@@ -31,6 +120,14 @@ pub struct Lattice {
     end: Vec<Vec<NodeId>>,
 }
 
+/// Best path totals of the nodes of a [`Lattice`], kept between searches by
+/// [`Lattice::find_path_resumed`].
+#[derive(Debug, Clone, Default)]
+pub(crate) struct PathScores {
+    total_scores: Vec<f64>,
+    previous_nodes: Vec<NodeId>,
+}
+
 ///
 impl Lattice {
     const NODE_ID_NONE: usize = !0usize;
@@ -38,8 +135,9 @@ impl Lattice {
 
     ///
     pub fn new(node_count: usize, length: usize) -> Self {
-        let start = vec![Vec::<NodeId>::new(); length];
-        let mut end = vec![Vec::<NodeId>::new(); length];
+        // Nodes may end right after the last character, so positions go up to `length`.
+        let start = vec![Vec::<NodeId>::new(); length + 1];
+        let mut end = vec![Vec::<NodeId>::new(); length + 1];
         end[0].push(Self::NODE_ID_BEGIN);
 
         Self {
@@ -58,32 +156,249 @@ impl Lattice {
         self.nodes.push(node);
     }
 
-    ///
-    pub(crate) fn find_path(&self) -> Vec<&LatticeNode> {
+    /// Nodes in insertion order, indexed by [`NodeId`].
+    pub fn nodes(&self) -> &[LatticeNode] {
+        &self.nodes
+    }
+
+    /// The nodes lying within `start..end` that don't match `exclude`, as a lattice of
+    /// their own with positions starting at 0, along with the [`NodeId`] each node has
+    /// here.
+    pub(crate) fn span(
+        &self,
+        start: usize,
+        end: usize,
+        exclude: impl Fn(&LatticeNode) -> bool,
+    ) -> (Lattice, Vec<NodeId>) {
+        let mut lattice = Lattice::new(0, end - start);
+        let mut node_ids = Vec::new();
+        for node_id in self.start[start..end].iter().flatten() {
+            let node = &self.nodes[*node_id];
+            if node.end <= end && !exclude(node) {
+                lattice.add_node(LatticeNode {
+                    start: node.start - start,
+                    end: node.end - start,
+                    ..*node
+                });
+                node_ids.push(*node_id);
+            }
+        }
+
+        (lattice, node_ids)
+    }
+
+    /// Removes every node matching `predicate` from the graph.
+    pub fn exclude(&mut self, predicate: impl Fn(&LatticeNode) -> bool) {
+        let nodes = &self.nodes;
+        for node_ids in self.start.iter_mut().chain(self.end.iter_mut()) {
+            node_ids.retain(|node_id| {
+                *node_id == Self::NODE_ID_BEGIN || !predicate(&nodes[*node_id])
+            });
+        }
+    }
+
+    /// Scales the score of one-character dictionary nodes by `factor` wherever a longer
+    /// node starts at the same position. Particles keep their score.
+    pub fn discount_dominated_single_chars(&mut self, factor: f32) {
+        for node_ids in self.start.iter() {
+            let has_longer = node_ids
+                .iter()
+                .any(|node_id| self.nodes[*node_id].end - self.nodes[*node_id].start > 1);
+            if !has_longer {
+                continue;
+            }
+
+            for node_id in node_ids.iter() {
+                let node = &mut self.nodes[*node_id];
+                let is_single_char_term =
+                    node.term_id.is_some() && node.end - node.start == 1;
+                if is_single_char_term && !node.pos.is_particle() {
+                    node.score *= factor;
+                }
+            }
+        }
+    }
+
+    /// Lowers the score of every unknown word node by `factor` times the share of its
+    /// span covered by dictionary nodes lying fully within it, so a grouping only wins
+    /// where the dictionary has no good analysis of its own.
+    pub fn penalize_covered_unknown(&mut self, factor: f32) {
+        let mut penalties = Vec::new();
+        for (node_id, node) in self.nodes.iter().enumerate() {
+            let length = node.end - node.start;
+            if node.term_id.is_some() || node.kind != TokenKind::Word || length < 2 {
+                continue;
+            }
+
+            let mut covered = vec![false; length];
+            for inner_id in self.start[node.start..node.end].iter().flatten() {
+                let inner = &self.nodes[*inner_id];
+                if inner.term_id.is_some() && inner.end <= node.end {
+                    covered[inner.start - node.start..inner.end - node.start].fill(true);
+                }
+            }
+
+            let coverage = covered.iter().filter(|c| **c).count() as f32 / length as f32;
+            if coverage > 0.0 {
+                penalties.push((node_id, factor * coverage * node.score));
+            }
+        }
+
+        for (node_id, penalty) in penalties {
+            self.nodes[node_id].score -= penalty;
+        }
+    }
+
+    /// Adds `bonus` to the score of unknown nodes spanning exactly `start..end`.
+    pub fn boost_unknown(&mut self, start: usize, end: usize, bonus: f32) {
+        for node_id in self.start[start].iter() {
+            let node = &mut self.nodes[*node_id];
+            if node.term_id.is_none() && node.end == end {
+                node.score += bonus;
+            }
+        }
+    }
+
+    /// The path with the highest total of node scores.
+    pub fn find_path(&self) -> NodePath<'_> {
+        self.find_path_with(|_, _| 0.0)
+    }
+
+    /// Up to `n` paths with the highest totals of node scores, best first.
+    pub fn find_paths(&self, n: usize) -> Vec<NodePath<'_>> {
+        self.find_paths_with(n, |_, _| 0.0)
+    }
+
+    /// Up to `n` best paths by [`Lattice::find_path_with`]'s totals, best first. Equal
+    /// totals keep the order in which the nodes were added.
+    pub(crate) fn find_paths_with(
+        &self,
+        n: usize,
+        connection: impl Fn(&LatticeNode, &LatticeNode) -> f32,
+    ) -> Vec<NodePath<'_>> {
         assert!(self.nodes.len() < Self::NODE_ID_BEGIN);
-        if (self.length == 0) || self.nodes.is_empty() {
+        if (self.length == 0) || self.nodes.is_empty() || n == 0 {
             return Vec::new();
         }
 
-        let mut total_scores: Vec<f32> =
-            self.nodes.iter().map(|node| node.score).collect();
-        let mut previous_nodes = vec![Self::NODE_ID_NONE; self.nodes.len()];
-
-        for i in self.start[0].iter() {
-            previous_nodes[*i] = Self::NODE_ID_BEGIN;
+        // The `n` best ways to reach each node, best first: the total up to and including
+        // the node, and the previous node with the rank of its way used.
+        let mut best: Vec<Vec<(f64, NodeId, usize)>> = vec![Vec::new(); self.nodes.len()];
+        for node_id in self.start[0].iter() {
+            let score = self.nodes[*node_id].score as f64;
+            best[*node_id].push((score, Self::NODE_ID_BEGIN, 0));
         }
 
         for i in 1..self.length {
             for right_node_id in self.start[i].iter() {
-                // let right_node = &self.nodes[*right_node_id];
+                let right_node = &self.nodes[*right_node_id];
+                let mut ways = Vec::new();
+                for left_node_id in self.end[i].iter() {
+                    let left_node = &self.nodes[*left_node_id];
+                    let connection_score = connection(left_node, right_node) as f64;
+                    for (rank, (total, _, _)) in best[*left_node_id].iter().enumerate() {
+                        let total = total + connection_score + right_node.score as f64;
+                        ways.push((total, *left_node_id, rank));
+                    }
+                }
+                ways.sort_by(|a, b| b.0.total_cmp(&a.0));
+                ways.truncate(n);
+                best[*right_node_id] = ways;
+            }
+        }
+
+        let mut endings: Vec<(f64, NodeId, usize)> = self.end[self.length]
+            .iter()
+            .flat_map(|node_id| {
+                let ways = best[*node_id].iter().enumerate();
+                ways.map(|(rank, (total, _, _))| (*total, *node_id, rank))
+            })
+            .collect();
+        endings.sort_by(|a, b| b.0.total_cmp(&a.0));
+        endings.truncate(n);
+
+        endings
+            .into_iter()
+            .map(|(score, mut node_id, mut rank)| {
+                let mut node_path = Vec::new();
+                loop {
+                    node_path.push(node_id);
+                    let (_, previous_node_id, previous_rank) = best[node_id][rank];
+                    if previous_node_id == Self::NODE_ID_BEGIN {
+                        break;
+                    }
+                    (node_id, rank) = (previous_node_id, previous_rank);
+                }
+                node_path.reverse();
+
+                NodePath {
+                    nodes: &self.nodes,
+                    node_path,
+                    score,
+                }
+            })
+            .collect()
+    }
+
+    /// The best path, where moving from a left node to an adjacent right node adds
+    /// `connection(left, right)` to the total.
+    pub(crate) fn find_path_with(
+        &self,
+        connection: impl Fn(&LatticeNode, &LatticeNode) -> f32,
+    ) -> NodePath<'_> {
+        self.find_path_resumed(&mut PathScores::default(), 0, connection)
+    }
+
+    /// [`Lattice::find_path_with`], taking the best totals of the nodes starting before
+    /// position `from` from `scores`, which holds what an earlier search of a lattice
+    /// with the same nodes up to there left. `scores` is updated for the next search.
+    pub(crate) fn find_path_resumed(
+        &self,
+        scores: &mut PathScores,
+        from: usize,
+        connection: impl Fn(&LatticeNode, &LatticeNode) -> f32,
+    ) -> NodePath<'_> {
+        assert!(self.nodes.len() < Self::NODE_ID_BEGIN);
+        let empty = NodePath {
+            nodes: &self.nodes,
+            node_path: Vec::new(),
+            score: 0.0,
+        };
+        if (self.length == 0) || self.nodes.is_empty() {
+            return empty;
+        }
+
+        // Resuming relies on nodes being added in order of their start, which puts the
+        // settled ones first.
+        let from = from.min(self.length);
+        let settled = self.start[..from].iter().map(Vec::len).sum();
+        let PathScores {
+            total_scores,
+            previous_nodes,
+        } = scores;
+        total_scores.truncate(settled);
+        total_scores.extend(self.nodes[settled..].iter().map(|node| node.score as f64));
+        previous_nodes.truncate(settled);
+        previous_nodes.resize(self.nodes.len(), Self::NODE_ID_NONE);
+
+        if from == 0 {
+            for i in self.start[0].iter() {
+                previous_nodes[*i] = Self::NODE_ID_BEGIN;
+            }
+        }
+
+        for i in from.max(1)..self.length {
+            for right_node_id in self.start[i].iter() {
+                let right_node = &self.nodes[*right_node_id];
                 let mut max_previous_node = None;
-                let mut max_previous_score = 0.0;
+                let mut max_previous_score = f64::NEG_INFINITY;
 
                 for left_node_id in self.end[i].iter() {
-                    // let left_node = &self.nodes[*left_node_id];
+                    let left_node = &self.nodes[*left_node_id];
 
                     if previous_nodes[*left_node_id] != Self::NODE_ID_NONE {
-                        let prev_total_score = total_scores[*left_node_id];
+                        let prev_total_score = total_scores[*left_node_id]
+                            + connection(left_node, right_node) as f64;
 
                         if prev_total_score > max_previous_score {
                             max_previous_score = prev_total_score;
@@ -100,9 +415,9 @@ impl Lattice {
         }
 
         let mut max_ending_node = None;
-        let mut max_ending_score = 0.0;
+        let mut max_ending_score = f64::NEG_INFINITY;
 
-        for node_id in self.end[self.length - 1].iter() {
+        for node_id in self.end[self.length].iter() {
             if previous_nodes[*node_id] != Self::NODE_ID_NONE {
                 let prev_total_score = total_scores[*node_id];
                 if prev_total_score > max_ending_score {
@@ -113,7 +428,7 @@ impl Lattice {
         }
 
         if max_ending_node.is_none() {
-            return Vec::new();
+            return empty;
         }
         let mut node_path = Vec::with_capacity(self.length);
         let mut current_node_id = max_ending_node.unwrap();
@@ -123,6 +438,77 @@ impl Lattice {
         }
         node_path.push(current_node_id);
 
-        node_path.iter().rev().map(|i| &self.nodes[*i]).collect()
+        node_path.reverse();
+        NodePath {
+            nodes: &self.nodes,
+            node_path,
+            score: max_ending_score,
+        }
+    }
+}
+
+/// A candidate node of a [`LatticeSnapshot`] together with what produced it.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SnapshotNode {
+    pub surface: String,
+    pub source: CandidateSource,
+    /// The node as scored, after the single-character discount.
+    pub node: LatticeNode,
+}
+
+/// Everything path finding saw for one text, from [`Tokenizer::export_lattice`].
+///
+/// Snapshots only hold the candidates of their text, so they can be shared to reproduce
+/// a segmentation without the dictionary.
+///
+/// [`Tokenizer::export_lattice`]: crate::tokenizer::Tokenizer::export_lattice
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct LatticeSnapshot {
+    pub text: String,
+    /// [`TokenizerOptions::stem_particle_penalty`] the path was chosen with.
+    ///
+    /// [`TokenizerOptions::stem_particle_penalty`]: crate::tokenizer::TokenizerOptions::stem_particle_penalty
+    pub stem_particle_penalty: f32,
+    /// [`TokenizerOptions::particle_connection_bonus`] the path was chosen with, `0.0`
+    /// for snapshots exported before it existed.
+    ///
+    /// [`TokenizerOptions::particle_connection_bonus`]: crate::tokenizer::TokenizerOptions::particle_connection_bonus
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub particle_connection_bonus: f32,
+    /// [`TokenizerOptions::nominalizer_bonus`] the path was chosen with, `0.0` for
+    /// snapshots exported before it existed.
+    ///
+    /// [`TokenizerOptions::nominalizer_bonus`]: crate::tokenizer::TokenizerOptions::nominalizer_bonus
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub nominalizer_bonus: f32,
+    /// Nodes in lattice order, so their indices are the [`NodeId`]s of `path`.
+    pub nodes: Vec<SnapshotNode>,
+    /// The best path at export time.
+    pub path: Vec<NodeId>,
+}
+
+impl LatticeSnapshot {
+    /// Rebuilds the lattice from the stored nodes and searches it again, returning the
+    /// path the current path finding picks.
+    pub fn replay(&self) -> Vec<NodeId> {
+        let length = self.text.chars().count();
+        let mut lattice = Lattice::new(self.nodes.len(), length);
+        for snapshot_node in self.nodes.iter() {
+            lattice.add_node(snapshot_node.node);
+        }
+
+        let chars: Vec<char> = self.text.chars().collect();
+        let weights = tokenizer::ConnectionWeights {
+            stem_particle_penalty: self.stem_particle_penalty,
+            particle_connection_bonus: self.particle_connection_bonus,
+            nominalizer_bonus: self.nominalizer_bonus,
+        };
+        lattice
+            .find_path_with(|left, right| {
+                tokenizer::connection_score(&chars, &weights, left, right)
+            })
+            .into_node_ids()
     }
 }
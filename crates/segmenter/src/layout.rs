@@ -0,0 +1,100 @@
+use crate::{
+    dictionary::PartOfSpeech,
+    symbols,
+    tokenizer::{Token, Tokenizer},
+};
+
+/// Tokens that are glued onto the chunk before them.
+const ATTACH_LEFT: PartOfSpeech = PartOfSpeech::PARTICLE
+    .union(PartOfSpeech::AUXILIARY)
+    .union(PartOfSpeech::AUXILIARY_VERB)
+    .union(PartOfSpeech::AUXILIARY_ADJECTIVE)
+    .union(PartOfSpeech::COPULA)
+    .union(PartOfSpeech::SUFFIX)
+    .union(PartOfSpeech::NOUN_SUFFIX);
+
+/// Tokens that are glued onto the chunk after them.
+const ATTACH_RIGHT: PartOfSpeech = PartOfSpeech::PREFIX.union(PartOfSpeech::NOUN_PREFIX);
+
+/// Opening brackets, which symbol tokens glue onto the chunk after them. Their vertical
+/// presentation forms count as well.
+const OPENING_BRACKETS: &[char] = &['「', '『', '（', '(', '［', '【'];
+
+///
+fn is_opening_bracket(c: Option<char>) -> bool {
+    c.is_some_and(|c| OPENING_BRACKETS.contains(&symbols::horizontal_form(c)))
+}
+
+///
+fn attaches_left(token: &Token) -> bool {
+    if token.pos.is_symbol() {
+        return !is_opening_bracket(token.token.chars().next());
+    }
+    token.pos.intersects(ATTACH_LEFT)
+}
+
+///
+fn attaches_right(token: &Token) -> bool {
+    if token.pos.is_symbol() {
+        return is_opening_bracket(token.token.chars().next_back());
+    }
+    token.pos.intersects(ATTACH_RIGHT)
+}
+
+/// Groups tokens into phrase chunks suitable for line-break hints in CJK layout.
+///
+/// A chunk is a content word together with the particles, auxiliaries and suffixes that
+/// follow it; prefixes and opening brackets are attached to the following word instead.
+/// Chunks longer than `max_phrase_len` characters are split again at the last internal
+/// token boundary that fits, so a single over-long token is the only way to exceed it.
+/// The returned slices tile `text` exactly, unless the tokenizer leaves all of it out,
+/// with text no token covers, like punctuation dropped by
+/// [`TokenizerOptions::drop_punctuation`], in the chunk before it.
+///
+/// [`TokenizerOptions::drop_punctuation`]: crate::tokenizer::TokenizerOptions::drop_punctuation
+pub fn chunk<'a>(
+    text: &'a str,
+    tokenizer: &Tokenizer,
+    max_phrase_len: usize,
+) -> Vec<&'a str> {
+    let tokens = tokenizer.tokenize(text);
+
+    // Each chunk is a range of token indices.
+    let mut chunks: Vec<std::ops::Range<usize>> = Vec::new();
+    let mut glue_next = false;
+    for (i, token) in tokens.iter().enumerate() {
+        match chunks.last_mut() {
+            Some(last) if glue_next || attaches_left(token) => last.end = i + 1,
+            _ => chunks.push(i..i + 1),
+        }
+        glue_next = attaches_right(token);
+    }
+
+    // Text outside every token goes with the token before it.
+    let mut offsets = Vec::with_capacity(tokens.len() + 1);
+    offsets.push(0);
+    offsets.extend(tokens.iter().skip(1).map(|token| token.byte_start));
+    offsets.push(text.len());
+
+    let mut result = Vec::with_capacity(chunks.len());
+    for range in chunks {
+        let mut start = range.start;
+        while start < range.end {
+            let mut end = start + 1;
+            let mut length = tokens[start].token.chars().count();
+            while end < range.end {
+                let next_length = tokens[end].token.chars().count();
+                if length + next_length > max_phrase_len {
+                    break;
+                }
+                length += next_length;
+                end += 1;
+            }
+
+            result.push(&text[offsets[start]..offsets[end]]);
+            start = end;
+        }
+    }
+
+    result
+}
@@ -1,3 +1,33 @@
+pub mod analysis;
+#[cfg(feature = "cache")]
+pub mod cache;
+pub mod candidate;
+pub mod counters;
+pub mod deinflect;
+pub mod detect;
 pub mod dictionary;
+pub mod diff;
+pub mod error;
+pub mod filter;
+pub mod format;
+pub mod frequency;
+pub mod function_words;
+pub mod graphemes;
+pub mod health;
+pub mod incremental;
+pub mod invisible;
 pub mod lattice;
+pub mod layout;
+pub mod merge;
+pub mod pipeline;
+pub mod prelude;
+#[cfg(feature = "romaji")]
+pub mod romaji;
+pub mod ruby;
+pub mod stats;
+pub mod symbols;
 pub mod tokenizer;
+pub mod unknown;
+
+/// Version of this crate, for records of what built a dictionary.
+pub const VERSION: &str = env!("CARGO_PKG_VERSION");
@@ -0,0 +1,210 @@
+//! Merging adjacent tokens into larger units by rules on their parts of speech.
+
+use crate::{
+    dictionary::PartOfSpeech,
+    tokenizer::{Token, TokenKind},
+};
+
+/// Verbs of every conjugation class.
+const VERB: PartOfSpeech = PartOfSpeech::ICHIDAN_VERB
+    .union(PartOfSpeech::GODAN_VERB)
+    .union(PartOfSpeech::KURU_VERB)
+    .union(PartOfSpeech::SURU_VERB);
+
+/// Auxiliaries of every kind.
+const AUXILIARY: PartOfSpeech = PartOfSpeech::AUXILIARY
+    .union(PartOfSpeech::AUXILIARY_VERB)
+    .union(PartOfSpeech::AUXILIARY_ADJECTIVE);
+
+/// Nouns of every kind.
+const NOUN: PartOfSpeech = PartOfSpeech::NOUN
+    .union(PartOfSpeech::NOUN_PROPER)
+    .union(PartOfSpeech::NOUN_TEMPORAL)
+    .union(PartOfSpeech::NOUN_ADVERB)
+    .union(PartOfSpeech::PRONOUN);
+
+/// What a side of a [`MergeRule`] matches.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum PosPattern {
+    /// Every token.
+    Any,
+    /// Tokens whose part of speech has any of the flags of the mask.
+    Pos(PartOfSpeech),
+    /// Tokens of the kind.
+    Kind(TokenKind),
+    /// Tokens with exactly this surface.
+    Surface(String),
+}
+
+impl PosPattern {
+    /// Whether `token` matches.
+    pub fn matches(&self, token: &Token) -> bool {
+        match self {
+            Self::Any => true,
+            Self::Pos(mask) => token.pos.intersects(*mask),
+            Self::Kind(kind) => token.kind == *kind,
+            Self::Surface(surface) => token.token == surface,
+        }
+    }
+}
+
+/// What a [`MergeRule`] does with the two tokens it matches.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum MergeAction {
+    /// Join them into one token.
+    Merge,
+    /// Keep them apart, whatever an earlier rule says.
+    Forbid,
+}
+
+/// A rule for two adjacent tokens: `action` applies where the first matches `left` and
+/// the second `right`.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MergeRule {
+    pub left: PosPattern,
+    pub right: PosPattern,
+    pub action: MergeAction,
+}
+
+impl MergeRule {
+    /// A rule merging `left` with `right`.
+    pub fn merge(left: PosPattern, right: PosPattern) -> Self {
+        Self {
+            left,
+            right,
+            action: MergeAction::Merge,
+        }
+    }
+
+    /// A rule keeping `left` and `right` apart.
+    pub fn forbid(left: PosPattern, right: PosPattern) -> Self {
+        Self {
+            left,
+            right,
+            action: MergeAction::Forbid,
+        }
+    }
+}
+
+/// Merges tokens by a list of [`MergeRule`]s, built up from nothing, which keeps every
+/// token as it is, or from one of the built-in rule sets.
+///
+/// Tokens are taken from left to right, and each is merged into the one before it when
+/// the last rule matching the pair says so, so rules added later take precedence. The
+/// left side of a rule is matched against the last token merged so far, which lets
+/// chains like 食べ|て|い|ます join up pair by pair.
+///
+/// A merged token spans all of its tokens and takes its term, part of speech and kind
+/// from the first of them. The tokens it was merged from become its components.
+#[derive(Debug, Clone, Default)]
+pub struct TokenMerger {
+    rules: Vec<MergeRule>,
+}
+
+impl TokenMerger {
+    /// A merger without rules, keeping every token apart.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Rules for reading: verbs and adjectives take their auxiliaries and the て/で
+    /// linking them to auxiliary verbs, prefixes join the nouns after them, and suffixes
+    /// the nouns before them.
+    pub fn conversational() -> Self {
+        let verb = || PosPattern::Pos(VERB);
+        let mut merger = Self::new()
+            .rule(MergeRule::merge(
+                PosPattern::Pos(VERB | PartOfSpeech::ADJECTIVE | AUXILIARY),
+                PosPattern::Pos(AUXILIARY),
+            ))
+            .with_affixes();
+        for linking in ["て", "で"] {
+            let linking = || PosPattern::Surface(linking.to_string());
+            merger = merger
+                .rule(MergeRule::merge(verb(), linking()))
+                .rule(MergeRule::merge(linking(), verb()));
+        }
+        merger
+    }
+
+    /// Rules for search indexing: prefixes and suffixes join their nouns, so お茶 and
+    /// 田中さん are indexed whole, but verbs are kept apart from their auxiliaries.
+    pub fn indexing() -> Self {
+        Self::new().with_affixes()
+    }
+
+    /// Adds `rule`, taking precedence over the rules already there.
+    pub fn rule(mut self, rule: MergeRule) -> Self {
+        self.rules.push(rule);
+        self
+    }
+
+    /// The rules, in the order they were added.
+    pub fn rules(&self) -> &[MergeRule] {
+        &self.rules
+    }
+
+    /// `tokens` of `text`, in order, with the ones the rules join merged.
+    pub fn apply<'a>(&self, text: &'a str, tokens: Vec<Token<'a>>) -> Vec<Token<'a>> {
+        let mut merged = Vec::with_capacity(tokens.len());
+        let mut run: Vec<Token<'a>> = Vec::new();
+        for token in tokens {
+            if run.last().is_some_and(|last| !self.merges(last, &token)) {
+                merged.push(combine(text, std::mem::take(&mut run)));
+            }
+            run.push(token);
+        }
+        if !run.is_empty() {
+            merged.push(combine(text, run));
+        }
+
+        merged
+    }
+
+    /// Whether the last rule matching `left` followed by `right` merges them.
+    fn merges(&self, left: &Token, right: &Token) -> bool {
+        self.rules
+            .iter()
+            .rev()
+            .find(|rule| rule.left.matches(left) && rule.right.matches(right))
+            .is_some_and(|rule| rule.action == MergeAction::Merge)
+    }
+
+    ///
+    fn with_affixes(self) -> Self {
+        self.rule(MergeRule::merge(
+            PosPattern::Pos(PartOfSpeech::PREFIX | PartOfSpeech::NOUN_PREFIX),
+            PosPattern::Pos(NOUN),
+        ))
+        .rule(MergeRule::merge(
+            PosPattern::Pos(NOUN),
+            PosPattern::Pos(PartOfSpeech::SUFFIX | PartOfSpeech::NOUN_SUFFIX),
+        ))
+    }
+}
+
+/// One token spanning the tokens of `run`, which follow each other in `text`.
+fn combine<'a>(text: &'a str, mut run: Vec<Token<'a>>) -> Token<'a> {
+    if run.len() == 1 {
+        return run.pop().unwrap();
+    }
+
+    let first = &run[0];
+    let last = &run[run.len() - 1];
+    let end = last.byte_start + last.token.len();
+    Token {
+        term_id: first.term_id,
+        token: &text[first.byte_start..end],
+        char_start: first.char_start,
+        byte_start: first.byte_start,
+        term_entry: first.term_entry,
+        pos: first.pos,
+        pos_alternatives: Vec::new(),
+        kind: first.kind,
+        repeat_count: first.repeat_count,
+        components: Some(run),
+    }
+}
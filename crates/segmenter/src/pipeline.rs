@@ -0,0 +1,126 @@
+use crate::analysis::OwnedToken;
+use crate::tokenizer::Tokenizer;
+use std::collections::HashMap;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::{mpsc, Arc, Mutex};
+
+/// Segments a stream of lines on a pool of worker threads, yielding results in input
+/// order.
+pub struct Segmenter {
+    tokenizer: Arc<Tokenizer>,
+    num_threads: usize,
+}
+
+impl Segmenter {
+    /// `num_threads` is clamped to at least one worker.
+    pub fn new(tokenizer: Arc<Tokenizer>, num_threads: usize) -> Self {
+        Self {
+            tokenizer,
+            num_threads: num_threads.max(1),
+        }
+    }
+
+    /// Tokenizes every line of `lines`, pairing each with its tokens.
+    ///
+    /// Lines are pulled from `lines` only as results are consumed, with at most two
+    /// lines per worker in flight, so an unbounded input never piles up in memory. The
+    /// workers stop once the returned iterator is dropped. If tokenizing a line panics,
+    /// the panic is resumed on the thread consuming the iterator when that line is due.
+    pub fn process<I: IntoIterator<Item = String>>(
+        &self,
+        lines: I,
+    ) -> impl Iterator<Item = (String, Vec<OwnedToken>)> {
+        let (job_sender, job_receiver) = mpsc::channel::<(usize, String)>();
+        let (result_sender, result_receiver) = mpsc::channel();
+        let job_receiver = Arc::new(Mutex::new(job_receiver));
+
+        for _ in 0..self.num_threads {
+            let tokenizer = self.tokenizer.clone();
+            let job_receiver = job_receiver.clone();
+            let result_sender = result_sender.clone();
+            std::thread::spawn(move || loop {
+                let job = job_receiver.lock().unwrap().recv();
+                let Ok((index, line)) = job else {
+                    break;
+                };
+
+                // Tokens and the entries they point to from the same dictionary, even
+                // while it is swapped. A panic is sent back in place of the tokens, so
+                // the consumer waiting for this line doesn't wait forever.
+                let tokens = panic::catch_unwind(AssertUnwindSafe(|| {
+                    let tokenizer = tokenizer.current();
+                    tokenizer
+                        .tokenize(&line)
+                        .iter()
+                        .map(|token| {
+                            OwnedToken::resolved(token, &|term_entry| {
+                                Some(tokenizer.entry(term_entry))
+                            })
+                        })
+                        .collect()
+                }));
+                if result_sender.send((index, line, tokens)).is_err() {
+                    break;
+                }
+            });
+        }
+
+        Ordered {
+            lines: lines.into_iter(),
+            job_sender,
+            result_receiver,
+            window: self.num_threads * 2,
+            sent: 0,
+            next: 0,
+            pending: HashMap::new(),
+        }
+    }
+}
+
+/// Result of one line: its index in the input, the line and its tokens, or the panic
+/// that tokenizing it raised.
+type Processed = (usize, String, std::thread::Result<Vec<OwnedToken>>);
+
+/// Feeds lines to the workers and puts their results back in order.
+struct Ordered<I> {
+    lines: I,
+    job_sender: mpsc::Sender<(usize, String)>,
+    result_receiver: mpsc::Receiver<Processed>,
+    /// Maximum number of lines handed out but not yet yielded.
+    window: usize,
+    /// Number of lines handed to the workers so far.
+    sent: usize,
+    /// Index of the next line to yield.
+    next: usize,
+    /// Results that arrived ahead of `next`.
+    pending: HashMap<usize, (String, std::thread::Result<Vec<OwnedToken>>)>,
+}
+
+impl<I: Iterator<Item = String>> Iterator for Ordered<I> {
+    type Item = (String, Vec<OwnedToken>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.sent - self.next < self.window {
+            let Some(line) = self.lines.next() else {
+                break;
+            };
+            self.job_sender.send((self.sent, line)).ok()?;
+            self.sent += 1;
+        }
+
+        while self.next < self.sent {
+            if let Some((line, tokens)) = self.pending.remove(&self.next) {
+                self.next += 1;
+                return match tokens {
+                    Ok(tokens) => Some((line, tokens)),
+                    Err(payload) => panic::resume_unwind(payload),
+                };
+            }
+
+            let (index, line, tokens) = self.result_receiver.recv().ok()?;
+            self.pending.insert(index, (line, tokens));
+        }
+
+        None
+    }
+}
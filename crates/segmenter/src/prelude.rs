@@ -0,0 +1,26 @@
+//! The types nearly every user of the crate needs, for a single glob import:
+//!
+//! ```
+//! use segmenter::prelude::*;
+//! ```
+//!
+//! What the prelude guarantees: items are only added to it in minor releases, never
+//! removed or renamed outside a major release, and it is the same with every set of
+//! features. It only holds types named after this crate's own concepts, so a glob
+//! import shouldn't clash with the standard prelude or common crates; a new item that
+//! clashes with a user's own type can still break their build, which is why additions
+//! are rare. Anything else stays at its module path.
+//!
+//! With the default `serde` feature every data type of the crate, these included,
+//! implements `Serialize` and `Deserialize`, except that [`Token`] and the types
+//! holding tokens borrow the tokenized text and only implement `Serialize`. Without it
+//! the crate doesn't depend on serde at all, and dictionaries can only be built in
+//! memory, e.g. with [`DictionaryBuilder`], since saving and loading them needs
+//! bincode.
+
+pub use crate::dictionary::{
+    Dictionary, DictionaryBuilder, DictionaryError, InflectionType, PartOfSpeech, Tag,
+    TermEntry, TermId,
+};
+pub use crate::error::SegmenterError;
+pub use crate::tokenizer::{Token, TokenKind, Tokenizer, TokenizerOptions};
@@ -0,0 +1,178 @@
+use crate::tokenizer::Token;
+use wana_kana::ConvertJapanese;
+
+/// Extended katakana combinations used for loanwords, checked before wana_kana.
+const COMBINATIONS: &[(&str, &str)] = &[
+    ("ティ", "ti"),
+    ("ディ", "di"),
+    ("トゥ", "tu"),
+    ("ドゥ", "du"),
+    ("テュ", "tyu"),
+    ("デュ", "dyu"),
+    ("ファ", "fa"),
+    ("フィ", "fi"),
+    ("フェ", "fe"),
+    ("フォ", "fo"),
+    ("フュ", "fyu"),
+    ("ウィ", "wi"),
+    ("ウェ", "we"),
+    ("ウォ", "wo"),
+    ("ヴァ", "va"),
+    ("ヴィ", "vi"),
+    ("ヴェ", "ve"),
+    ("ヴォ", "vo"),
+    ("ヴ", "vu"),
+    ("シェ", "she"),
+    ("ジェ", "je"),
+    ("チェ", "che"),
+    ("ツァ", "tsa"),
+    ("ツィ", "tsi"),
+    ("ツェ", "tse"),
+    ("ツォ", "tso"),
+    ("イェ", "ye"),
+    ("クァ", "kwa"),
+    ("グァ", "gwa"),
+];
+
+/// Hepburn spellings and their Kunrei-shiki equivalents, longest first.
+const HEPBURN: &[(&str, &str)] = &[
+    ("tch", "tty"),
+    ("shi", "si"),
+    ("sh", "sy"),
+    ("chi", "ti"),
+    ("ch", "ty"),
+    ("tsu", "tu"),
+    ("fu", "hu"),
+    ("ji", "zi"),
+    ("j", "zy"),
+];
+
+/// Small kana that combine with the preceding one into a single mora.
+const SMALL_KANA: &[char] = &['ャ', 'ュ', 'ョ', 'ァ', 'ィ', 'ゥ', 'ェ', 'ォ'];
+
+///
+fn is_katakana(c: char) -> bool {
+    matches!(c as u32, 0x30A1..=0x30FA | 0x30FC)
+}
+
+/// Romaji of one mora, and how many characters of `chars` it consumed.
+fn mora(chars: &[char]) -> (String, usize) {
+    for length in [2, 1] {
+        let Some(prefix) = chars.get(..length) else {
+            continue;
+        };
+        let prefix: String = prefix.iter().collect();
+        if let Some((_, romaji)) = COMBINATIONS.iter().find(|(kana, _)| *kana == prefix) {
+            return (romaji.to_string(), length);
+        }
+    }
+
+    let length = if chars.get(1).is_some_and(|c| SMALL_KANA.contains(c)) {
+        2
+    } else {
+        1
+    };
+    let kana: String = chars[..length].iter().collect();
+    (kana.to_romaji(), length)
+}
+
+/// Hepburn-style romaji of a katakana-only `text`, for loanwords and brand names.
+///
+/// Long vowel marks repeat the previous vowel (コーヒー → koohii) and ッ doubles the next
+/// consonant (キッチン → kitchin). `None` if `text` contains anything but katakana.
+pub fn katakana_to_romaji(text: &str) -> Option<String> {
+    let chars: Vec<char> = text.chars().collect();
+    if chars.is_empty() || !chars.iter().all(|c| is_katakana(*c)) {
+        return None;
+    }
+
+    let mut output = String::new();
+    let mut geminate = false;
+    let mut i = 0;
+    while i < chars.len() {
+        match chars[i] {
+            'ー' => {
+                if let Some(vowel) =
+                    output.chars().last().filter(|c| "aeiou".contains(*c))
+                {
+                    output.push(vowel);
+                }
+                i += 1;
+            }
+            'ッ' => {
+                geminate = true;
+                i += 1;
+            }
+            _ => {
+                let (romaji, length) = mora(&chars[i..]);
+                if std::mem::take(&mut geminate) {
+                    match romaji.chars().next() {
+                        Some(_) if romaji.starts_with("ch") => output.push('t'),
+                        Some(c) if !"aeiou".contains(c) => output.push(c),
+                        _ => {}
+                    }
+                }
+                output.push_str(&romaji);
+                i += length;
+            }
+        }
+    }
+
+    Some(output)
+}
+
+/// Romaji of any kana `text`: [`katakana_to_romaji`] for katakana, wana_kana otherwise.
+pub fn kana_to_romaji(text: &str) -> String {
+    katakana_to_romaji(text).unwrap_or_else(|| text.to_romaji())
+}
+
+/// Folds the spelling variants of romaji onto one form, so typed romaji can be compared
+/// with romanized kana: lowercase, without separators or long vowels (ō, ou and oo all
+/// become o) and with Kunrei-shiki consonants (shi → si, tsu → tu, chi → ti, fu → hu,
+/// ji → zi).
+///
+/// Folding merges some distinct words, like おばさん and おばあさん, which is fine for
+/// search suggestions but not for exact matching.
+pub fn fold_romaji(romaji: &str) -> String {
+    let mut text = String::with_capacity(romaji.len());
+    for c in romaji.chars().flat_map(char::to_lowercase) {
+        match c {
+            'ā' | 'â' => text.push('a'),
+            'ī' | 'î' => text.push('i'),
+            'ū' | 'û' => text.push('u'),
+            'ē' | 'ê' => text.push('e'),
+            'ō' | 'ô' => text.push('o'),
+            '-' | '\'' | ' ' | 'ー' => {}
+            c => text.push(c),
+        }
+    }
+    for (hepburn, kunrei) in HEPBURN {
+        text = text.replace(hepburn, kunrei);
+    }
+
+    let mut folded = String::with_capacity(text.len());
+    for c in text.chars() {
+        let is_long_vowel = match (folded.chars().last(), c) {
+            (Some(previous), 'a' | 'i' | 'u' | 'e' | 'o') if previous == c => true,
+            (Some('o'), 'u') => true,
+            _ => false,
+        };
+        if !is_long_vowel {
+            folded.push(c);
+        }
+    }
+
+    folded
+}
+
+impl Token<'_> {
+    /// Romaji hint for an unknown katakana token, see [`katakana_to_romaji`].
+    ///
+    /// Dictionary terms return `None`: their readings and glosses are better sources.
+    pub fn katakana_romaji(&self) -> Option<String> {
+        if self.term_id.is_some() {
+            return None;
+        }
+        katakana_to_romaji(self.token)
+    }
+}
@@ -0,0 +1,308 @@
+use crate::{
+    counters,
+    dictionary::{Dictionary, PartOfSpeech, Tag},
+    tokenizer::Token,
+};
+use std::borrow::Cow;
+
+/// Picks one of `candidates` (never empty) for `token`, given its neighbours.
+///
+/// Candidates are ordered by priority, and the returned index is clamped to the list.
+pub type ReadingSelector = fn(&Token, Option<&Token>, Option<&Token>, &[&str]) -> usize;
+
+/// Default [`ReadingSelector`]: always the highest-priority reading.
+pub fn highest_priority(
+    _token: &Token,
+    _prev: Option<&Token>,
+    _next: Option<&Token>,
+    _candidates: &[&str],
+) -> usize {
+    0
+}
+
+///
+fn is_kanji(c: char) -> bool {
+    matches!(c as u32, 0x3005 | 0x3400..=0x4DBF | 0x4E00..=0x9FFF | 0xF900..=0xFAFF)
+}
+
+/// Splits `surface` into the kana prefix, the part that needs a reading and the kana
+/// suffix (okurigana), returning the reading that belongs to the middle part.
+fn align<'a>(surface: &'a str, reading: &'a str) -> [&'a str; 4] {
+    let mut base = surface;
+    let mut ruby = reading;
+    while let (Some(a), Some(b)) = (base.chars().next_back(), ruby.chars().next_back()) {
+        if a != b || base.len() == a.len_utf8() {
+            break;
+        }
+        base = &base[..base.len() - a.len_utf8()];
+        ruby = &ruby[..ruby.len() - b.len_utf8()];
+    }
+    while let (Some(a), Some(b)) = (base.chars().next(), ruby.chars().next()) {
+        if a != b || base.len() == a.len_utf8() {
+            break;
+        }
+        base = &base[a.len_utf8()..];
+        ruby = &ruby[b.len_utf8()..];
+    }
+
+    let prefix_len = base.as_ptr() as usize - surface.as_ptr() as usize;
+    let prefix = &surface[..prefix_len];
+    let suffix = &surface[prefix_len + base.len()..];
+    [prefix, base, ruby, suffix]
+}
+
+///
+fn escape_html(text: &str, output: &mut String) {
+    for c in text.chars() {
+        match c {
+            '&' => output.push_str("&amp;"),
+            '<' => output.push_str("&lt;"),
+            '>' => output.push_str("&gt;"),
+            '"' => output.push_str("&quot;"),
+            _ => output.push(c),
+        }
+    }
+}
+
+/// A reading [`RubyRenderer::ruby_readings`] places above one or more tokens.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RubyReading<'d> {
+    /// Indices of the tokens the reading goes above.
+    pub tokens: std::ops::Range<usize>,
+    pub reading: Cow<'d, str>,
+    /// `false` for a number followed by a counter missing from [`counters::COUNTERS`],
+    /// whose readings are joined as they are, though the counter may change them.
+    pub confident: bool,
+}
+
+/// What the renderers write for a stretch of tokens: its surface as it is, or split by
+/// [`align`] around its reading.
+enum Piece<'a> {
+    Plain(&'a str),
+    Ruby([&'a str; 4]),
+}
+
+/// Renders tokens with readings above their kanji.
+pub struct RubyRenderer<'d> {
+    dictionary: &'d Dictionary,
+    selector: ReadingSelector,
+}
+
+///
+impl<'d> RubyRenderer<'d> {
+    ///
+    pub fn new(dictionary: &'d Dictionary) -> Self {
+        Self {
+            dictionary,
+            selector: highest_priority,
+        }
+    }
+
+    /// Replaces the [`highest_priority`] default used to choose between readings.
+    pub fn with_selector(mut self, selector: ReadingSelector) -> Self {
+        self.selector = selector;
+        self
+    }
+
+    /// Chosen reading for every token; `None` for tokens without kanji or readings.
+    pub fn readings(&self, tokens: &[Token]) -> Vec<Option<&'d str>> {
+        tokens
+            .iter()
+            .enumerate()
+            .map(|(i, token)| {
+                if !token.token.chars().any(is_kanji) {
+                    return None;
+                }
+
+                let candidates = self.dictionary.readings_for(token.token);
+                if candidates.is_empty() {
+                    return None;
+                }
+
+                let prev = i.checked_sub(1).map(|i| &tokens[i]);
+                let next = tokens.get(i + 1);
+                let index = (self.selector)(token, prev, next, &candidates);
+                Some(candidates[index.min(candidates.len() - 1)])
+            })
+            .collect()
+    }
+
+    /// The readings to render above `tokens`: the chosen reading of every token with
+    /// one, except for numbers followed by a counter, like 三 and 本, which share the
+    /// reading of the whole, さんぼん, from [`counters::counted_reading`]. A number may
+    /// be spread over several tokens or merged with its counter into one.
+    pub fn ruby_readings(&self, tokens: &[Token]) -> Vec<RubyReading<'d>> {
+        let readings = self.readings(tokens);
+        let mut ruby = Vec::new();
+        let mut i = 0;
+        while i < tokens.len() {
+            if let Some(counted) = self.counted_reading(tokens, &readings, i) {
+                i = counted.tokens.end;
+                ruby.push(counted);
+                continue;
+            }
+
+            if let Some(reading) = readings[i] {
+                ruby.push(RubyReading {
+                    tokens: i..i + 1,
+                    reading: Cow::Borrowed(reading),
+                    confident: true,
+                });
+            }
+            i += 1;
+        }
+
+        ruby
+    }
+
+    /// The reading of a number starting at token `start` and the counter after it,
+    /// whether in tokens of their own or merged into one without a reading. Counters
+    /// missing from [`counters::COUNTERS`] are recognized by their part of speech.
+    fn counted_reading(
+        &self,
+        tokens: &[Token],
+        readings: &[Option<&'d str>],
+        start: usize,
+    ) -> Option<RubyReading<'d>> {
+        let number_end = (start..tokens.len())
+            .find(|i| !counters::is_number(tokens[*i].token))
+            .unwrap_or(tokens.len());
+        let (number, counter, pos, counter_reading, end) = if number_end > start {
+            // Numbers spread over tokens, like 二十 and 三, are read as one.
+            let number: String = tokens[start..number_end]
+                .iter()
+                .map(|token| token.token)
+                .collect();
+            let counter = tokens.get(number_end)?;
+            let counter_reading = readings[number_end];
+            (
+                number,
+                counter.token,
+                counter.pos,
+                counter_reading,
+                number_end + 1,
+            )
+        } else {
+            let token = &tokens[start];
+            if readings[start].is_some() {
+                return None;
+            }
+            let split = token
+                .token
+                .char_indices()
+                .map(|(n, _)| n)
+                .rfind(|n| *n > 0 && counters::is_number(&token.token[..*n]))?;
+            let (number, counter) = token.token.split_at(split);
+            let last = token
+                .components
+                .as_ref()
+                .and_then(|components| components.last());
+            let pos = last.map_or(PartOfSpeech::NONE, |last| last.pos);
+            let counter_reading = self.dictionary.readings_for(counter).first().copied();
+            (number.to_string(), counter, pos, counter_reading, start + 1)
+        };
+
+        if let Some(reading) = counters::counted_reading(&number, counter) {
+            return Some(RubyReading {
+                tokens: start..end,
+                reading: Cow::Owned(reading),
+                confident: true,
+            });
+        }
+        if !pos.contains(PartOfSpeech::COUNTER) {
+            return None;
+        }
+        // Kana counters are read as they are written.
+        let counter_reading = match counter_reading {
+            Some(reading) => reading,
+            None if !counter.chars().any(is_kanji) => counter,
+            None => return None,
+        };
+        Some(RubyReading {
+            tokens: start..end,
+            reading: Cow::Owned(counters::number_reading(&number)? + counter_reading),
+            confident: false,
+        })
+    }
+
+    /// `token` split into the kana prefix, the part `reading` goes above and the kana
+    /// suffix, with the reading of the middle part. Words spelled with [`Tag::ATEJI`]
+    /// take the reading over their whole surface, as it doesn't follow their kanji.
+    fn align<'a>(&self, token: &'a Token, reading: &'a str) -> [&'a str; 4] {
+        let ateji = token
+            .term_entry
+            .and_then(|term_entry| {
+                self.dictionary.entries.get(term_entry.entry_index as usize)
+            })
+            .is_some_and(|entry| entry.tag.contains(Tag::ATEJI));
+        if ateji {
+            ["", token.token, reading, ""]
+        } else {
+            align(token.token, reading)
+        }
+    }
+
+    /// Calls `write` with every stretch of `tokens`, in order: tokens without a reading
+    /// as they are, and those under one of [`RubyRenderer::ruby_readings`] split around
+    /// it.
+    fn render(&self, tokens: &[Token], mut write: impl FnMut(Piece<'_>)) {
+        let mut readings = self.ruby_readings(tokens).into_iter().peekable();
+        let mut i = 0;
+        while i < tokens.len() {
+            let Some(reading) = readings.next_if(|reading| reading.tokens.start == i)
+            else {
+                write(Piece::Plain(tokens[i].token));
+                i += 1;
+                continue;
+            };
+
+            if reading.tokens.len() == 1 {
+                write(Piece::Ruby(self.align(&tokens[i], &reading.reading)));
+            } else {
+                let surface: String = tokens[reading.tokens.clone()]
+                    .iter()
+                    .map(|token| token.token)
+                    .collect();
+                write(Piece::Ruby(align(&surface, &reading.reading)));
+            }
+            i = reading.tokens.end;
+        }
+    }
+
+    /// HTML `<ruby>` markup, e.g. `<ruby>飲<rt>の</rt></ruby>み`.
+    pub fn html(&self, tokens: &[Token]) -> String {
+        let mut output = String::new();
+        self.render(tokens, |piece| match piece {
+            Piece::Plain(text) => escape_html(text, &mut output),
+            Piece::Ruby([prefix, base, ruby, suffix]) => {
+                escape_html(prefix, &mut output);
+                output.push_str("<ruby>");
+                escape_html(base, &mut output);
+                output.push_str("<rt>");
+                escape_html(ruby, &mut output);
+                output.push_str("</rt></ruby>");
+                escape_html(suffix, &mut output);
+            }
+        });
+
+        output
+    }
+
+    /// Plain-text furigana in bracket notation, e.g. `飲[の]み`.
+    pub fn furigana(&self, tokens: &[Token]) -> String {
+        let mut output = String::new();
+        self.render(tokens, |piece| match piece {
+            Piece::Plain(text) => output.push_str(text),
+            Piece::Ruby([prefix, base, ruby, suffix]) => {
+                output.push_str(prefix);
+                output.push_str(base);
+                output.push('[');
+                output.push_str(ruby);
+                output.push(']');
+                output.push_str(suffix);
+            }
+        });
+
+        output
+    }
+}
@@ -0,0 +1,75 @@
+//! Distributional statistics over segmented corpora.
+
+use crate::analysis::OwnedToken;
+use crate::dictionary::PartOfSpeech;
+use crate::pipeline::Segmenter;
+use std::collections::HashMap;
+
+/// Part of speech standing in for tokens without a dictionary term in [`pos_ngrams`].
+/// Every flag at once, which no word has, so it can't be mistaken for a real one.
+pub const UNKNOWN: PartOfSpeech = PartOfSpeech::all();
+
+/// The part of speech a token is counted as: its own, [`UNKNOWN`] for unknown tokens
+/// that aren't built-in symbols or function words.
+fn ngram_pos(token: &OwnedToken) -> PartOfSpeech {
+    if token.term_id.is_none() && token.pos.is_empty() {
+        UNKNOWN
+    } else {
+        token.pos
+    }
+}
+
+/// Counts the runs of `n` consecutive parts of speech in the segmentation of `texts`.
+///
+/// Texts are tokenized on the workers of `segmenter` as they are pulled from `texts`,
+/// and n-grams never span two texts. Blank tokens are left out. `n` is clamped to at
+/// least one.
+pub fn pos_ngrams<I: IntoIterator<Item = String>>(
+    segmenter: &Segmenter,
+    texts: I,
+    n: usize,
+) -> HashMap<Vec<PartOfSpeech>, u64> {
+    let n = n.max(1);
+    let mut counts = HashMap::new();
+    for (_, tokens) in segmenter.process(texts) {
+        let pos: Vec<PartOfSpeech> = tokens
+            .iter()
+            .filter(|token| !token.surface.trim().is_empty())
+            .map(ngram_pos)
+            .collect();
+        for ngram in pos.windows(n) {
+            *counts.entry(ngram.to_vec()).or_insert(0) += 1;
+        }
+    }
+
+    counts
+}
+
+/// `counts` as a table, most frequent first and ties in [`ngram_name`] order.
+pub fn sorted_ngrams(
+    counts: HashMap<Vec<PartOfSpeech>, u64>,
+) -> Vec<(Vec<PartOfSpeech>, u64)> {
+    let mut table: Vec<(String, Vec<PartOfSpeech>, u64)> = counts
+        .into_iter()
+        .map(|(ngram, count)| (ngram_name(&ngram), ngram, count))
+        .collect();
+    table.sort_by(|(a, _, a_count), (b, _, b_count)| b_count.cmp(a_count).then(a.cmp(b)));
+    table
+        .into_iter()
+        .map(|(_, ngram, count)| (ngram, count))
+        .collect()
+}
+
+/// The flag names of every part of speech, `UNKNOWN` for [`UNKNOWN`], joined like
+/// `NOUN PARTICLE GODAN_VERB|TRANSITIVE_VERB`.
+pub fn ngram_name(ngram: &[PartOfSpeech]) -> String {
+    let names: Vec<String> = ngram
+        .iter()
+        .map(|pos| match *pos {
+            UNKNOWN => "UNKNOWN".to_string(),
+            PartOfSpeech::NONE => "NONE".to_string(),
+            pos => pos.names().join("|"),
+        })
+        .collect();
+    names.join(" ")
+}
@@ -0,0 +1,76 @@
+use crate::dictionary::PartOfSpeech;
+
+/// Japanese punctuation, brackets and the middle dot. JMdict has no entries for these,
+/// so they are tagged here instead. ASCII punctuation is handled separately.
+const SYMBOLS: &[char] = &[
+    // Punctuation
+    '、', '。', '，', '．', '・', '･', '：', '；', '？', '！', '…', '‥', '〜', '～', '｡',
+    '､', // Brackets
+    '「', '」', '『', '』', '（', '）', '〔', '〕', '［', '］', '｛', '｝', '〈', '〉',
+    '《', '》', '【', '】', '〖', '〗', '〘', '〙', '〚', '〛', '｢', '｣',
+    // Quotes
+    '‘', '’', '“', '”', '〝', '〟',
+];
+
+/// The punctuation `c` stands for when it is a vertical presentation form (U+FE10 to
+/// U+FE19, U+FE30 to U+FE48) or a variant of the katakana middle dot, `c` otherwise.
+/// Text from vertical layouts, like ebooks or manga OCR, is full of these.
+pub fn horizontal_form(c: char) -> char {
+    match c {
+        '︐' => '，',
+        '︑' => '、',
+        '︒' => '。',
+        '︓' => '：',
+        '︔' => '；',
+        '︕' => '！',
+        '︖' => '？',
+        '︗' => '〖',
+        '︘' => '〗',
+        '︙' => '…',
+        '︰' => '‥',
+        '︱' => '—',
+        '︲' => '–',
+        '︳' | '︴' => '_',
+        '︵' => '（',
+        '︶' => '）',
+        '︷' => '｛',
+        '︸' => '｝',
+        '︹' => '〔',
+        '︺' => '〕',
+        '︻' => '【',
+        '︼' => '】',
+        '︽' => '《',
+        '︾' => '》',
+        '︿' => '〈',
+        '﹀' => '〉',
+        '﹁' => '「',
+        '﹂' => '」',
+        '﹃' => '『',
+        '﹄' => '』',
+        '﹇' => '［',
+        '﹈' => '］',
+        '･' | '·' | '‧' | '∙' | '⋅' => '・',
+        _ => c,
+    }
+}
+
+/// `text` with every character replaced by its [`horizontal_form`], `None` when none
+/// changes.
+pub fn to_horizontal(text: &str) -> Option<String> {
+    if text.chars().all(|c| horizontal_form(c) == c) {
+        return None;
+    }
+    Some(text.chars().map(horizontal_form).collect())
+}
+
+/// Whether `c`, or the punctuation it stands for in vertical text, is a built-in symbol.
+pub fn is_symbol(c: char) -> bool {
+    let c = horizontal_form(c);
+    c.is_ascii_punctuation() || SYMBOLS.contains(&c)
+}
+
+/// Returns [`PartOfSpeech::SYMBOL`] if `surface` consists only of built-in symbols.
+pub fn symbol_pos(surface: &str) -> Option<PartOfSpeech> {
+    let is_symbol_run = !surface.is_empty() && surface.chars().all(is_symbol);
+    is_symbol_run.then_some(PartOfSpeech::SYMBOL)
+}
@@ -1,373 +1,2970 @@
 use crate::{
-    dictionary::{Dictionary, DictionaryEntry, PartOfSpeech, Tag},
-    lattice::{Lattice, LatticeNode},
+    candidate::{CandidateSink, CandidateSource, PositionCandidates, ScoredCandidate},
+    deinflect,
+    detect::{self, JapaneseThresholds, SentenceRules},
+    dictionary::{
+        Dialect, Dictionary, DictionaryEntry, DictionaryId, Domain, InflectionType,
+        PartOfSpeech, SurfaceTrie, Tag, TermEntry, TermId,
+    },
+    error::SegmenterError,
+    filter, function_words, graphemes,
+    health::{self, SelfTestError, SelfTestReport, SelfTestThresholds},
+    invisible::VisibleText,
+    lattice::{Lattice, LatticeNode, LatticeSnapshot, NodeId, PathScores, SnapshotNode},
+    symbols,
+    unknown::{UnknownCategory, UnknownWordModel},
 };
-use regex::RegexSet;
-use std::sync::Arc;
+use regex::Regex;
+use std::borrow::Cow;
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, RwLock};
 
 ///
 #[derive(Clone, Copy, PartialEq, Eq)]
-enum WordCategory {
+pub(crate) enum WordCategory {
     Katakana,
     Kana,
     Word,
     NonWord,
 }
 
+/// Code points of the kanji category of the unknown-term tables, with 々 and 〇.
+const KANJI: &[std::ops::RangeInclusive<u32>] = &[
+    0x2E80..=0x2EF3,
+    0x2F00..=0x2FD5,
+    0x3005..=0x3005,
+    0x3007..=0x3007,
+    0x3400..=0x4DB5,
+    0x4E00..=0x9FA5,
+    0xF900..=0xFA2D,
+    0xFA30..=0xFA6A,
+];
+
+/// Code points of the hiragana category of the unknown-term tables, with the
+/// iteration marks ゝゞ.
+const HIRAGANA: &[std::ops::RangeInclusive<u32>] = &[0x3041..=0x309F];
+
+/// Code points of the katakana category of the unknown-term tables, with the long
+/// vowel mark ー, the iteration marks ヽヾ and half-width katakana.
+const KATAKANA: &[std::ops::RangeInclusive<u32>] = &[
+    0x30A1..=0x30FA,
+    0x30FC..=0x30FF,
+    0x31F0..=0x31FF,
+    0xFF66..=0xFF9F,
+];
+
 ///
-const REGEX_CATEGORIES: [(&str, WordCategory); 4] = [
-    (r"^\p{Katakana}+$", WordCategory::Katakana),
-    // (r"^\p{Hiragana}+$", WordCategory::Hiragana),
-    // (r"^\p{Han}+$", WordCategory::Kanji),
-    (r"^(\p{Katakana}|\p{Hiragana})+$", WordCategory::Kana),
-    (r"^(\p{Han}|\p{Hiragana})+$", WordCategory::Word),
-    (r"^[^々一-龯ァ-ヺヽヾぁ-ゔゝゞー]+$", WordCategory::NonWord),
+fn in_ranges(c: char, ranges: &[std::ops::RangeInclusive<u32>]) -> bool {
+    ranges.iter().any(|range| range.contains(&(c as u32)))
+}
+
+/// Whether `c` is kanji, hiragana or katakana by the unknown-term tables.
+fn is_japanese(c: char) -> bool {
+    in_ranges(c, KANJI) || in_ranges(c, HIRAGANA) || in_ranges(c, KATAKANA)
+}
+
+/// Scripts each word category is made of, tried in order. Surfaces matching none of
+/// them are [`WordCategory::NonWord`].
+const SCRIPT_CATEGORIES: [(&[&[std::ops::RangeInclusive<u32>]], WordCategory); 3] = [
+    (&[KATAKANA], WordCategory::Katakana),
+    (&[KATAKANA, HIRAGANA], WordCategory::Kana),
+    (&[KANJI, HIRAGANA], WordCategory::Word),
 ];
 
+/// A pattern matching the strings made of nothing but the characters of `scripts`.
+fn script_pattern(scripts: &[&[std::ops::RangeInclusive<u32>]]) -> String {
+    let class: String = scripts
+        .iter()
+        .flat_map(|ranges| ranges.iter())
+        .map(|range| format!(r"\x{{{:X}}}-\x{{{:X}}}", range.start(), range.end()))
+        .collect();
+    format!("^[{class}]+$")
+}
+
+/// Highest score a single candidate gets from its length, however long it is and
+/// whatever [`TokenizerOptions::word_length_power`] and
+/// [`TokenizerOptions::length_power`] are. Path totals are summed in `f64`, which keeps
+/// differences of a fraction of a point visible next to many nodes of this score.
+pub const MAX_NODE_SCORE: f32 = 1.0e12;
+
+/// A [`TokenizerOptions::window`] keeping the lattice small while leaving most of every
+/// window to commit.
+pub const DEFAULT_WINDOW: usize = 512;
+
+/// The first of [`SCRIPT_CATEGORIES`] whose scripts make up all of `word`.
 ///
-fn categorize_word(word: &str) -> WordCategory {
+/// The patterns are tried one by one, since a `RegexSet` allocates its matches on every
+/// call and this runs for every substring looked up.
+pub(crate) fn categorize_word(word: &str) -> WordCategory {
     lazy_static::lazy_static! {
-        static ref REGEX: RegexSet =
-            RegexSet::new(REGEX_CATEGORIES.iter().map(|(r, _)| r)).unwrap();
+        static ref REGEXES: Vec<(Regex, WordCategory)> = {
+            count_initialization();
+            SCRIPT_CATEGORIES
+                .iter()
+                .map(|(scripts, category)| {
+                    (Regex::new(&script_pattern(scripts)).unwrap(), *category)
+                })
+                .collect()
+        };
+    }
+
+    REGEXES
+        .iter()
+        .find(|(regex, _)| regex.is_match(word))
+        .map_or(WordCategory::NonWord, |(_, category)| *category)
+}
+
+/// Number of one-time initializations run so far, like compiling the patterns behind
+/// word categorization, for checking that [`Tokenizer::warm_up`] leaves none for later.
+pub fn initialization_count() -> usize {
+    INITIALIZATIONS.load(std::sync::atomic::Ordering::Relaxed)
+}
+
+///
+static INITIALIZATIONS: std::sync::atomic::AtomicUsize =
+    std::sync::atomic::AtomicUsize::new(0);
+
+/// Counts a one-time initialization for [`initialization_count`].
+pub(crate) fn count_initialization() {
+    INITIALIZATIONS.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+}
+
+/// How long each one-time initialization took in [`Tokenizer::warm_up`], next to nothing
+/// for those already done.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct WarmUpTimings {
+    /// Compiling the patterns that tell kana, katakana and kanji surfaces apart.
+    pub word_categories: std::time::Duration,
+    /// Building the deinflection rule table.
+    pub deinflection_rules: std::time::Duration,
+}
+
+impl WarmUpTimings {
+    /// Time spent on all components.
+    pub fn total(&self) -> std::time::Duration {
+        self.word_categories + self.deinflection_rules
+    }
+}
+
+///
+struct Category {
+    invoke: bool,
+    group: bool,
+    /// Grouped runs may also end right before a built-in function word, so the words
+    /// between the particles of kana-only text can be told apart.
+    split_before_function_words: bool,
+    func: fn(char) -> bool,
+}
+
+///
+const CATEGORIES: &[Category] = &[
+    // Space
+    Category {
+        invoke: false,
+        group: true,
+        split_before_function_words: false,
+        func: |c| matches!(c as u32, 0x0020 | 0x00D0 | 0x0009 | 0x000B | 0x000A),
+    },
+    // Kanji
+    Category {
+        invoke: false,
+        group: false,
+        split_before_function_words: false,
+        func: |c| in_ranges(c, KANJI),
+    },
+    // Symbol
+    Category {
+        invoke: true,
+        group: true,
+        split_before_function_words: false,
+        func: |c| {
+            matches!(c as u32,
+                0x0021..=0x002F
+                | 0x003A..=0x0040
+                | 0x005B..=0x0060
+                | 0x007B..=0x007E
+                | 0x00A1..=0x00BF
+                | 0xFF01..=0xFF0F
+                | 0xFF1A..=0xFF1F
+                | 0xFF3B..=0xFF40
+                | 0xFF5B..=0xFF65
+                | 0xFFE0..=0xFFEF
+                | 0x2000..=0x206F
+                | 0x20A0..=0x20CF
+                | 0x20D0..=0x20FF
+                | 0x2100..=0x214F
+                | 0x2190..=0x21FF
+                | 0x2200..=0x22FF
+                | 0x2300..=0x23FF
+                | 0x2460..=0x24FF
+                | 0x2501..=0x257F
+                | 0x2580..=0x259F
+                | 0x25A0..=0x25FF
+                | 0x2600..=0x26FE
+                | 0x2700..=0x27BF
+                | 0x27F0..=0x27FF
+                | 0x27C0..=0x27EF
+                | 0x2800..=0x28FF
+                | 0x2900..=0x297F
+                | 0x2B00..=0x2BFF
+                | 0x2A00..=0x2AFF
+                | 0x3300..=0x33FF
+                | 0x3200..=0x32FE
+                | 0x3000..=0x303F
+                | 0xFE10..=0xFE19
+                | 0xFE30..=0xFE4F
+                | 0xFE50..=0xFE6B
+                | 0x30FB
+            )
+        },
+    },
+    // Numeric
+    Category {
+        invoke: true,
+        group: true,
+        split_before_function_words: false,
+        func: |c| {
+            matches!(c as u32,
+                0x0030..=0x0039
+                | 0xFF10..=0xFF19
+                | 0x2070..=0x209F
+                | 0x2150..=0x218F
+            )
+        },
+    },
+    // Alpha
+    Category {
+        invoke: false,
+        group: true,
+        split_before_function_words: false,
+        func: |c| {
+            matches!(c as u32,
+                0x0041..=0x005A
+                | 0x0061..=0x007A
+                | 0x00C0..=0x00FF
+                | 0x0100..=0x017F
+                | 0x0180..=0x0236
+                | 0x1E00..=0x1EF9
+                | 0xFF21..=0xFF3A
+                | 0xFF41..=0xFF5A
+            )
+        },
+    },
+    // Hiragana
+    Category {
+        invoke: false,
+        group: true,
+        split_before_function_words: true,
+        func: |c| in_ranges(c, HIRAGANA),
+    },
+    // Katakana
+    Category {
+        invoke: true,
+        group: true,
+        split_before_function_words: false,
+        func: |c| in_ranges(c, KATAKANA),
+    },
+    // Greek
+    Category {
+        invoke: true,
+        group: true,
+        split_before_function_words: false,
+        func: |c| matches!(c as u32, 0x0374..=0x03FB),
+    },
+    // Cyrillic
+    Category {
+        invoke: true,
+        group: true,
+        split_before_function_words: false,
+        func: |c| matches!(c as u32, 0x0400..=0x04F9 | 0x0500..=0x050F),
+    },
+];
+
+/// Byte offset of every character of `text`, then `text.len()`, so the bytes of the
+/// characters `start..end` are `offsets[start]..offsets[end]`.
+pub(crate) fn char_offsets(text: &str) -> Vec<usize> {
+    text.char_indices()
+        .map(|(offset, _)| offset)
+        .chain(std::iter::once(text.len()))
+        .collect()
+}
+
+/// Small kana that belong to the mora of the kana before them.
+fn is_small_kana(c: char) -> bool {
+    matches!(
+        c,
+        'ぁ' | 'ぃ'
+            | 'ぅ'
+            | 'ぇ'
+            | 'ぉ'
+            | 'ゃ'
+            | 'ゅ'
+            | 'ょ'
+            | 'ゎ'
+            | 'ァ'
+            | 'ィ'
+            | 'ゥ'
+            | 'ェ'
+            | 'ォ'
+            | 'ャ'
+            | 'ュ'
+            | 'ョ'
+            | 'ヮ'
+    )
+}
+
+/// Kana of the e-row, which end the conditional stem of godan and ichidan verbs.
+fn is_e_row_kana(c: char) -> bool {
+    "えけげせぜてでねへべぺめれエケゲセゼテデネヘベペメレ".contains(c)
+}
+
+///
+fn is_sokuon(c: char) -> bool {
+    matches!(c, 'っ' | 'ッ')
+}
+
+///
+fn is_hiragana(c: char) -> bool {
+    matches!(c as u32, 0x3041..=0x309F)
+}
+
+///
+fn is_kana(c: char) -> bool {
+    matches!(c as u32, 0x3041..=0x3096 | 0x30A1..=0x30FA | 0x30FC)
+}
+
+/// `surface` with the kana iteration marks ゝゞヽヾ replaced by the kana they repeat,
+/// voiced for ゞ and ヾ, `None` without any. Marks with no kana before them are kept.
+fn expand_iteration_marks(surface: &str) -> Option<String> {
+    if !surface.contains(['ゝ', 'ゞ', 'ヽ', 'ヾ']) {
+        return None;
+    }
+
+    let mut expanded = String::with_capacity(surface.len());
+    let mut previous: Option<char> = None;
+    for c in surface.chars() {
+        let repeated = match c {
+            'ゝ' | 'ヽ' => previous,
+            'ゞ' | 'ヾ' => previous.map(voiced),
+            _ => None,
+        };
+        let c = repeated.unwrap_or(c);
+        expanded.push(c);
+        previous = (is_kana(c) && c != 'ー').then_some(c);
+    }
+
+    Some(expanded)
+}
+
+/// Most kana of a surface respelled by [`Tokenizer::lookup_folded`] with the other kana
+/// of their pair, so a long surface doesn't try every combination.
+const MAX_KANA_SWAPS: usize = 3;
+
+/// Fewest characters without kanji or kana in a row, like an English preamble, that
+/// [`Tokenizer::plain_chunks`] covers with grouped spans instead of looking at every
+/// position. Shorter runs aren't worth it.
+const MIN_PLAIN_RUN: usize = 512;
+
+/// Index of the first of [`CATEGORIES`] `c` belongs to.
+fn category_index(c: char) -> Option<usize> {
+    CATEGORIES.iter().position(|category| (category.func)(c))
+}
+
+/// How [`TokenizerOptions::fold_historical_kana`] respells `c`: the modern kana for
+/// ゐ, ゑ and を, and for the four kana of the づ/ず and ぢ/じ pairs, `c` itself along with
+/// the other kana of its pair. Katakana are respelled alike.
+fn folded_kana(c: char) -> (char, Option<char>) {
+    match c {
+        'ゐ' => ('い', None),
+        'ゑ' => ('え', None),
+        'を' => ('お', None),
+        'ヰ' => ('イ', None),
+        'ヱ' => ('エ', None),
+        'ヲ' => ('オ', None),
+        'づ' => ('づ', Some('ず')),
+        'ず' => ('ず', Some('づ')),
+        'ぢ' => ('ぢ', Some('じ')),
+        'じ' => ('じ', Some('ぢ')),
+        'ヅ' => ('ヅ', Some('ズ')),
+        'ズ' => ('ズ', Some('ヅ')),
+        'ヂ' => ('ヂ', Some('ジ')),
+        'ジ' => ('ジ', Some('ヂ')),
+        _ => (c, None),
+    }
+}
+
+/// The voiced kana for `c`, like が for か, or `c` itself when it has none.
+fn voiced(c: char) -> char {
+    const VOICEABLE: &str = "かきくけこさしすせそたちつてとはひふへほ";
+    // Distance between a katakana and the hiragana for the same sound.
+    const OFFSET: u32 = 0x60;
+
+    let katakana = matches!(c, 'ァ'..='ヶ');
+    let hiragana = if katakana {
+        char::from_u32(c as u32 - OFFSET).unwrap_or(c)
+    } else {
+        c
+    };
+    if katakana && hiragana == 'う' {
+        return 'ヴ';
+    }
+    if !VOICEABLE.contains(hiragana) {
+        return c;
+    }
+    char::from_u32(c as u32 + 1).unwrap_or(c)
+}
+
+///
+fn is_latin_letter(c: char) -> bool {
+    c.is_ascii_alphabetic() || matches!(c, 'Ａ'..='Ｚ' | 'ａ'..='ｚ')
+}
+
+///
+fn is_kanji_or_katakana(c: char) -> bool {
+    matches!(c as u32, 0x3005 | 0x3400..=0x4DBF | 0x4E00..=0x9FFF | 0xF900..=0xFAFF)
+        || matches!(c as u32, 0x30A1..=0x30FA | 0x30FC)
+}
+
+/// Character ranges of acronyms such as the AI of AI技術: whole Latin runs of one to five
+/// uppercase letters directly followed by kanji or katakana.
+fn acronym_runs(chars: &[char]) -> Vec<std::ops::Range<usize>> {
+    const MAX_ACRONYM_LENGTH: usize = 5;
+
+    let mut runs = Vec::new();
+    let mut start = 0;
+    while start < chars.len() {
+        let end = start
+            + chars[start..]
+                .iter()
+                .take_while(|c| is_latin_letter(**c))
+                .count();
+        if end == start {
+            start += 1;
+            continue;
+        }
+
+        let uppercase = chars[start..end]
+            .iter()
+            .all(|c| c.is_ascii_uppercase() || matches!(c, 'Ａ'..='Ｚ'));
+        let followed = chars.get(end).copied().is_some_and(is_kanji_or_katakana);
+        if uppercase && end - start <= MAX_ACRONYM_LENGTH && followed {
+            runs.push(start..end);
+        }
+        start = end;
+    }
+
+    runs
+}
+
+///
+fn is_katakana(c: char) -> bool {
+    matches!(c as u32, 0x30A1..=0x30FA | 0x30FC..=0x30FF | 0x31F0..=0x31FF | 0xFF66..=0xFF9F)
+}
+
+///
+fn is_kanji(c: char) -> bool {
+    matches!(c as u32, 0x3005 | 0x3400..=0x4DBF | 0x4E00..=0x9FFF | 0xF900..=0xFAFF)
+}
+
+/// Character ranges of whole katakana runs directly followed by kanji, like the トヨタ of
+/// トヨタ自動車.
+fn katakana_seams(chars: &[char]) -> Vec<std::ops::Range<usize>> {
+    let mut seams = Vec::new();
+    let mut start = 0;
+    while start < chars.len() {
+        let end = start
+            + chars[start..]
+                .iter()
+                .take_while(|c| is_katakana(**c))
+                .count();
+        if end == start {
+            start += 1;
+            continue;
+        }
+
+        if chars.get(end).copied().is_some_and(is_kanji) {
+            seams.push(start..end);
+        }
+        start = end;
+    }
+
+    seams
+}
+
+/// Whether `start..end` reaches over the end of one of `runs` into the word after it.
+fn crosses_acronym(runs: &[std::ops::Range<usize>], start: usize, end: usize) -> bool {
+    runs.iter().any(|run| start < run.end && run.end < end)
+}
+
+/// Whether `chars[index]` is a small kana completing the mora of the kana before it, so
+/// no token can start there. After anything else it starts a (truncated) fragment.
+fn continues_mora(chars: &[char], index: usize) -> bool {
+    index > 0
+        && chars.get(index).copied().is_some_and(is_small_kana)
+        && is_kana(chars[index - 1])
+}
+
+/// Whether `chars[index]` comes right after a sokuon, which doubles it.
+fn follows_sokuon(chars: &[char], index: usize) -> bool {
+    index > 0 && is_sokuon(chars[index - 1])
+}
+
+/// Where a token ending at character `end` has to end instead: past the kana that
+/// [`continues_mora`], and for kana, past a sokuon together with the kana it doubles.
+fn attached_end(chars: &[char], mut end: usize) -> usize {
+    while let Some(&c) = chars.get(end) {
+        let doubles_next = |next: &char| is_kana(*next) && !is_sokuon(*next);
+        if continues_mora(chars, end) {
+            end += 1;
+        } else if is_sokuon(c)
+            && is_kana(chars[end - 1])
+            && chars.get(end + 1).is_some_and(doubles_next)
+        {
+            end += 2;
+        } else {
+            break;
+        }
+    }
+
+    end
+}
+
+/// Number of times the first character of `chars` repeats back to back.
+fn repeat_run_length(mut chars: std::str::Chars) -> usize {
+    let Some(first) = chars.next() else {
+        return 0;
+    };
+
+    1 + chars.take_while(|c| *c == first).count()
+}
+
+/// What kind of span a token covers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum TokenKind {
+    /// A dictionary term or an unknown-term grouping.
+    #[default]
+    Word,
+    /// A run of one repeated character used for emphasis, such as ｗｗｗ or ！！！.
+    Emphasis,
+    /// A whole sentence in another language, left unsegmented by
+    /// [`TokenizerOptions::skip_non_japanese_sentences`].
+    NonWord,
+}
+
+/// A span of the tokenized text and the dictionary match it came from, if any.
+///
+/// [`Token::pos`] is the part of speech of the matched entry, and
+/// [`PartOfSpeech::NONE`] for unknown words, so an indexing pipeline can keep the content
+/// words by dropping particles:
+///
+/// ```
+/// use segmenter::dictionary::{DictionaryBuilder, PartOfSpeech, Tag};
+/// use segmenter::tokenizer::Tokenizer;
+/// use std::sync::Arc;
+///
+/// let mut builder = DictionaryBuilder::new();
+/// builder.add(None, "が", PartOfSpeech::PARTICLE, Tag::NONE);
+/// builder.add(Some("好き"), "すき", PartOfSpeech::NOUN, Tag::NONE);
+/// let tokenizer = Tokenizer::new(Arc::new(builder.build()));
+///
+/// let tokens = tokenizer.tokenize("タマが好き");
+/// let content: Vec<&str> = tokens
+///     .iter()
+///     .filter(|t| !t.pos.is_particle())
+///     .map(|t| t.token)
+///     .collect();
+/// assert_eq!(content, ["タマ", "好き"]);
+/// assert_eq!(tokens[0].pos, PartOfSpeech::NONE);
+/// ```
+///
+/// The entry's tags, dialects and domains are looked up when converting to an
+/// [`OwnedToken`](crate::analysis::OwnedToken). Tokens borrow their surface from the
+/// text, so with the `serde` feature they only serialize; convert them to owned tokens
+/// to read them back.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Token<'a> {
+    pub term_id: Option<u32>,
+    pub token: &'a str,
+    /// Character offset of the token in the tokenized text.
+    pub char_start: usize,
+    /// Byte offset of the token in the tokenized text.
+    pub byte_start: usize,
+    /// The dictionary match the token came from, with the inflection it was found as.
+    pub term_entry: Option<TermEntry>,
+    /// Part of speech of the dictionary entry, [`PartOfSpeech::SYMBOL`] for built-in
+    /// punctuation and [`PartOfSpeech::NONE`] for other unknown tokens.
+    pub pos: PartOfSpeech,
+    /// Parts of speech of every dictionary entry spelled like the token, `pos` first and
+    /// the others in the order their matches score, each set once, like the particle and
+    /// nominalizer analyses of の. Only filled in with
+    /// [`TokenizerOptions::pos_alternatives`]; empty for every other token.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub pos_alternatives: Vec<PartOfSpeech>,
+    /// Sub-segmentation of an expression token, when
+    /// [`TokenizerOptions::decompose_expressions`] is enabled.
+    pub components: Option<Vec<Token<'a>>>,
+    pub kind: TokenKind,
+    /// Number of identical tokens collapsed into this one by
+    /// [`TokenizerOptions::collapse_repeats`], `None` for every other token.
+    pub repeat_count: Option<usize>,
+}
+
+impl Token<'_> {
+    /// The dictionary the token's entry is in, `None` for unknown tokens.
+    pub fn dictionary_id(&self) -> Option<DictionaryId> {
+        self.term_entry.map(|term_entry| term_entry.dictionary)
+    }
+}
+
+/// A token of [`Tokenizer::tokenize_granular`] with its finer split.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct GranularToken<'a> {
+    pub coarse: Token<'a>,
+    /// Covers exactly the span of `coarse`, and is just `coarse` when it doesn't split.
+    pub fine: Vec<Token<'a>>,
+}
+
+/// Tokens of [`Tokenizer::tokenize_checked`] with the input none of them covers.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Tokenization<'a> {
+    pub tokens: Vec<Token<'a>>,
+    /// Byte ranges of the text outside every token, in text order and merged where
+    /// adjacent. Empty whenever the tokens tile the text.
+    pub gaps: Vec<std::ops::Range<usize>>,
+    /// Byte ranges and term ids of dictionary matches reaching across token boundaries,
+    /// such as 国際空港 where the path took 国際 and 空港, in text order. Only filled in with
+    /// [`TokenizerOptions::covering_entry_min_length`].
+    pub covering_entries: Vec<(std::ops::Range<usize>, TermId)>,
+}
+
+impl<'a> Tokenization<'a> {
+    /// `tokens` of `text` with the gaps they leave.
+    pub fn new(text: &str, tokens: Vec<Token<'a>>) -> Self {
+        let mut spans: Vec<std::ops::Range<usize>> = tokens
+            .iter()
+            .map(|token| token.byte_start..token.byte_start + token.token.len())
+            .collect();
+        spans.sort_by_key(|span| span.start);
+
+        let mut gaps = Vec::new();
+        let mut covered = 0;
+        for span in spans
+            .into_iter()
+            .chain(std::iter::once(text.len()..text.len()))
+        {
+            if span.start > covered {
+                gaps.push(covered..span.start);
+            }
+            covered = covered.max(span.end);
+        }
+
+        Self {
+            tokens,
+            gaps,
+            covering_entries: Vec::new(),
+        }
+    }
+}
+
+/// A token of [`Tokenizer::tokenize_document`] with the sentence and paragraph it is in.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct DocumentToken<'a> {
+    /// Offsets are in the whole document.
+    pub token: Token<'a>,
+    /// Index into [`DocumentTokens::sentences`].
+    pub sentence_index: usize,
+    /// Index into [`DocumentTokens::paragraphs`].
+    pub paragraph_index: usize,
+}
+
+/// Tokens of [`Tokenizer::tokenize_document`], with the ranges of them making up each
+/// sentence and paragraph.
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct DocumentTokens<'a> {
+    pub tokens: Vec<DocumentToken<'a>>,
+    /// Ranges into `tokens` of the sentences of the document, in text order.
+    pub sentences: Vec<std::ops::Range<usize>>,
+    /// Ranges into `tokens` of the paragraphs of the document, in text order.
+    pub paragraphs: Vec<std::ops::Range<usize>>,
+}
+
+impl<'a> DocumentTokens<'a> {
+    /// The tokens of sentence `index`.
+    pub fn sentence(&self, index: usize) -> &[DocumentToken<'a>] {
+        &self.tokens[self.sentences[index].clone()]
+    }
+
+    /// The tokens of paragraph `index`.
+    pub fn paragraph(&self, index: usize) -> &[DocumentToken<'a>] {
+        &self.tokens[self.paragraphs[index].clone()]
+    }
+}
+
+/// Tokens of [`Tokenizer::tokenize_iter`], made as they are taken from either end.
+pub struct TokenIter<'t, 'a> {
+    tokenizer: Current<'t>,
+    text: &'a str,
+    /// Those of [`char_offsets`] for `text`.
+    offsets: Vec<usize>,
+    lattice: Lattice,
+    node_ids: std::vec::IntoIter<NodeId>,
+    /// Tokens made up front, for sentence-wise tokenization.
+    tokens: std::vec::IntoIter<Token<'a>>,
+}
+
+impl<'a> Iterator for TokenIter<'_, 'a> {
+    type Item = Token<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self.node_ids.next() {
+            Some(node_id) => Some(self.tokenizer.path_token(
+                self.text,
+                &self.offsets,
+                &self.lattice.nodes()[node_id],
+            )),
+            None => self.tokens.next(),
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let length = self.node_ids.len() + self.tokens.len();
+        (length, Some(length))
+    }
+}
+
+impl DoubleEndedIterator for TokenIter<'_, '_> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        match self.node_ids.next_back() {
+            Some(node_id) => Some(self.tokenizer.path_token(
+                self.text,
+                &self.offsets,
+                &self.lattice.nodes()[node_id],
+            )),
+            None => self.tokens.next_back(),
+        }
+    }
+}
+
+impl ExactSizeIterator for TokenIter<'_, '_> {}
+
+/// Returned by [`Tokenizer::tokenize_checked`] in
+/// [`TokenizerOptions::strict_coverage`] mode when some input is left uncovered.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CoverageError {
+    /// See [`Tokenization::gaps`].
+    pub gaps: Vec<std::ops::Range<usize>>,
+}
+
+impl std::fmt::Display for CoverageError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "no token covers bytes")?;
+        for (i, gap) in self.gaps.iter().enumerate() {
+            let separator = if i == 0 { " " } else { ", " };
+            write!(f, "{separator}{}..{}", gap.start, gap.end)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for CoverageError {}
+
+///
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct TokenizerOptions {
+    /// How much of its score a one-character dictionary match keeps when a longer
+    /// candidate starts at the same position (`1.0` disables the penalty). Particles are
+    /// never penalized.
+    pub single_char_factor: f32,
+    /// Attach the best segmentation without the expression entry to expression tokens
+    /// such as かもしれない.
+    pub decompose_expressions: bool,
+    /// Minimum number of back-to-back repeats of one character that form a single
+    /// [`TokenKind::Emphasis`] token, whatever its category (`0` disables the rule).
+    pub emphasis_threshold: usize,
+    /// On a dictionary miss, look up the base forms suggested by [`deinflect`] instead,
+    /// for dictionaries built without pre-generated conjugations.
+    pub deinflect: bool,
+    /// Subtracted from a path where an unknown fragment ending in an e-row kana (the
+    /// stem of a conditional like 食べれ) is followed by the particle は or ば, so the
+    /// conditional is kept whole when the lattice offers a way to (`0.0` disables it).
+    pub stem_particle_penalty: f32,
+    /// Added to the score of an unknown span covering exactly an acronym (one to five
+    /// uppercase Latin letters followed by kanji or katakana, like the IT of IT企業). No
+    /// candidate but a dictionary word reaches over the end of an acronym either way.
+    pub acronym_bonus: f32,
+    /// Added, scaled like a kana span of its length, to the score of an unknown span
+    /// covering exactly a katakana run followed by kanji, so an unknown brand like the
+    /// トヨタ of トヨタ自動車 splits off at the script change rather than one character
+    /// off, unless a dictionary word covers the run and the kanji after it. Such a span
+    /// is always a candidate, however long the run.
+    pub katakana_seam_bonus: f32,
+    /// Subtracted from the score of a dictionary match on the other kana script than its
+    /// entry is listed with, see [`Dictionary::index_both_kana_scripts`], so the entry in
+    /// its own script wins a tie.
+    pub other_script_penalty: f32,
+    /// Subtracted, per point of [`TermEntry::form_penalty`], from the score of a
+    /// dictionary match on an irregular, outdated or rarely used spelling, so the
+    /// standard spelling and other analyses win close calls against it.
+    pub irregular_form_penalty: f32,
+    /// Added to the score of a dictionary match on a word tagged [`Tag::ATEJI`], so a
+    /// lexicalized spelling like 流石 wins over an analysis of its kanji one by one.
+    pub ateji_bonus: f32,
+    /// Longest unknown-term grouping, in characters; longer runs become consecutive
+    /// tokens of at most this length. Scores stop growing past this length too, so no
+    /// span can outweigh everything else however long the input.
+    pub max_unknown_length: usize,
+    /// Power of its length in characters a span of kanji, or of kanji and hiragana, is
+    /// scored by, so longer words win over a split into shorter ones.
+    pub word_length_power: f32,
+    /// Power of its length in characters every other span is scored by. Scores are
+    /// capped at [`MAX_NODE_SCORE`] for either power.
+    pub length_power: f32,
+    /// How likely the lengths of unknown words are, weighing the scores of unknown
+    /// spans other than hiragana runs. The default has no statistics and weighs every
+    /// length alike; a trained model comes with a `dict_generator count` table, see
+    /// [`FrequencyTable::unknown_lengths`].
+    ///
+    /// [`FrequencyTable::unknown_lengths`]: crate::frequency::FrequencyTable::unknown_lengths
+    pub unknown_words: Arc<UnknownWordModel>,
+    /// Added to the score of every match from a dictionary of
+    /// [`Tokenizer::with_dictionaries`], like a domain dictionary that should win over
+    /// the general one where both have a word.
+    pub dictionary_bonus: HashMap<DictionaryId, f32>,
+    /// Added to the score of every match on a word of any of the dialects of a key, so
+    /// a pipeline for Kansai-ben subtitles can favor おもろい and あかん, and one for
+    /// formal text can hold them back with a negative bonus. A word matching several
+    /// keys gets each of their bonuses.
+    pub dialect_bonus: HashMap<Dialect, f32>,
+    /// Added to the score of every match on a word used in any of the fields, so a
+    /// pipeline for medical text can favor the [`Domain::MEDICINE`] reading of a
+    /// homograph.
+    pub domain_boost: Option<(Domain, f32)>,
+    /// Added to the score of built-in hiragana function words (particles, auxiliaries
+    /// and copula forms) before length scaling, whether or not the dictionary has them.
+    pub function_word_bonus: f32,
+    /// Added to a path where a content word (anything but a particle, auxiliary, copula
+    /// or symbol) is directly followed by a particle.
+    pub particle_connection_bonus: f32,
+    /// Added to a path where a verb in a plain form (dictionary, past or negative) is
+    /// directly followed by the nominalizer の or a ん contraction like んです, as in
+    /// 行くのが好き.
+    pub nominalizer_bonus: f32,
+    /// Share of its score an unknown grouping loses when dictionary matches inside it
+    /// cover all of its span, and proportionally less for partial coverage (`0.0`
+    /// disables it). An unknown run spelling out a known word pair, like メロンパン,
+    /// then splits, while one the dictionary can't analyze fully stays whole.
+    pub unknown_coverage_penalty: f32,
+    /// Make [`Tokenizer::tokenize_checked`] fail instead of reporting gaps when some of
+    /// the input ends up in no token.
+    pub strict_coverage: bool,
+    /// Shortest dictionary match, in characters, that [`Tokenizer::tokenize_checked`]
+    /// reports in [`Tokenization::covering_entries`] when the path doesn't use it (`0`
+    /// disables the report).
+    pub covering_entry_min_length: usize,
+    /// Make [`Tokenizer::tokenize`] split the text into sentences and emit each one that
+    /// doesn't pass [`TokenizerOptions::japanese_thresholds`] as a single
+    /// [`TokenKind::NonWord`] token instead of segmenting it.
+    pub skip_non_japanese_sentences: bool,
+    /// When a sentence counts as Japanese, see [`detect::is_japanese`].
+    pub japanese_thresholds: JapaneseThresholds,
+    /// Where sentences end, for [`TokenizerOptions::skip_non_japanese_sentences`] and
+    /// [`Tokenizer::tokenize_document`].
+    pub sentence_rules: SentenceRules,
+    /// Make [`Tokenizer::tokenize`] and [`Tokenizer::tokenize_iter`] look past byte order
+    /// marks, zero-width spaces and other [invisible](crate::invisible) characters, so
+    /// 漢\u{200B}字 still matches 漢字. Tokens keep covering them, each belonging to the
+    /// token before it (the first token at the start of the text).
+    pub skip_invisible_characters: bool,
+    /// Make [`Tokenizer::tokenize`] take another look at texts ending in two or more
+    /// single-kana tokens the dictionary doesn't know, such as the ね|え of いいですねえ,
+    /// and match them and the token before them against the dictionary from the right,
+    /// longest word first. The fragments are replaced when that leaves fewer unknown
+    /// tokens, and no more tokens overall.
+    pub repair_trailing_fragments: bool,
+    /// Make [`Tokenizer::tokenize`] merge runs of at least this many consecutive tokens
+    /// with the same surface, like the の|の|の of stuttered speech or OCR noise, into one
+    /// token spanning the whole run, with [`Token::repeat_count`] set and the repeated
+    /// tokens as its components, see [`filter::collapse_repeats`]. Runs of one repeated
+    /// character may already form a [`TokenKind::Emphasis`] token first.
+    pub collapse_repeats: Option<usize>,
+    /// On a dictionary miss, look the surface up again in modern kana, with ゐ, ゑ and を
+    /// read as い, え and お and the kana of the づ/ず and ぢ/じ pairs swapped, so pre-reform
+    /// spellings like ゐる and variants like はなぢ find their entries. Such matches are
+    /// marked as [`TermEntry::folded`]. Off by default, as modern text has no use for it.
+    pub fold_historical_kana: bool,
+    /// Remember the substrings of a text the dictionaries don't have while generating
+    /// its candidates, so each is looked up once however many spans reach it. Only
+    /// exact misses are remembered, so the candidates are the same either way; turning
+    /// it off is for measuring what it saves. Without [`TokenizerOptions::deinflect`]
+    /// and [`TokenizerOptions::fold_historical_kana`] the matches are found with
+    /// [`Dictionary::common_prefix_search`] instead, and there are no misses to
+    /// remember.
+    pub cache_lookup_misses: bool,
+    /// Make [`Tokenizer::tokenize`] search the lattice of at most this many characters at
+    /// a time, so memory stays bounded on huge inputs without sentence breaks, like
+    /// subtitles minified onto one line. Of every window, the tokens ending at least a
+    /// word's length before its end are kept and the rest is tokenized again with the
+    /// next window, so the tokens are those of the whole text unless one of them is
+    /// longer than that. Windows are at least two words long; see [`DEFAULT_WINDOW`].
+    pub window: Option<usize>,
+    /// Make [`Tokenizer::tokenize`] fill in [`Token::pos_alternatives`] with the parts
+    /// of speech of the other dictionary entries on the span of each token, for
+    /// grammar checks that have to know when a token like の or で could be more than
+    /// one word.
+    pub pos_alternatives: bool,
+    /// Make [`Tokenizer::tokenize`] leave out punctuation and brackets, see
+    /// [`filter::drop_punctuation`]. The text they cover is then in no token.
+    pub drop_punctuation: bool,
+    /// Make [`Tokenizer::tokenize`] leave out particles, auxiliaries and copula forms,
+    /// see [`filter::drop_stopwords`]. The text they cover is then in no token.
+    pub drop_stopwords: bool,
+}
+
+impl Default for TokenizerOptions {
+    fn default() -> Self {
+        Self {
+            single_char_factor: 0.5,
+            decompose_expressions: false,
+            emphasis_threshold: 3,
+            deinflect: false,
+            stem_particle_penalty: 10.0,
+            acronym_bonus: 4.0,
+            katakana_seam_bonus: 8.0,
+            other_script_penalty: 1.0,
+            irregular_form_penalty: 2.0,
+            ateji_bonus: 4.0,
+            max_unknown_length: 64,
+            word_length_power: 3.0,
+            length_power: 2.0,
+            unknown_words: Arc::new(UnknownWordModel::default()),
+            dictionary_bonus: HashMap::new(),
+            dialect_bonus: HashMap::new(),
+            domain_boost: None,
+            function_word_bonus: 4.0,
+            particle_connection_bonus: 4.0,
+            nominalizer_bonus: 4.0,
+            unknown_coverage_penalty: 0.4,
+            strict_coverage: false,
+            covering_entry_min_length: 0,
+            skip_non_japanese_sentences: false,
+            japanese_thresholds: JapaneseThresholds::default(),
+            sentence_rules: SentenceRules::default(),
+            skip_invisible_characters: true,
+            repair_trailing_fragments: false,
+            collapse_repeats: None,
+            fold_historical_kana: false,
+            cache_lookup_misses: true,
+            window: None,
+            pos_alternatives: false,
+            drop_punctuation: false,
+            drop_stopwords: false,
+        }
+    }
+}
+
+impl TokenizerOptions {
+    /// Options for reading aids showing words with their readings and meanings:
+    /// conjugated words stay whole and are looked up by their base forms, and sentences
+    /// in other languages pass through as one token. Pair it with
+    /// [`TokenMerger::conversational`] to keep auxiliaries with their verbs.
+    /// Punctuation gets tokens of its own.
+    ///
+    /// [`TokenMerger::conversational`]: crate::merge::TokenMerger::conversational
+    pub fn reader() -> Self {
+        Self {
+            deinflect: true,
+            skip_non_japanese_sentences: true,
+            ..Self::default()
+        }
+    }
+
+    /// Options for search indexing: conjugated words are looked up by their base forms,
+    /// expressions like かもしれない come with their parts, so both can be indexed, and
+    /// punctuation and stopwords are left out. Pair it with
+    /// [`Tokenizer::tokenize_granular`] to index compounds by their parts too, and with
+    /// [`TokenMerger::indexing`]. Surfaces are slices of the text, so normalizing them,
+    /// e.g. to NFKC or lowercase Latin, is up to the index.
+    ///
+    /// [`TokenMerger::indexing`]: crate::merge::TokenMerger::indexing
+    pub fn search() -> Self {
+        Self {
+            deinflect: true,
+            decompose_expressions: true,
+            drop_punctuation: true,
+            drop_stopwords: true,
+            ..Self::default()
+        }
+    }
+
+    /// Options for linguistic analysis, keeping tokens as small as the dictionary has
+    /// them and the text as it is: no deinflection, so conjugations split at the
+    /// dictionary's stems, no emphasis tokens, expressions with their parts, invisible
+    /// characters as tokens of their own, every unused dictionary match reported by
+    /// [`Tokenizer::tokenize_checked`] and every part of speech a token could be.
+    pub fn analysis() -> Self {
+        Self {
+            decompose_expressions: true,
+            emphasis_threshold: 0,
+            covering_entry_min_length: 1,
+            skip_invisible_characters: false,
+            pos_alternatives: true,
+            ..Self::default()
+        }
+    }
+}
+
+///
+pub struct Tokenizer {
+    /// The dictionaries looked up, the main one first.
+    dictionaries: Vec<(DictionaryId, Arc<Dictionary>)>,
+    /// The [`Dictionary::surface_trie`] of every dictionary, in the same order.
+    tries: Vec<SurfaceTrie>,
+    options: TokenizerOptions,
+    /// Length in characters of the longest surface that can have a dictionary match:
+    /// the longest kanji or kana key, plus what deinflection can strip when
+    /// [`TokenizerOptions::deinflect`] is set.
+    max_lookup_length: usize,
+    /// Set for a dictionary without any surface, where every category groups its runs so
+    /// the output is plain script-based chunking.
+    group_every_category: bool,
+    /// The tokenizer for the dictionary last given to [`Tokenizer::swap_dictionary`],
+    /// which every call is handed to.
+    swapped: RwLock<Option<Arc<Tokenizer>>>,
+}
+
+/// A [`Tokenizer`] that keeps its dictionary while it is held, however often the
+/// dictionary is swapped meanwhile.
+pub(crate) enum Current<'t> {
+    /// The tokenizer itself, as no dictionary was swapped in.
+    Original(&'t Tokenizer),
+    /// The tokenizer for the dictionary swapped in last.
+    Swapped(Arc<Tokenizer>),
+}
+
+impl std::ops::Deref for Current<'_> {
+    type Target = Tokenizer;
+
+    fn deref(&self) -> &Tokenizer {
+        match self {
+            Self::Original(tokenizer) => tokenizer,
+            Self::Swapped(tokenizer) => tokenizer,
+        }
+    }
+}
+
+///
+impl Tokenizer {
+    /// Tokenizer with the default options.
+    ///
+    /// With an empty dictionary every maximal run of one character category (kanji,
+    /// hiragana, katakana, Latin, digits, symbols, spaces...) becomes one token, apart
+    /// from small kana and sokuon staying with the kana they belong to, and hiragana runs
+    /// splitting at the built-in [`function_words`]:
+    ///
+    /// ```
+    /// use segmenter::{dictionary::Dictionary, tokenizer::Tokenizer};
+    /// use std::sync::Arc;
+    ///
+    /// let tokenizer = Tokenizer::new(Arc::new(Dictionary::new()));
+    /// let tokens = tokenizer.tokenize("私は日本語のテキストを読む。");
+    /// let surfaces: Vec<&str> = tokens.iter().map(|token| token.token).collect();
+    /// assert_eq!(
+    ///     surfaces,
+    ///     ["私", "は", "日本語", "の", "テキスト", "を", "読", "む", "。"]
+    /// );
+    /// ```
+    pub fn new(dictionary: Arc<Dictionary>) -> Self {
+        Self::with_options(dictionary, TokenizerOptions::default())
+    }
+
+    ///
+    pub fn with_options(dictionary: Arc<Dictionary>, options: TokenizerOptions) -> Self {
+        Self::with_dictionaries(vec![(DictionaryId::MAIN, dictionary)], options)
+    }
+
+    /// Tokenizer looking words up in all of `dictionaries`, like a general dictionary
+    /// followed by domain dictionaries, each named by its [`DictionaryId`].
+    ///
+    /// Every dictionary's matches are candidates, and tokens tell which dictionary they
+    /// came from with [`Token::dictionary_id`], so term ids may repeat across them.
+    /// [`TokenizerOptions::dictionary_bonus`] makes a dictionary's words win over equal
+    /// matches of the others. The first dictionary is the main one, see
+    /// [`Tokenizer::dictionary`].
+    ///
+    /// # Panics
+    ///
+    /// If `dictionaries` is empty.
+    pub fn with_dictionaries(
+        dictionaries: Vec<(DictionaryId, Arc<Dictionary>)>,
+        options: TokenizerOptions,
+    ) -> Self {
+        assert!(!dictionaries.is_empty(), "a tokenizer needs a dictionary");
+        let max_key_length = dictionaries
+            .iter()
+            .flat_map(|(_, dictionary)| {
+                dictionary.kanji.keys().chain(dictionary.kana.keys())
+            })
+            .map(|key| key.chars().count())
+            .max()
+            .unwrap_or(0);
+        let max_lookup_length = if options.deinflect {
+            max_key_length + deinflect::max_growth()
+        } else {
+            max_key_length
+        };
+
+        let group_every_category = dictionaries.iter().all(|(_, dictionary)| {
+            dictionary.kanji.is_empty() && dictionary.kana.is_empty()
+        });
+        let tries = dictionaries
+            .iter()
+            .map(|(_, dictionary)| dictionary.surface_trie())
+            .collect();
+
+        Self {
+            dictionaries,
+            tries,
+            options,
+            max_lookup_length,
+            group_every_category,
+            swapped: RwLock::new(None),
+        }
+    }
+
+    /// Makes every later call use `dictionary` as the main dictionary, with the same
+    /// options and the same other dictionaries.
+    ///
+    /// Calls already running finish with the dictionary they started with, so every
+    /// result comes from one dictionary or the other, and tokenization never waits for
+    /// the swap beyond taking a lock that is only held to replace a pointer. The old
+    /// dictionary is dropped once the last call using it returns, unless it is shared
+    /// elsewhere. Positions kept by an [`IncrementalTokenizer`] are found again for the
+    /// new dictionary on its next append.
+    ///
+    /// [`IncrementalTokenizer`]: crate::incremental::IncrementalTokenizer
+    pub fn swap_dictionary(&self, dictionary: Arc<Dictionary>) {
+        let mut dictionaries = self.current().dictionaries.clone();
+        dictionaries[0].1 = dictionary;
+        let tokenizer = Self::with_dictionaries(dictionaries, self.options.clone());
+        *self.swapped.write().unwrap() = Some(Arc::new(tokenizer));
+    }
+
+    /// The tokenizer for the current dictionary, which stays the same while it is held.
+    pub(crate) fn current(&self) -> Current<'_> {
+        match self.swapped.read().unwrap().as_ref() {
+            Some(tokenizer) => Current::Swapped(tokenizer.clone()),
+            None => Current::Original(self),
+        }
+    }
+
+    /// Runs every one-time initialization the first tokenization would otherwise pay
+    /// for, and returns how long each took. Initialization is shared by all tokenizers
+    /// and runs once per process, so later calls take next to no time.
+    ///
+    /// The deinflection rules are built even when [`TokenizerOptions::deinflect`] is
+    /// off, as another tokenizer may need them.
+    pub fn warm_up(&self) -> WarmUpTimings {
+        let time = |initialize: &dyn Fn()| {
+            let start = std::time::Instant::now();
+            initialize();
+            start.elapsed()
+        };
+
+        WarmUpTimings {
+            word_categories: time(&|| {
+                categorize_word("");
+            }),
+            deinflection_rules: time(&|| {
+                deinflect::rules();
+            }),
+        }
+    }
+
+    /// [`Tokenizer::self_test_with`] the default [`SelfTestThresholds`].
+    pub fn self_test(&self) -> Result<SelfTestReport, SegmenterError> {
+        self.self_test_with(&SelfTestThresholds::default())
+    }
+
+    /// Checks that the main dictionary is fit to take traffic, for catching a file that
+    /// loaded but lost part of its content: its sections have to agree, enough entries
+    /// need a surface leading to them, and enough of the tokens of
+    /// [`health::CANARY_SENTENCES`] have to be dictionary words. Every surface is
+    /// visited once, which takes a fraction of loading the dictionary. Sections that
+    /// don't agree fail it as a [`SegmenterError::Dictionary`], the other checks as a
+    /// [`SegmenterError::SelfTest`].
+    pub fn self_test_with(
+        &self,
+        thresholds: &SelfTestThresholds,
+    ) -> Result<SelfTestReport, SegmenterError> {
+        // Empty dictionaries are as indexed as they get.
+        let fraction = |part: usize, whole: usize| {
+            if whole == 0 {
+                1.0
+            } else {
+                part as f64 / whole as f64
+            }
+        };
+
+        let start = std::time::Instant::now();
+        let dictionary = self.dictionary();
+        dictionary.validate()?;
+        let entries = dictionary.entries.len();
+        if entries < thresholds.min_entries {
+            return Err(SelfTestError::TooFewEntries {
+                entries,
+                min: thresholds.min_entries,
+            }
+            .into());
+        }
+
+        let mut indexed = vec![false; entries];
+        for term_entries in dictionary.kanji.values().chain(dictionary.kana.values()) {
+            for term_entry in term_entries.iter() {
+                indexed[term_entry.entry_index as usize] = true;
+            }
+        }
+        let indexed_fraction =
+            fraction(indexed.iter().filter(|indexed| **indexed).count(), entries);
+        if indexed_fraction < thresholds.min_indexed_fraction {
+            return Err(SelfTestError::Unindexed {
+                fraction: indexed_fraction,
+                min: thresholds.min_indexed_fraction,
+            }
+            .into());
+        }
+
+        let mut canary_tokens = 0;
+        let mut unknown = Vec::new();
+        for sentence in health::CANARY_SENTENCES {
+            for token in self.tokenize(sentence) {
+                if token.pos.is_symbol() || token.token.trim().is_empty() {
+                    continue;
+                }
+                canary_tokens += 1;
+                if token.term_id.is_none() {
+                    unknown.push(token.token.to_string());
+                }
+            }
+        }
+        let known_fraction = fraction(canary_tokens - unknown.len(), canary_tokens);
+        if known_fraction < thresholds.min_known_fraction {
+            return Err(SelfTestError::UnknownCanaries {
+                fraction: known_fraction,
+                min: thresholds.min_known_fraction,
+                unknown,
+            }
+            .into());
+        }
+
+        Ok(SelfTestReport {
+            entries,
+            indexed_fraction,
+            canary_tokens,
+            known_fraction,
+            elapsed: start.elapsed(),
+        })
+    }
+
+    /// Looks up `surface` in the map matching its script, falling back to its
+    /// deinflected forms when [`TokenizerOptions::deinflect`] is set, then to the
+    /// spelled-out form of its iteration marks, with こゝろ found as こころ, to its
+    /// horizontal punctuation for vertical presentation forms, see
+    /// [`symbols::horizontal_form`], and then to its modern kana spellings when
+    /// [`TokenizerOptions::fold_historical_kana`] is set.
+    fn lookup(&self, surface: &str) -> Option<(WordCategory, Cow<'_, [TermEntry]>)> {
+        self.lookup_spelling(surface)
+            .or_else(|| {
+                let expanded = expand_iteration_marks(surface)?;
+                self.lookup_spelling(&expanded)
+            })
+            .or_else(|| {
+                let horizontal = symbols::to_horizontal(surface)?;
+                self.lookup_spelling(&horizontal)
+            })
+            .or_else(|| self.lookup_folded(surface))
+    }
+
+    /// [`Tokenizer::lookup_spelling`] of the spellings [`folded_kana`] gives `surface`,
+    /// the one keeping every kana of the づ/ず and ぢ/じ pairs first, with the matches
+    /// marked as [`TermEntry::folded`]. Every spelling is written into the same buffer.
+    /// Katakana stay katakana, so a dictionary indexed with
+    /// [`Dictionary::index_both_kana_scripts`] finds いる for ヰル as well.
+    fn lookup_folded(
+        &self,
+        surface: &str,
+    ) -> Option<(WordCategory, Cow<'_, [TermEntry]>)> {
+        if !self.options.fold_historical_kana {
+            return None;
+        }
+
+        let respelled = surface.chars().any(|c| folded_kana(c).0 != c);
+        let swaps = surface
+            .chars()
+            .filter(|c| folded_kana(*c).1.is_some())
+            .count()
+            .min(MAX_KANA_SWAPS);
+        if !respelled && swaps == 0 {
+            return None;
+        }
+
+        let mut folded = String::with_capacity(surface.len());
+        // Bit `i` of a variant swaps the `i`-th kana of a pair for the other one.
+        for variant in 0..(1usize << swaps) {
+            if variant == 0 && !respelled {
+                continue;
+            }
+
+            folded.clear();
+            let mut swap = 0;
+            for c in surface.chars() {
+                let (c, other) = folded_kana(c);
+                match other {
+                    Some(other) if swap < swaps => {
+                        folded.push(if variant & (1 << swap) != 0 { other } else { c });
+                        swap += 1;
+                    }
+                    _ => folded.push(c),
+                }
+            }
+
+            if let Some((category, term_entries)) = self.lookup_spelling(&folded) {
+                let term_entries = term_entries
+                    .iter()
+                    .map(|term_entry| TermEntry {
+                        folded: true,
+                        ..*term_entry
+                    })
+                    .collect();
+                return Some((category, Cow::Owned(term_entries)));
+            }
+        }
+
+        None
+    }
+
+    /// [`Tokenizer::lookup`] of `surface` as it is spelled.
+    fn lookup_spelling(
+        &self,
+        surface: &str,
+    ) -> Option<(WordCategory, Cow<'_, [TermEntry]>)> {
+        let category = categorize_word(surface);
+        if let Some(term_entries) = self.lookup_exact(category, surface) {
+            return Some((category, term_entries));
+        }
+
+        if !self.options.deinflect
+            || !matches!(category, WordCategory::Kana | WordCategory::Word)
+        {
+            return None;
+        }
+
+        let mut term_entries: Vec<TermEntry> = Vec::new();
+        for deinflection in deinflect::deinflect(surface) {
+            let base_category = categorize_word(&deinflection.term);
+            let Some(candidates) = self.lookup_exact(base_category, &deinflection.term)
+            else {
+                continue;
+            };
+
+            for candidate in candidates.iter() {
+                let valid = deinflection.accepts(self.entry(candidate).pos);
+                let seen = term_entries.iter().any(|term_entry| {
+                    term_entry.entry_index == candidate.entry_index
+                        && term_entry.dictionary == candidate.dictionary
+                });
+                if valid && !seen {
+                    term_entries.push(TermEntry {
+                        inflection_type: deinflection.inflection,
+                        ..*candidate
+                    });
+                }
+            }
+        }
+
+        (!term_entries.is_empty()).then_some((category, Cow::Owned(term_entries)))
+    }
+
+    /// The main dictionary tokens are looked up in, the one last given to
+    /// [`Tokenizer::swap_dictionary`] if any.
+    pub fn dictionary(&self) -> Arc<Dictionary> {
+        self.current().dictionaries[0].1.clone()
+    }
+
+    /// Every dictionary tokens are looked up in, the main one first.
+    pub fn dictionaries(&self) -> Vec<(DictionaryId, Arc<Dictionary>)> {
+        self.current().dictionaries.clone()
+    }
+
+    /// The options the tokenizer was built with, which a swapped-in dictionary keeps.
+    pub fn options(&self) -> &TokenizerOptions {
+        &self.options
+    }
+
+    /// The dictionary entry of `term_entry`, in the dictionary it was found in.
+    pub(crate) fn entry(&self, term_entry: &TermEntry) -> &DictionaryEntry {
+        let (_, dictionary) = self
+            .dictionaries
+            .iter()
+            .find(|(id, _)| *id == term_entry.dictionary)
+            .unwrap_or(&self.dictionaries[0]);
+        &dictionary.entries[term_entry.entry_index as usize]
+    }
+
+    /// The matches of `surface` in the map matching its script in every dictionary, in
+    /// dictionary order, see [`in_kana_map`], merged by [`merge_matches`].
+    fn lookup_exact(
+        &self,
+        category: WordCategory,
+        surface: &str,
+    ) -> Option<Cow<'_, [TermEntry]>> {
+        let kana = in_kana_map(category, surface)?;
+        merge_matches(self.dictionaries.iter().filter_map(|(id, dictionary)| {
+            let map = if kana {
+                &dictionary.kana
+            } else {
+                &dictionary.kanji
+            };
+            Some((*id, map.get(surface)?.as_slice()))
+        }))
+    }
+
+    /// Number of characters the unknown-term grouping rules would put into one token
+    /// starting with `chars`' first character, at most
+    /// [`TokenizerOptions::max_unknown_length`]. `clusters` are the cluster boundaries of
+    /// `chars`, and marks extending a grouped character are grouped along with it.
+    fn unknown_run_length(&self, chars: &[char], clusters: &[bool]) -> usize {
+        let Some(first) = chars.first() else {
+            return 0;
+        };
+
+        let max_length = self.options.max_unknown_length;
+        match CATEGORIES.iter().find(|category| (category.func)(*first)) {
+            Some(category) if self.groups(category) => {
+                let rest = (1..chars.len())
+                    .take_while(|i| (category.func)(chars[*i]) || !clusters[*i]);
+                1 + rest.take(max_length.saturating_sub(1)).count()
+            }
+            _ => 1,
+        }
+    }
+
+    /// `offsets` holds the byte offset of every character of `text`, then `text.len()`.
+    fn inner_loop<'a, Fn>(
+        text: &'a str,
+        offsets: &[usize],
+        start: usize,
+        length: usize,
+        mut inner: Fn,
+    ) where
+        Fn: FnMut(&'a str, usize, usize),
+    {
+        let start_pos = offsets[start];
+        for (end, end_pos) in offsets.iter().enumerate().take(length + 1).skip(start + 1)
+        {
+            let substring = &text[start_pos..*end_pos];
+
+            inner(substring, start, end);
+        }
+    }
+
+    /// Whether runs of `category` are grouped into one unknown term.
+    fn groups(&self, category: &Category) -> bool {
+        category.group || self.group_every_category
+    }
+
+    /// `offsets` holds the byte offset of every character of `text`, then `text.len()`,
+    /// and `clusters` the cluster boundaries of `text`. Groupings take the marks
+    /// extending their characters along.
+    fn inner_loop_unknown_term<'a, Fn>(
+        &self,
+        force: bool,
+        text: &'a str,
+        offsets: &[usize],
+        clusters: &[bool],
+        start: usize,
+        mut inner: Fn,
+    ) where
+        Fn: FnMut(&'a str, usize, usize),
+    {
+        let length = offsets.len() - 1;
+        if start >= length {
+            return;
+        }
+
+        let start_pos = offsets[start];
+        let mut categorized = false;
+        for category in CATEGORIES.iter() {
+            if !force && !category.invoke {
+                continue;
+            }
+
+            if self.groups(category) {
+                let (count, end_pos, end) = {
+                    let mut count: usize = 0;
+                    let mut end_pos: usize = 0;
+                    let mut end = start;
+
+                    let iter =
+                        ((start + 1)..=length).zip(text[start_pos..].char_indices());
+                    let max_length = self.options.max_unknown_length.max(1);
+                    for (end_idx, (char_idx, c)) in iter.take(max_length) {
+                        let extends = count > 0 && !clusters[end_idx - 1];
+                        if (category.func)(c) || extends {
+                            count += 1;
+                            end_pos = start_pos + char_idx + c.len_utf8();
+                            end = end_idx;
+                        } else {
+                            break;
+                        }
+                    }
+
+                    (count, end_pos, end)
+                };
+
+                if count == 0 {
+                    continue;
+                }
+
+                let substring = &text[start_pos..end_pos];
+                categorized = true;
+                inner(substring, start, end);
+
+                // Shorter groupings, for the unknown-word model to weigh against the run.
+                let model = &self.options.unknown_words;
+                if model.has_statistics(UnknownCategory::of(substring)) {
+                    for split in ((start + 1)..end).filter(|split| clusters[*split]) {
+                        inner(&text[start_pos..offsets[split]], start, split);
+                    }
+                }
+
+                if category.split_before_function_words {
+                    // Single kana are left to the dictionary and the function words.
+                    for split in (start + 2)..end {
+                        let (head, rest) = (
+                            &text[start_pos..offsets[split]],
+                            &text[offsets[split]..end_pos],
+                        );
+                        if head.ends_with(is_sokuon) {
+                            continue;
+                        }
+                        if function_words::function_words_at(rest).next().is_some() {
+                            inner(head, start, split);
+                        }
+                    }
+                }
+            } else if let Some(c) = text[start_pos..].chars().next() {
+                if !(category.func)(c) {
+                    continue;
+                }
+                let end = start + 1;
+                let substring = &text[start_pos..start_pos + c.len_utf8()];
+                categorized = true;
+                inner(substring, start, end);
+            }
+        }
+
+        // Characters outside every category (emoji, control characters, ...) still need a
+        // node, or the lattice would have no path across them.
+        if force && !categorized {
+            inner(&text[start_pos..offsets[start + 1]], start, start + 1);
+        }
+    }
+
+    ///
+    pub fn tokenize<'a>(&self, text: &'a str) -> Vec<Token<'a>> {
+        if let Current::Swapped(current) = self.current() {
+            return current.tokenize(text);
+        }
+
+        let mut tokens = match self.visible_text(text) {
+            Some(visible) => self
+                .tokenize_visible(&visible.text)
+                .into_iter()
+                .map(|token| restore_token(&visible, text, token))
+                .collect(),
+            None => self.tokenize_visible(text),
+        };
+        if self.options.drop_punctuation {
+            filter::drop_punctuation(&mut tokens);
+        }
+        if self.options.drop_stopwords {
+            filter::drop_stopwords(&mut tokens);
+        }
+
+        tokens
+    }
+
+    /// `text` without its invisible characters, `None` if there are none to skip.
+    fn visible_text(&self, text: &str) -> Option<VisibleText> {
+        if !self.options.skip_invisible_characters {
+            return None;
+        }
+
+        VisibleText::new(text)
+    }
+
+    /// Whether [`Tokenizer::tokenize`] takes the best path through the lattice of the
+    /// whole of `text`, rather than of its sentences or its visible characters, and
+    /// keeps all of them.
+    pub(crate) fn searches_whole_text(&self, text: &str) -> bool {
+        !self.options.skip_non_japanese_sentences
+            && !self.options.drop_punctuation
+            && !self.options.drop_stopwords
+            && !self.options.repair_trailing_fragments
+            && self.options.collapse_repeats.is_none()
+            && self.options.window.is_none()
+            && self.visible_text(text).is_none()
+    }
+
+    /// [`Tokenizer::tokenize`] of a text without invisible characters to skip.
+    fn tokenize_visible<'a>(&self, text: &'a str) -> Vec<Token<'a>> {
+        if self.options.skip_non_japanese_sentences {
+            return self.tokenize_sentences(text);
+        }
+
+        self.tokenize_text(text)
+    }
+
+    /// [`Tokenizer::tokenize`] as an iterator over the best path, so consumers can walk
+    /// it from the end, e.g. to give every token the part of speech of the next one.
+    /// Tokens are only made once taken.
+    pub fn tokenize_iter<'t, 'a>(&'t self, text: &'a str) -> TokenIter<'t, 'a> {
+        let tokenizer = self.current();
+        if !tokenizer.searches_whole_text(text) {
+            let tokens = tokenizer.tokenize(text).into_iter();
+            return TokenIter {
+                tokenizer,
+                text,
+                offsets: Vec::new(),
+                lattice: Lattice::new(0, 0),
+                node_ids: Vec::new().into_iter(),
+                tokens,
+            };
+        }
+
+        let lattice = tokenizer.build_lattice(text);
+        let node_ids = tokenizer.best_path_ids(&lattice, text).into_iter();
+        TokenIter {
+            tokenizer,
+            text,
+            offsets: char_offsets(text),
+            lattice,
+            node_ids,
+            tokens: Vec::new().into_iter(),
+        }
+    }
+
+    /// The candidate among the `n` best segmentations of `text` that `rescore` rates
+    /// highest, for picking segmentations with an external model. Equal ratings go to
+    /// the candidate the lattice scores higher, so a constant `rescore` gives
+    /// [`Tokenizer::tokenize`]'s result.
+    ///
+    /// Every candidate is turned into tokens before it is rated, using byte offsets
+    /// computed once for the text. Sentences aren't skipped as with
+    /// [`TokenizerOptions::skip_non_japanese_sentences`].
+    pub fn tokenize_rescored<'a, F>(
+        &self,
+        text: &'a str,
+        n: usize,
+        rescore: F,
+    ) -> Vec<Token<'a>>
+    where
+        F: Fn(&[Token]) -> f32,
+    {
+        if let Current::Swapped(current) = self.current() {
+            return current.tokenize_rescored(text, n, rescore);
+        }
+
+        let lattice = self.build_lattice(text);
+        let weights = ConnectionWeights::from(&self.options);
+        let chars: Vec<char> = text.chars().collect();
+        let paths = lattice.find_paths_with(n.max(1), |left, right| {
+            connection_score(&chars, &weights, left, right)
+        });
+
+        let offsets = char_offsets(text);
+        let mut best: Option<(f32, Vec<Token<'a>>)> = None;
+        for path in paths.iter() {
+            let tokens: Vec<Token<'a>> = path
+                .iter()
+                .map(|node| {
+                    self.node_token(text, offsets[node.start], offsets[node.end], node)
+                })
+                .collect();
+            let score = rescore(&tokens);
+            if best
+                .as_ref()
+                .is_none_or(|(best_score, _)| score > *best_score)
+            {
+                best = Some((score, tokens));
+            }
+        }
+
+        best.map(|(_, tokens)| tokens).unwrap_or_default()
+    }
+
+    /// [`Tokenizer::tokenize`] one sentence at a time, keeping non-Japanese sentences
+    /// whole.
+    fn tokenize_sentences<'a>(&self, text: &'a str) -> Vec<Token<'a>> {
+        let mut tokens = Vec::new();
+        let mut char_start = 0;
+        for range in detect::sentences_with(text, &self.options.sentence_rules) {
+            let sentence = &text[range.clone()];
+            let score = detect::is_japanese(sentence);
+            if score.passes(&self.options.japanese_thresholds) {
+                tokens.extend(self.tokenize_text(sentence).into_iter().map(
+                    |mut token| {
+                        shift_token(&mut token, char_start, range.start);
+                        token
+                    },
+                ));
+            } else {
+                tokens.push(Token {
+                    term_id: None,
+                    token: sentence,
+                    char_start,
+                    byte_start: range.start,
+                    term_entry: None,
+                    pos: PartOfSpeech::NONE,
+                    pos_alternatives: Vec::new(),
+                    components: None,
+                    kind: TokenKind::NonWord,
+                    repeat_count: None,
+                });
+            }
+            char_start += sentence.chars().count();
+        }
+
+        tokens
+    }
+
+    ///
+    fn tokenize_text<'a>(&self, text: &'a str) -> Vec<Token<'a>> {
+        let mut tokens = match self.options.window {
+            Some(window) => self.tokenize_windows(text, window),
+            None => {
+                let lattice = self.build_lattice(text);
+                let offsets = char_offsets(text);
+
+                // #TODO: Avoid unnecessary memory allocation when creating a path?
+                self.best_path(&lattice, text)
+                    .iter()
+                    .map(|node| self.path_token(text, &offsets, node))
+                    .collect()
+            }
+        };
+        if self.options.repair_trailing_fragments {
+            self.repair_trailing_fragments(text, &mut tokens);
+        }
+        if let Some(min_run) = self.options.collapse_repeats {
+            tokens = filter::collapse_repeats(text, tokens, min_run);
+        }
+
+        tokens
+    }
+
+    /// The best path through `text` a window of [`TokenizerOptions::window`] characters
+    /// at a time.
+    ///
+    /// Every window but the first starts with the last token kept, so its connection to
+    /// the next one is scored as in the whole text. A window that can't keep any token by
+    /// the rule keeps its first one.
+    fn tokenize_windows<'a>(&self, text: &'a str, window: usize) -> Vec<Token<'a>> {
+        let reach = self.reach();
+        let window = window.max(2 * reach);
+        let offsets = char_offsets(text);
+        let length = offsets.len() - 1;
+
+        let mut tokens: Vec<Token<'a>> = Vec::new();
+        // Characters covered by `tokens`.
+        let mut committed = 0;
+        while committed < length {
+            let end = length.min(committed + window);
+            let last = tokens.last().map_or(committed, |token| token.char_start);
+            let mut path = self.window_path(text, &offsets, last..end, committed);
+            if path.is_empty() {
+                // No path runs through the end of the last token, so leave it out.
+                path = self.window_path(text, &offsets, committed..end, committed);
+            }
+
+            let keep_before = if end == length { length } else { end - reach };
+            let kept = path
+                .iter()
+                .take_while(|token| {
+                    token.char_start + token.token.chars().count() <= keep_before
+                })
+                .count();
+            path.truncate(kept.max(1));
+            let Some(last) = path.last() else {
+                break;
+            };
+            committed = last.char_start + last.token.chars().count();
+            tokens.extend(path);
+        }
+
+        tokens
+    }
+
+    /// The tokens of the best path through the characters `range` of `text` from
+    /// character `from` on, with no node crossing `from`.
+    fn window_path<'a>(
+        &self,
+        text: &'a str,
+        offsets: &[usize],
+        range: std::ops::Range<usize>,
+        from: usize,
+    ) -> Vec<Token<'a>> {
+        let surface = &text[offsets[range.start]..offsets[range.end]];
+        let mut lattice = self.build_lattice(surface);
+        let boundary = from - range.start;
+        lattice.exclude(|node| node.start < boundary && node.end > boundary);
+        let surface_offsets = char_offsets(surface);
+
+        self.best_path(&lattice, surface)
+            .into_iter()
+            .filter(|node| node.start >= boundary)
+            .map(|node| {
+                let mut token = self.path_token(surface, &surface_offsets, node);
+                shift_token(&mut token, range.start, offsets[range.start]);
+                token
+            })
+            .collect()
+    }
+
+    /// Replaces the single-kana unknown tokens `tokens` of `text` end in, and the token
+    /// before them, by a right-to-left longest match against the dictionary, see
+    /// [`TokenizerOptions::repair_trailing_fragments`].
+    fn repair_trailing_fragments<'a>(&self, text: &'a str, tokens: &mut Vec<Token<'a>>) {
+        let is_fragment = |token: &Token| {
+            let mut chars = token.token.chars();
+            token.term_id.is_none()
+                && chars.next().is_some_and(is_kana)
+                && chars.next().is_none()
+        };
+        let fragments = tokens
+            .iter()
+            .rev()
+            .take_while(|token| is_fragment(token))
+            .count();
+        if fragments < 2 {
+            return;
+        }
+
+        let first = tokens.len().saturating_sub(fragments + 1);
+        let (char_start, byte_start) =
+            (tokens[first].char_start, tokens[first].byte_start);
+        let offsets: Vec<usize> = text[byte_start..]
+            .char_indices()
+            .map(|(offset, _)| byte_start + offset)
+            .chain(std::iter::once(text.len()))
+            .collect();
+
+        let mut nodes = Vec::new();
+        let mut end = offsets.len() - 1;
+        while end > 0 {
+            // The longest dictionary word ending here, else the character itself.
+            let node = (0..end)
+                .find_map(|start| {
+                    let surface = &text[offsets[start]..offsets[end]];
+                    let (_, term_entries) = self.lookup(surface)?;
+                    let (start, end) = (char_start + start, char_start + end);
+                    term_entries
+                        .iter()
+                        .map(|term_entry| {
+                            let source = CandidateSource::Dictionary(*term_entry);
+                            self.node(surface, start, end, source)
+                        })
+                        .max_by(|a, b| a.score.total_cmp(&b.score))
+                })
+                .unwrap_or_else(|| {
+                    let surface = &text[offsets[end - 1]..offsets[end]];
+                    let (start, end) = (char_start + end - 1, char_start + end);
+                    self.node(surface, start, end, CandidateSource::Unknown)
+                });
+            end = node.start - char_start;
+            nodes.push(node);
+        }
+
+        let unknown_before = tokens[first..]
+            .iter()
+            .filter(|token| token.term_id.is_none())
+            .count();
+        let unknown_after = nodes.iter().filter(|node| node.term_id.is_none()).count();
+        if unknown_after >= unknown_before || nodes.len() > tokens.len() - first {
+            return;
+        }
+
+        tokens.truncate(first);
+        for node in nodes.iter().rev() {
+            let start_pos = offsets[node.start - char_start];
+            let end_pos = offsets[node.end - char_start];
+            tokens.push(self.node_token(text, start_pos, end_pos, node));
+        }
+    }
+
+    /// [`Tokenizer::tokenize`], along with the byte ranges of `text` that no token
+    /// covers. With [`TokenizerOptions::strict_coverage`] any such gap is an error.
+    ///
+    /// The tokens are those of [`Tokenizer::tokenize`] with every option applied, so
+    /// skipped sentences, collapsed repeats and the like are reflected in the gaps.
+    /// Covering entries are searched in the lattice of the whole text.
+    pub fn tokenize_checked<'a>(
+        &self,
+        text: &'a str,
+    ) -> Result<Tokenization<'a>, CoverageError> {
+        if let Current::Swapped(current) = self.current() {
+            return current.tokenize_checked(text);
+        }
+
+        let (tokens, lattice) = self.tokens_and_lattice(text);
+        let mut tokenization = Tokenization::new(text, tokens);
+        let min_length = self.options.covering_entry_min_length;
+        if min_length > 0 {
+            let lattice = lattice.unwrap_or_else(|| self.build_lattice(text));
+            tokenization.covering_entries =
+                covering_entries(text, &lattice, &tokenization.tokens, min_length);
+        }
+        if self.options.strict_coverage && !tokenization.gaps.is_empty() {
+            return Err(CoverageError {
+                gaps: tokenization.gaps,
+            });
+        }
+
+        Ok(tokenization)
+    }
+
+    /// Every token of [`Tokenizer::tokenize`] together with its own best split.
+    ///
+    /// The split of a dictionary token is the best path through the lattice of the whole
+    /// text within its span, leaving out the nodes that cover all of it, so 東京国際空港
+    /// splits into 東京, 国際 and 空港 when those are entries. Single characters, unknown
+    /// and emphasis tokens, and tokens with no other way to cover their span, split into
+    /// themselves. The fine tokens of each coarse token always tile it exactly.
+    pub fn tokenize_granular<'a>(&self, text: &'a str) -> Vec<GranularToken<'a>> {
+        if let Current::Swapped(current) = self.current() {
+            return current.tokenize_granular(text);
+        }
+
+        let (tokens, lattice) = self.tokens_and_lattice(text);
+        let lattice = lattice.unwrap_or_else(|| self.build_lattice(text));
+        let offsets = char_offsets(text);
+        tokens
+            .into_iter()
+            .map(|coarse| {
+                let start = coarse.char_start;
+                let end = start + coarse.token.chars().count();
+                let splittable = coarse.term_id.is_some() && end - start > 1;
+                let (span, node_ids) = if splittable {
+                    lattice.span(start, end, |other| {
+                        other.start == start && other.end == end
+                    })
+                } else {
+                    (Lattice::new(0, 0), Vec::new())
+                };
+
+                let span_text = &coarse.token;
+                let fine: Vec<Token<'a>> = self
+                    .best_path_ids(&span, span_text)
+                    .into_iter()
+                    .map(|span_node_id| {
+                        let node = &lattice.nodes()[node_ids[span_node_id]];
+                        self.path_token(text, &offsets, node)
+                    })
+                    .collect();
+
+                GranularToken {
+                    fine: if fine.is_empty() {
+                        vec![coarse.clone()]
+                    } else {
+                        fine
+                    },
+                    coarse,
+                }
+            })
+            .collect()
+    }
+
+    /// The tokens of [`Tokenizer::tokenize`], and the lattice of the whole text when they
+    /// are its best path.
+    fn tokens_and_lattice<'a>(&self, text: &'a str) -> (Vec<Token<'a>>, Option<Lattice>) {
+        if !self.searches_whole_text(text) {
+            return (self.tokenize(text), None);
+        }
+
+        let lattice = self.build_lattice(text);
+        let offsets = char_offsets(text);
+        let tokens = self
+            .best_path(&lattice, text)
+            .iter()
+            .map(|node| self.path_token(text, &offsets, node))
+            .collect();
+        (tokens, Some(lattice))
+    }
+
+    /// [`Tokenizer::tokenize`] of every sentence of a document, with the index of the
+    /// sentence and paragraph each token is in.
+    ///
+    /// Paragraphs are separated by blank lines and split into sentences by
+    /// [`TokenizerOptions::sentence_rules`], which by default keep quoted speech in the
+    /// sentence around it. Whitespace alone, such as the line break after a sentence,
+    /// goes with the sentence after it. The tokens cover the paragraphs but not the blank
+    /// lines between them, and their offsets are in `text`.
+    pub fn tokenize_document<'a>(&self, text: &'a str) -> DocumentTokens<'a> {
+        let mut document = DocumentTokens::default();
+        let (mut byte_position, mut char_position) = (0, 0);
+        for (paragraph_index, paragraph) in
+            detect::paragraphs(text).into_iter().enumerate()
+        {
+            let paragraph_start = document.tokens.len();
+            let mut sentences = detect::sentences_with(
+                &text[paragraph.clone()],
+                &self.options.sentence_rules,
+            );
+            // Whitespace-only sentences are joined to the one after them, or to the one
+            // before at the end of the paragraph, which can't be whitespace too.
+            let mut i = 0;
+            while i < sentences.len() {
+                let sentence = &text[paragraph.start..][sentences[i].clone()];
+                if !sentence.trim().is_empty() || sentences.len() == 1 {
+                    i += 1;
+                } else if i + 1 < sentences.len() {
+                    sentences[i + 1].start = sentences[i].start;
+                    sentences.remove(i);
+                } else {
+                    sentences[i - 1].end = sentences[i].end;
+                    sentences.remove(i);
+                }
+            }
+
+            for sentence in sentences {
+                let start = paragraph.start + sentence.start;
+                char_position += text[byte_position..start].chars().count();
+                byte_position = start;
+
+                let sentence_index = document.sentences.len();
+                let sentence_start = document.tokens.len();
+                let end = paragraph.start + sentence.end;
+                document
+                    .tokens
+                    .extend(self.tokenize(&text[start..end]).into_iter().map(
+                        |mut token| {
+                            shift_token(&mut token, char_position, byte_position);
+                            DocumentToken {
+                                token,
+                                sentence_index,
+                                paragraph_index,
+                            }
+                        },
+                    ));
+                document
+                    .sentences
+                    .push(sentence_start..document.tokens.len());
+            }
+            document
+                .paragraphs
+                .push(paragraph_start..document.tokens.len());
+        }
+
+        document
+    }
+
+    /// The token for a node on a path through a lattice of `text`.
+    /// `offsets` are those of [`char_offsets`] for `text`.
+    pub(crate) fn path_token<'a>(
+        &self,
+        text: &'a str,
+        offsets: &[usize],
+        node: &LatticeNode,
+    ) -> Token<'a> {
+        self.node_token(text, offsets[node.start], offsets[node.end], node)
+    }
+
+    /// The token for `node` spanning bytes `start_pos..end_pos` of `text`.
+    fn node_token<'a>(
+        &self,
+        text: &'a str,
+        start_pos: usize,
+        end_pos: usize,
+        node: &LatticeNode,
+    ) -> Token<'a> {
+        let surface = &text[start_pos..end_pos];
+        let components = (self.options.decompose_expressions
+            && node.pos.contains(PartOfSpeech::EXPRESSION))
+        .then(|| self.decompose(surface, node.start, start_pos));
+        let pos_alternatives = if self.options.pos_alternatives {
+            self.pos_alternatives(surface, node)
+        } else {
+            Vec::new()
+        };
+
+        Token {
+            term_id: node.term_id,
+            token: surface,
+            char_start: node.start,
+            byte_start: start_pos,
+            term_entry: node.term_entry,
+            pos: node.pos,
+            pos_alternatives,
+            components,
+            kind: node.kind,
+            repeat_count: None,
+        }
+    }
+
+    /// Parts of speech of the dictionary entries spelled `surface`, `node`'s first and
+    /// the others by the score of their matches on its span, each set once. Empty for
+    /// nodes of no dictionary entry.
+    fn pos_alternatives(&self, surface: &str, node: &LatticeNode) -> Vec<PartOfSpeech> {
+        if node.term_entry.is_none() {
+            return Vec::new();
+        }
+
+        let mut scored: Vec<(f32, PartOfSpeech)> = self
+            .lookup(surface)
+            .map(|(_, term_entries)| {
+                term_entries
+                    .iter()
+                    .map(|term_entry| {
+                        let source = CandidateSource::Dictionary(*term_entry);
+                        let alternative =
+                            self.node(surface, node.start, node.end, source);
+                        (alternative.score, alternative.pos)
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+        // Stable, so entries scoring alike stay in dictionary order.
+        scored.sort_by(|a, b| b.0.total_cmp(&a.0));
+
+        let mut alternatives = vec![node.pos];
+        for (_, pos) in scored {
+            if !alternatives.contains(&pos) {
+                alternatives.push(pos);
+            }
+        }
+        alternatives
+    }
+
+    /// Best segmentation of an expression's surface that doesn't use an expression entry
+    /// spanning all of it. Offsets are shifted by the expression's own.
+    fn decompose<'a>(
+        &self,
+        surface: &'a str,
+        char_start: usize,
+        byte_start: usize,
+    ) -> Vec<Token<'a>> {
+        let length = surface.chars().count();
+        let mut lattice = self.build_lattice(surface);
+        lattice.exclude(|node| {
+            node.start == 0
+                && node.end == length
+                && node.pos.contains(PartOfSpeech::EXPRESSION)
+        });
+
+        let offsets = char_offsets(surface);
+        self.best_path(&lattice, surface)
+            .iter()
+            .map(|node| {
+                let start_pos = offsets[node.start];
+                Token {
+                    term_id: node.term_id,
+                    token: &surface[start_pos..offsets[node.end]],
+                    char_start: char_start + node.start,
+                    byte_start: byte_start + start_pos,
+                    term_entry: node.term_entry,
+                    pos: node.pos,
+                    pos_alternatives: Vec::new(),
+                    components: None,
+                    kind: node.kind,
+                    repeat_count: None,
+                }
+            })
+            .collect()
+    }
+
+    /// Best path through a lattice built from `text`, applying the connection rules.
+    fn best_path<'l>(&self, lattice: &'l Lattice, text: &str) -> Vec<&'l LatticeNode> {
+        self.best_path_ids(lattice, text)
+            .into_iter()
+            .map(|node_id| &lattice.nodes()[node_id])
+            .collect()
+    }
+
+    ///
+    fn best_path_ids(&self, lattice: &Lattice, text: &str) -> Vec<NodeId> {
+        let weights = ConnectionWeights::from(&self.options);
+        if weights == ConnectionWeights::default() {
+            return lattice.find_path().into_node_ids();
+        }
+
+        let chars: Vec<char> = text.chars().collect();
+        lattice
+            .find_path_with(|left, right| connection_score(&chars, &weights, left, right))
+            .into_node_ids()
+    }
+
+    /// [`Tokenizer::best_path_ids`] by [`Lattice::find_path_resumed`] from position
+    /// `from`, with the totals `scores` holds for the nodes before it.
+    pub(crate) fn resumed_path_ids(
+        &self,
+        lattice: &Lattice,
+        text: &str,
+        scores: &mut PathScores,
+        from: usize,
+    ) -> Vec<NodeId> {
+        let weights = ConnectionWeights::from(&self.options);
+        if weights == ConnectionWeights::default() {
+            return lattice
+                .find_path_resumed(scores, from, |_, _| 0.0)
+                .into_node_ids();
+        }
+
+        let chars: Vec<char> = text.chars().collect();
+        lattice
+            .find_path_resumed(scores, from, |left, right| {
+                connection_score(&chars, &weights, left, right)
+            })
+            .into_node_ids()
+    }
+
+    /// Byte offsets inside `text` where the best path allows a line break.
+    ///
+    /// The offsets are sorted and exclude `0` and `text.len()`. Token boundaries right
+    /// before `。`, `、` or `」`, and right after `「`, are left out, as are those of their
+    /// vertical presentation forms.
+    pub fn boundaries(&self, text: &str) -> Vec<usize> {
+        const NO_BREAK_BEFORE: &[char] = &['。', '、', '」'];
+        const NO_BREAK_AFTER: &[char] = &['「'];
+        if let Current::Swapped(current) = self.current() {
+            return current.boundaries(text);
+        }
+
+        let lattice = self.build_lattice(text);
+        let path = self.best_path(&lattice, text);
+
+        let mut boundaries = Vec::with_capacity(path.len());
+        let mut chars = text.char_indices().peekable();
+        let mut previous = None;
+        let mut position = 0;
+        for node in path.iter().take(path.len().saturating_sub(1)) {
+            while position < node.end {
+                previous = chars.next().map(|(_, c)| c);
+                position += 1;
+            }
+
+            let Some(&(offset, next)) = chars.peek() else {
+                break;
+            };
+            let allowed = !NO_BREAK_BEFORE.contains(&symbols::horizontal_form(next))
+                && !previous.is_some_and(|c| {
+                    NO_BREAK_AFTER.contains(&symbols::horizontal_form(c))
+                });
+            if allowed {
+                boundaries.push(offset);
+            }
+        }
+
+        boundaries
+    }
+
+    /// Feeds every candidate span of `text` to `sink`, without building a lattice.
+    ///
+    /// Each dictionary entry matching a substring is reported separately. Unknown-term
+    /// groupings are only reported for categories that always run, unless no dictionary
+    /// entry starts at that position, in which case every category is tried. Runs of at
+    /// least [`TokenizerOptions::emphasis_threshold`] repeated characters are reported
+    /// once, from where the run begins.
+    ///
+    /// No candidate leaves a small kana (ゃ, ァ, ...) after a kana to start the next one:
+    /// dictionary matches that would are reported as unknown spans including it, and
+    /// unknown spans are extended over it. Unknown kana spans likewise keep a following
+    /// っ/ッ together with the kana after it. Candidates never cross the end of an
+    /// acronym, see [`TokenizerOptions::acronym_bonus`], and whole katakana runs before
+    /// kanji are always reported, see [`TokenizerOptions::katakana_seam_bonus`].
+    ///
+    /// Candidates never start or end inside a grapheme cluster either, see
+    /// [`graphemes`]: those that would start inside one are dropped, and those that would
+    /// end inside one are reported as unknown spans extended to its end.
+    ///
+    /// Long stretches without kanji or kana, like kilobytes of English before the
+    /// Japanese content, are scanned once rather than position by position: away from
+    /// their ends, every run of one character category is reported as one unknown span,
+    /// split every [`TokenizerOptions::max_unknown_length`] characters, and nothing else
+    /// is looked up there.
+    pub fn generate_candidates<S: CandidateSink>(&self, text: &str, sink: &mut S) {
+        if let Current::Swapped(current) = self.current() {
+            return current.generate_candidates(text, sink);
+        }
+
+        self.generate_candidates_from(text, 0, sink);
+    }
+
+    /// [`Tokenizer::generate_candidates`] for the candidates starting at character
+    /// `from` or later, still looking at the whole of `text` around them.
+    pub(crate) fn generate_candidates_from<S: CandidateSink>(
+        &self,
+        text: &str,
+        from: usize,
+        sink: &mut S,
+    ) {
+        let chars: Vec<char> = text.chars().collect();
+        let acronyms = acronym_runs(&chars);
+        let seams = katakana_seams(&chars);
+        let offsets = char_offsets(text);
+        let clusters = graphemes::cluster_boundaries(&chars);
+        let sink = &mut ClusterBoundarySink {
+            text,
+            offsets: &offsets,
+            clusters: &clusters,
+            sink: &mut AcronymBoundarySink {
+                acronyms: &acronyms,
+                sink,
+            },
+        };
+        let length = chars.len();
+        // Substrings no dictionary has, which the spans starting elsewhere run into
+        // again, like a particle and the character after it.
+        let mut misses: HashSet<&str> = HashSet::new();
+        if self.options.cache_lookup_misses {
+            misses.reserve(length);
+        }
+        // Matches of the surface tries at one position: their end, dictionary and entries.
+        let mut prefix_matches: Vec<(usize, DictionaryId, &[TermEntry])> = Vec::new();
+        let plain = self.plain_chunks(&chars, &clusters);
+        let mut next_plain = plain.partition_point(|chunk| chunk.end <= from);
+        // Without any respelling only the surfaces of the dictionaries can match, so the
+        // matches are those the surface tries find, with no span looked up.
+        let as_spelled = !self.options.deinflect
+            && !self.options.fold_historical_kana
+            && !chars.iter().any(|c| {
+                matches!(c, 'ゝ' | 'ゞ' | 'ヽ' | 'ヾ')
+                    || symbols::horizontal_form(*c) != *c
+            });
+
+        for start in from..length {
+            if let Some(chunk) =
+                plain.get(next_plain).filter(|chunk| chunk.start <= start)
+            {
+                if chunk.start == start {
+                    let surface = &text[offsets[chunk.start]..offsets[chunk.end]];
+                    sink.accept(
+                        surface,
+                        chunk.start,
+                        chunk.end,
+                        CandidateSource::Unknown,
+                    );
+                }
+                if start + 1 == chunk.end {
+                    next_plain += 1;
+                }
+                continue;
+            }
+
+            // The katakana grouping already covers runs up to the longest grouping.
+            let max_length = self.options.max_unknown_length.max(1);
+            let seam = seams.iter().find(|seam| seam.start == start);
+            if let Some(seam) = seam.filter(|seam| seam.len() > max_length) {
+                let surface = &text[offsets[seam.start]..offsets[seam.end]];
+                sink.accept(surface, seam.start, seam.end, CandidateSource::Unknown);
+            }
+
+            if let Some(run) = self.emphasis_run(text, &offsets, start) {
+                let surface = &text[offsets[start]..offsets[start + run]];
+                sink.accept(surface, start, start + run, CandidateSource::Emphasis);
+            }
+
+            let mut found_any_term = false;
+            let mut term_ends = Vec::new();
+            let lookup_end = length.min(start + self.max_lookup_length);
+            let mut accept_matches = |end: usize, term_entries: &[TermEntry]| {
+                if continues_mora(&chars, end) {
+                    let end = attached_end(&chars, end);
+                    let surface = &text[offsets[start]..offsets[end]];
+                    sink.accept(surface, start, end, CandidateSource::Unknown);
+                    return;
+                }
+
+                let surface = &text[offsets[start]..offsets[end]];
+                for term_entry in term_entries.iter() {
+                    let source = CandidateSource::Dictionary(*term_entry);
+                    sink.accept(surface, start, end, source);
+                    found_any_term |= true;
+                }
+                term_ends.push(end);
+            };
+            if as_spelled {
+                let rest = &text[offsets[start]..offsets[lookup_end]];
+                prefix_matches.clear();
+                for ((id, dictionary), trie) in self.dictionaries.iter().zip(&self.tries)
+                {
+                    for (length, kana, term_entries) in
+                        dictionary.common_prefix_matches(trie, rest)
+                    {
+                        let surface = &rest[..length];
+                        if in_kana_map(categorize_word(surface), surface) != Some(kana) {
+                            continue;
+                        }
+                        let end = offsets
+                            .partition_point(|offset| *offset < offsets[start] + length);
+                        prefix_matches.push((end, *id, term_entries));
+                    }
+                }
+
+                // Shortest first, and in dictionary order for the same end.
+                prefix_matches.sort_by_key(|(end, _, _)| *end);
+                for matches in prefix_matches.chunk_by(|a, b| a.0 == b.0) {
+                    let end = matches[0].0;
+                    let matches = matches.iter().map(|(_, id, entries)| (*id, *entries));
+                    if let Some(term_entries) = merge_matches(matches) {
+                        accept_matches(end, &term_entries);
+                    }
+                }
+            } else {
+                Self::inner_loop(
+                    text,
+                    &offsets,
+                    start,
+                    lookup_end,
+                    |substring, _, end| {
+                        if misses.contains(substring) {
+                            return;
+                        }
+                        let found = self.lookup(substring);
+                        if found.is_none() && self.options.cache_lookup_misses {
+                            misses.insert(substring);
+                        }
+
+                        if let Some((_, term_entries)) = found {
+                            accept_matches(end, &term_entries);
+                        }
+                    },
+                );
+            }
+
+            // Dictionary entries for a function word already get its bonus. A function
+            // word never opens the text, and can't start or end inside a mora or next to
+            // a sokuon.
+            if start > 0
+                && !continues_mora(&chars, start)
+                && !follows_sokuon(&chars, start)
+            {
+                for word in function_words::function_words_at(&text[offsets[start]..]) {
+                    let end = start + word.chars().count();
+                    if !term_ends.contains(&end) && attached_end(&chars, end) == end {
+                        sink.accept(word, start, end, CandidateSource::FunctionWord);
+                    }
+                }
+            }
+
+            self.inner_loop_unknown_term(
+                !found_any_term,
+                text,
+                &offsets,
+                &clusters,
+                start,
+                |_, start, end| {
+                    if continues_mora(&chars, start) {
+                        return;
+                    }
+
+                    let end = attached_end(&chars, end);
+                    let surface = &text[offsets[start]..offsets[end]];
+                    sink.accept(surface, start, end, CandidateSource::Unknown);
+                },
+            );
+        }
     }
 
-    let matches = REGEX.matches(word);
-    for (i, (_, category)) in REGEX_CATEGORIES.iter().enumerate() {
-        if matches.matched(i) {
-            return *category;
+    /// The unknown spans covering the runs of more than [`MIN_PLAIN_RUN`] characters of
+    /// `chars` without kanji or kana, but for a word's length at either end, in order.
+    ///
+    /// Every run of one category of [`CATEGORIES`] gets spans of at most
+    /// [`TokenizerOptions::max_unknown_length`] characters, and every character of no
+    /// category one of its own. Spans start and end at category changes and on grapheme
+    /// cluster boundaries, so candidates from the positions before them end where they
+    /// start, as unknown groupings never span two categories.
+    fn plain_chunks(
+        &self,
+        chars: &[char],
+        clusters: &[bool],
+    ) -> Vec<std::ops::Range<usize>> {
+        let reach = self.reach();
+        let max_length = self.options.max_unknown_length.max(1);
+        // Only asked inside a run, away from both ends of `chars`.
+        let starts_chunk = |i: usize| {
+            let category = category_index(chars[i]);
+            clusters[i]
+                && (category.is_none() || category != category_index(chars[i - 1]))
+        };
+
+        let mut chunks = Vec::new();
+        let mut run_start = 0;
+        for run_end in 0..=chars.len() {
+            if run_end < chars.len() && !is_japanese(chars[run_end]) {
+                continue;
+            }
+            let run = run_start..run_end;
+            run_start = run_end + 1;
+            if run.len() <= MIN_PLAIN_RUN.max(4 * reach) {
+                continue;
+            }
+
+            let (first, last) = (run.start + reach, run.end - reach);
+            let Some(mut start) = (first..last).find(|i| starts_chunk(*i)) else {
+                continue;
+            };
+            let Some(end) = (start + 1..=last).rev().find(|i| starts_chunk(*i)) else {
+                continue;
+            };
+            while start < end {
+                let category = category_index(chars[start]);
+                let continues = |i: usize| {
+                    let same = category.is_some() && category_index(chars[i]) == category;
+                    !clusters[i] || (same && i - start < max_length)
+                };
+                let mut chunk_end = start + 1;
+                while chunk_end < end && continues(chunk_end) {
+                    chunk_end += 1;
+                }
+                chunks.push(start..chunk_end);
+                start = chunk_end;
+            }
         }
-    }
 
-    WordCategory::NonWord
-}
+        chunks
+    }
 
-///
-#[derive(Debug, Copy, Clone)]
-pub struct Token<'a> {
-    pub term_id: Option<u32>,
-    pub token: &'a str,
-}
+    /// Length of the emphasis run beginning at character `start`, if one begins there.
+    fn emphasis_run(&self, text: &str, offsets: &[usize], start: usize) -> Option<usize> {
+        if self.options.emphasis_threshold == 0 {
+            return None;
+        }
 
-///
-pub struct Tokenizer {
-    dictionary: Arc<Dictionary>,
-}
+        let start_pos = offsets[start];
+        let first = text[start_pos..].chars().next();
+        if text[..start_pos].chars().next_back() == first {
+            // Only report the run from its first character.
+            return None;
+        }
 
-///
-impl Tokenizer {
-    ///
-    pub fn new(dictionary: Arc<Dictionary>) -> Self {
-        Self { dictionary }
+        let run = repeat_run_length(text[start_pos..].chars());
+        (run >= self.options.emphasis_threshold).then_some(run)
     }
 
-    fn inner_loop<'a, Fn>(text: &'a str, start: usize, length: usize, mut inner: Fn)
-    where
-        Fn: FnMut(&'a str, usize, usize),
-    {
-        let start_pos = text.char_indices().nth(start).map(|(n, _)| n).unwrap();
-        for end in (start + 1)..length {
-            let end_pos = text.char_indices().nth(end).map(|(n, _)| n).unwrap();
-            let substring = &text[start_pos..end_pos];
+    /// The `k` best-scoring candidates starting at every character position of `text`,
+    /// for tuning weights. No path is searched.
+    pub fn debug_candidates(&self, text: &str, k: usize) -> Vec<PositionCandidates> {
+        if let Current::Swapped(current) = self.current() {
+            return current.debug_candidates(text, k);
+        }
 
-            inner(substring, start, end);
+        let mut sink = ScoringSink {
+            tokenizer: self,
+            candidates: Vec::new(),
+        };
+        self.generate_candidates_from(text, 0, &mut sink);
+
+        let length = text.chars().count();
+        let mut positions: Vec<PositionCandidates> = (0..length)
+            .map(|position| PositionCandidates {
+                position,
+                candidates: Vec::new(),
+            })
+            .collect();
+        for (surface, node, source) in sink.candidates {
+            positions[node.start].candidates.push(ScoredCandidate {
+                surface,
+                score: node.score,
+                source,
+                pos: node.pos,
+            });
+        }
+
+        for position in positions.iter_mut() {
+            // Stable, so ties keep generation order.
+            position
+                .candidates
+                .sort_by(|a, b| b.score.total_cmp(&a.score));
+            position.candidates.truncate(k);
         }
+
+        positions
     }
 
-    fn inner_loop_unknown_term<'a, Fn>(
-        force: bool,
-        text: &'a str,
-        start: usize,
-        length: usize,
-        mut inner: Fn,
-    ) where
-        Fn: FnMut(&'a str, usize, usize),
-    {
-        if (start + 1) >= length {
-            return;
+    /// The lattice [`Tokenizer::tokenize`] searches for `text`, with the surface and
+    /// source of every node and the path it picks, for reproducing a segmentation
+    /// without the dictionary.
+    pub fn export_lattice(&self, text: &str) -> LatticeSnapshot {
+        if let Current::Swapped(current) = self.current() {
+            return current.export_lattice(text);
         }
 
-        struct Category {
-            invoke: bool,
-            group: bool,
-            func: fn(char) -> bool,
+        let mut sink = ScoringSink {
+            tokenizer: self,
+            candidates: Vec::new(),
+        };
+        self.generate_candidates_from(text, 0, &mut sink);
+
+        let mut lattice = Lattice::new(sink.candidates.len(), text.chars().count());
+        for (_, node, _) in sink.candidates.iter() {
+            lattice.add_node(*node);
         }
+        self.rescore(&mut lattice, text);
 
-        const CATEGORIES: &[Category] = &[
-            // Space
-            Category {
-                invoke: false,
-                group: true,
-                func: |c| matches!(c as u32, 0x0020 | 0x00D0 | 0x0009 | 0x000B | 0x000A),
-            },
-            // Kanji
-            Category {
-                invoke: false,
-                group: false,
-                func: |c| {
-                    matches!(c as u32,
-                        0x2E80..=0x2EF3
-                        | 0x2F00..=0x2FD5
-                        | 0x3005
-                        | 0x3007
-                        | 0x3400..=0x4DB5
-                        | 0x4E00..=0x9FA5
-                        | 0xF900..=0xFA2D
-                        | 0xFA30..=0xFA6A
-                    )
-                },
-            },
-            // Symbol
-            Category {
-                invoke: true,
-                group: true,
-                func: |c| {
-                    matches!(c as u32,
-                        0x0021..=0x002F
-                        | 0x003A..=0x0040
-                        | 0x005B..=0x0060
-                        | 0x007B..=0x007E
-                        | 0x00A1..=0x00BF
-                        | 0xFF01..=0xFF0F
-                        | 0xFF1A..=0xFF1F
-                        | 0xFF3B..=0xFF40
-                        | 0xFF5B..=0xFF65
-                        | 0xFFE0..=0xFFEF
-                        | 0x2000..=0x206F
-                        | 0x20A0..=0x20CF
-                        | 0x20D0..=0x20FF
-                        | 0x2100..=0x214F
-                        | 0x2190..=0x21FF
-                        | 0x2200..=0x22FF
-                        | 0x2300..=0x23FF
-                        | 0x2460..=0x24FF
-                        | 0x2501..=0x257F
-                        | 0x2580..=0x259F
-                        | 0x25A0..=0x25FF
-                        | 0x2600..=0x26FE
-                        | 0x2700..=0x27BF
-                        | 0x27F0..=0x27FF
-                        | 0x27C0..=0x27EF
-                        | 0x2800..=0x28FF
-                        | 0x2900..=0x297F
-                        | 0x2B00..=0x2BFF
-                        | 0x2A00..=0x2AFF
-                        | 0x3300..=0x33FF
-                        | 0x3200..=0x32FE
-                        | 0x3000..=0x303F
-                        | 0xFE30..=0xFE4F
-                        | 0xFE50..=0xFE6B
-                    )
-                },
-            },
-            // Numeric
-            Category {
-                invoke: true,
-                group: true,
-                func: |c| {
-                    matches!(c as u32,
-                        0x0030..=0x0039
-                        | 0xFF10..=0xFF19
-                        | 0x2070..=0x209F
-                        | 0x2150..=0x218F
-                    )
-                },
-            },
-            // Alpha
-            Category {
-                invoke: false,
-                group: true,
-                func: |c| {
-                    matches!(c as u32,
-                        0x0041..=0x005A
-                        | 0x0061..=0x007A
-                        | 0x00C0..=0x00FF
-                        | 0x0100..=0x017F
-                        | 0x0180..=0x0236
-                        | 0x1E00..=0x1EF9
-                        | 0xFF21..=0xFF3A
-                        | 0xFF41..=0xFF5A
-                    )
-                },
-            },
-            // Hiragana
-            Category {
-                invoke: false,
-                group: true,
-                func: |c| matches!(c as u32, 0x3041..=0x309F),
-            },
-            // Katakana
-            Category {
-                invoke: true,
-                group: true,
-                func: |c| {
-                    matches!(c as u32,
-                        0x30A1..=0x30FF
-                        | 0x31F0..=0x31FF
-                        | 0xFF66..=0xFF9D
-                        | 0xFF9E..=0xFF9F
-                    )
-                },
-            },
-            // Greek
-            Category {
-                invoke: true,
-                group: true,
-                func: |c| matches!(c as u32, 0x0374..=0x03FB),
-            },
-            // Cyrillic
-            Category {
-                invoke: true,
-                group: true,
-                func: |c| matches!(c as u32, 0x0400..=0x04F9 | 0x0500..=0x050F),
-            },
-        ];
+        let path = self.best_path_ids(&lattice, text);
+        let nodes = sink
+            .candidates
+            .into_iter()
+            .zip(lattice.nodes())
+            .map(|((surface, _, source), node)| SnapshotNode {
+                surface,
+                source,
+                node: *node,
+            })
+            .collect();
 
-        for category in CATEGORIES.iter() {
-            if !force && !category.invoke {
-                continue;
+        LatticeSnapshot {
+            text: text.to_string(),
+            stem_particle_penalty: self.options.stem_particle_penalty,
+            particle_connection_bonus: self.options.particle_connection_bonus,
+            nominalizer_bonus: self.options.nominalizer_bonus,
+            nodes,
+            path,
+        }
+    }
+
+    ///
+    fn build_lattice(&self, text: &str) -> Lattice {
+        let length = text.chars().count();
+        // A few candidates per position in typical text.
+        let node_count = length * 4;
+        let mut lattice = self.add_candidates(text, 0, Lattice::new(node_count, length));
+        self.rescore(&mut lattice, text);
+        lattice
+    }
+
+    /// `lattice` with the candidates of `text` starting at character `from` or later
+    /// added as nodes, not yet rescored.
+    pub(crate) fn add_candidates(
+        &self,
+        text: &str,
+        from: usize,
+        lattice: Lattice,
+    ) -> Lattice {
+        let mut sink = LatticeSink {
+            tokenizer: self,
+            lattice,
+        };
+        self.generate_candidates_from(text, from, &mut sink);
+        sink.lattice
+    }
+
+    /// Number of leading positions of `chars` from which
+    /// [`Tokenizer::generate_candidates`] reports the same candidates whatever text is
+    /// appended to it.
+    ///
+    /// Those are the positions whose candidates all end before the text could attach a
+    /// small kana, a doubled kana or the rest of a grapheme cluster to them, and that
+    /// lie before the katakana run and the repeated characters the text ends with, and
+    /// before a run without kanji or kana long enough for [`Tokenizer::plain_chunks`].
+    pub(crate) fn settled_length(&self, chars: &[char]) -> usize {
+        let Some(&last) = chars.last() else {
+            return 0;
+        };
+        let length = chars.len();
+
+        // Candidates ending at `open_end` or later may end elsewhere once text follows.
+        let clusters = graphemes::cluster_boundaries(chars);
+        let last_cluster = (0..length).rev().find(|i| clusters[*i]).unwrap_or(0);
+        let mut open_end = (last_cluster + 1).min(length - 1);
+        for end in (1..open_end).rev() {
+            if end + 2 < open_end {
+                break;
             }
+            if attached_end(chars, end) >= open_end {
+                open_end = end;
+            }
+        }
 
-            let start_pos = text.char_indices().nth(start).map(|(n, _)| n).unwrap();
-            if category.group {
-                let (count, end_pos, end) = {
-                    let mut count: usize = 0;
-                    let mut end_pos: usize = 0;
-                    let mut end = start;
+        let reach = self.reach();
+        let katakana = chars.iter().rev().take_while(|c| is_katakana(**c)).count();
+        let repeated = chars.iter().rev().take_while(|c| **c == last).count();
+        let plain = chars.iter().rev().take_while(|c| !is_japanese(**c)).count();
+        let plain = if plain > MIN_PLAIN_RUN.max(4 * reach) {
+            plain
+        } else {
+            0
+        };
 
-                    let iter = ((start + 1)..length).zip(text.char_indices().skip(start));
-                    for (end_idx, (char_idx, c)) in iter {
-                        if (category.func)(c) {
-                            count += 1;
-                            end_pos = char_idx;
-                            end = end_idx;
-                        } else {
-                            break;
-                        }
-                    }
+        open_end
+            .saturating_sub(reach)
+            .min(length - katakana)
+            .min(length - repeated)
+            .min(length - plain)
+    }
 
-                    (count, end_pos, end)
-                };
+    /// Length in characters of the longest candidate, but for katakana seams and
+    /// emphasis runs: the longest dictionary lookup, unknown grouping or function word.
+    fn reach(&self) -> usize {
+        self.max_lookup_length
+            .max(self.options.max_unknown_length.max(1))
+            .max(function_words::max_function_word_length())
+    }
 
-                if count == 0 {
-                    continue;
-                }
+    /// Applies [`TokenizerOptions::unknown_coverage_penalty`],
+    /// [`TokenizerOptions::single_char_factor`], [`TokenizerOptions::acronym_bonus`] and
+    /// [`TokenizerOptions::katakana_seam_bonus`] to a lattice of `text`.
+    pub(crate) fn rescore(&self, lattice: &mut Lattice, text: &str) {
+        if self.options.unknown_coverage_penalty != 0.0 {
+            lattice.penalize_covered_unknown(self.options.unknown_coverage_penalty);
+        }
 
-                let substring = &text[start_pos..end_pos];
-                inner(substring, start, end);
-            } else if let Some((end_pos, c)) = text.char_indices().nth(start) {
-                if !(category.func)(c) {
-                    continue;
-                }
-                let end = start + 1;
-                let substring = &text[start_pos..end_pos];
-                inner(substring, start, end);
+        if self.options.single_char_factor != 1.0 {
+            lattice.discount_dominated_single_chars(self.options.single_char_factor);
+        }
+
+        let chars: Vec<char> = text.chars().collect();
+        if self.options.acronym_bonus != 0.0 {
+            for run in acronym_runs(&chars) {
+                lattice.boost_unknown(run.start, run.end, self.options.acronym_bonus);
+            }
+        }
+
+        if self.options.katakana_seam_bonus != 0.0 {
+            let max_length = self.options.max_unknown_length.max(1);
+            for run in katakana_seams(&chars) {
+                let length = run.len().min(max_length) as f32;
+                let power = self.options.length_power;
+                let bonus = self.options.katakana_seam_bonus * length.powf(power);
+                lattice.boost_unknown(run.start, run.end, bonus);
             }
         }
     }
 
+    /// Longest-match segmentation that skips the lattice entirely.
     ///
-    pub fn tokenize<'a>(&self, text: &'a str) -> Vec<Token<'a>> {
-        let length = text.chars().count();
-        let node_count = ((length * (length + 1)) as f32 / 2.0).ceil() as usize;
-        let mut lattice = Lattice::new(node_count, length);
+    /// At every position the longest dictionary surface is taken, and positions without
+    /// any match fall back to the unknown-term grouping rules. The output always covers
+    /// the whole input, but since alternative splits are never weighed against each other
+    /// the quality is noticeably lower than [`Tokenizer::tokenize`]; use it only where
+    /// speed and predictability matter more (e.g. prefix highlighting). Emphasis runs
+    /// take precedence over dictionary matches, and small kana stay with the token before
+    /// them as in [`Tokenizer::generate_candidates`], and no token crosses the end of an
+    /// acronym or splits a grapheme cluster.
+    pub fn tokenize_greedy<'a>(&self, text: &'a str) -> Vec<Token<'a>> {
+        if let Current::Swapped(current) = self.current() {
+            return current.tokenize_greedy(text);
+        }
 
-        for start in 0..length {
-            let mut found_any_term = false;
-            Self::inner_loop(text, start, length, |substring, start, end| {
-                let category = categorize_word(substring);
-                let term_entry = match category {
-                    WordCategory::Kana | WordCategory::Katakana => {
-                        self.dictionary.kana.get(substring)
-                    }
-                    WordCategory::Word => self.dictionary.kanji.get(substring),
-                    WordCategory::NonWord => None,
-                };
+        let chars: Vec<char> = text.chars().collect();
+        let offsets = char_offsets(text);
+        let length = offsets.len() - 1;
+        let acronyms = acronym_runs(&chars);
+        let clusters = graphemes::cluster_boundaries(&chars);
 
-                if let Some(term_entries) = term_entry {
-                    for term_entry in term_entries.iter() {
-                        let dictionary_entry =
-                            &self.dictionary.entries[term_entry.entry_index as usize];
+        let mut tokens = Vec::new();
+        let mut start = 0;
+        while start < length {
+            let emphasis_run = self
+                .emphasis_run(text, &offsets, start)
+                .filter(|run| clusters[start + run]);
+            if let Some(run) = emphasis_run {
+                let surface = &text[offsets[start]..offsets[start + run]];
+                tokens.push(Token {
+                    term_id: None,
+                    token: surface,
+                    char_start: start,
+                    byte_start: offsets[start],
+                    term_entry: None,
+                    pos: symbols::symbol_pos(surface).unwrap_or(PartOfSpeech::NONE),
+                    pos_alternatives: Vec::new(),
+                    components: None,
+                    kind: TokenKind::Emphasis,
+                    repeat_count: None,
+                });
+                start += run;
+                continue;
+            }
 
-                        let term_id = dictionary_entry.term_id;
-                        let score = self.get_score(
-                            end - start,
-                            category,
-                            &Some(dictionary_entry),
-                        );
-                        lattice.add_node(LatticeNode {
-                            term_id: Some(term_id),
-                            start,
-                            end,
-                            score,
-                        });
-                        found_any_term |= true;
-                    }
+            let max_end = length.min(start + self.max_lookup_length);
+            let longest = ((start + 1)..=max_end).rev().find_map(|end| {
+                if continues_mora(&chars, end)
+                    || !clusters[end]
+                    || crosses_acronym(&acronyms, start, end)
+                {
+                    return None;
                 }
+                let substring = &text[offsets[start]..offsets[end]];
+                let (category, term_entries) = self.lookup(substring)?;
+                let best_entry = term_entries
+                    .iter()
+                    .map(|term_entry| (*term_entry, self.entry(term_entry)))
+                    .fold(
+                        None,
+                        |best: Option<(TermEntry, &DictionaryEntry, f32)>,
+                         (term_entry, entry)| {
+                            let score =
+                                self.get_score(end - start, category, &Some(entry));
+                            match best {
+                                Some((_, _, best_score)) if best_score >= score => best,
+                                _ => Some((term_entry, entry, score)),
+                            }
+                        },
+                    )?;
+
+                let (term_entry, entry, _) = best_entry;
+                Some((end, Some(entry.term_id), Some(term_entry), entry.pos))
             });
 
-            Self::inner_loop_unknown_term(
-                !found_any_term,
-                text,
-                start,
-                length,
-                |substring, start, end| {
-                    let category = categorize_word(substring);
-                    let score = self.get_score(end - start, category, &None);
-                    lattice.add_node(LatticeNode {
-                        term_id: None,
-                        start,
-                        end,
-                        score,
-                    });
-                },
-            );
+            let (end, term_id, term_entry, pos) = longest.unwrap_or_else(|| {
+                let run = self.unknown_run_length(&chars[start..], &clusters[start..]);
+                let end = attached_end(&chars, start + run);
+                let end = graphemes::next_boundary(&clusters, end);
+                let surface = &text[offsets[start]..offsets[end]];
+                let pos = symbols::symbol_pos(surface).unwrap_or(PartOfSpeech::NONE);
+                (end, None, None, pos)
+            });
 
-            // for end in (start + 1)..length {
-            //     let start_pos = text.char_indices().nth(start).map(|(n, _)| n).unwrap();
-            //     let end_pos = text.char_indices().nth(end).map(|(n, _)| n).unwrap();
-            //     let substring = &text[start_pos..end_pos];
-
-            //     let category = categorize_word(substring);
-
-            //     let term_entry = match category {
-            //         WordCategory::Kana | WordCategory::Katakana => {
-            //             self.dictionary.kana.get(substring)
-            //         }
-            //         WordCategory::Word => self.dictionary.kanji.get(substring),
-            //         WordCategory::NonWord => None,
-            //     };
-
-            //     let dictionary_entry = term_entry.map(|term_entry| {
-            //         &self.dictionary.entries[term_entry.entry_index as usize]
-            //     });
-
-            //     let term_id =
-            //         dictionary_entry.map(|dictionary_entry| dictionary_entry.term_id);
-            //     let score = self.get_score(end - start, category, &dictionary_entry);
-            //     lattice.add_node(LatticeNode {
-            //         term_id,
-            //         start,
-            //         end,
-            //         score,
-            //     });
-            // }
+            tokens.push(Token {
+                term_id,
+                token: &text[offsets[start]..offsets[end]],
+                char_start: start,
+                byte_start: offsets[start],
+                term_entry,
+                pos,
+                pos_alternatives: Vec::new(),
+                components: None,
+                kind: TokenKind::Word,
+                repeat_count: None,
+            });
+            start = end;
         }
 
-        // #TODO: Avoid unnecessary memory allocation when creating a path?
-        lattice
-            .find_path()
-            .iter()
-            .map(|node| {
-                let start_pos =
-                    text.char_indices().nth(node.start).map(|(n, _)| n).unwrap();
-                let end_pos = text.char_indices().nth(node.end).map(|(n, _)| n).unwrap();
+        tokens
+    }
 
-                Token {
-                    term_id: node.term_id,
-                    token: &text[start_pos..end_pos],
+    /// Scored lattice node for a candidate span.
+    fn node(
+        &self,
+        surface: &str,
+        start: usize,
+        end: usize,
+        source: CandidateSource,
+    ) -> LatticeNode {
+        let category = categorize_word(surface);
+        match source {
+            CandidateSource::Dictionary(term_entry) => {
+                let dictionary_entry = self.entry(&term_entry);
+                let mut score =
+                    self.get_score(end - start, category, &Some(dictionary_entry))
+                        + self.function_word_score(surface, end - start);
+                if let Some(bonus) =
+                    self.options.dictionary_bonus.get(&term_entry.dictionary)
+                {
+                    score += bonus;
                 }
-            })
-            .collect()
+                score += self
+                    .options
+                    .dialect_bonus
+                    .iter()
+                    .filter(|(dialect, _)| dictionary_entry.dialect.intersects(**dialect))
+                    .map(|(_, bonus)| bonus)
+                    .sum::<f32>();
+                if let Some((domain, boost)) = self.options.domain_boost {
+                    if dictionary_entry.domain.intersects(domain) {
+                        score += boost;
+                    }
+                }
+                if term_entry.other_script {
+                    score -= self.options.other_script_penalty;
+                }
+                score -=
+                    self.options.irregular_form_penalty * term_entry.form_penalty as f32;
+                if dictionary_entry.tag.contains(Tag::ATEJI) {
+                    score += self.options.ateji_bonus;
+                }
+
+                LatticeNode {
+                    term_id: Some(dictionary_entry.term_id),
+                    term_entry: Some(term_entry),
+                    pos: dictionary_entry.pos,
+                    start,
+                    end,
+                    score,
+                    kind: TokenKind::Word,
+                }
+            }
+            CandidateSource::Unknown => LatticeNode {
+                term_id: None,
+                term_entry: None,
+                pos: symbols::symbol_pos(surface).unwrap_or(PartOfSpeech::NONE),
+                start,
+                end,
+                score: self.unknown_score(surface, end - start, category),
+                kind: TokenKind::Word,
+            },
+            CandidateSource::FunctionWord => LatticeNode {
+                term_id: None,
+                term_entry: None,
+                pos: function_words::function_word_pos(surface)
+                    .unwrap_or(PartOfSpeech::NONE),
+                start,
+                end,
+                score: self.get_score(end - start, category, &None)
+                    + self.function_word_score(surface, end - start),
+                kind: TokenKind::Word,
+            },
+            // Scored like a word-category span so the run outweighs any grouping that
+            // splits it.
+            CandidateSource::Emphasis => LatticeNode {
+                term_id: None,
+                term_entry: None,
+                pos: symbols::symbol_pos(surface).unwrap_or(PartOfSpeech::NONE),
+                start,
+                end,
+                score: self.get_score(end - start, WordCategory::Word, &None),
+                kind: TokenKind::Emphasis,
+            },
+        }
+    }
+
+    /// Score of an unknown span. Hiragana runs usually hold several words, so they only
+    /// grow linearly and a split at the function words in them can win.
+    fn unknown_score(
+        &self,
+        surface: &str,
+        text_len: usize,
+        category: WordCategory,
+    ) -> f32 {
+        if surface.chars().all(is_hiragana) {
+            let text_len = text_len.min(self.options.max_unknown_length.max(1));
+            self.get_score(1, category, &None) * text_len as f32
+        } else {
+            let weight = self.options.unknown_words.weight(category.into(), text_len);
+            self.get_score(text_len, category, &None) * weight
+        }
+    }
+
+    /// [`TokenizerOptions::function_word_bonus`] scaled like a kana span of `text_len`,
+    /// if `surface` is a built-in function word.
+    fn function_word_score(&self, surface: &str, text_len: usize) -> f32 {
+        if function_words::function_word_pos(surface).is_none() {
+            return 0.0;
+        }
+
+        let scaled = self.options.function_word_bonus
+            * (text_len as f32).powf(self.options.length_power);
+        scaled.min(MAX_NODE_SCORE)
     }
 
     ///
@@ -431,13 +3028,304 @@ impl Tokenizer {
         }
 
         let power = if category == WordCategory::Word {
-            3.0
+            self.options.word_length_power
         } else {
-            2.0
+            self.options.length_power
         };
 
+        let text_len = text_len.min(self.options.max_unknown_length.max(1));
         score *= (text_len as f32).powf(power);
 
-        score
+        score.min(MAX_NODE_SCORE)
+    }
+}
+
+/// Whether a surface of `category` is looked up in the kana map of a dictionary rather
+/// than its kanji map: the kana map for kana, the kanji map for anything else with at
+/// least one Japanese character, like 学生 or Tシャツ, and neither (`None`) without.
+fn in_kana_map(category: WordCategory, surface: &str) -> Option<bool> {
+    match category {
+        WordCategory::Kana | WordCategory::Katakana => Some(true),
+        WordCategory::Word => Some(false),
+        WordCategory::NonWord if surface.chars().any(is_japanese) => Some(false),
+        WordCategory::NonWord => None,
+    }
+}
+
+/// The matches of one surface in several dictionaries, in dictionary order, each tagged
+/// with the dictionary it comes from. They are only copied when they don't all come
+/// from a dictionary named [`DictionaryId::MAIN`], whose stored entries already say so.
+fn merge_matches<'d>(
+    matches: impl IntoIterator<Item = (DictionaryId, &'d [TermEntry])>,
+) -> Option<Cow<'d, [TermEntry]>> {
+    let mut found: Option<Cow<'d, [TermEntry]>> = None;
+    for (id, term_entries) in matches {
+        if found.is_none() && id == DictionaryId::MAIN {
+            found = Some(Cow::Borrowed(term_entries));
+            continue;
+        }
+        let found = found.get_or_insert_with(|| Cow::Owned(Vec::new())).to_mut();
+        found.extend(term_entries.iter().map(|term_entry| TermEntry {
+            dictionary: id,
+            ..*term_entry
+        }));
+    }
+
+    found
+}
+
+/// Dictionary nodes of at least `min_length` characters that reach across a boundary
+/// between `tokens`, as byte ranges of `text` with their term ids, without duplicates.
+/// Matches within a single token, including the ones it was made from, are left out.
+fn covering_entries(
+    text: &str,
+    lattice: &Lattice,
+    tokens: &[Token],
+    min_length: usize,
+) -> Vec<(std::ops::Range<usize>, TermId)> {
+    let offsets = char_offsets(text);
+    let spans: Vec<(usize, usize)> = tokens
+        .iter()
+        .map(|token| {
+            (
+                token.char_start,
+                token.char_start + token.token.chars().count(),
+            )
+        })
+        .collect();
+    let within_one_token = |node: &LatticeNode| {
+        spans
+            .iter()
+            .any(|&(start, end)| start <= node.start && node.end <= end)
+    };
+
+    let mut entries: Vec<(std::ops::Range<usize>, TermId)> = Vec::new();
+    for node in lattice.nodes().iter() {
+        let Some(term_id) = node.term_id else {
+            continue;
+        };
+        if node.end - node.start < min_length || within_one_token(node) {
+            continue;
+        }
+
+        let entry = (offsets[node.start]..offsets[node.end], term_id);
+        if !entries.contains(&entry) {
+            entries.push(entry);
+        }
+    }
+
+    entries.sort_by_key(|(range, term_id)| (range.start, range.end, *term_id));
+    entries
+}
+
+/// Moves `token` and its components from the start of a slice to `char_start` and
+/// `byte_start` of the text the slice was taken from.
+fn shift_token(token: &mut Token, char_start: usize, byte_start: usize) {
+    token.char_start += char_start;
+    token.byte_start += byte_start;
+    for component in token.components.iter_mut().flatten() {
+        shift_token(component, char_start, byte_start);
+    }
+}
+
+/// `token` of [`VisibleText::text`] as a token of `text`, the text it was made from,
+/// along with its components.
+fn restore_token<'a>(visible: &VisibleText, text: &'a str, token: Token) -> Token<'a> {
+    let char_end = token.char_start + token.token.chars().count();
+    let byte_start = visible.original_byte(token.char_start);
+    let byte_end = visible.original_byte(char_end);
+    let components = token.components.map(|components| {
+        components
+            .into_iter()
+            .map(|component| restore_token(visible, text, component))
+            .collect()
+    });
+
+    Token {
+        term_id: token.term_id,
+        token: &text[byte_start..byte_end],
+        char_start: visible.original_char(token.char_start),
+        byte_start,
+        term_entry: token.term_entry,
+        pos: token.pos,
+        pos_alternatives: token.pos_alternatives,
+        components,
+        kind: token.kind,
+        repeat_count: token.repeat_count,
+    }
+}
+
+/// The [`TokenizerOptions`] that [`connection_score`] applies.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub(crate) struct ConnectionWeights {
+    pub stem_particle_penalty: f32,
+    pub particle_connection_bonus: f32,
+    pub nominalizer_bonus: f32,
+}
+
+impl From<&TokenizerOptions> for ConnectionWeights {
+    fn from(options: &TokenizerOptions) -> Self {
+        Self {
+            stem_particle_penalty: options.stem_particle_penalty,
+            particle_connection_bonus: options.particle_connection_bonus,
+            nominalizer_bonus: options.nominalizer_bonus,
+        }
+    }
+}
+
+/// Verb classes whose plain forms can be nominalized by の or ん.
+const VERB: PartOfSpeech = PartOfSpeech::GODAN_VERB
+    .union(PartOfSpeech::ICHIDAN_VERB)
+    .union(PartOfSpeech::KURU_VERB)
+    .union(PartOfSpeech::SURU_VERB);
+
+/// Whether `node` is a dictionary verb in its dictionary, past or negative form.
+fn is_plain_verb(node: &LatticeNode) -> bool {
+    use InflectionType::*;
+    let inflection = node.term_entry.map(|term_entry| term_entry.inflection_type);
+    node.pos.intersects(VERB)
+        && matches!(inflection, Some(DictionaryForm | Past | Negative))
+}
+
+/// Score for moving from `left` to `right` in a lattice of `chars`: minus the stem
+/// particle penalty where an unknown fragment ending in an e-row kana is followed by the
+/// particle は or ば, see [`TokenizerOptions::stem_particle_penalty`], the particle
+/// connection bonus where a content word is followed by a particle, see
+/// [`TokenizerOptions::particle_connection_bonus`], plus the nominalizer bonus where a
+/// plain verb is followed by の or a ん contraction, see
+/// [`TokenizerOptions::nominalizer_bonus`].
+pub(crate) fn connection_score(
+    chars: &[char],
+    weights: &ConnectionWeights,
+    left: &LatticeNode,
+    right: &LatticeNode,
+) -> f32 {
+    let is_topic_or_conditional = right.pos.is_particle()
+        && right.end - right.start == 1
+        && matches!(chars[right.start], 'は' | 'ば');
+    if is_topic_or_conditional
+        && left.term_id.is_none()
+        && is_e_row_kana(chars[left.end - 1])
+    {
+        return -weights.stem_particle_penalty;
+    }
+
+    let mut score = 0.0;
+    let is_content_word =
+        left.kind == TokenKind::Word && !left.pos.intersects(FUNCTIONAL);
+    if is_content_word && right.pos.is_particle() {
+        score += weights.particle_connection_bonus;
+    }
+
+    let is_nominalizer = match chars[right.start] {
+        'の' => right.end - right.start == 1 && right.pos.is_particle(),
+        'ん' => right.pos.intersects(PartOfSpeech::AUXILIARY_VERB),
+        _ => false,
+    };
+    if is_nominalizer && is_plain_verb(left) {
+        score += weights.nominalizer_bonus;
+    }
+
+    score
+}
+
+/// Parts of speech that don't start a [`connection_score`] particle bonus.
+const FUNCTIONAL: PartOfSpeech = PartOfSpeech::PARTICLE
+    .union(PartOfSpeech::AUXILIARY)
+    .union(PartOfSpeech::AUXILIARY_ADJECTIVE)
+    .union(PartOfSpeech::AUXILIARY_VERB)
+    .union(PartOfSpeech::COPULA)
+    .union(PartOfSpeech::PREFIX)
+    .union(PartOfSpeech::SYMBOL);
+
+/// Adds every candidate to a lattice as a scored node.
+struct LatticeSink<'t> {
+    tokenizer: &'t Tokenizer,
+    lattice: Lattice,
+}
+
+impl CandidateSink for LatticeSink<'_> {
+    fn accept(
+        &mut self,
+        surface: &str,
+        start: usize,
+        end: usize,
+        source: CandidateSource,
+    ) {
+        let node = self.tokenizer.node(surface, start, end, source);
+        self.lattice.add_node(node);
+    }
+}
+
+/// Drops the candidates reaching over the end of an acronym before passing the rest on,
+/// apart from dictionary words, like Tシャツ, which the acronym is part of.
+struct AcronymBoundarySink<'a, S> {
+    acronyms: &'a [std::ops::Range<usize>],
+    sink: &'a mut S,
+}
+
+impl<S: CandidateSink> CandidateSink for AcronymBoundarySink<'_, S> {
+    fn accept(
+        &mut self,
+        surface: &str,
+        start: usize,
+        end: usize,
+        source: CandidateSource,
+    ) {
+        let dictionary = matches!(source, CandidateSource::Dictionary(_));
+        if dictionary || !crosses_acronym(self.acronyms, start, end) {
+            self.sink.accept(surface, start, end, source);
+        }
+    }
+}
+
+/// Drops the candidates starting inside a grapheme cluster and extends those ending
+/// inside one, as unknown spans, before passing them on.
+struct ClusterBoundarySink<'a, S> {
+    text: &'a str,
+    offsets: &'a [usize],
+    clusters: &'a [bool],
+    sink: &'a mut S,
+}
+
+impl<S: CandidateSink> CandidateSink for ClusterBoundarySink<'_, S> {
+    fn accept(
+        &mut self,
+        surface: &str,
+        start: usize,
+        end: usize,
+        source: CandidateSource,
+    ) {
+        if !self.clusters[start] {
+            return;
+        }
+
+        if self.clusters[end] {
+            self.sink.accept(surface, start, end, source);
+        } else {
+            let end = graphemes::next_boundary(self.clusters, end);
+            let surface = &self.text[self.offsets[start]..self.offsets[end]];
+            self.sink
+                .accept(surface, start, end, CandidateSource::Unknown);
+        }
+    }
+}
+
+/// Keeps every candidate together with the node it would become.
+struct ScoringSink<'t> {
+    tokenizer: &'t Tokenizer,
+    candidates: Vec<(String, LatticeNode, CandidateSource)>,
+}
+
+impl CandidateSink for ScoringSink<'_> {
+    fn accept(
+        &mut self,
+        surface: &str,
+        start: usize,
+        end: usize,
+        source: CandidateSource,
+    ) {
+        let node = self.tokenizer.node(surface, start, end, source);
+        self.candidates.push((surface.to_string(), node, source));
     }
 }
@@ -0,0 +1,115 @@
+//! How long the words the dictionary doesn't know tend to be, for deciding whether an
+//! unknown run like メルカリポイント is one word or several.
+
+use crate::tokenizer::{self, WordCategory};
+use std::collections::HashMap;
+
+/// Longest length the model tells apart. Longer words are counted and scored as this
+/// long.
+pub const MAX_MODEL_LENGTH: usize = 32;
+
+/// Script of an unknown word, as far as the model is concerned.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum UnknownCategory {
+    /// Only katakana, like most loanwords and brand names.
+    Katakana,
+    /// Hiragana and katakana mixed.
+    Kana,
+    /// Kanji, or kanji and hiragana.
+    Kanji,
+    /// Anything else, like Latin letters and digits.
+    Other,
+}
+
+impl UnknownCategory {
+    /// The category of `surface`.
+    pub fn of(surface: &str) -> Self {
+        Self::from(tokenizer::categorize_word(surface))
+    }
+}
+
+impl From<WordCategory> for UnknownCategory {
+    fn from(category: WordCategory) -> Self {
+        match category {
+            WordCategory::Katakana => Self::Katakana,
+            WordCategory::Kana => Self::Kana,
+            WordCategory::Word => Self::Kanji,
+            WordCategory::NonWord => Self::Other,
+        }
+    }
+}
+
+/// Probabilities of the lengths of unknown words, per [`UnknownCategory`].
+///
+/// The score of an unknown span is its length power times [`UnknownWordModel::weight`],
+/// so lengths words are seldom seen with lose against a split into likelier ones. The
+/// default model has no statistics for any category, which weighs every length alike
+/// and leaves the bare length power. Hiragana-only runs are scored by their length
+/// alone either way.
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct UnknownWordModel {
+    /// For every category with statistics, the probability of each length from one
+    /// character up to [`MAX_MODEL_LENGTH`].
+    lengths: HashMap<UnknownCategory, Vec<f64>>,
+}
+
+impl UnknownWordModel {
+    /// A model of the lengths counted in `counts`, keyed by category and length in
+    /// characters. Lengths past [`MAX_MODEL_LENGTH`] count as that long, and every
+    /// length gets one more occurrence, so none is ruled out entirely. Categories
+    /// without counts keep weighing every length alike.
+    pub fn train(counts: &HashMap<(UnknownCategory, usize), u64>) -> Self {
+        let mut tallies: HashMap<UnknownCategory, Vec<u64>> = HashMap::new();
+        for (&(category, length), &count) in counts {
+            if length == 0 || count == 0 {
+                continue;
+            }
+            let tally = tallies
+                .entry(category)
+                .or_insert_with(|| vec![0; MAX_MODEL_LENGTH]);
+            tally[length.min(MAX_MODEL_LENGTH) - 1] += count;
+        }
+
+        let lengths = tallies
+            .into_iter()
+            .map(|(category, tally)| {
+                let total = tally.iter().sum::<u64>() + MAX_MODEL_LENGTH as u64;
+                let probabilities = tally
+                    .iter()
+                    .map(|count| (count + 1) as f64 / total as f64)
+                    .collect();
+                (category, probabilities)
+            })
+            .collect();
+
+        Self { lengths }
+    }
+
+    /// Whether the model has statistics for `category`, which makes the tokenizer
+    /// consider every shorter grouping of an unknown run of it too.
+    pub fn has_statistics(&self, category: UnknownCategory) -> bool {
+        self.lengths.contains_key(&category)
+    }
+
+    /// Probability of an unknown word of `category` being `length` characters long,
+    /// `None` without statistics for the category.
+    pub fn probability(&self, category: UnknownCategory, length: usize) -> Option<f64> {
+        let probabilities = self.lengths.get(&category)?;
+        let index = length.clamp(1, MAX_MODEL_LENGTH) - 1;
+        Some(probabilities[index])
+    }
+
+    /// Factor the score of an unknown span of `category` and `length` characters is
+    /// multiplied with: the probability of the length relative to the likeliest one,
+    /// `1.0` without statistics for the category.
+    pub fn weight(&self, category: UnknownCategory, length: usize) -> f32 {
+        let Some(probability) = self.probability(category, length) else {
+            return 1.0;
+        };
+        let likeliest = self.lengths[&category].iter().copied().fold(0.0, f64::max);
+
+        (probability / likeliest) as f32
+    }
+}
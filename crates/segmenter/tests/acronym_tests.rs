@@ -0,0 +1,74 @@
+use segmenter::dictionary::{DictionaryBuilder, PartOfSpeech, Tag};
+use segmenter::tokenizer::{Token, Tokenizer, TokenizerOptions};
+use std::sync::Arc;
+
+const WORDS: &[(Option<&str>, &str, PartOfSpeech)] = &[
+    (Some("技術"), "ぎじゅつ", PartOfSpeech::NOUN),
+    (Some("技"), "わざ", PartOfSpeech::NOUN),
+    (Some("術"), "じゅつ", PartOfSpeech::NOUN),
+    (Some("企業"), "きぎょう", PartOfSpeech::NOUN),
+    (None, "データ", PartOfSpeech::NOUN),
+    (None, "ニュース", PartOfSpeech::NOUN),
+    (None, "の", PartOfSpeech::PARTICLE),
+];
+
+fn tokenizer(options: TokenizerOptions) -> Tokenizer {
+    let mut builder = DictionaryBuilder::new();
+    for (kanji, kana, pos) in WORDS {
+        builder.add(*kanji, kana, *pos, Tag::NONE);
+    }
+    Tokenizer::with_options(Arc::new(builder.build()), options)
+}
+
+fn surfaces<'a>(tokens: &[Token<'a>]) -> Vec<&'a str> {
+    tokens.iter().map(|token| token.token).collect()
+}
+
+#[test]
+fn acronyms_stay_whole_before_japanese() {
+    let tokenizer = tokenizer(TokenizerOptions::default());
+    for (text, expected) in [
+        ("AI技術", &["AI", "技術"][..]),
+        ("AI技", &["AI", "技"]),
+        ("IT企業", &["IT", "企業"]),
+        ("GPSデータ", &["GPS", "データ"]),
+        ("NHKのニュース", &["NHK", "の", "ニュース"]),
+        ("ＮＨＫのニュース", &["ＮＨＫ", "の", "ニュース"]),
+    ] {
+        for tokens in [tokenizer.tokenize(text), tokenizer.tokenize_greedy(text)] {
+            assert_eq!(surfaces(&tokens), expected, "{text}");
+            assert_eq!(tokens[0].term_id, None, "{text}");
+        }
+    }
+}
+
+#[test]
+fn acronym_runs_get_a_bonus() {
+    let score = |tokenizer: &Tokenizer, text: &str| {
+        let snapshot = tokenizer.export_lattice(text);
+        snapshot.nodes[snapshot.path[0]].node.score
+    };
+
+    let default = tokenizer(TokenizerOptions::default());
+    let disabled = tokenizer(TokenizerOptions {
+        acronym_bonus: 0.0,
+        ..Default::default()
+    });
+    assert_eq!(score(&default, "AI技術"), score(&disabled, "AI技術") + 4.0);
+
+    // Lowercase, long or not followed by kanji or katakana: not an acronym.
+    for text in ["iPhone技術", "ABCDEF技術", "AIの技術", "AI"] {
+        assert_eq!(score(&default, text), score(&disabled, text), "{text}");
+    }
+}
+
+#[test]
+fn no_candidate_crosses_an_acronym() {
+    let tokenizer = tokenizer(TokenizerOptions::default());
+    let snapshot = tokenizer.export_lattice("AI技術");
+    assert!(snapshot
+        .nodes
+        .iter()
+        .all(|node| node.node.end <= 2 || node.node.start >= 2));
+    assert_eq!(surfaces(&tokenizer.tokenize("AI技術")), ["AI", "技術"]);
+}
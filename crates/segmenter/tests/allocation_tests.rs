@@ -0,0 +1,69 @@
+//! Dictionary lookups during candidate generation borrow the probed substrings instead
+//! of allocating keys. This binary counts every allocation, so it holds a single test.
+
+use segmenter::candidate::{CandidateSink, CandidateSource};
+use segmenter::dictionary::{DictionaryBuilder, PartOfSpeech, Tag};
+use segmenter::tokenizer::{Tokenizer, TokenizerOptions};
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+struct CountingAllocator;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+struct NullSink;
+
+impl CandidateSink for NullSink {
+    fn accept(&mut self, _: &str, _: usize, _: usize, _: CandidateSource) {}
+}
+
+/// A tokenizer whose longest key, which never matches the text, is `key_length`
+/// characters long, so every position probes that many substrings. Lookup misses
+/// aren't cached, since the cache grows with the number of substrings probed.
+fn tokenizer(key_length: usize) -> Tokenizer {
+    let mut builder = DictionaryBuilder::new();
+    builder.add(
+        None,
+        &"ゑ".repeat(key_length),
+        PartOfSpeech::NOUN,
+        Tag::NONE,
+    );
+    let options = TokenizerOptions {
+        cache_lookup_misses: false,
+        ..TokenizerOptions::default()
+    };
+    Tokenizer::with_options(Arc::new(builder.build()), options)
+}
+
+fn allocations(tokenizer: &Tokenizer, text: &str) -> usize {
+    let before = ALLOCATIONS.load(Ordering::Relaxed);
+    tokenizer.generate_candidates(text, &mut NullSink);
+    ALLOCATIONS.load(Ordering::Relaxed) - before
+}
+
+#[test]
+fn lookups_do_not_allocate() {
+    let text = "あいうえおかきくけこさしすせそたちつてと".repeat(5);
+    let (short, long) = (tokenizer(2), tokenizer(24));
+    // Warm up anything initialized on first use.
+    allocations(&short, &text);
+    allocations(&long, &text);
+
+    // Twelve times as many lookups for the same candidates.
+    assert_eq!(allocations(&short, &text), allocations(&long, &text));
+}
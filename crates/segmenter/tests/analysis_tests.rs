@@ -0,0 +1,145 @@
+mod common;
+
+use common::fixture_dictionary;
+#[cfg(feature = "serde")]
+use segmenter::analysis::OwnedToken;
+use segmenter::analysis::{AnalysisMismatch, AnalysisResult, SCHEMA_VERSION};
+use segmenter::dictionary::{InflectionType, PartOfSpeech, Tag};
+use segmenter::tokenizer::{Tokenizer, TokenizerOptions};
+use std::sync::Arc;
+
+fn analyze(tokenizer: &Tokenizer, text: &str) -> AnalysisResult {
+    let dictionary = tokenizer.dictionary();
+    let tokens = tokenizer.tokenize(text);
+    AnalysisResult::new(text, &tokens, &dictionary, dictionary.metadata())
+}
+
+#[test]
+fn owned_tokens_carry_offsets() {
+    let tokenizer = Tokenizer::new(Arc::new(fixture_dictionary()));
+    let text = "「猫」を見た。";
+    let result = analyze(&tokenizer, text);
+
+    for token in result.tokens.iter() {
+        assert_eq!(&text[token.byte_start..token.byte_end], token.surface);
+        let chars: String = text
+            .chars()
+            .skip(token.char_start)
+            .take(token.char_end - token.char_start)
+            .collect();
+        assert_eq!(chars, token.surface);
+    }
+
+    let cat = &result.tokens[1];
+    assert_eq!(
+        (cat.surface.as_str(), cat.char_start, cat.byte_start),
+        ("猫", 1, 3)
+    );
+    assert_eq!(cat.inflection, Some(InflectionType::DictionaryForm));
+    assert_eq!(cat.tag, Tag::NONE);
+    assert_eq!(result.tokens[0].inflection, None);
+    assert_eq!(result.tokens[0].pos, PartOfSpeech::SYMBOL);
+}
+
+#[test]
+fn components_are_offset_within_the_text() {
+    let tokenizer = Tokenizer::with_options(
+        Arc::new(fixture_dictionary()),
+        TokenizerOptions {
+            decompose_expressions: true,
+            ..Default::default()
+        },
+    );
+    let text = "猫かもしれない";
+    let result = analyze(&tokenizer, text);
+
+    let components = result.tokens[1].components.as_ref().unwrap();
+    assert_eq!(components[0].char_start, 1);
+    for component in components {
+        assert_eq!(
+            &text[component.byte_start..component.byte_end],
+            component.surface
+        );
+    }
+}
+
+#[test]
+fn inflection_comes_from_deinflection() {
+    let tokenizer = Tokenizer::with_options(
+        Arc::new(fixture_dictionary()),
+        TokenizerOptions {
+            deinflect: true,
+            ..Default::default()
+        },
+    );
+    let result = analyze(&tokenizer, "東京に行かない");
+    let last = result.tokens.last().unwrap();
+    assert_eq!(last.surface, "行かない");
+    assert_eq!(last.inflection, Some(InflectionType::Negative));
+}
+
+#[test]
+#[cfg(feature = "serde")]
+fn results_round_trip_through_serde() {
+    let tokenizer = Tokenizer::new(Arc::new(fixture_dictionary()));
+    for text in ["私は猫を見た", "面白いｗｗｗ", "スマートフォンを見た。", ""]
+    {
+        let result = analyze(&tokenizer, text);
+        assert_eq!(result.schema_version, SCHEMA_VERSION);
+
+        let json = serde_json::to_string(&result).unwrap();
+        let restored: AnalysisResult = serde_json::from_str(&json).unwrap();
+        assert_eq!(restored, result, "{text}");
+        assert_eq!(
+            restored.validate(text, &tokenizer.dictionary().metadata()),
+            Ok(())
+        );
+
+        let bytes = bincode::serialize(&result.tokens).unwrap();
+        let restored: Vec<OwnedToken> = bincode::deserialize(&bytes).unwrap();
+        assert_eq!(restored, result.tokens, "{text}");
+    }
+}
+
+#[test]
+fn dictionary_metadata_mismatch_is_detected() {
+    let tokenizer = Tokenizer::new(Arc::new(fixture_dictionary()));
+    let text = "私は猫を見た";
+    let result = analyze(&tokenizer, text);
+
+    // Same content, same metadata.
+    let metadata = fixture_dictionary().metadata();
+    assert_eq!(metadata, tokenizer.dictionary().metadata());
+    assert_eq!(result.validate(text, &metadata), Ok(()));
+
+    let mut changed = fixture_dictionary();
+    changed.add_reading(0, "びょう");
+    let changed = changed.metadata();
+    assert_eq!(changed.entry_count, metadata.entry_count);
+    assert_ne!(changed.fingerprint, metadata.fingerprint);
+    assert_eq!(
+        result.validate(text, &changed),
+        Err(AnalysisMismatch::Dictionary {
+            stored: metadata,
+            current: changed,
+        })
+    );
+}
+
+#[test]
+fn text_and_schema_mismatches_are_detected() {
+    let tokenizer = Tokenizer::new(Arc::new(fixture_dictionary()));
+    let metadata = tokenizer.dictionary().metadata();
+    let mut result = analyze(&tokenizer, "猫を見た");
+
+    assert_eq!(
+        result.validate("猫を見る", &metadata),
+        Err(AnalysisMismatch::Text)
+    );
+
+    result.schema_version = SCHEMA_VERSION + 1;
+    assert_eq!(
+        result.validate("猫を見た", &metadata),
+        Err(AnalysisMismatch::SchemaVersion(SCHEMA_VERSION + 1))
+    );
+}
@@ -0,0 +1,85 @@
+mod common;
+
+use common::{fixture_dictionary, surfaces};
+use segmenter::dictionary::{Dictionary, PartOfSpeech, Tag};
+use segmenter::ruby::RubyRenderer;
+use segmenter::tokenizer::{Tokenizer, TokenizerOptions};
+use std::sync::Arc;
+
+/// The fixture dictionary with 流石 and 大人しい, spelled with ateji and gikun, and
+/// words for the kanji of 流石 on their own.
+fn dictionary() -> Dictionary {
+    let mut dictionary = fixture_dictionary();
+    for (term_id, kanji, kana, pos, tag) in [
+        (1000, "流", "りゅう", PartOfSpeech::NOUN_SUFFIX, Tag::NONE),
+        (1001, "石", "いし", PartOfSpeech::NOUN, Tag::NONE),
+        (
+            1002,
+            "石川",
+            "いしかわ",
+            PartOfSpeech::NOUN_PROPER,
+            Tag::NONE,
+        ),
+        (1003, "流石", "さすが", PartOfSpeech::ADVERB, Tag::ATEJI),
+        (
+            1004,
+            "大人しい",
+            "おとなしい",
+            PartOfSpeech::ADJECTIVE,
+            Tag::ATEJI,
+        ),
+        (1005, "大人", "おとな", PartOfSpeech::NOUN, Tag::NONE),
+    ] {
+        dictionary.add_word(term_id, Some(kanji), kana, pos, tag);
+    }
+    dictionary
+}
+
+#[test]
+fn ateji_stay_whole() {
+    let tokenizer = Tokenizer::new(Arc::new(dictionary()));
+    assert_eq!(surfaces(&tokenizer.tokenize("流石だ")), ["流石", "だ"]);
+    assert_eq!(
+        surfaces(&tokenizer.tokenize("流石石川さん")),
+        ["流石", "石川", "さん"]
+    );
+}
+
+#[test]
+fn the_bonus_decides_close_calls() {
+    let dictionary = Arc::new(dictionary());
+    let tokenize = |ateji_bonus| {
+        let options = TokenizerOptions {
+            ateji_bonus,
+            ..TokenizerOptions::default()
+        };
+        let tokenizer = Tokenizer::with_options(dictionary.clone(), options);
+        tokenizer
+            .tokenize("流石川")
+            .iter()
+            .map(|token| token.token.to_string())
+            .collect::<Vec<_>>()
+    };
+
+    assert_eq!(tokenize(0.0), ["流", "石川"]);
+    assert_eq!(tokenize(4.0), ["流石", "川"]);
+}
+
+#[test]
+fn ateji_ruby_covers_the_whole_word() {
+    let dictionary = Arc::new(dictionary());
+    let tokenizer = Tokenizer::new(dictionary.clone());
+    let renderer = RubyRenderer::new(&dictionary);
+
+    let tokens = tokenizer.tokenize("流石");
+    assert_eq!(renderer.html(&tokens), "<ruby>流石<rt>さすが</rt></ruby>");
+    assert_eq!(renderer.furigana(&tokens), "流石[さすが]");
+
+    // Okurigana stay out of the ruby of other words, as usual.
+    let tokens = tokenizer.tokenize("大人しい面白い");
+    assert_eq!(surfaces(&tokens), ["大人しい", "面白い"]);
+    assert_eq!(
+        renderer.furigana(&tokens),
+        "大人しい[おとなしい]面白[おもしろ]い"
+    );
+}
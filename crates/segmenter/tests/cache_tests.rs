@@ -0,0 +1,124 @@
+#![cfg(feature = "cache")]
+
+mod common;
+
+use common::fixture_dictionary;
+use segmenter::analysis::OwnedToken;
+use segmenter::cache::CachedTokenizer;
+use segmenter::dictionary::{PartOfSpeech, Tag};
+use segmenter::tokenizer::Tokenizer;
+use std::sync::Arc;
+
+fn cached(capacity: usize) -> CachedTokenizer {
+    CachedTokenizer::new(
+        Arc::new(Tokenizer::new(Arc::new(fixture_dictionary()))),
+        capacity,
+    )
+}
+
+fn surfaces(tokens: &[OwnedToken]) -> Vec<&str> {
+    tokens.iter().map(|token| token.surface.as_str()).collect()
+}
+
+#[test]
+fn repeated_texts_are_hits() {
+    let cache = cached(8);
+    assert_eq!(cache.hit_rate(), 0.0);
+
+    let first = cache.tokenize("猫を見た");
+    assert_eq!(surfaces(&first), ["猫", "を", "見た"]);
+    assert_eq!((cache.hits(), cache.misses()), (0, 1));
+
+    let again = cache.tokenize("猫を見た");
+    assert!(Arc::ptr_eq(&first, &again));
+    cache.tokenize("私は学生です");
+    cache.tokenize("猫を見た");
+    assert_eq!((cache.hits(), cache.misses()), (2, 2));
+    assert_eq!(cache.hit_rate(), 0.5);
+    assert_eq!(cache.len(), 2);
+
+    // Cached tokens are the tokenizer's own.
+    let expected: Vec<OwnedToken> = cache
+        .tokenizer()
+        .tokenize("猫を見た")
+        .iter()
+        .map(|token| OwnedToken::from((token, &*cache.tokenizer().dictionary())))
+        .collect();
+    assert_eq!(*again, expected[..]);
+
+    cache.clear();
+    assert!(cache.is_empty());
+    cache.tokenize("猫を見た");
+    assert_eq!(cache.misses(), 3);
+}
+
+#[test]
+fn the_least_recently_used_text_is_evicted() {
+    let cache = cached(2);
+    cache.tokenize("猫");
+    cache.tokenize("私");
+    // 猫 is used again, so 私 is the one to go.
+    cache.tokenize("猫");
+    cache.tokenize("学生");
+    assert_eq!(cache.len(), 2);
+
+    let misses = cache.misses();
+    cache.tokenize("猫");
+    cache.tokenize("学生");
+    assert_eq!(cache.misses(), misses);
+    cache.tokenize("私");
+    assert_eq!(cache.misses(), misses + 1);
+    assert_eq!(cache.len(), 2);
+}
+
+#[test]
+fn large_caches_keep_their_capacity() {
+    let cache = cached(1000);
+    for i in 0..1500 {
+        cache.tokenize(&format!("猫{i}"));
+    }
+    assert_eq!(cache.len(), 1000);
+}
+
+#[test]
+fn swapping_the_dictionary_invalidates_results() {
+    let cache = cached(8);
+    assert_eq!(surfaces(&cache.tokenize("メロン")), ["メロン"]);
+    assert_eq!(cache.tokenize("メロン")[0].term_id, None);
+
+    let mut dictionary = fixture_dictionary();
+    dictionary.add_word(1000, None, "メロン", PartOfSpeech::NOUN, Tag::NONE);
+    cache.tokenizer().swap_dictionary(Arc::new(dictionary));
+    assert_eq!(cache.tokenize("メロン")[0].term_id, Some(1000));
+    assert_eq!((cache.hits(), cache.misses()), (1, 2));
+
+    // A dictionary of the same content finds the results cached for it.
+    cache
+        .tokenizer()
+        .swap_dictionary(Arc::new(fixture_dictionary()));
+    assert_eq!(cache.tokenize("メロン")[0].term_id, None);
+    assert_eq!(cache.hits(), 2);
+}
+
+#[test]
+fn threads_share_the_cache() {
+    let cache = Arc::new(cached(64));
+    let threads: Vec<_> = (0..4)
+        .map(|_| {
+            let cache = cache.clone();
+            std::thread::spawn(move || {
+                for _ in 0..50 {
+                    assert_eq!(
+                        surfaces(&cache.tokenize("猫を見た")),
+                        ["猫", "を", "見た"]
+                    );
+                }
+            })
+        })
+        .collect();
+    for thread in threads {
+        thread.join().unwrap();
+    }
+    assert_eq!(cache.hits() + cache.misses(), 200);
+    assert!(cache.misses() <= 4);
+}
@@ -0,0 +1,100 @@
+mod common;
+
+use common::fixture_dictionary;
+use segmenter::{
+    candidate::{CandidateSource, RecordingSink},
+    dictionary::{Dictionary, InflectionType},
+    tokenizer::Tokenizer,
+};
+use std::sync::Arc;
+
+#[cfg(test)]
+lazy_static::lazy_static! {
+    static ref DICTIONARY: Arc<Dictionary> = Arc::new(fixture_dictionary());
+    static ref TOKENIZER: Tokenizer = Tokenizer::new(DICTIONARY.clone());
+}
+
+fn candidates(text: &str) -> Vec<(String, usize, usize, CandidateSource)> {
+    let mut sink = RecordingSink::default();
+    TOKENIZER.generate_candidates(text, &mut sink);
+    sink.candidates
+        .into_iter()
+        .map(|c| (c.surface, c.start, c.end, c.source))
+        .collect()
+}
+
+fn dictionary(surface: &str) -> CandidateSource {
+    let term_entry = DICTIONARY
+        .kanji
+        .get(surface)
+        .or_else(|| DICTIONARY.kana.get(surface))
+        .unwrap()[0];
+    assert_eq!(term_entry.inflection_type, InflectionType::DictionaryForm);
+    CandidateSource::Dictionary(term_entry)
+}
+
+#[test]
+fn dictionary_matches_suppress_optional_unknown_categories() {
+    assert_eq!(
+        candidates("猫を"),
+        [
+            ("猫".to_string(), 0, 1, dictionary("猫")),
+            ("を".to_string(), 1, 2, dictionary("を")),
+        ]
+    );
+}
+
+#[test]
+fn unknown_positions_try_every_category() {
+    assert_eq!(
+        candidates("ネコ"),
+        [
+            ("ネコ".to_string(), 0, 2, CandidateSource::Unknown),
+            ("コ".to_string(), 1, 2, CandidateSource::Unknown),
+        ]
+    );
+    assert_eq!(
+        candidates("鼠"),
+        [("鼠".to_string(), 0, 1, CandidateSource::Unknown)]
+    );
+}
+
+#[test]
+fn always_invoked_categories_run_next_to_dictionary_matches() {
+    assert_eq!(
+        candidates("パンダ"),
+        [
+            ("パン".to_string(), 0, 2, dictionary("パン")),
+            ("パンダ".to_string(), 0, 3, CandidateSource::Unknown),
+            ("ンダ".to_string(), 1, 3, CandidateSource::Unknown),
+            ("ダ".to_string(), 2, 3, CandidateSource::Unknown),
+        ]
+    );
+}
+
+#[test]
+fn empty_text_has_no_candidates() {
+    assert!(candidates("").is_empty());
+}
+
+#[test]
+fn debug_candidates_golden() {
+    let tokenizer = Tokenizer::new(Arc::new(fixture_dictionary()));
+    let table: String = tokenizer
+        .debug_candidates("東京都に行く", 2)
+        .iter()
+        .map(ToString::to_string)
+        .collect();
+    assert_eq!(
+        table,
+        concat!(
+            "   0  東京                  48.00  dictionary  NOUN_PROPER\n",
+            "   0  東                     6.00  dictionary  NOUN\n",
+            "   1  京都                  48.00  dictionary  NOUN_PROPER\n",
+            "   2  都                     6.00  dictionary  NOUN_SUFFIX\n",
+            "   3  に                    14.00  dictionary  PARTICLE\n",
+            "   4  行く                  48.00  dictionary  GODAN_VERB\n",
+            "   5  く                     1.00  unknown     -\n",
+        )
+    );
+}
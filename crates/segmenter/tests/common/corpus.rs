@@ -0,0 +1,103 @@
+//! Reproducible Japanese texts and a small dictionary for them, shared by the benchmarks
+//! and by tests that check performance-sensitive code on realistic input.
+#![allow(dead_code)]
+
+use segmenter::dictionary::{Dictionary, DictionaryBuilder, PartOfSpeech, Tag};
+
+/// A hundred everyday sentences, one per line.
+pub const SAMPLE: &str = include_str!("../../benches/data/sentences.txt");
+
+/// The words of [`bench_dictionary`], one per line: kanji surface (empty for kana-only
+/// words), kana surface and part of speech, separated by tabs. Lines starting with `#`
+/// are comments.
+pub const DICTIONARY_WORDS: &str = include_str!("../../benches/data/dictionary.tsv");
+
+/// Deterministic xorshift generator, so every run gets the same texts.
+pub struct Rng(pub u64);
+
+impl Rng {
+    pub fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    pub fn below(&mut self, bound: usize) -> usize {
+        (self.next() % bound as u64) as usize
+    }
+}
+
+/// The sentences of [`SAMPLE`].
+pub fn sample_sentences() -> Vec<&'static str> {
+    SAMPLE.lines().collect()
+}
+
+/// The dictionary of [`DICTIONARY_WORDS`], which knows most of the words of [`SAMPLE`].
+pub fn bench_dictionary() -> Dictionary {
+    let mut builder = DictionaryBuilder::new();
+    for line in DICTIONARY_WORDS.lines() {
+        if line.starts_with('#') || line.is_empty() {
+            continue;
+        }
+        let mut fields = line.split('\t');
+        let (Some(kanji), Some(kana), Some(pos)) =
+            (fields.next(), fields.next(), fields.next())
+        else {
+            panic!("malformed benchmark dictionary line {line:?}");
+        };
+        let pos: PartOfSpeech = pos.parse().unwrap();
+        let kanji = (!kanji.is_empty()).then_some(kanji);
+        builder.add(kanji, kana, pos, Tag::NONE);
+    }
+
+    builder.build()
+}
+
+/// Exactly `length` characters of made-up Japanese: sentences of the words of
+/// [`DICTIONARY_WORDS`] in random order, ending in 。 or 、 now and then, and cut off
+/// at the end. The same `seed` always gives the same text.
+pub fn generate_text(seed: u64, length: usize) -> String {
+    generate(seed, length, false)
+}
+
+/// [`generate_text`] with every word written in kana, like text for children or
+/// learners, where most lookups miss.
+pub fn generate_kana_text(seed: u64, length: usize) -> String {
+    generate(seed, length, true)
+}
+
+/// [`generate_text`], with the words in kana if `kana_only`.
+fn generate(seed: u64, length: usize, kana_only: bool) -> String {
+    let words: Vec<&str> = DICTIONARY_WORDS
+        .lines()
+        .filter(|line| !line.starts_with('#') && !line.is_empty())
+        .map(|line| {
+            let mut fields = line.split('\t');
+            let kanji = fields.next().unwrap_or_default();
+            let kana = fields.next().unwrap_or_default();
+            if kanji.is_empty() || kana_only {
+                kana
+            } else {
+                kanji
+            }
+        })
+        .collect();
+
+    let mut rng = Rng(seed.max(1));
+    let mut text = String::new();
+    let mut chars = 0;
+    while chars < length {
+        let piece = match rng.below(12) {
+            0 => "。",
+            1 => "、",
+            _ => words[rng.below(words.len())],
+        };
+        for c in piece.chars().take(length - chars) {
+            text.push(c);
+            chars += 1;
+        }
+    }
+
+    text
+}
@@ -0,0 +1,81 @@
+use segmenter::dictionary::{Dictionary, DictionaryBuilder, PartOfSpeech, Tag};
+use segmenter::tokenizer::Token;
+
+pub mod corpus;
+
+/// (kanji surface, kana surfaces, part of speech). The first kana surface is the
+/// highest-priority reading.
+const WORDS: &[(Option<&str>, &[&str], PartOfSpeech)] = &[
+    (Some("猫"), &["ねこ"], PartOfSpeech::NOUN),
+    (None, &["を"], PartOfSpeech::PARTICLE),
+    (Some("見た"), &["みた"], PartOfSpeech::ICHIDAN_VERB),
+    (Some("私"), &["わたし"], PartOfSpeech::PRONOUN),
+    (None, &["は"], PartOfSpeech::PARTICLE),
+    (Some("学生"), &["がくせい"], PartOfSpeech::NOUN),
+    (None, &["です"], PartOfSpeech::COPULA),
+    (Some("東京"), &["とうきょう"], PartOfSpeech::NOUN_PROPER),
+    (None, &["に"], PartOfSpeech::PARTICLE),
+    (Some("行く"), &["いく"], PartOfSpeech::GODAN_VERB),
+    (None, &["パン"], PartOfSpeech::NOUN),
+    (
+        Some("今日"),
+        &["きょう", "こんにち"],
+        PartOfSpeech::NOUN_TEMPORAL,
+    ),
+    (Some("天気"), &["てんき"], PartOfSpeech::NOUN),
+    (None, &["が"], PartOfSpeech::PARTICLE),
+    (Some("良い"), &["よい", "いい"], PartOfSpeech::ADJECTIVE),
+    (None, &["お"], PartOfSpeech::PREFIX),
+    (Some("茶"), &["ちゃ"], PartOfSpeech::NOUN),
+    (Some("飲み"), &["のみ"], PartOfSpeech::GODAN_VERB),
+    (None, &["ました"], PartOfSpeech::AUXILIARY_VERB),
+    (Some("田中"), &["たなか"], PartOfSpeech::NOUN_PROPER),
+    (None, &["さん"], PartOfSpeech::SUFFIX),
+    (None, &["の"], PartOfSpeech::PARTICLE),
+    (Some("方"), &["ほう"], PartOfSpeech::NOUN),
+    (Some("方"), &["かた"], PartOfSpeech::SUFFIX),
+    (None, &["コーヒー"], PartOfSpeech::NOUN),
+    (None, &["と"], PartOfSpeech::PARTICLE),
+    (Some("会社"), &["かいしゃ"], PartOfSpeech::NOUN),
+    (Some("働く"), &["はたらく"], PartOfSpeech::GODAN_VERB),
+    (Some("日"), &["ひ"], PartOfSpeech::NOUN),
+    (Some("日本"), &["にほん"], PartOfSpeech::NOUN_PROPER),
+    (Some("本人"), &["ほんにん"], PartOfSpeech::NOUN),
+    (Some("人"), &["ひと"], PartOfSpeech::NOUN),
+    (Some("東"), &["ひがし"], PartOfSpeech::NOUN),
+    (Some("京都"), &["きょうと"], PartOfSpeech::NOUN_PROPER),
+    (Some("都"), &["と"], PartOfSpeech::NOUN_SUFFIX),
+    (Some("手"), &["て"], PartOfSpeech::NOUN),
+    (Some("伝う"), &["つたう"], PartOfSpeech::GODAN_VERB),
+    (Some("手伝う"), &["てつだう"], PartOfSpeech::GODAN_VERB),
+    (Some("気"), &["き"], PartOfSpeech::NOUN),
+    (None, &["なる"], PartOfSpeech::GODAN_VERB),
+    (Some("気になる"), &["きになる"], PartOfSpeech::EXPRESSION),
+    (None, &["か"], PartOfSpeech::PARTICLE),
+    (None, &["も"], PartOfSpeech::PARTICLE),
+    (None, &["しれ"], PartOfSpeech::ICHIDAN_VERB),
+    (None, &["ない"], PartOfSpeech::AUXILIARY_ADJECTIVE),
+    (None, &["かもしれない"], PartOfSpeech::EXPRESSION),
+    (Some("面白い"), &["おもしろい"], PartOfSpeech::ADJECTIVE),
+    (None, &["すごい"], PartOfSpeech::ADJECTIVE),
+    (None, &["また"], PartOfSpeech::ADVERB),
+    (Some("今度"), &["こんど"], PartOfSpeech::NOUN),
+];
+
+/// Small dictionary shared by the integration tests.
+pub fn fixture_dictionary() -> Dictionary {
+    let mut builder = DictionaryBuilder::new();
+    for (kanji, kana, pos) in WORDS {
+        let term_id = builder.add(*kanji, kana[0], *pos, Tag::NONE);
+        for reading in kana[1..].iter() {
+            builder.add_reading(term_id, reading);
+        }
+    }
+
+    builder.build()
+}
+
+#[allow(dead_code)]
+pub fn surfaces<'a>(tokens: &[Token<'a>]) -> Vec<&'a str> {
+    tokens.iter().map(|token| token.token).collect()
+}
@@ -0,0 +1,110 @@
+use segmenter::dictionary::{Dictionary, DictionaryBuilder, PartOfSpeech, Tag};
+use segmenter::tokenizer::{Token, Tokenizer, TokenizerOptions};
+use std::sync::Arc;
+
+const WORDS: &[(Option<&str>, &str, PartOfSpeech)] = &[
+    (Some("雨"), "あめ", PartOfSpeech::NOUN),
+    (None, "が", PartOfSpeech::PARTICLE),
+    (None, "は", PartOfSpeech::PARTICLE),
+    (None, "ば", PartOfSpeech::PARTICLE),
+    (None, "それ", PartOfSpeech::PRONOUN),
+    (Some("早く"), "はやく", PartOfSpeech::ADVERB),
+    (Some("食べる"), "たべる", PartOfSpeech::ICHIDAN_VERB),
+    (Some("行く"), "いく", PartOfSpeech::GODAN_VERB),
+    (Some("降る"), "ふる", PartOfSpeech::GODAN_VERB),
+];
+
+/// Conditional forms pre-generated as extra surfaces of the verbs above.
+const CONDITIONALS: &[(&str, &str)] = &[
+    ("食べれば", "食べる"),
+    ("行けば", "行く"),
+    ("降れば", "降る"),
+];
+
+const SENTENCES: &[(&str, &[&str])] = &[
+    ("食べれば", &["食べれば"]),
+    ("早く行けば", &["早く", "行けば"]),
+    ("雨が降れば", &["雨", "が", "降れば"]),
+    ("それは", &["それ", "は"]),
+];
+
+fn dictionary(with_conditionals: bool) -> Dictionary {
+    let mut builder = DictionaryBuilder::new();
+    for (kanji, kana, pos) in WORDS {
+        builder.add(*kanji, kana, *pos, Tag::NONE);
+    }
+    let mut dictionary = builder.build();
+
+    if with_conditionals {
+        for (conditional, base) in CONDITIONALS {
+            let term_entry = dictionary.kanji[*base][0];
+            dictionary
+                .kanji
+                .insert(conditional.to_string(), vec![term_entry]);
+        }
+    }
+    dictionary
+}
+
+fn surfaces<'a>(tokens: &[Token<'a>]) -> Vec<&'a str> {
+    tokens.iter().map(|token| token.token).collect()
+}
+
+fn term_ids(tokens: &[Token]) -> Vec<Option<u32>> {
+    tokens.iter().map(|token| token.term_id).collect()
+}
+
+#[test]
+fn conditionals_in_dictionary() {
+    let tokenizer = Tokenizer::new(Arc::new(dictionary(true)));
+    for (text, expected) in SENTENCES {
+        let tokens = tokenizer.tokenize(text);
+        assert_eq!(surfaces(&tokens), *expected, "{text}");
+        assert!(tokens.iter().all(|token| token.term_id.is_some()), "{text}");
+    }
+}
+
+#[test]
+fn conditionals_deinflected() {
+    let expanded = Tokenizer::new(Arc::new(dictionary(true)));
+    let deinflecting = Tokenizer::with_options(
+        Arc::new(dictionary(false)),
+        TokenizerOptions {
+            deinflect: true,
+            ..Default::default()
+        },
+    );
+
+    for (text, expected) in SENTENCES {
+        let tokens = deinflecting.tokenize(text);
+        assert_eq!(surfaces(&tokens), *expected, "{text}");
+        assert_eq!(
+            term_ids(&tokens),
+            term_ids(&expanded.tokenize(text)),
+            "{text}"
+        );
+    }
+}
+
+#[test]
+fn stem_particle_penalty_keeps_path_complete() {
+    // A katakana stem can only be followed by the particle, penalized or not.
+    let dictionary = Arc::new(dictionary(false));
+    let disabled = Tokenizer::with_options(
+        dictionary.clone(),
+        TokenizerOptions {
+            stem_particle_penalty: 0.0,
+            ..Default::default()
+        },
+    );
+    let tokenizer = Tokenizer::new(dictionary);
+
+    for text in ["イケば", "ハゲは", "それは", "雨が降れば"] {
+        assert_eq!(
+            surfaces(&tokenizer.tokenize(text)),
+            surfaces(&disabled.tokenize(text)),
+            "{text}"
+        );
+    }
+    assert_eq!(surfaces(&tokenizer.tokenize("イケば")), ["イケ", "ば"]);
+}
@@ -0,0 +1,108 @@
+#[path = "common/corpus.rs"]
+mod corpus;
+
+use corpus::DICTIONARY_WORDS;
+use segmenter::candidate::RecordingSink;
+use segmenter::tokenizer::{Tokenizer, TokenizerOptions};
+use std::sync::Arc;
+
+#[test]
+fn generated_texts_are_reproducible() {
+    for length in [0, 1, 20, 200, 2000] {
+        let text = corpus::generate_text(7, length);
+        assert_eq!(text.chars().count(), length);
+        assert_eq!(text, corpus::generate_text(7, length));
+    }
+    assert_ne!(corpus::generate_text(7, 200), corpus::generate_text(8, 200));
+    assert_eq!(corpus::generate_text(0, 20).chars().count(), 20);
+}
+
+#[test]
+fn the_sample_has_a_hundred_sentences() {
+    let sentences = corpus::sample_sentences();
+    assert_eq!(sentences.len(), 100);
+    assert!(sentences.iter().all(|sentence| sentence.ends_with('。')));
+}
+
+#[test]
+fn the_bench_dictionary_knows_the_sample() {
+    let dictionary = corpus::bench_dictionary();
+    dictionary.validate().unwrap();
+    let words = DICTIONARY_WORDS
+        .lines()
+        .filter(|line| !line.starts_with('#'))
+        .count();
+    assert_eq!(dictionary.entries.len(), words);
+
+    let tokenizer = Tokenizer::new(Arc::new(dictionary));
+    let (mut known, mut total) = (0, 0);
+    for sentence in corpus::sample_sentences() {
+        let tokenization = tokenizer.tokenize_checked(sentence).unwrap();
+        assert!(tokenization.gaps.is_empty(), "{sentence}");
+        total += tokenization.tokens.len();
+        known += tokenization
+            .tokens
+            .iter()
+            .filter(|token| token.term_id.is_some())
+            .count();
+    }
+    assert!(known * 2 > total, "{known} of {total} tokens known");
+}
+
+#[test]
+fn kana_texts_have_no_kanji() {
+    let text = corpus::generate_kana_text(7, 200);
+    assert_eq!(text.chars().count(), 200);
+    assert!(text.chars().all(|c| !matches!(c as u32, 0x4E00..=0x9FFF)));
+}
+
+#[test]
+fn cached_misses_keep_the_candidates() {
+    let dictionary = Arc::new(corpus::bench_dictionary());
+    let candidates = |cache_lookup_misses, text: &str| {
+        let options = TokenizerOptions {
+            cache_lookup_misses,
+            ..TokenizerOptions::default()
+        };
+        let tokenizer = Tokenizer::with_options(dictionary.clone(), options);
+        let mut sink = RecordingSink::default();
+        tokenizer.generate_candidates(text, &mut sink);
+        sink.candidates
+    };
+
+    for text in [
+        corpus::generate_kana_text(7, 500),
+        corpus::generate_text(7, 500),
+    ] {
+        assert_eq!(candidates(true, &text), candidates(false, &text));
+    }
+}
+
+#[test]
+fn article_tokens_tile_it_every_way() {
+    let tokenizer = Tokenizer::new(Arc::new(corpus::bench_dictionary()));
+    let article = corpus::generate_text(7, 5000);
+    let tokens = tokenizer.tokenize(&article);
+
+    let (mut char_start, mut byte_start) = (0, 0);
+    for token in tokens.iter() {
+        assert_eq!(
+            (token.char_start, token.byte_start),
+            (char_start, byte_start)
+        );
+        assert_eq!(
+            &article[byte_start..byte_start + token.token.len()],
+            token.token
+        );
+        char_start += token.token.chars().count();
+        byte_start += token.token.len();
+    }
+    assert_eq!(byte_start, article.len());
+
+    let mut reversed: Vec<_> = tokenizer.tokenize_iter(&article).rev().collect();
+    reversed.reverse();
+    let checked = tokenizer.tokenize_checked(&article).unwrap().tokens;
+    for other in [reversed, checked] {
+        assert_eq!(format!("{other:?}"), format!("{tokens:?}"));
+    }
+}
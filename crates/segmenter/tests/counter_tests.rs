@@ -0,0 +1,99 @@
+use segmenter::{
+    counters::{counted_reading, is_number, number_reading},
+    dictionary::{Dictionary, DictionaryBuilder, PartOfSpeech, Tag},
+    ruby::RubyRenderer,
+    tokenizer::Tokenizer,
+};
+use std::sync::Arc;
+
+#[cfg(test)]
+lazy_static::lazy_static! {
+    static ref DICTIONARY: Arc<Dictionary> = Arc::new(counter_dictionary());
+    static ref TOKENIZER: Tokenizer = Tokenizer::new(DICTIONARY.clone());
+}
+
+/// Numerals and counters read as they are on their own, so that only the counter
+/// table gets their joined readings right.
+fn counter_dictionary() -> Dictionary {
+    let mut builder = DictionaryBuilder::new();
+    for (kanji, kana) in [("一", "いち"), ("二", "に"), ("三", "さん"), ("五", "ご")]
+    {
+        builder.add(Some(kanji), kana, PartOfSpeech::NUMERIC, Tag::NONE);
+    }
+    for (kanji, kana) in [("本", "ほん"), ("人", "にん"), ("枚", "まい")] {
+        builder.add(Some(kanji), kana, PartOfSpeech::COUNTER, Tag::NONE);
+    }
+    builder.add(Some("猫"), "ねこ", PartOfSpeech::NOUN, Tag::NONE);
+    builder.add(None, "が", PartOfSpeech::PARTICLE, Tag::NONE);
+    builder.build()
+}
+
+#[test]
+fn numbers_join_counters_with_sound_changes() {
+    let cases = [
+        ("一", "本", "いっぽん"),
+        ("三", "本", "さんぼん"),
+        ("六", "匹", "ろっぴき"),
+        ("十", "杯", "じゅっぱい"),
+        ("3", "杯", "さんばい"),
+        ("三百", "本", "さんびゃっぽん"),
+        ("八", "分", "はっぷん"),
+        ("何", "階", "なんがい"),
+        ("一", "人", "ひとり"),
+        ("二", "人", "ふたり"),
+        ("四", "人", "よにん"),
+        ("二十", "日", "はつか"),
+        ("十四", "日", "じゅうよっか"),
+        ("１２", "冊", "じゅうにさつ"),
+    ];
+    for (number, counter, reading) in cases {
+        assert_eq!(
+            counted_reading(number, counter).as_deref(),
+            Some(reading),
+            "{number}{counter}"
+        );
+    }
+}
+
+#[test]
+fn numbers_are_read_with_their_sound_changes() {
+    assert_eq!(
+        number_reading("三千八百").as_deref(),
+        Some("さんぜんはっぴゃく")
+    );
+    assert_eq!(number_reading("2万").as_deref(), Some("にまん"));
+    assert!(is_number("何"));
+    assert!(!is_number("0"));
+    assert!(!is_number("猫"));
+    assert_eq!(counted_reading("三", "枚"), None);
+}
+
+#[test]
+fn renderer_reads_numbers_with_their_counter() {
+    let renderer = RubyRenderer::new(&DICTIONARY);
+
+    let tokens = TOKENIZER.tokenize("猫が三本");
+    assert_eq!(renderer.furigana(&tokens), "猫[ねこ]が三本[さんぼん]");
+    assert_eq!(
+        renderer.html(&tokens),
+        "<ruby>猫<rt>ねこ</rt></ruby>が<ruby>三本<rt>さんぼん</rt></ruby>"
+    );
+
+    let tokens = TOKENIZER.tokenize("一人");
+    assert_eq!(renderer.furigana(&tokens), "一人[ひとり]");
+}
+
+#[test]
+fn unknown_counters_are_not_confident() {
+    let renderer = RubyRenderer::new(&DICTIONARY);
+
+    let tokens = TOKENIZER.tokenize("五枚");
+    let readings = renderer.ruby_readings(&tokens);
+    assert_eq!(readings.len(), 1);
+    assert_eq!(readings[0].tokens, 0..2);
+    assert_eq!(readings[0].reading, "ごまい");
+    assert!(!readings[0].confident);
+
+    let tokens = TOKENIZER.tokenize("二人");
+    assert!(renderer.ruby_readings(&tokens)[0].confident);
+}
@@ -0,0 +1,61 @@
+use segmenter::dictionary::{DictionaryBuilder, PartOfSpeech, Tag};
+use segmenter::tokenizer::{Token, Tokenizer, TokenizerOptions};
+use std::sync::Arc;
+
+const WORDS: &[&str] = &["パン", "メロン", "ピザ"];
+
+fn tokenizer(unknown_coverage_penalty: f32) -> Tokenizer {
+    let mut builder = DictionaryBuilder::new();
+    for kana in WORDS {
+        builder.add(None, kana, PartOfSpeech::NOUN, Tag::NONE);
+    }
+    let options = TokenizerOptions {
+        unknown_coverage_penalty,
+        ..Default::default()
+    };
+    Tokenizer::with_options(Arc::new(builder.build()), options)
+}
+
+fn surfaces<'a>(tokens: &[Token<'a>]) -> Vec<&'a str> {
+    tokens.iter().map(|token| token.token).collect()
+}
+
+#[test]
+fn fully_covered_unknown_runs_split() {
+    let default = tokenizer(TokenizerOptions::default().unknown_coverage_penalty);
+    let disabled = tokenizer(0.0);
+    for (text, expected) in [
+        ("メロンパン", &["メロン", "パン"][..]),
+        ("ピザパン", &["ピザ", "パン"]),
+    ] {
+        assert_eq!(surfaces(&default.tokenize(text)), expected, "{text}");
+        assert_eq!(surfaces(&disabled.tokenize(text)), [text], "{text}");
+    }
+}
+
+#[test]
+fn partly_covered_unknown_runs_stay_whole() {
+    let tokenizer = tokenizer(TokenizerOptions::default().unknown_coverage_penalty);
+    let tokens = tokenizer.tokenize("パンテック");
+    assert_eq!(surfaces(&tokens), ["パンテック"]);
+    assert_eq!(tokens[0].term_id, None);
+}
+
+#[test]
+fn penalty_scales_with_coverage() {
+    let score = |factor: f32, text: &str| {
+        let snapshot = tokenizer(factor).export_lattice(text);
+        let node = snapshot
+            .nodes
+            .iter()
+            .find(|node| node.surface == text)
+            .unwrap();
+        node.node.score
+    };
+
+    // パン covers two of the five characters of パンテック.
+    let full = score(0.0, "パンテック");
+    assert!((score(0.5, "パンテック") - full * (1.0 - 0.5 * 0.4)).abs() < 1e-3);
+    let full = score(0.0, "メロンパン");
+    assert!((score(0.5, "メロンパン") - full * 0.5).abs() < 1e-3);
+}
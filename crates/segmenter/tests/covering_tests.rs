@@ -0,0 +1,75 @@
+use segmenter::dictionary::{DictionaryBuilder, PartOfSpeech, Tag};
+use segmenter::tokenizer::{Tokenizer, TokenizerOptions};
+use std::sync::Arc;
+
+const WORDS: &[(&str, &str)] = &[
+    ("国際", "こくさい"),
+    ("空港", "くうこう"),
+    ("国際空港", "こくさいくうこう"),
+    ("成田", "なりた"),
+];
+
+fn tokenizer(options: TokenizerOptions) -> Tokenizer {
+    let mut builder = DictionaryBuilder::new();
+    for (kanji, kana) in WORDS {
+        builder.add(Some(kanji), kana, PartOfSpeech::NOUN, Tag::NONE);
+    }
+    builder.add(None, "に", PartOfSpeech::PARTICLE, Tag::NONE);
+    Tokenizer::with_options(Arc::new(builder.build()), options)
+}
+
+#[test]
+fn unused_compounds_are_reported() {
+    // Scores stop growing at two characters, so the compound loses to its parts.
+    let tokenizer = tokenizer(TokenizerOptions {
+        max_unknown_length: 2,
+        covering_entry_min_length: 3,
+        ..Default::default()
+    });
+    let text = "成田国際空港に";
+    let tokenization = tokenizer.tokenize_checked(text).unwrap();
+    let surfaces: Vec<&str> = tokenization
+        .tokens
+        .iter()
+        .map(|token| token.token)
+        .collect();
+    assert_eq!(surfaces, ["成田", "国際", "空港", "に"]);
+
+    assert_eq!(tokenization.covering_entries, [(6..18, 2)]);
+    let (range, _) = &tokenization.covering_entries[0];
+    assert_eq!(&text[range.clone()], "国際空港");
+}
+
+#[test]
+fn used_and_short_matches_are_not_reported() {
+    // The compound is on the path, and 国際 and 空港 lie within it.
+    let options = TokenizerOptions {
+        covering_entry_min_length: 2,
+        ..Default::default()
+    };
+    let tokenization = tokenizer(options)
+        .tokenize_checked("成田国際空港に")
+        .unwrap();
+    assert_eq!(tokenization.tokens[1].token, "国際空港");
+    assert!(tokenization.covering_entries.is_empty());
+
+    let options = TokenizerOptions {
+        max_unknown_length: 2,
+        covering_entry_min_length: 5,
+        ..Default::default()
+    };
+    let tokenization = tokenizer(options).tokenize_checked("国際空港").unwrap();
+    assert_eq!(tokenization.tokens.len(), 2);
+    assert!(tokenization.covering_entries.is_empty());
+}
+
+#[test]
+fn report_is_off_by_default() {
+    let tokenizer = tokenizer(TokenizerOptions {
+        max_unknown_length: 2,
+        ..Default::default()
+    });
+    let tokenization = tokenizer.tokenize_checked("国際空港").unwrap();
+    assert_eq!(tokenization.tokens.len(), 2);
+    assert!(tokenization.covering_entries.is_empty());
+}
@@ -0,0 +1,117 @@
+use segmenter::deinflect::{deinflect, Deinflection};
+use segmenter::dictionary::{InflectionType, PartOfSpeech};
+
+fn find<'a>(
+    results: &'a [Deinflection],
+    term: &str,
+    pos: PartOfSpeech,
+) -> &'a Deinflection {
+    results
+        .iter()
+        .find(|result| result.term == term && result.pos.intersects(pos))
+        .unwrap_or_else(|| panic!("no `{term}` in {results:?}"))
+}
+
+#[test]
+fn single_rules() {
+    for (surface, base, pos, inflection) in [
+        (
+            "食べた",
+            "食べる",
+            PartOfSpeech::ICHIDAN_VERB,
+            InflectionType::Past,
+        ),
+        (
+            "読んで",
+            "読む",
+            PartOfSpeech::GODAN_VERB,
+            InflectionType::Te,
+        ),
+        (
+            "書かない",
+            "書く",
+            PartOfSpeech::GODAN_VERB,
+            InflectionType::Negative,
+        ),
+        (
+            "高ければ",
+            "高い",
+            PartOfSpeech::ADJECTIVE,
+            InflectionType::Conditional,
+        ),
+        (
+            "行った",
+            "行く",
+            PartOfSpeech::GODAN_VERB,
+            InflectionType::Past,
+        ),
+        (
+            "勉強した",
+            "勉強する",
+            PartOfSpeech::SURU_VERB,
+            InflectionType::Past,
+        ),
+        (
+            "来ない",
+            "来る",
+            PartOfSpeech::KURU_VERB,
+            InflectionType::Negative,
+        ),
+    ] {
+        let results = deinflect(surface);
+        assert_eq!(
+            find(&results, base, pos).inflection,
+            inflection,
+            "{surface}"
+        );
+    }
+}
+
+#[test]
+fn chained_rules_combine_inflections() {
+    for (surface, base, pos, inflection) in [
+        (
+            "食べなかった",
+            "食べる",
+            PartOfSpeech::ICHIDAN_VERB,
+            InflectionType::NegativePast,
+        ),
+        (
+            "書かなくて",
+            "書く",
+            PartOfSpeech::GODAN_VERB,
+            InflectionType::NegativeTe,
+        ),
+        (
+            "高くなかった",
+            "高い",
+            PartOfSpeech::ADJECTIVE,
+            InflectionType::NegativePast,
+        ),
+        (
+            "食べられない",
+            "食べる",
+            PartOfSpeech::ICHIDAN_VERB,
+            InflectionType::NegativePotential,
+        ),
+        (
+            "書かせられる",
+            "書く",
+            PartOfSpeech::GODAN_VERB,
+            InflectionType::CausativePassive,
+        ),
+    ] {
+        let results = deinflect(surface);
+        assert_eq!(
+            find(&results, base, pos).inflection,
+            inflection,
+            "{surface}"
+        );
+    }
+}
+
+#[test]
+fn uninflected_surfaces_have_no_candidates() {
+    assert!(deinflect("猫").is_empty());
+    assert!(deinflect("").is_empty());
+}
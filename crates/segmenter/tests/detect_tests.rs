@@ -0,0 +1,241 @@
+mod common;
+
+use common::{fixture_dictionary, surfaces};
+use segmenter::detect::{self, JapaneseThresholds, SentenceRules};
+use segmenter::tokenizer::{Token, TokenKind, Tokenizer, TokenizerOptions};
+use std::sync::Arc;
+
+const ENGLISH: &str = "The quick brown fox jumps over the lazy dog. It was not amused, \
+                       and it said so at length!";
+const CHINESE: &str = "我们今天去公园散步。";
+const MIXED: &str =
+    "今日は天気が良い。This is an English sentence. 我们今天去公园散步。私は猫を見た。";
+
+fn tokenizer(thresholds: JapaneseThresholds) -> Tokenizer {
+    let options = TokenizerOptions {
+        skip_non_japanese_sentences: true,
+        japanese_thresholds: thresholds,
+        ..Default::default()
+    };
+    Tokenizer::with_options(Arc::new(fixture_dictionary()), options)
+}
+
+fn non_words<'a>(tokens: &[Token<'a>]) -> Vec<&'a str> {
+    tokens
+        .iter()
+        .filter(|token| token.kind == TokenKind::NonWord)
+        .map(|token| token.token)
+        .collect()
+}
+
+#[test]
+fn scores_tell_japanese_from_other_languages() {
+    let thresholds = JapaneseThresholds::default();
+    let score = detect::is_japanese("私は猫を見た。");
+    assert_eq!(score.letters, 6);
+    assert!(score.passes(&thresholds));
+
+    let score = detect::is_japanese(ENGLISH);
+    assert_eq!(score.kana + score.kanji, 0.0);
+    assert!(!score.passes(&thresholds));
+
+    // Chinese is all kanji, so only the missing kana gives it away.
+    let score = detect::is_japanese(CHINESE);
+    assert_eq!(score.kanji, 1.0);
+    assert!(!score.passes(&thresholds));
+
+    // Too few letters to judge.
+    assert!(detect::is_japanese("OK!").passes(&thresholds));
+    assert!(detect::is_japanese("2024。").passes(&thresholds));
+}
+
+#[test]
+fn sentences_tile_the_text() {
+    let text = "「行く？」と聞いた。Pi is 3.14. Really?! はい";
+    let sentences: Vec<&str> = detect::sentences(text)
+        .into_iter()
+        .map(|range| &text[range])
+        .collect();
+    assert_eq!(
+        sentences,
+        ["「行く？」と聞いた。", "Pi is 3.14.", " Really?!", " はい"]
+    );
+    assert_eq!(sentences.concat(), text);
+    assert!(detect::sentences("").is_empty());
+}
+
+#[test]
+fn sentences_do_not_end_inside_quotes() {
+    let text = "「猫だ。『見た！』と言った。」と聞いた。はい。「また\n今度。";
+    let sentences: Vec<&str> = detect::sentences(text)
+        .into_iter()
+        .map(|range| &text[range])
+        .collect();
+    assert_eq!(
+        sentences,
+        [
+            "「猫だ。『見た！』と言った。」と聞いた。",
+            "はい。",
+            "「また\n",
+            "今度。"
+        ]
+    );
+}
+
+/// Inputs that naive splitting gets wrong, with their sentences by the default rules.
+const TRICKY_SENTENCES: &[(&str, &[&str])] = &[
+    ("えっと……それは無理。", &["えっと……それは無理。"]),
+    ("えー……。まあいいか。", &["えー……。", "まあいいか。"]),
+    (
+        "本当に！？信じられない。",
+        &["本当に！？", "信じられない。"],
+    ),
+    ("何？？？ そうなの。", &["何？？？", " そうなの。"]),
+    (
+        "He moved to the U.S.A. last year. Then he left.",
+        &["He moved to the U.S.A. last year.", " Then he left."],
+    ),
+    (
+        "See e.g. the manual. It helps.",
+        &["See e.g. the manual.", " It helps."],
+    ),
+    (
+        "Dr. Sato met Mr. Tanaka. They talked.",
+        &["Dr. Sato met Mr. Tanaka.", " They talked."],
+    ),
+    ("Wait... what? OK.", &["Wait... what?", " OK."]),
+    (
+        "Version 3.5 is out. Download it.",
+        &["Version 3.5 is out.", " Download it."],
+    ),
+    ("Visit example.com. Now.", &["Visit example.com.", " Now."]),
+    ("1. 項目\n2. 次の項目", &["1. 項目\n", "2. 次の項目"]),
+    ("１．りんご\n２．みかん", &["１．りんご\n", "２．みかん"]),
+    ("2024年。", &["2024年。"]),
+    ("(笑)大丈夫。次。", &["(笑)大丈夫。", "次。"]),
+    ("彼は「はい。」と言った。", &["彼は「はい。」と言った。"]),
+    (
+        "（注。これは例です。）本文です。",
+        &["（注。これは例です。）本文です。"],
+    ),
+    ("「どうして？」「さあ。」", &["「どうして？」「さあ。」"]),
+    (
+        "「閉じないまま。次。\n新しい行。",
+        &["「閉じないまま。次。\n", "新しい行。"],
+    ),
+    ("ここで終わり\n次の行", &["ここで終わり\n", "次の行"]),
+    (
+        "これは例です.次の文です. 最後です.",
+        &["これは例です.次の文です.", " 最後です."],
+    ),
+];
+
+fn split<'a>(text: &'a str, rules: &SentenceRules) -> Vec<&'a str> {
+    detect::sentences_with(text, rules)
+        .into_iter()
+        .map(|range| &text[range])
+        .collect()
+}
+
+#[test]
+fn default_rules_handle_tricky_sentences() {
+    let rules = SentenceRules::default();
+    for (text, expected) in TRICKY_SENTENCES {
+        assert_eq!(split(text, &rules), *expected, "{text}");
+    }
+}
+
+#[test]
+fn sentence_rules_can_be_turned_off() {
+    let rules = SentenceRules {
+        merge_terminator_runs: false,
+        ..SentenceRules::default()
+    };
+    assert_eq!(
+        split("本当に！？信じられない。", &rules),
+        ["本当に！", "？", "信じられない。"]
+    );
+
+    let rules = SentenceRules {
+        keep_brackets_whole: false,
+        ..SentenceRules::default()
+    };
+    assert_eq!(
+        split("「行く？」と聞いた。", &rules),
+        ["「行く？」", "と聞いた。"]
+    );
+
+    let rules = SentenceRules {
+        numbered_lists: false,
+        abbreviations: Vec::new(),
+        ..SentenceRules::default()
+    };
+    assert_eq!(split("1. 項目", &rules), ["1.", " 項目"]);
+    assert_eq!(
+        split("See e.g. the manual.", &rules),
+        ["See e.g.", " the manual."]
+    );
+
+    // Only the terminators and brackets listed count.
+    let rules = SentenceRules {
+        terminators: vec!['。'],
+        brackets: vec![('【', '】')],
+        ..SentenceRules::default()
+    };
+    assert_eq!(
+        split("本当？【注。】はい。「え。」", &rules),
+        ["本当？【注。】はい。", "「え。」"]
+    );
+}
+
+#[test]
+fn non_japanese_sentences_stay_whole() {
+    let tokenizer = tokenizer(JapaneseThresholds::default());
+    let tokens = tokenizer.tokenize(MIXED);
+    assert_eq!(
+        non_words(&tokens),
+        ["This is an English sentence.", " 我们今天去公园散步。"]
+    );
+
+    let segmented: Vec<&str> = surfaces(&tokens)
+        .into_iter()
+        .filter(|surface| !non_words(&tokens).contains(surface))
+        .collect();
+    assert_eq!(
+        segmented,
+        [
+            "今日", "は", "天気", "が", "良い", "。", "私", "は", "猫", "を", "見た",
+            "。"
+        ]
+    );
+
+    // Offsets point into the whole text.
+    let mut char_start = 0;
+    for token in tokens.iter() {
+        assert_eq!(token.char_start, char_start, "{}", token.token);
+        assert_eq!(&MIXED[token.byte_start..][..token.token.len()], token.token);
+        char_start += token.token.chars().count();
+    }
+}
+
+#[test]
+fn english_paragraphs_are_one_token_per_sentence() {
+    let tokens = tokenizer(JapaneseThresholds::default()).tokenize(ENGLISH);
+    assert_eq!(tokens.len(), 2);
+    assert!(tokens.iter().all(|token| token.kind == TokenKind::NonWord));
+
+    // Without the option the lattice runs over all of it.
+    let tokenizer = Tokenizer::new(Arc::new(fixture_dictionary()));
+    assert!(non_words(&tokenizer.tokenize(ENGLISH)).is_empty());
+}
+
+#[test]
+fn thresholds_are_configurable() {
+    let tokenizer = tokenizer(JapaneseThresholds {
+        min_kana: 0.0,
+        ..Default::default()
+    });
+    let tokens = tokenizer.tokenize(CHINESE);
+    assert!(non_words(&tokens).is_empty());
+    assert!(tokens.len() > 1);
+}
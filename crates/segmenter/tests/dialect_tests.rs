@@ -0,0 +1,77 @@
+mod common;
+
+use common::fixture_dictionary;
+use segmenter::analysis::{self, OwnedToken};
+use segmenter::dictionary::{Dialect, Dictionary, PartOfSpeech, Tag};
+use segmenter::tokenizer::{Tokenizer, TokenizerOptions};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+const NAOSU: u32 = 1000;
+const NAOSU_KANSAI: u32 = 1001;
+
+/// The fixture dictionary with なおす both as standard 直す, to fix, and as the
+/// Kansai-ben word for putting something away.
+fn dictionary() -> Dictionary {
+    let mut dictionary = fixture_dictionary();
+    let pos = PartOfSpeech::GODAN_VERB;
+    dictionary.add_word(NAOSU, Some("直す"), "なおす", pos, Tag::NONE);
+    let kansai = dictionary.add_word(NAOSU_KANSAI, None, "なおす", pos, Tag::NONE);
+    dictionary.entries[kansai as usize].dialect = Dialect::KANSAI;
+    dictionary
+}
+
+fn term_id(dictionary: &Arc<Dictionary>, kansai_bonus: Option<f32>) -> Option<u32> {
+    let options = TokenizerOptions {
+        dialect_bonus: HashMap::from_iter(
+            kansai_bonus.map(|bonus| (Dialect::KANSAI, bonus)),
+        ),
+        ..TokenizerOptions::default()
+    };
+    let tokenizer = Tokenizer::with_options(dictionary.clone(), options);
+    let tokens = tokenizer.tokenize("なおす");
+    assert_eq!(tokens.len(), 1);
+    tokens[0].term_id
+}
+
+#[test]
+fn dialect_words_can_be_boosted_and_penalized() {
+    let dictionary = Arc::new(dictionary());
+
+    assert_eq!(term_id(&dictionary, None), Some(NAOSU));
+    assert_eq!(term_id(&dictionary, Some(1.0)), Some(NAOSU_KANSAI));
+    assert_eq!(term_id(&dictionary, Some(-1.0)), Some(NAOSU));
+    // Bonuses for other dialects leave Kansai-ben alone.
+    let options = TokenizerOptions {
+        dialect_bonus: HashMap::from([(Dialect::TOUHOKU | Dialect::TOSA, 10.0)]),
+        ..TokenizerOptions::default()
+    };
+    let tokenizer = Tokenizer::with_options(dictionary.clone(), options);
+    assert_eq!(tokenizer.tokenize("なおす")[0].term_id, Some(NAOSU));
+}
+
+#[test]
+fn exports_name_the_dialects() {
+    let dictionary = Arc::new(dictionary());
+    let options = TokenizerOptions {
+        dialect_bonus: HashMap::from([(Dialect::KANSAI, 1.0)]),
+        ..TokenizerOptions::default()
+    };
+    let tokenizer = Tokenizer::with_options(dictionary.clone(), options);
+    let tokens: Vec<OwnedToken> = tokenizer
+        .tokenize("猫をなおす")
+        .iter()
+        .map(|token| OwnedToken::from((token, &*dictionary)))
+        .collect();
+
+    assert_eq!(tokens[2].dialect, Dialect::KANSAI);
+    assert_eq!(
+        analysis::tsv(&tokens),
+        "猫\tNOUN\t\t\t\nを\tPARTICLE\t\t\t\nなおす\tGODAN_VERB\t\tKANSAI\t\n"
+    );
+    #[cfg(feature = "serde")]
+    {
+        let json = serde_json::to_value(&tokens[2]).unwrap();
+        assert_eq!(json["dialect"], "KANSAI");
+    }
+}
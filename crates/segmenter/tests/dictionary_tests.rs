@@ -0,0 +1,353 @@
+use segmenter::dictionary::{
+    Dictionary, DictionaryBuilder, DictionaryError, InflectionType, PartOfSpeech, Tag,
+    TermEntry,
+};
+
+#[test]
+fn builder_keeps_indices_consistent() {
+    let mut builder = DictionaryBuilder::new();
+    let neko = builder.add(Some("猫"), "ねこ", PartOfSpeech::NOUN, Tag::NONE);
+    let wo = builder.add(None, "を", PartOfSpeech::PARTICLE, Tag::NONE);
+    let kyou = builder.add(Some("今日"), "きょう", PartOfSpeech::NOUN, Tag::NONE);
+    builder.add_reading(kyou, "こんにち");
+    let dictionary = builder.build();
+
+    assert_eq!([neko, wo, kyou], [0, 1, 2]);
+    assert_eq!(dictionary.entries.len(), 3);
+    for (surface, term_id) in [("猫", neko), ("今日", kyou)] {
+        let term_entries = &dictionary.kanji[surface];
+        assert_eq!(term_entries.len(), 1);
+        let entry = &dictionary.entries[term_entries[0].entry_index as usize];
+        assert_eq!(entry.term_id, term_id);
+        assert_eq!(
+            term_entries[0].inflection_type,
+            InflectionType::DictionaryForm
+        );
+    }
+    for (surface, term_id) in [
+        ("ねこ", neko),
+        ("を", wo),
+        ("きょう", kyou),
+        ("こんにち", kyou),
+    ] {
+        let entry_index = dictionary.kana[surface][0].entry_index;
+        assert_eq!(dictionary.entries[entry_index as usize].term_id, term_id);
+    }
+    assert!(dictionary.entries[wo as usize].pos.is_particle());
+    assert_eq!(dictionary.readings_for("今日"), ["きょう", "こんにち"]);
+}
+
+#[test]
+fn common_prefix_search_finds_every_surface_a_text_starts_with() {
+    let mut builder = DictionaryBuilder::new();
+    builder.add(Some("東"), "ひがし", PartOfSpeech::NOUN, Tag::NONE);
+    builder.add(Some("東京"), "とうきょう", PartOfSpeech::NOUN, Tag::NONE);
+    builder.add(
+        Some("東京都"),
+        "とうきょうと",
+        PartOfSpeech::NOUN,
+        Tag::NONE,
+    );
+    builder.add(Some("京都"), "きょうと", PartOfSpeech::NOUN, Tag::NONE);
+    builder.add(None, "と", PartOfSpeech::PARTICLE, Tag::NONE);
+    builder.add(None, "とう", PartOfSpeech::NOUN, Tag::NONE);
+    let dictionary = builder.build();
+    let trie = dictionary.surface_trie();
+
+    let lengths: Vec<usize> = dictionary
+        .common_prefix_search(&trie, "東京都庁")
+        .map(|(length, _)| length)
+        .collect();
+    assert_eq!(lengths, [3, 6, 9]);
+
+    // The same matches as probing the maps with every prefix.
+    let text = "とうきょうと東京都と京都へ";
+    for (start, _) in text.char_indices() {
+        let rest = &text[start..];
+        let ends = rest.char_indices().skip(1).map(|(end, _)| end);
+        let probed: Vec<(usize, &[TermEntry])> = ends
+            .chain([rest.len()])
+            .flat_map(|end| {
+                let surface = &rest[..end];
+                [dictionary.kanji.get(surface), dictionary.kana.get(surface)]
+                    .into_iter()
+                    .flatten()
+                    .map(move |term_entries| (end, term_entries.as_slice()))
+            })
+            .collect();
+        let found: Vec<_> = dictionary.common_prefix_search(&trie, rest).collect();
+        assert_eq!(found, probed, "{rest}");
+    }
+
+    let empty = Dictionary::new();
+    assert_eq!(
+        empty
+            .common_prefix_search(&empty.surface_trie(), "東京")
+            .count(),
+        0
+    );
+}
+
+fn numbered_dictionary(size: usize) -> Dictionary {
+    let mut builder = DictionaryBuilder::new();
+    for i in 0..size {
+        let kanji = format!("語{i}");
+        let kana = format!("ご{i}");
+        builder.add(Some(&kanji), &kana, PartOfSpeech::NOUN, Tag::NONE);
+    }
+    builder.build()
+}
+
+#[cfg(feature = "serde")]
+fn temp_path(name: &str) -> std::path::PathBuf {
+    std::env::temp_dir().join(format!("segmenter-{}-{name}", std::process::id()))
+}
+
+#[test]
+fn memory_report_grows_with_dictionary() {
+    let small = numbered_dictionary(10).memory_report();
+    let large = numbered_dictionary(1000).memory_report();
+
+    assert!(small.entries < large.entries);
+    assert!(small.readings < large.readings);
+    assert!(small.kanji_keys < large.kanji_keys);
+    assert!(small.kana_keys < large.kana_keys);
+    assert!(small.term_entries < large.term_entries);
+    assert!(small.total() < large.total());
+    assert!(Dictionary::new().memory_report().total() <= small.total());
+}
+
+#[test]
+#[cfg(feature = "serde")]
+fn save_records_section_lengths() {
+    use segmenter::dictionary::DictionaryHeader;
+
+    let dictionary = numbered_dictionary(100);
+    let path = temp_path("sections.bin");
+    let header = dictionary.save(&path).unwrap();
+
+    let bytes = std::fs::read(&path).unwrap();
+    let header_len = 8 + 4 + 8 * DictionaryHeader::SECTION_COUNT;
+    let payload: u64 = header.section_lengths.iter().sum();
+    assert_eq!(bytes.len() as u64, header_len as u64 + payload);
+    assert_eq!(
+        DictionaryHeader::read(&mut bytes.as_slice()).unwrap(),
+        header
+    );
+
+    let loaded = Dictionary::load(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(loaded.entries.len(), 100);
+    assert_eq!(loaded.kanji["語42"], dictionary.kanji["語42"]);
+    assert_eq!(loaded.kana["ご7"], dictionary.kana["ご7"]);
+    assert_eq!(loaded.readings, dictionary.readings);
+    assert_eq!(loaded.kanji_forms, dictionary.kanji_forms);
+}
+
+#[cfg(feature = "serde")]
+fn assert_same(loaded: &Dictionary, dictionary: &Dictionary) {
+    assert_eq!(loaded.entries.len(), dictionary.entries.len());
+    assert_eq!(loaded.readings, dictionary.readings);
+    assert_eq!(loaded.kanji_forms, dictionary.kanji_forms);
+    assert_eq!(loaded.kanji, dictionary.kanji);
+    assert_eq!(loaded.kana, dictionary.kana);
+}
+
+#[test]
+#[cfg(feature = "serde")]
+fn loads_from_memory() {
+    let dictionary = numbered_dictionary(100);
+    let mut bytes = Vec::new();
+    dictionary.write_to(&mut bytes).unwrap();
+
+    let path = temp_path("memory.bin");
+    dictionary.save(&path).unwrap();
+    let saved = std::fs::read(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(saved, bytes);
+
+    assert_same(&Dictionary::from_bytes(&bytes).unwrap(), &dictionary);
+    assert_same(
+        &Dictionary::from_reader(std::io::Cursor::new(&bytes)).unwrap(),
+        &dictionary,
+    );
+}
+
+#[test]
+#[cfg(feature = "serde")]
+fn equal_dictionaries_write_identical_bytes() {
+    // Every map has its own hasher keys, so the two iterate in different orders.
+    let write = || {
+        let mut bytes = Vec::new();
+        numbered_dictionary(100).write_to(&mut bytes).unwrap();
+        bytes
+    };
+    assert_eq!(write(), write());
+}
+
+#[test]
+#[cfg(feature = "serde")]
+fn truncated_input_is_rejected() {
+    let mut bytes = Vec::new();
+    numbered_dictionary(10).write_to(&mut bytes).unwrap();
+
+    for length in [0, 5, 20, bytes.len() / 2, bytes.len() - 1] {
+        let truncated = &bytes[..length];
+        assert!(
+            matches!(
+                Dictionary::from_bytes(truncated),
+                Err(DictionaryError::Truncated)
+            ),
+            "{length}"
+        );
+        assert!(
+            matches!(
+                Dictionary::from_reader(truncated),
+                Err(DictionaryError::Truncated)
+            ),
+            "{length}"
+        );
+    }
+
+    assert!(matches!(
+        Dictionary::from_bytes(b"not a dictionary at all"),
+        Err(DictionaryError::NotADictionary)
+    ));
+}
+
+#[test]
+#[cfg(feature = "serde")]
+fn other_format_versions_are_rejected() {
+    use segmenter::dictionary::DictionaryHeader;
+
+    let bytes = numbered_dictionary(10).to_bytes().unwrap();
+    let path = temp_path("bumped.bin");
+    for version in [
+        DictionaryHeader::VERSION + 1,
+        DictionaryHeader::VERSION - 1,
+        0,
+    ] {
+        let mut bumped = bytes.clone();
+        bumped[8..12].copy_from_slice(&version.to_le_bytes());
+        std::fs::write(&path, &bumped).unwrap();
+
+        let loaded = Dictionary::load(&path);
+        assert!(
+            matches!(loaded, Err(DictionaryError::UnsupportedVersion(v)) if v == version),
+            "{version}"
+        );
+        assert!(matches!(
+            Dictionary::from_bytes(&bumped),
+            Err(DictionaryError::UnsupportedVersion(_))
+        ));
+        assert!(matches!(
+            Dictionary::from_reader(bumped.as_slice()),
+            Err(DictionaryError::UnsupportedVersion(_))
+        ));
+    }
+    std::fs::remove_file(&path).unwrap();
+
+    assert_eq!(Dictionary::from_bytes(&bytes).unwrap().entries.len(), 10);
+    assert!(matches!(
+        Dictionary::load(temp_path("missing.bin")),
+        Err(DictionaryError::Io(_))
+    ));
+}
+
+#[test]
+fn lemmas_follow_the_script_of_the_matched_surface() {
+    use segmenter::tokenizer::{Tokenizer, TokenizerOptions};
+
+    let mut builder = DictionaryBuilder::new();
+    let taberu = builder.add(
+        Some("食べる"),
+        "たべる",
+        PartOfSpeech::ICHIDAN_VERB,
+        Tag::NONE,
+    );
+    let sugoi = builder.add(None, "すごい", PartOfSpeech::ADJECTIVE, Tag::NONE);
+    let dictionary = std::sync::Arc::new(builder.build());
+    let options = TokenizerOptions {
+        deinflect: true,
+        ..Default::default()
+    };
+    let tokenizer = Tokenizer::with_options(dictionary.clone(), options);
+
+    for (text, lemma) in [
+        ("食べた", "食べる"),
+        ("たべた", "たべる"),
+        ("食べる", "食べる"),
+        ("すごかった", "すごい"),
+    ] {
+        let tokens = tokenizer.tokenize(text);
+        assert_eq!(tokens.len(), 1, "{text}");
+        let term_id = tokens[0].term_id.unwrap();
+        assert_eq!(
+            dictionary.lemma_matching_script(term_id, tokens[0].token),
+            Some(lemma)
+        );
+    }
+
+    // Entries without a form in the sample's script fall back to the other one.
+    assert_eq!(
+        dictionary.lemma_matching_script(sugoi, "凄い"),
+        Some("すごい")
+    );
+    assert_eq!(
+        dictionary.lemma_matching_script(taberu, "タベル"),
+        Some("たべる")
+    );
+    assert_eq!(dictionary.lemma_matching_script(99, "食べた"), None);
+
+    let mut dictionary = Dictionary::new();
+    dictionary.add_word(7, Some("煙草"), "たばこ", PartOfSpeech::NOUN, Tag::NONE);
+    dictionary.readings[0].clear();
+    assert_eq!(dictionary.lemma_matching_script(7, "たばこ"), Some("煙草"));
+}
+
+#[test]
+fn kanji_forms_must_match_the_entries() {
+    let mut dictionary = Dictionary::new();
+    dictionary.add_word(0, Some("猫"), "ねこ", PartOfSpeech::NOUN, Tag::NONE);
+    dictionary.add_word(1, None, "を", PartOfSpeech::PARTICLE, Tag::NONE);
+    assert_eq!(dictionary.kanji_forms, [vec!["猫".to_string()], vec![]]);
+
+    dictionary.kanji_forms.pop();
+    assert!(matches!(
+        dictionary.validate(),
+        Err(DictionaryError::Invalid(_))
+    ));
+}
+
+#[test]
+fn debug_output_summarizes_counts() {
+    let dictionary = numbered_dictionary(100);
+    let debug = format!("{dictionary:?}");
+    assert_eq!(
+        debug,
+        "Dictionary { entries: 100, readings: 100, kanji_forms: 100, kanji: 100, kana: 100 }"
+    );
+    assert_eq!(
+        format!("{:?}", Dictionary::default()),
+        format!("{:?}", Dictionary::new())
+    );
+}
+
+#[test]
+fn shrink_to_fit_keeps_lookups() {
+    let mut dictionary = numbered_dictionary(1000);
+    let before = dictionary.memory_report();
+    let metadata = dictionary.metadata();
+    let lookups: Vec<_> = ["語42", "ご7", "語999"]
+        .iter()
+        .map(|surface| dictionary.lookup_detailed(surface))
+        .collect();
+
+    dictionary.shrink_to_fit();
+    assert!(dictionary.memory_report().total() <= before.total());
+    assert_eq!(dictionary.metadata(), metadata);
+    for (surface, hits) in ["語42", "ご7", "語999"].iter().zip(lookups) {
+        assert_eq!(dictionary.lookup_detailed(surface), hits);
+    }
+    assert_eq!(dictionary.kanji["語42"].capacity(), 1);
+}
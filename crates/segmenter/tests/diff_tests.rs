@@ -0,0 +1,108 @@
+mod common;
+
+use common::fixture_dictionary;
+use segmenter::dictionary::{DictionaryBuilder, DictionaryId, PartOfSpeech, Tag};
+use segmenter::diff::{self, ChangePattern};
+use segmenter::tokenizer::{Tokenizer, TokenizerOptions};
+use std::sync::Arc;
+
+const CORPUS: &[&str] = &[
+    "東京都に行く",
+    "私は学生です",
+    "東京都の会社で働く",
+    "お茶を飲みました",
+];
+
+/// A tokenizer of the fixture dictionary, and one knowing 東京都 and お茶 too.
+fn tokenizers() -> (Tokenizer, Tokenizer) {
+    let mut builder = DictionaryBuilder::new();
+    builder.add(
+        Some("東京都"),
+        "とうきょうと",
+        PartOfSpeech::NOUN_PROPER,
+        Tag::NONE,
+    );
+    builder.add(Some("お茶"), "おちゃ", PartOfSpeech::NOUN, Tag::NONE);
+    let dictionaries = vec![
+        (DictionaryId::MAIN, Arc::new(fixture_dictionary())),
+        (DictionaryId(1), Arc::new(builder.build())),
+    ];
+    let b = Tokenizer::with_dictionaries(dictionaries, TokenizerOptions::default());
+    (Tokenizer::new(fixture_dictionary().into()), b)
+}
+
+fn strings(surfaces: &[&str]) -> Vec<String> {
+    surfaces.iter().map(|surface| surface.to_string()).collect()
+}
+
+#[test]
+fn identical_tokenizers_agree_everywhere() {
+    let (a, _) = tokenizers();
+    let report = diff::compare(&a, &a, CORPUS);
+
+    assert_eq!(report.texts, CORPUS.len());
+    assert!(report.changed.is_empty());
+    assert!(report.patterns.is_empty());
+    assert_eq!((report.precision, report.recall), (1.0, 1.0));
+}
+
+#[test]
+fn changed_texts_and_patterns_are_reported() {
+    let (a, b) = tokenizers();
+    let report = diff::compare(&a, &b, CORPUS);
+
+    let changed: Vec<&str> = report.changed.iter().map(|text| &*text.text).collect();
+    assert_eq!(
+        changed,
+        ["東京都に行く", "東京都の会社で働く", "お茶を飲みました"]
+    );
+    assert_eq!(
+        report.changed[0].before,
+        strings(&["東京", "都", "に", "行く"])
+    );
+    assert_eq!(report.changed[0].after, strings(&["東京都", "に", "行く"]));
+    assert_eq!(
+        report.patterns,
+        [
+            ChangePattern {
+                before: strings(&["東京", "都"]),
+                after: strings(&["東京都"]),
+                count: 2,
+            },
+            ChangePattern {
+                before: strings(&["お", "茶"]),
+                after: strings(&["お茶"]),
+                count: 1,
+            },
+        ]
+    );
+    assert_eq!(report.top_patterns(1), &report.patterns[..1]);
+    assert_eq!(report.top_patterns(10).len(), 2);
+}
+
+#[test]
+fn boundary_scores_take_the_first_tokenizer_as_reference() {
+    let (a, b) = tokenizers();
+    let report = diff::compare(&a, &b, CORPUS);
+
+    // The first tokenizer draws 15 boundaries, 3 of them inside 東京都 and お茶, and
+    // the second draws only the 12 others.
+    assert_eq!(report.precision, 1.0);
+    assert_eq!(report.recall, 12.0 / 15.0);
+
+    let reversed = diff::compare(&b, &a, CORPUS);
+    assert_eq!((reversed.precision, reversed.recall), (12.0 / 15.0, 1.0));
+}
+
+#[test]
+#[cfg(feature = "serde")]
+fn reports_round_trip_through_json() {
+    let (a, b) = tokenizers();
+    let report = diff::compare(&a, &b, CORPUS.iter().map(|text| text.to_string()));
+
+    let json = serde_json::to_string(&report).unwrap();
+    assert_eq!(
+        serde_json::from_str::<diff::DiffReport>(&json).unwrap(),
+        report
+    );
+}
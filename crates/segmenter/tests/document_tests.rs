@@ -0,0 +1,111 @@
+mod common;
+
+use common::{fixture_dictionary, surfaces};
+use segmenter::detect;
+use segmenter::tokenizer::{DocumentTokens, Token, Tokenizer};
+use std::sync::Arc;
+
+/// Three paragraphs, the first with quoted speech holding two sentences of its own and
+/// a second line, the last after two blank lines and some indentation.
+const DOCUMENT: &str = "私は学生です。「猫を見た。パンを見た。」と田中さんは言った。\n\
+                        今日は天気が良い。\n\
+                        \n\
+                        東京に行く。\n\
+                        \n\
+                        \u{3000}\n\
+                        \u{3000}また今度。\n";
+
+/// The text each sentence of `document` covers.
+fn sentence_texts<'a>(document: &DocumentTokens<'a>) -> Vec<&'a str> {
+    (0..document.sentences.len())
+        .map(|index| {
+            let tokens = document.sentence(index);
+            let (first, last) = (&tokens[0].token, &tokens[tokens.len() - 1].token);
+            &DOCUMENT[first.byte_start..last.byte_start + last.token.len()]
+        })
+        .collect()
+}
+
+#[test]
+fn paragraphs_are_separated_by_blank_lines() {
+    let paragraphs: Vec<&str> = detect::paragraphs(DOCUMENT)
+        .into_iter()
+        .map(|range| &DOCUMENT[range])
+        .collect();
+    assert_eq!(
+        paragraphs,
+        [
+            "私は学生です。「猫を見た。パンを見た。」と田中さんは言った。\n今日は天気が良い。",
+            "東京に行く。",
+            "\u{3000}また今度。"
+        ]
+    );
+    assert!(detect::paragraphs("\n \n").is_empty());
+}
+
+#[test]
+fn tokens_know_their_sentence_and_paragraph() {
+    let tokenizer = Tokenizer::new(Arc::new(fixture_dictionary()));
+    let document = tokenizer.tokenize_document(DOCUMENT);
+
+    assert_eq!(
+        sentence_texts(&document),
+        [
+            "私は学生です。",
+            "「猫を見た。パンを見た。」と田中さんは言った。",
+            "\n今日は天気が良い。",
+            "東京に行く。",
+            "\u{3000}また今度。"
+        ]
+    );
+    assert_eq!(document.paragraphs.len(), 3);
+    assert_eq!(document.paragraphs[0].start, document.sentences[0].start);
+    assert_eq!(document.paragraphs[0].end, document.sentences[2].end);
+    assert_eq!(document.paragraphs[1], document.sentences[3]);
+    assert_eq!(document.paragraphs[2], document.sentences[4]);
+
+    let paragraph_of_sentence = [0, 0, 0, 1, 2];
+    for (sentence_index, range) in document.sentences.iter().enumerate() {
+        for token in &document.tokens[range.clone()] {
+            assert_eq!(token.sentence_index, sentence_index);
+            assert_eq!(token.paragraph_index, paragraph_of_sentence[sentence_index]);
+        }
+    }
+}
+
+#[test]
+fn offsets_are_in_the_document() {
+    let tokenizer = Tokenizer::new(Arc::new(fixture_dictionary()));
+    let document = tokenizer.tokenize_document(DOCUMENT);
+
+    for token in document.tokens.iter().map(|token| &token.token) {
+        assert_eq!(
+            &DOCUMENT[token.byte_start..token.byte_start + token.token.len()],
+            token.token
+        );
+        assert_eq!(
+            DOCUMENT[..token.byte_start].chars().count(),
+            token.char_start
+        );
+    }
+
+    // Each sentence is tokenized on its own.
+    let quoted = "「猫を見た。パンを見た。」と田中さんは言った。";
+    let tokens: Vec<Token> = document
+        .sentence(1)
+        .iter()
+        .map(|token| token.token.clone())
+        .collect();
+    assert_eq!(surfaces(&tokens), surfaces(&tokenizer.tokenize(quoted)));
+}
+
+#[test]
+fn empty_documents_have_no_sentences() {
+    let tokenizer = Tokenizer::new(Arc::new(fixture_dictionary()));
+    for text in ["", "\n\n", " \n\u{3000}"] {
+        let document = tokenizer.tokenize_document(text);
+        assert!(document.tokens.is_empty());
+        assert!(document.sentences.is_empty());
+        assert!(document.paragraphs.is_empty());
+    }
+}
@@ -0,0 +1,116 @@
+mod common;
+
+use common::{fixture_dictionary, surfaces};
+use segmenter::candidate::RecordingSink;
+use segmenter::dictionary::{
+    Dictionary, DictionaryBuilder, DictionaryId, PartOfSpeech, Tag,
+};
+use segmenter::pipeline::Segmenter;
+use segmenter::tokenizer::{Token, Tokenizer, TokenizerOptions};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+const MEDICAL: DictionaryId = DictionaryId(1);
+
+const TEXT: &str = "学生が心筋梗塞で入院した";
+
+/// A medical dictionary knowing 心筋梗塞, and 学生 too, marked rare to tell it apart.
+fn medical_dictionary() -> Dictionary {
+    let mut builder = DictionaryBuilder::new();
+    builder.add(
+        Some("心筋梗塞"),
+        "しんきんこうそく",
+        PartOfSpeech::NOUN,
+        Tag::NONE,
+    );
+    builder.add(Some("学生"), "がくせい", PartOfSpeech::NOUN, Tag::RARE);
+    builder.build()
+}
+
+fn tokenizer(options: TokenizerOptions) -> Tokenizer {
+    let dictionaries = vec![
+        (DictionaryId::MAIN, Arc::new(fixture_dictionary())),
+        (MEDICAL, Arc::new(medical_dictionary())),
+    ];
+    Tokenizer::with_dictionaries(dictionaries, options)
+}
+
+fn find<'a>(tokens: &'a [Token], surface: &str) -> &'a Token<'a> {
+    tokens.iter().find(|token| token.token == surface).unwrap()
+}
+
+#[test]
+fn domain_words_report_their_dictionary() {
+    let general = Tokenizer::new(Arc::new(fixture_dictionary()));
+    assert!(!surfaces(&general.tokenize(TEXT)).contains(&"心筋梗塞"));
+
+    let tokenizer = tokenizer(TokenizerOptions::default());
+    let tokens = tokenizer.tokenize(TEXT);
+    assert_eq!(surfaces(&tokens)[..4], ["学生", "が", "心筋梗塞", "で"]);
+
+    let infarction = find(&tokens, "心筋梗塞");
+    assert_eq!(infarction.dictionary_id(), Some(MEDICAL));
+    // Term ids only mean something together with the dictionary.
+    let student = find(&tokens, "学生");
+    assert_eq!(student.dictionary_id(), Some(DictionaryId::MAIN));
+    assert_eq!(infarction.term_id, Some(0));
+    assert_eq!(
+        find(&tokens, "が").dictionary_id(),
+        Some(DictionaryId::MAIN)
+    );
+    assert_eq!(find(&tokens, "入").dictionary_id(), None);
+
+    assert_eq!(tokenizer.dictionaries().len(), 2);
+    assert_eq!(
+        tokenizer.dictionary().kanji.len(),
+        fixture_dictionary().kanji.len()
+    );
+}
+
+#[test]
+fn dictionary_bonus_prefers_a_domain() {
+    // Equal matches go to the dictionary listed first.
+    let tokens = tokenizer(TokenizerOptions::default()).tokenize(TEXT);
+    assert_eq!(
+        find(&tokens, "学生").dictionary_id(),
+        Some(DictionaryId::MAIN)
+    );
+
+    let options = TokenizerOptions {
+        dictionary_bonus: HashMap::from([(MEDICAL, 1.0)]),
+        ..TokenizerOptions::default()
+    };
+    let tokens = tokenizer(options.clone()).tokenize(TEXT);
+    assert_eq!(find(&tokens, "学生").dictionary_id(), Some(MEDICAL));
+
+    // Owned tokens take their tags from the dictionary each token came from.
+    let segmenter = Segmenter::new(Arc::new(tokenizer(options)), 2);
+    let (_, tokens) = segmenter.process([TEXT.to_string()]).next().unwrap();
+    assert_eq!(tokens[0].surface, "学生");
+    assert_eq!(tokens[0].tag, Tag::RARE);
+}
+
+#[test]
+fn swapping_keeps_the_domain_dictionaries() {
+    let tokenizer = tokenizer(TokenizerOptions::default());
+    tokenizer.swap_dictionary(Arc::new(Dictionary::new()));
+    let tokens = tokenizer.tokenize(TEXT);
+    assert_eq!(find(&tokens, "学生").dictionary_id(), Some(MEDICAL));
+    assert_eq!(find(&tokens, "心筋梗塞").dictionary_id(), Some(MEDICAL));
+}
+
+#[test]
+fn trie_matches_are_the_lookups_of_every_span() {
+    let candidates = |options: TokenizerOptions| {
+        let mut sink = RecordingSink::default();
+        tokenizer(options).generate_candidates(TEXT, &mut sink);
+        sink.candidates
+    };
+
+    // Folding has nothing to respell in the text, but makes every span be looked up.
+    let probed = candidates(TokenizerOptions {
+        fold_historical_kana: true,
+        ..TokenizerOptions::default()
+    });
+    assert_eq!(candidates(TokenizerOptions::default()), probed);
+}
@@ -0,0 +1,85 @@
+mod common;
+
+use common::fixture_dictionary;
+use segmenter::analysis::{self, OwnedToken};
+use segmenter::dictionary::{Dictionary, Domain, PartOfSpeech, Tag};
+use segmenter::tokenizer::{Tokenizer, TokenizerOptions};
+use std::sync::Arc;
+
+const MOUSE: u32 = 1000;
+const COMPUTER_MOUSE: u32 = 1001;
+
+/// The fixture dictionary with マウス both as the animal and as the pointing device of
+/// computing.
+fn dictionary() -> Dictionary {
+    let mut dictionary = fixture_dictionary();
+    dictionary.add_word(MOUSE, None, "マウス", PartOfSpeech::NOUN, Tag::NONE);
+    let computing = dictionary.add_word(
+        COMPUTER_MOUSE,
+        None,
+        "マウス",
+        PartOfSpeech::NOUN,
+        Tag::NONE,
+    );
+    dictionary.entries[computing as usize].domain = Domain::COMPUTING;
+    dictionary
+}
+
+fn term_id(
+    dictionary: &Arc<Dictionary>,
+    domain_boost: Option<(Domain, f32)>,
+) -> Option<u32> {
+    let options = TokenizerOptions {
+        domain_boost,
+        ..TokenizerOptions::default()
+    };
+    let tokenizer = Tokenizer::with_options(dictionary.clone(), options);
+    let tokens = tokenizer.tokenize("マウス");
+    assert_eq!(tokens.len(), 1);
+    tokens[0].term_id
+}
+
+#[test]
+fn domain_words_win_only_when_boosted() {
+    let dictionary = Arc::new(dictionary());
+
+    assert_eq!(term_id(&dictionary, None), Some(MOUSE));
+    assert_eq!(
+        term_id(&dictionary, Some((Domain::COMPUTING, 1.0))),
+        Some(COMPUTER_MOUSE)
+    );
+    assert_eq!(
+        term_id(
+            &dictionary,
+            Some((Domain::COMPUTING | Domain::MEDICINE, 1.0))
+        ),
+        Some(COMPUTER_MOUSE)
+    );
+    assert_eq!(
+        term_id(&dictionary, Some((Domain::MEDICINE, 1.0))),
+        Some(MOUSE)
+    );
+}
+
+#[test]
+fn exports_name_the_domains() {
+    let dictionary = Arc::new(dictionary());
+    let options = TokenizerOptions {
+        domain_boost: Some((Domain::COMPUTING, 1.0)),
+        ..TokenizerOptions::default()
+    };
+    let tokenizer = Tokenizer::with_options(dictionary.clone(), options);
+    let tokens: Vec<OwnedToken> = tokenizer
+        .tokenize("マウス")
+        .iter()
+        .map(|token| OwnedToken::from((token, &*dictionary)))
+        .collect();
+
+    assert_eq!(tokens[0].domain, Domain::COMPUTING);
+    assert_eq!(analysis::tsv(&tokens), "マウス\tNOUN\t\t\tCOMPUTING\n");
+    #[cfg(feature = "serde")]
+    {
+        let json = serde_json::to_value(&tokens[0]).unwrap();
+        assert_eq!(json["domain"], "COMPUTING");
+    }
+}
@@ -0,0 +1,121 @@
+use segmenter::dictionary::{Dictionary, PartOfSpeech, Tag};
+use segmenter::tokenizer::{Token, Tokenizer};
+use std::sync::Arc;
+
+lazy_static::lazy_static! {
+    static ref TOKENIZER: Tokenizer = Tokenizer::new(Arc::new(Dictionary::new()));
+}
+
+const TEXTS: &[&str] = &[
+    "",
+    "a",
+    "さくら",
+    "私は学生です。",
+    "AI技術とスマートフォン123",
+    "  猫  ",
+    "ｗｗｗ!!",
+    "きゃっと",
+    "ャーナル",
+    "ドキっとした",
+    "日本語のテキスト、です！",
+    "🐈‍⬛と\u{301}e\u{0}\t\n",
+    "ΑΒΓとАБВ",
+];
+
+fn surfaces<'a>(tokens: &[Token<'a>]) -> Vec<&'a str> {
+    tokens.iter().map(|token| token.token).collect()
+}
+
+#[test]
+fn runs_of_one_category_are_single_tokens() {
+    for (text, expected) in [
+        ("さくら", &["さくら"][..]),
+        ("私は学生です。", &["私", "は", "学生", "です", "。"]),
+        (
+            "AI技術とスマートフォン123",
+            &["AI", "技術", "と", "スマートフォン", "123"],
+        ),
+        (
+            "日本語のテキスト、です！",
+            &["日本語", "の", "テキスト", "、", "です", "！"],
+        ),
+        ("  猫  ", &["  ", "猫", "  "]),
+        // Small kana and sokuon still stay with their kana.
+        ("ドキっとした", &["ドキっと", "した"]),
+    ] {
+        assert_eq!(surfaces(&TOKENIZER.tokenize(text)), expected, "{text}");
+        assert_eq!(
+            surfaces(&TOKENIZER.tokenize_greedy(text)),
+            expected,
+            "{text}"
+        );
+    }
+}
+
+#[test]
+fn output_covers_the_whole_input() {
+    for text in TEXTS {
+        for tokens in [TOKENIZER.tokenize(text), TOKENIZER.tokenize_greedy(text)] {
+            assert_eq!(surfaces(&tokens).concat(), *text);
+
+            let mut char_start = 0;
+            let mut byte_start = 0;
+            for token in tokens.iter() {
+                assert!(!token.token.is_empty(), "{text}");
+                assert_eq!(token.term_id, None, "{text}");
+                assert_eq!(
+                    (token.char_start, token.byte_start),
+                    (char_start, byte_start)
+                );
+                char_start += token.token.chars().count();
+                byte_start += token.token.len();
+            }
+        }
+    }
+}
+
+#[test]
+fn lattice_and_greedy_agree() {
+    for text in TEXTS {
+        assert_eq!(
+            surfaces(&TOKENIZER.tokenize(text)),
+            surfaces(&TOKENIZER.tokenize_greedy(text)),
+            "{text}"
+        );
+    }
+}
+
+#[test]
+fn other_entry_points_handle_an_empty_dictionary() {
+    for text in TEXTS {
+        let tokens = TOKENIZER.tokenize(text);
+
+        let mut offset = 0;
+        let mut offsets = Vec::new();
+        for token in tokens.iter().take(tokens.len().saturating_sub(1)) {
+            offset += token.token.len();
+            offsets.push(offset);
+        }
+        let boundaries = TOKENIZER.boundaries(text);
+        assert!(boundaries.iter().all(|boundary| offsets.contains(boundary)));
+
+        let granular = TOKENIZER.tokenize_granular(text);
+        assert_eq!(granular.len(), tokens.len());
+        assert!(granular.iter().all(|token| token.fine.len() == 1));
+
+        let snapshot = TOKENIZER.export_lattice(text);
+        assert_eq!(snapshot.replay(), snapshot.path);
+        assert_eq!(
+            TOKENIZER.debug_candidates(text, 3).len(),
+            text.chars().count()
+        );
+    }
+}
+
+#[test]
+fn kanji_are_not_grouped_once_the_dictionary_has_words() {
+    let mut dictionary = Dictionary::new();
+    dictionary.add_word(1, None, "は", PartOfSpeech::PARTICLE, Tag::NONE);
+    let tokenizer = Tokenizer::new(Arc::new(dictionary));
+    assert_eq!(surfaces(&tokenizer.tokenize("学生は")), ["学", "生", "は"]);
+}
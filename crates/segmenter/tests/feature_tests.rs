@@ -0,0 +1,91 @@
+//! Builds with and without the default `serde` feature: run it with
+//! `--no-default-features` too.
+
+mod common;
+
+use common::fixture_dictionary;
+#[cfg(feature = "serde")]
+use common::surfaces;
+use segmenter::prelude::*;
+use std::sync::Arc;
+
+#[test]
+fn the_prelude_is_enough_to_tokenize() {
+    let mut builder = DictionaryBuilder::new();
+    let term_id: TermId = builder.add(Some("猫"), "ねこ", PartOfSpeech::NOUN, Tag::NONE);
+    let dictionary: Dictionary = builder.build();
+    let tokenizer =
+        Tokenizer::with_options(Arc::new(dictionary), TokenizerOptions::default());
+
+    let tokens: Vec<Token> = tokenizer.tokenize("猫");
+    assert_eq!(tokens[0].term_id, Some(term_id));
+    assert_eq!(tokens[0].kind, TokenKind::Word);
+    let term_entry: TermEntry = tokens[0].term_entry.unwrap();
+    assert_eq!(term_entry.inflection_type, InflectionType::DictionaryForm);
+
+    let validated: Result<(), DictionaryError> = fixture_dictionary().validate();
+    assert!(validated.is_ok());
+    let checked: Result<_, SegmenterError> = tokenizer.self_test();
+    assert!(checked.is_err());
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn tokens_round_trip_through_serde_as_owned_tokens() {
+    use segmenter::analysis::OwnedToken;
+
+    let dictionary = Arc::new(fixture_dictionary());
+    let tokenizer = Tokenizer::new(dictionary.clone());
+    // Surfaces JSON has to escape, like newlines and quotes.
+    let text = "私は「猫を見た。」と言った。\n\n東京に\"行く\"。";
+    let tokens = tokenizer.tokenize(text);
+
+    let json = serde_json::to_string(&tokens).unwrap();
+    let surfaces_in_json: Vec<String> =
+        serde_json::from_str::<Vec<serde_json::Value>>(&json)
+            .unwrap()
+            .iter()
+            .map(|token| token["token"].as_str().unwrap().to_string())
+            .collect();
+    assert_eq!(surfaces_in_json, surfaces(&tokens));
+
+    let owned: Vec<OwnedToken> = tokens
+        .iter()
+        .map(|token| OwnedToken::from((token, dictionary.as_ref())))
+        .collect();
+    let json = serde_json::to_string(&owned).unwrap();
+    let restored: Vec<OwnedToken> = serde_json::from_str(&json).unwrap();
+    assert_eq!(restored, owned);
+    assert!(restored.iter().any(|token| token.surface.contains('\n')));
+
+    let document = tokenizer.tokenize_document(text);
+    let json: serde_json::Value = serde_json::to_value(&document).unwrap();
+    assert_eq!(
+        json["tokens"].as_array().unwrap().len(),
+        document.tokens.len()
+    );
+    assert_eq!(
+        json["sentences"].as_array().unwrap().len(),
+        document.sentences.len()
+    );
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn options_deserialize_from_partial_configs() {
+    let options: TokenizerOptions =
+        serde_json::from_str(r#"{ "window": 256, "deinflect": true }"#).unwrap();
+    assert_eq!(options.window, Some(256));
+    assert!(options.deinflect);
+    assert_eq!(
+        options.max_unknown_length,
+        TokenizerOptions::default().max_unknown_length
+    );
+
+    let json = serde_json::to_string(&TokenizerOptions::search()).unwrap();
+    let restored: TokenizerOptions = serde_json::from_str(&json).unwrap();
+    assert_eq!(
+        format!("{restored:?}"),
+        format!("{:?}", TokenizerOptions::search())
+    );
+}
@@ -0,0 +1,47 @@
+use segmenter::dictionary::{PartOfSpeech, Tag, UnknownFlagName};
+
+#[test]
+fn part_of_speech_names_cover_every_bit() {
+    assert_eq!(PartOfSpeech::ALL_NAMED.len(), 32);
+    let mut all = PartOfSpeech::NONE;
+    for (flag, name) in PartOfSpeech::ALL_NAMED {
+        assert_eq!(flag.bits().count_ones(), 1, "{name}");
+        assert_eq!(flag.names(), [*name]);
+        assert_eq!(name.parse::<PartOfSpeech>(), Ok(*flag));
+        all |= *flag;
+    }
+    assert_eq!(all, PartOfSpeech::all());
+    assert_eq!(
+        all.names().join("|").parse::<PartOfSpeech>(),
+        Ok(PartOfSpeech::all())
+    );
+}
+
+#[test]
+fn tag_names_cover_every_bit() {
+    assert_eq!(Tag::ALL_NAMED.len(), 13);
+    let mut all = Tag::NONE;
+    for (flag, name) in Tag::ALL_NAMED {
+        assert_eq!(flag.bits().count_ones(), 1, "{name}");
+        assert_eq!(flag.names(), [*name]);
+        assert_eq!(name.parse::<Tag>(), Ok(*flag));
+        all |= *flag;
+    }
+    assert_eq!(all, Tag::all());
+    assert_eq!(all.names().join("|").parse::<Tag>(), Ok(Tag::all()));
+}
+
+#[test]
+fn pipe_separated_names() {
+    let flags = PartOfSpeech::NOUN | PartOfSpeech::SURU_VERB;
+    assert_eq!(flags.names(), ["NOUN", "SURU_VERB"]);
+    assert_eq!("NOUN|SURU_VERB".parse(), Ok(flags));
+    assert_eq!(" SURU_VERB | NOUN ".parse(), Ok(flags));
+    assert_eq!("".parse(), Ok(PartOfSpeech::NONE));
+    assert!(PartOfSpeech::NONE.names().is_empty());
+    assert_eq!(
+        "NOUN|VERB".parse::<PartOfSpeech>(),
+        Err(UnknownFlagName("VERB".to_string()))
+    );
+    assert_eq!("RARE|ARCHAIC".parse(), Ok(Tag::RARE | Tag::ARCHAIC));
+}
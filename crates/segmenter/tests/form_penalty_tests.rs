@@ -0,0 +1,86 @@
+mod common;
+
+use common::{fixture_dictionary, surfaces};
+use segmenter::dictionary::{Dictionary, PartOfSpeech, Tag};
+use segmenter::tokenizer::{Tokenizer, TokenizerOptions};
+use std::sync::Arc;
+
+const NIOI: u32 = 1000;
+const KUSAI: u32 = 1001;
+const TSUBUYAKU: u32 = 1002;
+
+/// The fixture dictionary with 臭い both as the standard spelling of くさい and as a
+/// rarely used one of におい, added first, and 呟く known only by a rare spelling.
+fn dictionary() -> Dictionary {
+    let mut dictionary = fixture_dictionary();
+    let nioi =
+        dictionary.add_word(NIOI, Some("匂い"), "におい", PartOfSpeech::NOUN, Tag::NONE);
+    dictionary.add_kanji_form(nioi, "臭い", 2);
+    dictionary.add_word(
+        KUSAI,
+        Some("臭い"),
+        "くさい",
+        PartOfSpeech::ADJECTIVE,
+        Tag::NONE,
+    );
+    let tsubuyaku = dictionary.add_word(
+        TSUBUYAKU,
+        None,
+        "つぶやく",
+        PartOfSpeech::GODAN_VERB,
+        Tag::NONE,
+    );
+    dictionary.add_kanji_form(tsubuyaku, "呟く", 2);
+    dictionary
+}
+
+fn term_ids(tokenizer: &Tokenizer, text: &str) -> Vec<Option<u32>> {
+    tokenizer
+        .tokenize(text)
+        .iter()
+        .map(|token| token.term_id)
+        .collect()
+}
+
+#[test]
+fn rare_kanji_forms_are_indexed_with_their_penalty() {
+    let dictionary = dictionary();
+    let penalties: Vec<u8> = dictionary.kanji["臭い"]
+        .iter()
+        .map(|term_entry| term_entry.form_penalty)
+        .collect();
+    // The standard spelling comes first whatever the order the words were added in.
+    assert_eq!(penalties, [0, 2]);
+    let nioi = dictionary.kanji["匂い"][0].entry_index as usize;
+    assert_eq!(dictionary.kanji_forms[nioi], ["匂い", "臭い"]);
+}
+
+#[test]
+fn standard_spellings_win_over_rare_ones() {
+    let tokenizer = Tokenizer::new(Arc::new(dictionary()));
+    assert_eq!(term_ids(&tokenizer, "臭い"), [Some(KUSAI)]);
+    assert_eq!(
+        term_ids(&tokenizer, "猫が臭い"),
+        [Some(0), Some(13), Some(KUSAI)]
+    );
+
+    let options = TokenizerOptions {
+        irregular_form_penalty: 0.0,
+        ..TokenizerOptions::default()
+    };
+    let tokenizer = Tokenizer::with_options(Arc::new(dictionary()), options);
+    assert_eq!(term_ids(&tokenizer, "臭い"), [Some(KUSAI)]);
+    assert_eq!(
+        dictionary().best_entry_for("臭い").map(|hit| hit.term_id),
+        Some(KUSAI)
+    );
+}
+
+#[test]
+fn rare_spellings_still_match_on_their_own() {
+    let tokenizer = Tokenizer::new(Arc::new(dictionary()));
+    let tokens = tokenizer.tokenize("猫が呟く");
+    assert_eq!(surfaces(&tokens), ["猫", "が", "呟く"]);
+    assert_eq!(tokens[2].term_id, Some(TSUBUYAKU));
+    assert_eq!(tokens[2].term_entry.unwrap().form_penalty, 2);
+}
@@ -0,0 +1,72 @@
+mod common;
+
+use common::fixture_dictionary;
+use segmenter::format::{
+    unwakati, unwakati_with, wakati, wakati_preserving, wakati_preserving_with,
+};
+use segmenter::tokenizer::Tokenizer;
+use std::sync::Arc;
+
+fn tokenizer() -> Tokenizer {
+    Tokenizer::new(Arc::new(fixture_dictionary()))
+}
+
+#[test]
+fn wakati_joins_tokens_with_spaces() {
+    let tokenizer = tokenizer();
+    let text = "私は学生です";
+    assert_eq!(wakati(&tokenizer.tokenize(text)), "私 は 学生 です");
+
+    // Other whitespace becomes a plain separator.
+    let text = " 猫を\t見た。";
+    assert_eq!(wakati(&tokenizer.tokenize(text)), "猫 を 見た 。");
+}
+
+#[test]
+fn wakati_keeps_lines() {
+    let tokenizer = tokenizer();
+    let text = "私は学生です\n猫を見た\r\n\n今日は良い天気";
+    assert_eq!(
+        wakati(&tokenizer.tokenize(text)),
+        "私 は 学生 です\n猫 を 見た\n\n今日 は 良い 天気"
+    );
+}
+
+#[test]
+fn preserving_wakati_leaves_whitespace_alone() {
+    let tokenizer = tokenizer();
+    let golden = [
+        ("私は学生です", "私 は 学生 です"),
+        (
+            "私は学生です\n猫を見た\r\n\n",
+            "私 は 学生 です\n猫 を 見た\r\n\n",
+        ),
+        (" 猫を\t見た。", " 猫 を\t見た 。"),
+        ("今日は\u{3000}良い天気", "今日 は\u{3000}良い 天気"),
+        // Spaces between words get one more, so they tell apart from boundaries.
+        ("猫 を見た", "猫  を 見た"),
+        ("Hello  world 猫", "Hello   world  猫"),
+    ];
+    for (text, expected) in golden {
+        let tokens = tokenizer.tokenize(text);
+        let output = wakati_preserving(text, &tokens);
+        assert_eq!(output, expected, "{text:?}");
+        assert_eq!(unwakati(&output), text);
+    }
+}
+
+#[test]
+fn preserving_wakati_takes_any_marker() {
+    let tokenizer = tokenizer();
+    let golden = [
+        ("私は学生です\n猫を見た", "私/は/学生/です\n猫/を/見た"),
+        ("猫 を見た", "猫 を/見た"),
+        ("1/2の猫", "1//2/の/猫"),
+    ];
+    for (text, expected) in golden {
+        let tokens = tokenizer.tokenize(text);
+        let output = wakati_preserving_with(text, &tokens, '/');
+        assert_eq!(output, expected, "{text:?}");
+        assert_eq!(unwakati_with(&output, '/'), text);
+    }
+}
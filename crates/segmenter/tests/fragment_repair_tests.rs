@@ -0,0 +1,68 @@
+mod common;
+
+use common::{fixture_dictionary, surfaces};
+use segmenter::dictionary::{Dictionary, PartOfSpeech, Tag};
+use segmenter::tokenizer::{Tokenizer, TokenizerOptions};
+use std::sync::Arc;
+
+/// The fixture dictionary with sentence-final particle pairs. Their characters are
+/// built-in function words too, which a strong function word bonus prefers on their
+/// own.
+fn dictionary() -> Dictionary {
+    let mut dictionary = fixture_dictionary();
+    dictionary.add_word(1000, None, "ねえ", PartOfSpeech::PARTICLE, Tag::NONE);
+    dictionary.add_word(1001, None, "よな", PartOfSpeech::PARTICLE, Tag::NONE);
+    dictionary
+}
+
+fn tokenizer(dictionary: Dictionary, repair_trailing_fragments: bool) -> Tokenizer {
+    let options = TokenizerOptions {
+        function_word_bonus: 40.0,
+        repair_trailing_fragments,
+        ..TokenizerOptions::default()
+    };
+    Tokenizer::with_options(Arc::new(dictionary), options)
+}
+
+#[test]
+fn trailing_fragments_are_matched_from_the_right() {
+    let fragmented = tokenizer(dictionary(), false);
+    let repaired = tokenizer(dictionary(), true);
+    for (text, before, after) in [
+        (
+            "天気がいいですねえ",
+            &["天気", "が", "いい", "です", "ね", "え"][..],
+            &["天気", "が", "いい", "です", "ねえ"][..],
+        ),
+        (
+            "会社にいくよな",
+            &["会社", "に", "いく", "よ", "な"],
+            &["会社", "に", "いく", "よな"],
+        ),
+    ] {
+        assert_eq!(surfaces(&fragmented.tokenize(text)), before, "{text}");
+        let tokens = repaired.tokenize(text);
+        assert_eq!(surfaces(&tokens), after, "{text}");
+        let last = tokens.last().unwrap();
+        assert!(last.term_id.is_some(), "{text}");
+        assert_eq!(last.byte_start + last.token.len(), text.len());
+        assert_eq!(
+            format!("{:?}", repaired.tokenize_iter(text).collect::<Vec<_>>()),
+            format!("{tokens:?}")
+        );
+    }
+}
+
+#[test]
+fn fragments_stay_without_a_better_match() {
+    let repaired = tokenizer(fixture_dictionary(), true);
+    assert_eq!(
+        surfaces(&repaired.tokenize("天気がいいですねえ")),
+        ["天気", "が", "いい", "です", "ね", "え"]
+    );
+    // A single fragment is left to the lattice.
+    assert_eq!(
+        surfaces(&repaired.tokenize("猫をみたのね")),
+        ["猫", "を", "みた", "の", "ね"]
+    );
+}
@@ -0,0 +1,75 @@
+mod common;
+
+use segmenter::dictionary::Dictionary;
+use segmenter::filter;
+use segmenter::tokenizer::{CoverageError, Tokenization, Tokenizer, TokenizerOptions};
+use std::sync::Arc;
+
+fn tokenizer(dictionary: Dictionary, strict_coverage: bool) -> Tokenizer {
+    let options = TokenizerOptions {
+        strict_coverage,
+        ..Default::default()
+    };
+    Tokenizer::with_options(Arc::new(dictionary), options)
+}
+
+/// Inputs that used to lose characters: emoji, scripts without a category, marks
+/// without a base character and control characters.
+const TEXTS: &[&str] = &[
+    "😀",
+    "👨\u{200D}👩\u{200D}👧🇯🇵",
+    "ကမ္ဘာ",
+    "한국어",
+    "\u{301}\u{3099}",
+    "\u{0}\u{7}",
+    "猫😀を見た",
+    "",
+];
+
+#[test]
+fn full_coverage_reports_no_gaps() {
+    for strict in [false, true] {
+        for dictionary in [common::fixture_dictionary(), Dictionary::new()] {
+            let tokenizer = tokenizer(dictionary, strict);
+            for text in TEXTS {
+                let tokenization = tokenizer.tokenize_checked(text).unwrap();
+                assert!(tokenization.gaps.is_empty(), "{text}");
+                let surfaces: String = tokenization
+                    .tokens
+                    .iter()
+                    .map(|token| token.token)
+                    .collect();
+                assert_eq!(surfaces, *text);
+            }
+        }
+    }
+}
+
+#[test]
+fn gaps_are_the_byte_ranges_outside_every_token() {
+    let tokenizer = tokenizer(common::fixture_dictionary(), false);
+    let text = "「猫」を見た。";
+    let mut tokens = tokenizer.tokenize(text);
+    filter::drop_punctuation(&mut tokens);
+
+    let tokenization = Tokenization::new(text, tokens);
+    assert_eq!(tokenization.gaps, [0..3, 6..9, 18..21]);
+    for gap in tokenization.gaps.iter() {
+        assert!(text[gap.clone()].chars().all(|c| "「」。".contains(c)));
+    }
+
+    // Adjacent gaps merge, and an empty token list leaves exactly the whole text.
+    let mut tokens = tokenizer.tokenize("猫。」");
+    filter::drop_punctuation(&mut tokens);
+    assert_eq!(Tokenization::new("猫。」", tokens).gaps, vec![3..9]);
+    assert_eq!(Tokenization::new("猫", Vec::new()).gaps, vec![0..3]);
+    assert!(Tokenization::new("", Vec::new()).gaps.is_empty());
+}
+
+#[test]
+fn coverage_errors_list_the_gaps() {
+    let error = CoverageError {
+        gaps: vec![0..3, 6..9],
+    };
+    assert_eq!(error.to_string(), "no token covers bytes 0..3, 6..9");
+}
@@ -0,0 +1,95 @@
+use segmenter::dictionary::{DictionaryBuilder, PartOfSpeech, Tag};
+use segmenter::tokenizer::{GranularToken, Token, Tokenizer};
+use std::sync::Arc;
+
+const WORDS: &[(Option<&str>, &str, PartOfSpeech)] = &[
+    (
+        Some("東京国際空港"),
+        "とうきょうこくさいくうこう",
+        PartOfSpeech::NOUN_PROPER,
+    ),
+    (Some("東京"), "とうきょう", PartOfSpeech::NOUN_PROPER),
+    (Some("国際"), "こくさい", PartOfSpeech::NOUN),
+    (Some("空港"), "くうこう", PartOfSpeech::NOUN),
+    (
+        Some("自然言語処理"),
+        "しぜんげんごしょり",
+        PartOfSpeech::NOUN,
+    ),
+    (Some("自然"), "しぜん", PartOfSpeech::NOUN),
+    (Some("言語"), "げんご", PartOfSpeech::NOUN),
+    (Some("処理"), "しょり", PartOfSpeech::NOUN),
+    (None, "に", PartOfSpeech::PARTICLE),
+    (Some("行く"), "いく", PartOfSpeech::GODAN_VERB),
+];
+
+fn tokenizer() -> Tokenizer {
+    let mut builder = DictionaryBuilder::new();
+    for (kanji, kana, pos) in WORDS {
+        builder.add(*kanji, kana, *pos, Tag::NONE);
+    }
+    Tokenizer::new(Arc::new(builder.build()))
+}
+
+fn surfaces<'a>(tokens: &[Token<'a>]) -> Vec<&'a str> {
+    tokens.iter().map(|token| token.token).collect()
+}
+
+fn assert_tiles(text: &str, tokens: &[GranularToken]) {
+    let mut byte_start = 0;
+    for token in tokens {
+        assert_eq!(token.coarse.byte_start, byte_start, "{text}");
+        assert_eq!(surfaces(&token.fine).concat(), token.coarse.token, "{text}");
+
+        let mut fine_start = byte_start;
+        for fine in token.fine.iter() {
+            assert_eq!(fine.byte_start, fine_start, "{text}");
+            assert_eq!(&text[fine.byte_start..][..fine.token.len()], fine.token);
+            fine_start += fine.token.len();
+        }
+        byte_start += token.coarse.token.len();
+    }
+    assert_eq!(byte_start, text.len(), "{text}");
+}
+
+#[test]
+fn compounds_split_into_their_parts() {
+    let tokenizer = tokenizer();
+    for (text, fine) in [
+        ("東京国際空港", &["東京", "国際", "空港"][..]),
+        ("自然言語処理", &["自然", "言語", "処理"]),
+    ] {
+        let tokens = tokenizer.tokenize_granular(text);
+        assert_tiles(text, &tokens);
+        assert_eq!(tokens.len(), 1, "{text}");
+        assert_eq!(tokens[0].coarse.token, text);
+        assert_eq!(surfaces(&tokens[0].fine), fine, "{text}");
+        assert!(tokens[0].fine.iter().all(|token| token.term_id.is_some()));
+    }
+}
+
+#[test]
+fn coarse_tokens_match_tokenize() {
+    let tokenizer = tokenizer();
+    let text = "東京国際空港に行く、佐藤";
+    let tokens = tokenizer.tokenize_granular(text);
+    assert_tiles(text, &tokens);
+
+    let coarse: Vec<Token> = tokens.iter().map(|token| token.coarse.clone()).collect();
+    assert_eq!(surfaces(&coarse), surfaces(&tokenizer.tokenize(text)));
+
+    // Tokens without another way to cover their span are their own split.
+    for token in tokens[1..].iter() {
+        assert_eq!(
+            surfaces(&token.fine),
+            [token.coarse.token],
+            "{}",
+            token.coarse.token
+        );
+    }
+}
+
+#[test]
+fn empty_text_has_no_tokens() {
+    assert!(tokenizer().tokenize_granular("").is_empty());
+}
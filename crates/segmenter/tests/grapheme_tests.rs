@@ -0,0 +1,111 @@
+mod common;
+
+use segmenter::dictionary::Dictionary;
+use segmenter::graphemes::cluster_boundaries;
+use segmenter::tokenizer::{Token, Tokenizer};
+use std::sync::Arc;
+
+lazy_static::lazy_static! {
+    static ref TOKENIZER: Tokenizer = Tokenizer::new(Arc::new(common::fixture_dictionary()));
+    static ref EMPTY: Tokenizer = Tokenizer::new(Arc::new(Dictionary::new()));
+}
+
+const TEXTS: &[&str] = &[
+    // Composed and decomposed が.
+    "かがく",
+    "か\u{3099}か\u{3099}く",
+    "私は学生か\u{3099}好きです。",
+    "は\u{309A}んだ",
+    // 葛 with an ideographic variation selector.
+    "葛\u{E0100}城市に行く",
+    "禰\u{FE00}豆子",
+    "e\u{301}te\u{301}",
+    "👨\u{200D}👩\u{200D}👧です",
+    "👍\u{1F3FD}と🇯🇵🇺🇸",
+    "ああああ\u{3099}",
+    "行\u{E0101}\u{E0101}",
+];
+
+/// Asserts that `tokens` tile `text` and only start and end on cluster boundaries.
+fn assert_clusters_kept(text: &str, tokens: &[Token]) {
+    let chars: Vec<char> = text.chars().collect();
+    let clusters = cluster_boundaries(&chars);
+    assert_eq!(
+        tokens.iter().map(|token| token.token).collect::<String>(),
+        text
+    );
+    for token in tokens {
+        let end = token.char_start + token.token.chars().count();
+        assert!(clusters[token.char_start], "{text}: {:?}", token.token);
+        assert!(clusters[end], "{text}: {:?}", token.token);
+    }
+}
+
+#[test]
+fn cluster_boundaries_follow_the_base_character() {
+    let boundaries = |text: &str| -> Vec<usize> {
+        let chars: Vec<char> = text.chars().collect();
+        let clusters = cluster_boundaries(&chars);
+        (0..=chars.len()).filter(|i| clusters[*i]).collect()
+    };
+
+    assert_eq!(boundaries(""), [0]);
+    assert_eq!(boundaries("かがく"), [0, 1, 2, 3]);
+    assert_eq!(boundaries("か\u{3099}か\u{3099}く"), [0, 2, 4, 5]);
+    assert_eq!(boundaries("葛\u{E0100}城"), [0, 2, 3]);
+    assert_eq!(boundaries("👨\u{200D}👩\u{200D}👧"), [0, 5]);
+    assert_eq!(boundaries("🇯🇵🇺🇸🇫"), [0, 2, 4, 5]);
+    assert_eq!(boundaries("\r\n\n\u{301}"), [0, 2, 3, 4]);
+    // A joiner between letters only keeps itself with the letter before it.
+    assert_eq!(boundaries("a\u{200D}b"), [0, 2, 3]);
+}
+
+#[test]
+fn tokens_never_split_a_cluster() {
+    for tokenizer in [&*TOKENIZER, &*EMPTY] {
+        for text in TEXTS {
+            assert_clusters_kept(text, &tokenizer.tokenize(text));
+            assert_clusters_kept(text, &tokenizer.tokenize_greedy(text));
+            let fine: Vec<Token> = tokenizer
+                .tokenize_granular(text)
+                .into_iter()
+                .flat_map(|token| token.fine)
+                .collect();
+            assert_clusters_kept(text, &fine);
+
+            let chars: Vec<char> = text.chars().collect();
+            let clusters = cluster_boundaries(&chars);
+            for offset in tokenizer.boundaries(text) {
+                let index = text[..offset].chars().count();
+                assert!(clusters[index], "{text}: {offset}");
+            }
+        }
+    }
+}
+
+#[test]
+fn composed_and_decomposed_text_split_alike() {
+    let surfaces = |text: &str| -> Vec<String> {
+        TOKENIZER
+            .tokenize(text)
+            .iter()
+            .map(|token| token.token.chars().filter(|c| *c != '\u{3099}').collect())
+            .collect()
+    };
+
+    // The decomposed が isn't the particle, but stays with its か.
+    assert_eq!(surfaces("猫か\u{3099}"), ["猫", "か"]);
+    let tokens = TOKENIZER.tokenize("猫か\u{3099}");
+    assert_eq!(tokens[1].token, "か\u{3099}");
+    assert_eq!(tokens[1].term_id, None);
+}
+
+#[test]
+fn variation_selectors_stay_with_their_kanji() {
+    let tokens = EMPTY.tokenize("葛\u{E0100}城市");
+    assert_eq!(tokens[0].token, "葛\u{E0100}城市");
+
+    let tokens = TOKENIZER.tokenize("猫\u{FE00}は");
+    let surfaces: Vec<&str> = tokens.iter().map(|token| token.token).collect();
+    assert_eq!(surfaces, ["猫\u{FE00}", "は"]);
+}
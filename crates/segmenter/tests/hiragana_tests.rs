@@ -0,0 +1,135 @@
+mod common;
+
+use common::{fixture_dictionary, surfaces};
+use segmenter::candidate::CandidateSource;
+use segmenter::dictionary::{Dictionary, PartOfSpeech};
+use segmenter::tokenizer::{Tokenizer, TokenizerOptions};
+use std::sync::Arc;
+
+lazy_static::lazy_static! {
+    static ref TOKENIZER: Tokenizer = Tokenizer::new(Arc::new(fixture_dictionary()));
+    /// Without a dictionary, the function words are all there is to split on.
+    static ref KANA_ONLY: Tokenizer = Tokenizer::new(Arc::new(Dictionary::new()));
+}
+
+#[test]
+fn function_words_split_kana_only_text() {
+    for (text, expected) in [
+        (
+            "きのうはあめがふった",
+            &["きのう", "は", "あめ", "が", "ふった"][..],
+        ),
+        (
+            "ねこがにわであそぶ",
+            &["ねこ", "が", "にわ", "で", "あそぶ"],
+        ),
+        (
+            "まどからやまがみえる",
+            &["まど", "から", "やま", "が", "みえる"],
+        ),
+        (
+            "ともだちとうみへいく",
+            &["ともだち", "と", "うみ", "へ", "いく"],
+        ),
+        (
+            "しゅくだいはまだです",
+            &["しゅくだい", "は", "まだ", "です"],
+        ),
+        ("おちゃをのみました", &["おちゃ", "を", "のみ", "ました"]),
+        // A sokuon stays with the kana it doubles.
+        (
+            "ぼくはがっこうにいきます",
+            &["ぼく", "は", "がっこう", "に", "いき", "ます"],
+        ),
+    ] {
+        assert_eq!(surfaces(&KANA_ONLY.tokenize(text)), expected, "{text}");
+    }
+}
+
+#[test]
+fn function_words_combine_with_dictionary_words() {
+    for (text, expected) in [
+        (
+            "わたしはねこをみた",
+            &["わたし", "は", "ねこ", "を", "みた"][..],
+        ),
+        ("いぬがすきだ", &["いぬ", "が", "すき", "だ"]),
+        ("みずをのみたい", &["みず", "を", "のみ", "たい"]),
+        (
+            "しゅくだいはまだです",
+            &["しゅくだい", "は", "まだ", "です"],
+        ),
+    ] {
+        assert_eq!(surfaces(&TOKENIZER.tokenize(text)), expected, "{text}");
+    }
+}
+
+/// Readings picked for sentences with more than one valid split. These pin the current
+/// behavior so scoring changes show up here, not the one true segmentation.
+#[test]
+fn ambiguous_sentences() {
+    for (text, expected) in [
+        // ここでは きものを or ここで はきものを
+        (
+            "ここではきものをぬいでください",
+            &["ここ", "では", "きも", "の", "を", "ぬい", "で", "ください"][..],
+        ),
+        (
+            "にわにはにわにわとりがいる",
+            &["にわ", "には", "にわ", "に", "わとり", "が", "いる"],
+        ),
+        (
+            "すもももももももものうち",
+            &[
+                "すも", "も", "も", "も", "も", "も", "も", "も", "の", "うち",
+            ],
+        ),
+    ] {
+        assert_eq!(surfaces(&KANA_ONLY.tokenize(text)), expected, "{text}");
+    }
+}
+
+#[test]
+fn function_words_are_candidates_without_dictionary_entries() {
+    let candidates = KANA_ONLY.debug_candidates("ねこがにわ", 8);
+    let particle = candidates[2]
+        .candidates
+        .iter()
+        .find(|candidate| candidate.surface == "が")
+        .unwrap();
+    assert_eq!(particle.source, CandidateSource::FunctionWord);
+    assert_eq!(particle.pos, PartOfSpeech::PARTICLE);
+
+    // The fixture has が, so its entry gets the bonus instead.
+    let candidates = TOKENIZER.debug_candidates("ねこがにわ", 8);
+    assert!(candidates[2]
+        .candidates
+        .iter()
+        .filter(|candidate| candidate.surface == "が")
+        .all(|candidate| matches!(candidate.source, CandidateSource::Dictionary(_))));
+}
+
+#[test]
+fn function_words_never_open_the_text() {
+    for text in ["ともだち", "とても", "にわとり"] {
+        let first = KANA_ONLY.tokenize(text)[0].token;
+        assert!(first.chars().count() > 1, "{text}");
+    }
+}
+
+#[test]
+fn bonuses_can_be_disabled() {
+    let tokenizer = Tokenizer::with_options(
+        Arc::new(Dictionary::new()),
+        TokenizerOptions {
+            function_word_bonus: 0.0,
+            particle_connection_bonus: 0.0,
+            ..Default::default()
+        },
+    );
+    let text = "きのうはあめがふった";
+    assert_ne!(
+        surfaces(&tokenizer.tokenize(text)),
+        surfaces(&KANA_ONLY.tokenize(text))
+    );
+}
@@ -0,0 +1,57 @@
+mod common;
+
+use common::{fixture_dictionary, surfaces};
+use segmenter::dictionary::{Dictionary, PartOfSpeech, Tag};
+use segmenter::tokenizer::{Token, Tokenizer, TokenizerOptions};
+use std::sync::Arc;
+
+/// The fixture dictionary with words pre-reform texts spell differently.
+fn dictionary() -> Dictionary {
+    let mut dictionary = fixture_dictionary();
+    dictionary.add_word(1000, None, "いる", PartOfSpeech::ICHIDAN_VERB, Tag::NONE);
+    dictionary.add_word(1001, Some("鼻血"), "はなじ", PartOfSpeech::NOUN, Tag::NONE);
+    dictionary.index_both_kana_scripts();
+    dictionary
+}
+
+fn tokenizer(fold_historical_kana: bool) -> Tokenizer {
+    let options = TokenizerOptions {
+        fold_historical_kana,
+        ..TokenizerOptions::default()
+    };
+    Tokenizer::with_options(Arc::new(dictionary()), options)
+}
+
+fn folded(token: &Token) -> bool {
+    token.term_entry.is_some_and(|term_entry| term_entry.folded)
+}
+
+#[test]
+fn historical_spellings_match_modern_entries() {
+    let tokenizer = tokenizer(true);
+    for (text, term_id) in [("ゐる", 1000), ("ヰル", 1000), ("はなぢ", 1001)] {
+        let tokens = tokenizer.tokenize(text);
+        assert_eq!(surfaces(&tokens), [text], "{text}");
+        assert_eq!(tokens[0].term_id, Some(term_id), "{text}");
+        assert!(folded(&tokens[0]), "{text}");
+    }
+}
+
+#[test]
+fn folding_is_off_by_default() {
+    let tokens = tokenizer(false).tokenize("はなぢ");
+    assert!(tokens.iter().all(|token| token.term_id != Some(1001)));
+    assert!(!tokens.iter().any(folded));
+}
+
+#[test]
+fn modern_spellings_are_untouched() {
+    let tokenizer = tokenizer(true);
+    let tokens = tokenizer.tokenize("猫を見た");
+    assert_eq!(surfaces(&tokens), ["猫", "を", "見た"]);
+    assert!(!tokens.iter().any(folded));
+
+    let tokens = tokenizer.tokenize("はなじ");
+    assert_eq!(tokens[0].term_id, Some(1001));
+    assert!(!folded(&tokens[0]));
+}
@@ -0,0 +1,84 @@
+use segmenter::dictionary::{Dictionary, PartOfSpeech, Tag, TermEntry};
+use segmenter::tokenizer::Tokenizer;
+use std::sync::Arc;
+
+/// Four words read かな, added worst first: an archaic noun, a proper noun, a common
+/// noun and a particle. Then four read かみ, where nothing but the order tells the two
+/// common nouns apart.
+fn dictionary() -> Dictionary {
+    let mut dictionary = Dictionary::new();
+    dictionary.add_word(100, Some("哉"), "かな", PartOfSpeech::NOUN, Tag::ARCHAIC);
+    dictionary.add_word(
+        101,
+        Some("加奈"),
+        "かな",
+        PartOfSpeech::NOUN_PROPER,
+        Tag::NONE,
+    );
+    dictionary.add_word(102, Some("仮名"), "かな", PartOfSpeech::NOUN, Tag::NONE);
+    dictionary.add_word(103, None, "かな", PartOfSpeech::PARTICLE, Tag::NONE);
+
+    dictionary.add_word(
+        200,
+        Some("加美"),
+        "かみ",
+        PartOfSpeech::NOUN_PROPER,
+        Tag::NONE,
+    );
+    dictionary.add_word(201, Some("守"), "かみ", PartOfSpeech::NOUN, Tag::ARCHAIC);
+    dictionary.add_word(203, Some("神"), "かみ", PartOfSpeech::NOUN, Tag::NONE);
+    dictionary.add_word(202, Some("紙"), "かみ", PartOfSpeech::NOUN, Tag::NONE);
+    dictionary
+}
+
+fn term_ids(dictionary: &Dictionary, term_entries: &[TermEntry]) -> Vec<u32> {
+    term_entries
+        .iter()
+        .map(|term_entry| dictionary.entries[term_entry.entry_index as usize].term_id)
+        .collect()
+}
+
+#[test]
+fn homographs_are_kept_in_order() {
+    let dictionary = dictionary();
+    assert_eq!(
+        term_ids(&dictionary, &dictionary.kana["かな"]),
+        [103, 102, 101, 100]
+    );
+    assert_eq!(
+        term_ids(&dictionary, &dictionary.kana["かみ"]),
+        [202, 203, 200, 201]
+    );
+}
+
+#[test]
+#[cfg(feature = "serde")]
+fn loading_sorts_term_entries() {
+    let mut dictionary = dictionary();
+    dictionary.kana.get_mut("かな").unwrap().reverse();
+    let mut bytes = Vec::new();
+    dictionary.write_to(&mut bytes).unwrap();
+
+    let loaded = Dictionary::from_bytes(&bytes).unwrap();
+    assert_eq!(
+        term_ids(&loaded, &loaded.kana["かな"]),
+        [103, 102, 101, 100]
+    );
+    dictionary.sort_term_entries();
+    assert_eq!(loaded.kana["かな"], dictionary.kana["かな"]);
+}
+
+#[test]
+fn the_tokenizer_picks_the_best_entry() {
+    let dictionary = Arc::new(dictionary());
+    let tokenizer = Tokenizer::new(dictionary.clone());
+    for (surface, best) in [("かな", 103), ("かみ", 202), ("仮名", 102)] {
+        let hit = dictionary.best_entry_for(surface).unwrap();
+        assert_eq!(hit.term_id, best, "{surface}");
+
+        let tokens = tokenizer.tokenize(surface);
+        assert_eq!(tokens.len(), 1, "{surface}");
+        assert_eq!(tokens[0].term_id, Some(hit.term_id), "{surface}");
+    }
+    assert_eq!(dictionary.best_entry_for("かも"), None);
+}
@@ -0,0 +1,153 @@
+mod common;
+
+use common::{fixture_dictionary, surfaces};
+use segmenter::incremental::IncrementalTokenizer;
+use segmenter::tokenizer::{Tokenizer, TokenizerOptions};
+use std::sync::Arc;
+
+/// Pieces the random texts are made of: dictionary words, function words, and the
+/// characters that attach to what comes before or after them.
+const PIECES: &[&str] = &[
+    "猫",
+    "を",
+    "見た",
+    "私",
+    "は",
+    "学生",
+    "です",
+    "東京",
+    "に",
+    "行く",
+    "今日",
+    "天気",
+    "が",
+    "良い",
+    "お茶",
+    "飲みました",
+    "田中さん",
+    "の",
+    "方",
+    "コーヒー",
+    "会社",
+    "日本",
+    "人",
+    "気になる",
+    "かもしれない",
+    "ねこ",
+    "みた",
+    "たべ",
+    "ちゃ",
+    "ゃ",
+    "っ",
+    "ッ",
+    "ー",
+    "ア",
+    "イ",
+    "トヨタ",
+    "パン",
+    "AI",
+    "技術",
+    "Ab",
+    "！",
+    "！！！",
+    "ーーー",
+    "。",
+    "、",
+    " ",
+    "12",
+    "👍",
+    "👨",
+    "\u{200D}",
+    "🏻",
+    "ǎ",
+    "\u{301}",
+    "ー自動車",
+];
+
+/// Deterministic xorshift generator, so failures reproduce.
+struct Rng(u64);
+
+impl Rng {
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    fn below(&mut self, bound: usize) -> usize {
+        (self.next() % bound as u64) as usize
+    }
+}
+
+/// Appends random suffixes one at a time, checking the tokens against a full
+/// tokenization after each.
+fn assert_matches_full_tokenization(options: TokenizerOptions, seed: u64) {
+    let tokenizer = Tokenizer::with_options(Arc::new(fixture_dictionary()), options);
+    let mut rng = Rng(seed);
+    for _ in 0..20 {
+        let mut incremental = IncrementalTokenizer::new(&tokenizer);
+        for _ in 0..30 {
+            let suffix: String = (0..1 + rng.below(3))
+                .map(|_| PIECES[rng.below(PIECES.len())])
+                .collect();
+            incremental.push_str(&suffix);
+
+            let expected = tokenizer.tokenize(incremental.text());
+            assert_eq!(
+                format!("{:?}", incremental.tokens()),
+                format!("{expected:?}"),
+                "after appending {suffix:?} to get {:?}",
+                incremental.text()
+            );
+        }
+    }
+}
+
+#[test]
+fn appending_gives_the_tokens_of_the_whole_text() {
+    assert_matches_full_tokenization(TokenizerOptions::default(), 0x5EED);
+}
+
+#[test]
+fn appending_with_short_groupings_and_no_connection_weights() {
+    let options = TokenizerOptions {
+        max_unknown_length: 2,
+        emphasis_threshold: 2,
+        stem_particle_penalty: 0.0,
+        particle_connection_bonus: 0.0,
+        nominalizer_bonus: 0.0,
+        ..TokenizerOptions::default()
+    };
+    assert_matches_full_tokenization(options, 0xC0FFEE);
+}
+
+#[test]
+fn invisible_characters_are_left_to_the_tokenizer() {
+    let tokenizer = Tokenizer::new(Arc::new(fixture_dictionary()));
+    let mut incremental = IncrementalTokenizer::new(&tokenizer);
+    incremental.push_str("猫を\u{200B}");
+    incremental.push_str("見た");
+    assert_eq!(
+        surfaces(&incremental.tokens()),
+        ["猫", "を\u{200B}", "見た"]
+    );
+    assert_eq!(
+        format!("{:?}", incremental.tokens()),
+        format!("{:?}", tokenizer.tokenize("猫を\u{200B}見た"))
+    );
+}
+
+#[test]
+fn invalidate_starts_over() {
+    let tokenizer = Tokenizer::new(Arc::new(fixture_dictionary()));
+    let mut incremental = IncrementalTokenizer::new(&tokenizer);
+    incremental.push_str("私は学生");
+    incremental.invalidate();
+    assert_eq!(incremental.text(), "");
+    assert!(incremental.tokens().is_empty());
+
+    incremental.push_str("猫を");
+    incremental.push_str("見た");
+    assert_eq!(surfaces(&incremental.tokens()), ["猫", "を", "見た"]);
+}
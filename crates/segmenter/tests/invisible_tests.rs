@@ -0,0 +1,115 @@
+mod common;
+
+use common::fixture_dictionary;
+use segmenter::invisible::VisibleText;
+use segmenter::tokenizer::{Token, Tokenizer, TokenizerOptions};
+use std::sync::Arc;
+
+fn tokenizer(options: TokenizerOptions) -> Tokenizer {
+    Tokenizer::with_options(Arc::new(fixture_dictionary()), options)
+}
+
+fn surfaces<'a>(tokens: &[Token<'a>]) -> Vec<&'a str> {
+    tokens.iter().map(|token| token.token).collect()
+}
+
+/// Every token starts where the one before it ended, character and byte offsets alike.
+fn assert_tiles(text: &str, tokens: &[Token]) {
+    let (mut char_start, mut byte_start) = (0, 0);
+    for token in tokens {
+        assert_eq!(
+            (token.char_start, token.byte_start),
+            (char_start, byte_start)
+        );
+        assert_eq!(
+            &text[byte_start..byte_start + token.token.len()],
+            token.token
+        );
+        char_start += token.token.chars().count();
+        byte_start += token.token.len();
+    }
+    assert_eq!(byte_start, text.len());
+}
+
+#[test]
+fn zero_width_space_inside_a_word_is_looked_past() {
+    let text = "学\u{200B}生です";
+    let tokens = tokenizer(TokenizerOptions::default()).tokenize(text);
+
+    assert_eq!(surfaces(&tokens), ["学\u{200B}生", "です"]);
+    assert!(tokens[0].term_id.is_some());
+    assert_tiles(text, &tokens);
+}
+
+#[test]
+fn leading_bom_goes_with_the_first_token() {
+    let text = "\u{FEFF}猫を見た";
+    let tokens = tokenizer(TokenizerOptions::default()).tokenize(text);
+
+    assert_eq!(surfaces(&tokens), ["\u{FEFF}猫", "を", "見た"]);
+    assert_eq!(tokens[1].char_start, 2);
+    assert_tiles(text, &tokens);
+}
+
+#[test]
+fn soft_hyphens_and_joiners_are_skipped_too() {
+    let text = "猫\u{00AD}を\u{2060}見\u{200C}た";
+    let tokens = tokenizer(TokenizerOptions::default()).tokenize(text);
+
+    assert_eq!(
+        surfaces(&tokens),
+        ["猫\u{00AD}", "を\u{2060}", "見\u{200C}た"]
+    );
+    assert_tiles(text, &tokens);
+}
+
+#[test]
+fn iterator_matches_tokenize() {
+    let tokenizer = tokenizer(TokenizerOptions::default());
+    let text = "\u{FEFF}学\u{200B}生です";
+    let tokens = tokenizer.tokenize(text);
+    let iterated: Vec<Token> = tokenizer.tokenize_iter(text).collect();
+
+    assert_eq!(surfaces(&iterated), surfaces(&tokens));
+}
+
+#[test]
+fn disabled_invisibles_break_words() {
+    let options = TokenizerOptions {
+        skip_invisible_characters: false,
+        ..TokenizerOptions::default()
+    };
+    let text = "学\u{200B}生です";
+    let tokens = tokenizer(options).tokenize(text);
+
+    assert!(!surfaces(&tokens).contains(&"学\u{200B}生"));
+    assert_tiles(text, &tokens);
+}
+
+#[test]
+fn emoji_joiners_are_kept() {
+    let family = "👨\u{200D}👩\u{200D}👧";
+    assert!(VisibleText::new(family).is_none());
+
+    let visible = VisibleText::new("猫\u{200D}\u{FEFF}").unwrap();
+    assert_eq!(visible.text, "猫");
+    assert_eq!(visible.original_char(1), 3);
+    assert!(VisibleText::new("\u{FEFF}\u{200B}").is_none());
+}
+
+#[test]
+fn checked_and_granular_tokens_look_past_invisible_characters() {
+    let tokenizer = tokenizer(TokenizerOptions::default());
+    let text = "学\u{200B}生です";
+    let tokens = tokenizer.tokenize(text);
+
+    let tokenization = tokenizer.tokenize_checked(text).unwrap();
+    assert_eq!(surfaces(&tokenization.tokens), surfaces(&tokens));
+    assert!(tokenization.gaps.is_empty());
+
+    let granular = tokenizer.tokenize_granular(text);
+    let coarse: Vec<Token> = granular.iter().map(|token| token.coarse.clone()).collect();
+    assert_eq!(surfaces(&coarse), surfaces(&tokens));
+    let fine: Vec<Token> = granular.into_iter().flat_map(|token| token.fine).collect();
+    assert_tiles(text, &fine);
+}
@@ -0,0 +1,51 @@
+mod common;
+
+use common::{fixture_dictionary, surfaces};
+use segmenter::dictionary::{Dictionary, PartOfSpeech, Tag};
+use segmenter::tokenizer::{TokenKind, Tokenizer};
+use std::sync::Arc;
+
+/// The fixture dictionary with words JMdict spells out, where older texts write an
+/// iteration mark.
+fn dictionary() -> Dictionary {
+    let mut dictionary = fixture_dictionary();
+    dictionary.add_word(1000, None, "こころ", PartOfSpeech::NOUN, Tag::NONE);
+    dictionary.add_word(1001, None, "ただ", PartOfSpeech::ADVERB, Tag::NONE);
+    dictionary.add_word(1002, None, "バナナ", PartOfSpeech::NOUN, Tag::NONE);
+    dictionary.add_word(1003, None, "ガガ", PartOfSpeech::NOUN, Tag::NONE);
+    dictionary
+}
+
+#[test]
+fn iteration_marks_match_the_spelled_out_entry() {
+    let tokenizer = Tokenizer::new(Arc::new(dictionary()));
+    for (text, surface, term_id) in [
+        ("こゝろを見た", "こゝろ", 1000),
+        ("たゞ", "たゞ", 1001),
+        ("バナヽ", "バナヽ", 1002),
+        ("ガヾ", "ガヾ", 1003),
+    ] {
+        let tokens = tokenizer.tokenize(text);
+        assert_eq!(tokens[0].token, surface, "{text}");
+        assert_eq!(tokens[0].term_id, Some(term_id), "{text}");
+        assert_eq!(tokens[0].kind, TokenKind::Word, "{text}");
+    }
+    assert_eq!(
+        surfaces(&tokenizer.tokenize("こゝろを見た")),
+        ["こゝろ", "を", "見た"]
+    );
+}
+
+#[test]
+fn unknown_words_with_iteration_marks_stay_whole() {
+    let tokenizer = Tokenizer::new(Arc::new(dictionary()));
+    let tokens = tokenizer.tokenize("みすゞ");
+    assert_eq!(surfaces(&tokens), ["みすゞ"]);
+    assert_eq!(tokens[0].term_id, None);
+
+    // Marks with nothing to repeat are left as they are.
+    for text in ["ゝ", "ゞ", "ヽヾ", "猫ゝ", "ーゞ"] {
+        let tokens = tokenizer.tokenize(text);
+        assert_eq!(surfaces(&tokens).concat(), text);
+    }
+}
@@ -0,0 +1,78 @@
+mod common;
+
+use common::{fixture_dictionary, surfaces};
+use segmenter::dictionary::{Dictionary, PartOfSpeech, Tag};
+use segmenter::tokenizer::{Tokenizer, TokenizerOptions};
+use std::sync::Arc;
+
+/// The fixture dictionary with a katakana loanword and a hiragana onomatopoeia, indexed
+/// in both scripts.
+fn dictionary() -> Dictionary {
+    let mut dictionary = fixture_dictionary();
+    dictionary.add_word(1000, None, "アルバイト", PartOfSpeech::NOUN, Tag::NONE);
+    dictionary.add_word(1001, None, "ふわふわ", PartOfSpeech::ADVERB, Tag::NONE);
+    dictionary.index_both_kana_scripts();
+    dictionary
+}
+
+fn term_ids(tokenizer: &Tokenizer, text: &str) -> Vec<Option<u32>> {
+    tokenizer
+        .tokenize(text)
+        .iter()
+        .map(|token| token.term_id)
+        .collect()
+}
+
+#[test]
+fn both_scripts_match() {
+    let dictionary = dictionary();
+    for (surface, term_id) in [
+        ("アルバイト", 1000),
+        ("あるばいと", 1000),
+        ("ふわふわ", 1001),
+        ("フワフワ", 1001),
+        ("ネコ", 0),
+    ] {
+        let hits = dictionary.lookup_detailed(surface);
+        assert_eq!(hits.len(), 1, "{surface}");
+        assert_eq!(hits[0].term_id, term_id, "{surface}");
+        let other_script = dictionary.kana[surface][0].other_script;
+        assert_eq!(other_script, !["アルバイト", "ふわふわ"].contains(&surface));
+    }
+
+    let tokenizer = Tokenizer::new(Arc::new(dictionary));
+    let tokens = tokenizer.tokenize("あるばいとをした");
+    assert_eq!(surfaces(&tokens)[..2], ["あるばいと", "を"]);
+    assert_eq!(tokens[0].term_id, Some(1000));
+}
+
+#[test]
+fn indexing_twice_adds_nothing() {
+    let mut dictionary = dictionary();
+    let kana_keys = dictionary.kana.len();
+    assert_eq!(dictionary.index_both_kana_scripts(), 0);
+    assert_eq!(dictionary.kana.len(), kana_keys);
+}
+
+#[test]
+fn the_canonical_script_wins_ties() {
+    // はし is listed in hiragana only after its katakana homograph was copied over.
+    let mut dictionary = fixture_dictionary();
+    dictionary.add_word(1000, None, "ハシ", PartOfSpeech::NOUN, Tag::NONE);
+    dictionary.index_both_kana_scripts();
+    dictionary.add_word(1001, None, "はし", PartOfSpeech::NOUN, Tag::NONE);
+    let dictionary = Arc::new(dictionary);
+
+    let tokenizer = Tokenizer::new(dictionary.clone());
+    assert_eq!(term_ids(&tokenizer, "はし"), [Some(1001)]);
+    assert_eq!(term_ids(&tokenizer, "ハシ"), [Some(1000)]);
+
+    let options = TokenizerOptions {
+        other_script_penalty: 0.0,
+        ..TokenizerOptions::default()
+    };
+    let tokenizer = Tokenizer::with_options(dictionary.clone(), options);
+    // Homograph order still puts the copy last.
+    assert_eq!(term_ids(&tokenizer, "はし"), [Some(1001)]);
+    assert!(!dictionary.kana["はし"][0].other_script);
+}
@@ -0,0 +1,112 @@
+mod common;
+
+use common::fixture_dictionary;
+use segmenter::dictionary::PartOfSpeech;
+use segmenter::lattice::{Lattice, LatticeNode};
+use segmenter::tokenizer::{TokenKind, Tokenizer};
+use std::sync::Arc;
+
+fn node(start: usize, end: usize, score: f32) -> LatticeNode {
+    LatticeNode {
+        term_id: None,
+        term_entry: None,
+        pos: PartOfSpeech::NONE,
+        start,
+        end,
+        score,
+        kind: TokenKind::Word,
+    }
+}
+
+/// Three characters where the first two are best taken together.
+fn lattice() -> Lattice {
+    let mut lattice = Lattice::new(5, 3);
+    for (start, end, score) in [
+        (0, 1, 1.0),
+        (1, 2, 1.0),
+        (2, 3, 1.0),
+        (0, 2, 5.0),
+        (1, 3, 1.0),
+    ] {
+        lattice.add_node(node(start, end, score));
+    }
+    lattice
+}
+
+fn spans<'a>(nodes: impl Iterator<Item = &'a LatticeNode>) -> Vec<(usize, usize)> {
+    nodes.map(|node| (node.start, node.end)).collect()
+}
+
+#[test]
+fn paths_iterate_both_ways() {
+    let lattice = lattice();
+    let path = lattice.find_path();
+    assert_eq!(path.len(), 2);
+    assert_eq!(path.node_ids(), [3, 2]);
+    assert_eq!(spans(path.iter()), [(0, 2), (2, 3)]);
+    assert_eq!(spans(path.iter().rev()), [(2, 3), (0, 2)]);
+    assert_eq!(path.iter().len(), 2);
+
+    // Both ends can be taken from the same iterator.
+    let mut nodes = path.iter();
+    assert_eq!(nodes.next_back().map(|node| node.start), Some(2));
+    assert_eq!(nodes.next().map(|node| node.start), Some(0));
+    assert!(nodes.next().is_none());
+    assert!(nodes.next_back().is_none());
+}
+
+#[test]
+fn paths_index_and_score() {
+    let lattice = lattice();
+    let path = lattice.find_path();
+    assert_eq!(path.get(0).map(|node| node.score), Some(5.0));
+    assert_eq!(path.get(1).map(|node| node.end), Some(3));
+    assert!(path.get(2).is_none());
+    assert_eq!(path.score(), 6.0);
+
+    let empty = Lattice::new(0, 0);
+    let path = empty.find_path();
+    assert!(path.is_empty());
+    assert_eq!(path.score(), 0.0);
+    assert!(path.iter().next_back().is_none());
+}
+
+#[test]
+fn paths_are_listed_best_first() {
+    let mut lattice = Lattice::new(4, 2);
+    for (start, end, score) in [(0, 1, 1.0), (1, 2, 2.0), (0, 2, 4.0), (1, 2, 0.5)] {
+        lattice.add_node(node(start, end, score));
+    }
+
+    let paths = lattice.find_paths(8);
+    let scores: Vec<f64> = paths.iter().map(|path| path.score()).collect();
+    assert_eq!(scores, [4.0, 3.0, 1.5]);
+    assert_eq!(paths[1].node_ids(), [0, 1]);
+    assert_eq!(paths[0].node_ids(), lattice.find_path().node_ids());
+    assert_eq!(lattice.find_paths(2).len(), 2);
+    assert!(lattice.find_paths(0).is_empty());
+}
+
+#[test]
+fn token_iterators_follow_the_path() {
+    let tokenizer = Tokenizer::new(Arc::new(fixture_dictionary()));
+    let text = "私は猫を見た。";
+    let surfaces: Vec<&str> = tokenizer
+        .tokenize(text)
+        .iter()
+        .map(|token| token.token)
+        .collect();
+
+    let tokens = tokenizer.tokenize_iter(text);
+    assert_eq!(tokens.len(), surfaces.len());
+    let forward: Vec<&str> = tokens.map(|token| token.token).collect();
+    assert_eq!(forward, surfaces);
+
+    let mut backward: Vec<&str> = tokenizer
+        .tokenize_iter(text)
+        .rev()
+        .map(|token| token.token)
+        .collect();
+    backward.reverse();
+    assert_eq!(backward, surfaces);
+}
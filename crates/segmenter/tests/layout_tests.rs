@@ -0,0 +1,56 @@
+mod common;
+
+use common::fixture_dictionary;
+use segmenter::layout;
+use segmenter::tokenizer::{Tokenizer, TokenizerOptions};
+use std::sync::Arc;
+
+#[cfg(test)]
+lazy_static::lazy_static! {
+    static ref TOKENIZER: Tokenizer = Tokenizer::new(Arc::new(fixture_dictionary()));
+}
+
+#[test]
+fn headlines() {
+    const GOLDEN: &[(&str, &[&str])] = &[
+        ("今日は天気が良いです", &["今日は", "天気が", "良いです"]),
+        ("お茶を飲みました", &["お茶を", "飲みました"]),
+        (
+            "田中さんが東京に行く。",
+            &["田中さんが", "東京に", "行く。"],
+        ),
+        ("「猫」を見た", &["「猫」を", "見た"]),
+    ];
+
+    for (text, expected) in GOLDEN {
+        assert_eq!(layout::chunk(text, &TOKENIZER, 16), *expected, "{text}");
+    }
+}
+
+#[test]
+fn oversized_chunks_are_split() {
+    assert_eq!(
+        layout::chunk("田中さんが東京に行く", &TOKENIZER, 3),
+        ["田中", "さんが", "東京に", "行く"]
+    );
+    // A single token longer than the limit is kept whole.
+    assert_eq!(
+        layout::chunk("飲みました", &TOKENIZER, 1),
+        ["飲み", "ました"]
+    );
+}
+
+#[test]
+fn empty_text() {
+    assert!(layout::chunk("", &TOKENIZER, 8).is_empty());
+}
+
+#[test]
+fn dropped_tokens_go_with_the_chunk_before_them() {
+    let dictionary = Arc::new(fixture_dictionary());
+    let tokenizer = Tokenizer::with_options(dictionary, TokenizerOptions::search());
+    assert_eq!(
+        layout::chunk("「猫」を見た。", &tokenizer, 16),
+        ["「猫」を", "見た。"]
+    );
+}
@@ -0,0 +1,146 @@
+mod common;
+
+use common::{fixture_dictionary, surfaces};
+use segmenter::dictionary::{PartOfSpeech, Tag};
+use segmenter::tokenizer::{Tokenizer, TokenizerOptions};
+use std::sync::Arc;
+
+#[test]
+fn long_katakana_runs_are_clamped() {
+    let tokenizer = Tokenizer::new(Arc::new(fixture_dictionary()));
+    let max_length = TokenizerOptions::default().max_unknown_length;
+    let text = "アイウエオカキクケコ".repeat(1_000);
+
+    for tokens in [tokenizer.tokenize(&text), tokenizer.tokenize_greedy(&text)] {
+        assert_eq!(surfaces(&tokens).concat(), text);
+        assert!(tokens.len() >= 10_000 / max_length);
+        assert!(tokens
+            .iter()
+            .all(|token| token.token.chars().count() <= max_length));
+    }
+
+    let snapshot = tokenizer.export_lattice(&text);
+    assert!(snapshot
+        .nodes
+        .iter()
+        .all(|node| node.node.score.is_finite()));
+    assert_eq!(snapshot.replay(), snapshot.path);
+}
+
+#[test]
+fn clamp_is_configurable() {
+    let tokenizer = Tokenizer::with_options(
+        Arc::new(fixture_dictionary()),
+        TokenizerOptions {
+            max_unknown_length: 4,
+            ..Default::default()
+        },
+    );
+    assert_eq!(
+        surfaces(&tokenizer.tokenize("スマートフォンを見た")),
+        ["スマート", "フォン", "を", "見た"]
+    );
+    assert_eq!(
+        surfaces(&tokenizer.tokenize_greedy("スマートフォンを見た")),
+        ["スマート", "フォン", "を", "見た"]
+    );
+}
+
+#[test]
+fn long_mixed_text_keeps_finite_scores() {
+    let tokenizer = Tokenizer::new(Arc::new(fixture_dictionary()));
+    let text = format!(
+        "猫を見た{}東京に行く{}",
+        "ｗ".repeat(5_000),
+        "1".repeat(5_000)
+    );
+    let tokens = tokenizer.tokenize(&text);
+    assert_eq!(surfaces(&tokens).concat(), text);
+    assert_eq!(surfaces(&tokens[..3]), ["猫", "を", "見た"]);
+
+    let snapshot = tokenizer.export_lattice(&text);
+    assert!(snapshot
+        .nodes
+        .iter()
+        .all(|node| node.node.score.is_finite()));
+}
+
+/// About 2800 characters of English, ending in a space.
+fn english() -> String {
+    "lorem ipsum dolor sit amet, consectetur adipiscing. ".repeat(54)
+}
+
+#[test]
+fn japanese_after_a_long_english_prefix_is_segmented_alike() {
+    let tokenizer = Tokenizer::new(Arc::new(fixture_dictionary()));
+    let japanese = "私は学生です。今日は天気が良い。東京に行く。";
+    let prefix = english();
+    let text = format!("{prefix}{japanese}");
+    let tokens = tokenizer.tokenize(&text);
+    assert_eq!(surfaces(&tokens).concat(), text);
+
+    let alone = tokenizer.tokenize(japanese);
+    let after_prefix: Vec<_> = tokens
+        .iter()
+        .filter(|token| token.byte_start >= prefix.len())
+        .collect();
+    assert_eq!(after_prefix.len(), alone.len());
+    for (token, expected) in after_prefix.iter().zip(alone.iter()) {
+        assert_eq!(token.token, expected.token);
+        assert_eq!(token.byte_start, prefix.len() + expected.byte_start);
+        assert_eq!(token.term_id, expected.term_id);
+    }
+
+    // The English is still split into words, spaces and punctuation.
+    for surface in surfaces(&tokens[..tokens.len() - alone.len()]) {
+        let word = surface.chars().all(|c| c.is_ascii_alphabetic());
+        assert!(word || [" ", ",", "."].contains(&surface), "{surface:?}");
+    }
+}
+
+#[test]
+fn words_at_the_edges_of_a_long_english_run_are_found() {
+    // Words reaching into the run from the Japanese on either side, as words without
+    // kanji or kana never match.
+    let mut dictionary = fixture_dictionary();
+    dictionary.add_word(
+        1000,
+        Some("新NISA"),
+        "しんにーさ",
+        PartOfSpeech::NOUN,
+        Tag::NONE,
+    );
+    dictionary.add_word(
+        1001,
+        Some("Tシャツ"),
+        "てぃーしゃつ",
+        PartOfSpeech::NOUN,
+        Tag::NONE,
+    );
+    let tokenizer = Tokenizer::new(Arc::new(dictionary));
+
+    for english in [String::new(), english()] {
+        let text = format!("猫を見た新NISA {english}Tシャツを見た");
+        let tokens = tokenizer.tokenize(&text);
+        assert_eq!(surfaces(&tokens).concat(), text);
+        assert_eq!(surfaces(&tokens[..5]), ["猫", "を", "見た", "新NISA", " "]);
+        assert_eq!(
+            surfaces(&tokens[tokens.len() - 3..]),
+            ["Tシャツ", "を", "見た"]
+        );
+    }
+}
+
+#[test]
+fn long_latin_words_are_split_inside_english_runs() {
+    let tokenizer = Tokenizer::new(Arc::new(fixture_dictionary()));
+    let max_length = TokenizerOptions::default().max_unknown_length;
+    let text = format!("猫{}{}{}猫", english(), "x".repeat(300), english());
+    let tokens = tokenizer.tokenize(&text);
+
+    assert_eq!(surfaces(&tokens).concat(), text);
+    assert!(tokens
+        .iter()
+        .all(|token| token.token.chars().count() <= max_length));
+    assert_eq!(surfaces(&tokens[..2]), ["猫", "lorem"]);
+}
@@ -0,0 +1,129 @@
+use segmenter::dictionary::{
+    Dictionary, DictionaryBuilder, InflectionType, MatchSource, PartOfSpeech, PrefixHit,
+    Tag,
+};
+
+const WORDS: &[(Option<&str>, &str, PartOfSpeech)] = &[
+    (Some("橋"), "はし", PartOfSpeech::NOUN),
+    (Some("箸"), "はし", PartOfSpeech::NOUN),
+    (Some("端"), "はし", PartOfSpeech::NOUN),
+    (Some("食べる"), "たべる", PartOfSpeech::ICHIDAN_VERB),
+    (Some("見る"), "みる", PartOfSpeech::ICHIDAN_VERB),
+    (Some("見た"), "みた", PartOfSpeech::NOUN),
+    (Some("食べ物"), "たべもの", PartOfSpeech::NOUN),
+    (None, "たべ", PartOfSpeech::NOUN),
+];
+
+fn dictionary() -> Dictionary {
+    let mut builder = DictionaryBuilder::new();
+    for (kanji, kana, pos) in WORDS {
+        builder.add(*kanji, kana, *pos, Tag::NONE);
+    }
+    builder.build()
+}
+
+#[test]
+fn conjugated_surfaces_find_their_base_form() {
+    let dictionary = dictionary();
+    let hits = dictionary.lookup_detailed("食べた");
+    assert_eq!(hits.len(), 1);
+    assert_eq!(hits[0].entry_index, 3);
+    assert_eq!(hits[0].inflection_type, InflectionType::Past);
+    assert_eq!(hits[0].pos, PartOfSpeech::ICHIDAN_VERB);
+    assert_eq!(
+        hits[0].matched_via,
+        MatchSource::Deinflected {
+            base: "食べる".to_string()
+        }
+    );
+
+    // Kana surfaces deinflect too.
+    let hits = dictionary.lookup_detailed("たべなかった");
+    assert_eq!(hits.len(), 1);
+    assert_eq!(hits[0].inflection_type, InflectionType::NegativePast);
+}
+
+#[test]
+fn homographs_are_all_returned() {
+    let dictionary = dictionary();
+    let hits = dictionary.lookup_detailed("はし");
+    assert_eq!(
+        hits.iter().map(|hit| hit.entry_index).collect::<Vec<_>>(),
+        [0, 1, 2]
+    );
+    assert!(hits.iter().all(|hit| hit.matched_via == MatchSource::Kana
+        && hit.inflection_type == InflectionType::DictionaryForm));
+
+    let hits = dictionary.lookup_detailed("箸");
+    assert_eq!(hits.len(), 1);
+    assert_eq!(
+        (hits[0].entry_index, &hits[0].matched_via),
+        (1, &MatchSource::Kanji)
+    );
+}
+
+#[test]
+fn exact_matches_come_before_deinflected_ones() {
+    let dictionary = dictionary();
+    let hits = dictionary.lookup_detailed("見た");
+    assert_eq!(hits.len(), 2);
+    assert_eq!(
+        (hits[0].entry_index, &hits[0].matched_via),
+        (5, &MatchSource::Kanji)
+    );
+    assert_eq!(hits[1].entry_index, 4);
+    assert_eq!(hits[1].inflection_type, InflectionType::Past);
+
+    assert!(dictionary.lookup_detailed("猫").is_empty());
+    assert!(dictionary.lookup_detailed("").is_empty());
+}
+
+fn prefix_surfaces<'a>(hits: &[PrefixHit<'a>]) -> Vec<&'a str> {
+    hits.iter().map(|hit| hit.surface).collect()
+}
+
+#[test]
+fn prefix_search_orders_by_priority() {
+    let dictionary = dictionary();
+    assert_eq!(
+        prefix_surfaces(&dictionary.prefix_search("食べ", 10)),
+        ["食べる", "食べ物"]
+    );
+    assert_eq!(
+        prefix_surfaces(&dictionary.prefix_search("たべ", 10)),
+        ["たべる", "たべもの", "たべ"]
+    );
+    assert_eq!(
+        prefix_surfaces(&dictionary.prefix_search("見", 10)),
+        ["見る", "見た"]
+    );
+
+    // Homographs collapse into one surface with the best priority.
+    let hits = dictionary.prefix_search("は", 10);
+    assert_eq!(
+        hits,
+        [PrefixHit {
+            surface: "はし",
+            priority: 0
+        }]
+    );
+    assert!(dictionary.prefix_search("猫", 10).is_empty());
+}
+
+#[test]
+fn prefix_search_respects_the_limit() {
+    let dictionary = dictionary();
+    assert_eq!(
+        prefix_surfaces(&dictionary.prefix_search("たべ", 1)),
+        ["たべる"]
+    );
+    assert!(dictionary.prefix_search("たべ", 0).is_empty());
+
+    // An empty prefix matches every surface.
+    let all = dictionary.prefix_search("", usize::MAX);
+    assert_eq!(all.len(), dictionary.kanji.len() + dictionary.kana.len());
+    assert_eq!(
+        prefix_surfaces(&dictionary.prefix_search("", 3)),
+        ["はし", "橋", "箸"]
+    );
+}
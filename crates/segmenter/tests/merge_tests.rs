@@ -0,0 +1,91 @@
+use segmenter::dictionary::{Dictionary, DictionaryBuilder, PartOfSpeech, Tag};
+use segmenter::merge::{MergeRule, PosPattern, TokenMerger};
+use segmenter::tokenizer::{Token, Tokenizer};
+use std::sync::Arc;
+
+/// Verb stems and the て linking them, as a dictionary without conjugated forms has
+/// them, and a prefixed noun.
+fn dictionary() -> Dictionary {
+    let mut builder = DictionaryBuilder::new();
+    for (kanji, kana, pos) in [
+        (Some("食べ"), "たべ", PartOfSpeech::ICHIDAN_VERB),
+        (None, "て", PartOfSpeech::PARTICLE),
+        (None, "い", PartOfSpeech::ICHIDAN_VERB),
+        (None, "お", PartOfSpeech::PREFIX),
+        (Some("茶"), "ちゃ", PartOfSpeech::NOUN),
+        (None, "を", PartOfSpeech::PARTICLE),
+    ] {
+        builder.add(kanji, kana, pos, Tag::NONE);
+    }
+    builder.build()
+}
+
+fn merged(merger: &TokenMerger, text: &str) -> Vec<String> {
+    let tokenizer = Tokenizer::new(Arc::new(dictionary()));
+    merger
+        .apply(text, tokenizer.tokenize(text))
+        .iter()
+        .map(|token| token.token.to_string())
+        .collect()
+}
+
+#[test]
+fn conversational_rules_join_verbs_with_their_auxiliaries() {
+    let merger = TokenMerger::conversational();
+    assert_eq!(merged(&merger, "食べています"), ["食べています"]);
+    assert_eq!(
+        merged(&merger, "お茶を食べています"),
+        ["お茶", "を", "食べています"]
+    );
+}
+
+#[test]
+fn indexing_rules_keep_verbs_apart() {
+    let merger = TokenMerger::indexing();
+    assert_eq!(
+        merged(&merger, "食べています"),
+        ["食べ", "て", "い", "ます"]
+    );
+    assert_eq!(
+        merged(&merger, "お茶を食べています"),
+        ["お茶", "を", "食べ", "て", "い", "ます"]
+    );
+}
+
+#[test]
+fn without_rules_nothing_is_merged() {
+    let merger = TokenMerger::new();
+    assert_eq!(merged(&merger, "お茶を"), ["お", "茶", "を"]);
+}
+
+#[test]
+fn later_rules_take_precedence() {
+    let merger = TokenMerger::conversational().rule(MergeRule::forbid(
+        PosPattern::Surface("て".to_string()),
+        PosPattern::Any,
+    ));
+    assert_eq!(merged(&merger, "食べています"), ["食べて", "います"]);
+}
+
+#[test]
+fn merged_tokens_keep_the_first_term_and_their_parts() {
+    let text = "お茶を";
+    let tokenizer = Tokenizer::new(Arc::new(dictionary()));
+    let tokens = TokenMerger::indexing().apply(text, tokenizer.tokenize(text));
+
+    let ocha = &tokens[0];
+    assert_eq!(
+        (ocha.token, ocha.char_start, ocha.byte_start),
+        ("お茶", 0, 0)
+    );
+    assert_eq!(ocha.term_id, Some(3));
+    assert_eq!(ocha.pos, PartOfSpeech::PREFIX);
+    let components: Vec<&str> = ocha
+        .components
+        .iter()
+        .flatten()
+        .map(|token: &Token| token.token)
+        .collect();
+    assert_eq!(components, ["お", "茶"]);
+    assert_eq!((tokens[1].token, tokens[1].char_start), ("を", 2));
+}
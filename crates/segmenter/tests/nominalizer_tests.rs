@@ -0,0 +1,83 @@
+use segmenter::dictionary::{DictionaryBuilder, PartOfSpeech, Tag};
+use segmenter::tokenizer::{Token, Tokenizer, TokenizerOptions};
+use std::sync::Arc;
+
+const WORDS: &[(Option<&str>, &str, PartOfSpeech)] = &[
+    (Some("行く"), "いく", PartOfSpeech::GODAN_VERB),
+    (Some("食べる"), "たべる", PartOfSpeech::ICHIDAN_VERB),
+    (Some("読む"), "よむ", PartOfSpeech::GODAN_VERB),
+    (Some("見る"), "みる", PartOfSpeech::ICHIDAN_VERB),
+    (Some("好き"), "すき", PartOfSpeech::NOUN),
+    (Some("本"), "ほん", PartOfSpeech::NOUN),
+    (None, "やめる", PartOfSpeech::ICHIDAN_VERB),
+];
+
+fn tokenizer(nominalizer_bonus: f32) -> Tokenizer {
+    let mut builder = DictionaryBuilder::new();
+    for (kanji, kana, pos) in WORDS {
+        builder.add(*kanji, kana, *pos, Tag::NONE);
+    }
+    let options = TokenizerOptions {
+        nominalizer_bonus,
+        deinflect: true,
+        ..Default::default()
+    };
+    Tokenizer::with_options(Arc::new(builder.build()), options)
+}
+
+fn surfaces<'a>(tokens: &[Token<'a>]) -> Vec<&'a str> {
+    tokens.iter().map(|token| token.token).collect()
+}
+
+#[test]
+fn nominalized_verbs() {
+    let tokenizer = tokenizer(TokenizerOptions::default().nominalizer_bonus);
+    for (text, expected) in [
+        ("行くのが好き", &["行く", "の", "が", "好き"][..]),
+        (
+            "本を読むのが好きだ",
+            &["本", "を", "読む", "の", "が", "好き", "だ"],
+        ),
+        ("見るのをやめた", &["見る", "の", "を", "やめた"]),
+        ("食べるんです", &["食べる", "んです"]),
+        ("行くんですか", &["行く", "んですか"]),
+    ] {
+        assert_eq!(surfaces(&tokenizer.tokenize(text)), expected, "{text}");
+    }
+}
+
+#[test]
+fn contractions_are_auxiliaries() {
+    let tokenizer = tokenizer(TokenizerOptions::default().nominalizer_bonus);
+    for text in ["食べたんだ", "食べるんです", "行かないんですか"] {
+        let tokens = tokenizer.tokenize(text);
+        let contraction = tokens.last().unwrap();
+        assert!(contraction.token.starts_with('ん'), "{text}");
+        assert_eq!(contraction.pos, PartOfSpeech::AUXILIARY_VERB, "{text}");
+    }
+}
+
+#[test]
+fn bonus_applies_to_plain_verbs_only() {
+    // A large negative bonus pushes the path away from every transition it applies to.
+    let tokenizer = tokenizer(-1000.0);
+    assert_ne!(
+        surfaces(&tokenizer.tokenize("行くのが好き"))[..2],
+        ["行く", "の"]
+    );
+    let tokens = tokenizer.tokenize("食べるんです");
+    assert!(tokens
+        .iter()
+        .all(|token| token.pos != PartOfSpeech::AUXILIARY_VERB));
+
+    // Neither ので nor a noun before の is a nominalizer.
+    assert_eq!(surfaces(&tokenizer.tokenize("行くので")), ["行く", "ので"]);
+    assert_eq!(surfaces(&tokenizer.tokenize("本の")), ["本", "の"]);
+}
+
+#[test]
+fn snapshots_replay_with_the_bonus() {
+    let snapshot = tokenizer(-1000.0).export_lattice("行くのが好き");
+    assert_eq!(snapshot.nominalizer_bonus, -1000.0);
+    assert_eq!(snapshot.replay(), snapshot.path);
+}
@@ -0,0 +1,75 @@
+mod common;
+
+use common::fixture_dictionary;
+use segmenter::analysis::OwnedToken;
+use segmenter::pipeline::Segmenter;
+use segmenter::tokenizer::Tokenizer;
+use std::sync::Arc;
+
+const SENTENCES: &[&str] = &[
+    "猫を見た",
+    "私は学生です",
+    "東京に行く",
+    "「猫」を見た。",
+    "佐藤さんが会社に行く",
+    "面白いｗｗｗ",
+    "",
+];
+
+fn lines(count: usize) -> Vec<String> {
+    (0..count)
+        .map(|i| format!("{}{i}", SENTENCES[i % SENTENCES.len()]))
+        .collect()
+}
+
+#[test]
+fn parallel_output_matches_serial() {
+    let tokenizer = Arc::new(Tokenizer::new(Arc::new(fixture_dictionary())));
+    let input = lines(10_000);
+
+    let serial: Vec<(String, Vec<OwnedToken>)> = input
+        .iter()
+        .map(|line| {
+            let tokens = tokenizer
+                .tokenize(line)
+                .iter()
+                .map(|token| OwnedToken::from((token, &*tokenizer.dictionary())))
+                .collect();
+            (line.clone(), tokens)
+        })
+        .collect();
+
+    let segmenter = Segmenter::new(tokenizer, 4);
+    let parallel: Vec<_> = segmenter.process(input).collect();
+    assert_eq!(parallel.len(), serial.len());
+    assert!(parallel == serial);
+}
+
+#[test]
+fn results_can_be_consumed_lazily() {
+    let tokenizer = Arc::new(Tokenizer::new(Arc::new(fixture_dictionary())));
+    let segmenter = Segmenter::new(tokenizer, 2);
+
+    // An endless input only gets pulled as far as the results are needed.
+    let endless = (0..).map(|i| format!("猫を見た{i}"));
+    let firsts: Vec<String> = segmenter
+        .process(endless)
+        .take(3)
+        .map(|(line, _)| line)
+        .collect();
+    assert_eq!(firsts, ["猫を見た0", "猫を見た1", "猫を見た2"]);
+
+    assert_eq!(segmenter.process(Vec::new()).count(), 0);
+}
+
+#[test]
+fn a_panicking_worker_panics_the_consumer() {
+    // Tokens point at entries the dictionary no longer has, so resolving them panics.
+    let mut dictionary = fixture_dictionary();
+    dictionary.entries.clear();
+    let tokenizer = Arc::new(Tokenizer::new(Arc::new(dictionary)));
+    let segmenter = Segmenter::new(tokenizer, 2);
+
+    let result = std::panic::catch_unwind(|| segmenter.process(lines(10)).count());
+    assert!(result.is_err());
+}
@@ -0,0 +1,64 @@
+mod common;
+
+use common::{fixture_dictionary, surfaces};
+use segmenter::dictionary::{Dictionary, PartOfSpeech, Tag};
+use segmenter::tokenizer::{Tokenizer, TokenizerOptions};
+use std::sync::Arc;
+
+/// The fixture dictionary with の as a nominalizer besides a particle, and で as a
+/// particle and the te-form of the copula.
+fn dictionary() -> Dictionary {
+    let mut dictionary = fixture_dictionary();
+    dictionary.add_word(1000, None, "の", PartOfSpeech::NOUN, Tag::NONE);
+    dictionary.add_word(1001, None, "で", PartOfSpeech::PARTICLE, Tag::NONE);
+    dictionary.add_word(1002, None, "で", PartOfSpeech::COPULA, Tag::NONE);
+    // Same part of speech as the other particle entry, so it adds no alternative.
+    dictionary.add_word(1003, None, "の", PartOfSpeech::PARTICLE, Tag::NONE);
+    dictionary
+}
+
+fn tokenizer(pos_alternatives: bool) -> Tokenizer {
+    let options = TokenizerOptions {
+        pos_alternatives,
+        ..TokenizerOptions::default()
+    };
+    Tokenizer::with_options(Arc::new(dictionary()), options)
+}
+
+#[test]
+fn ambiguous_particles_report_every_part_of_speech() {
+    let tokens = tokenizer(true).tokenize("猫の方");
+    assert_eq!(surfaces(&tokens), ["猫", "の", "方"]);
+    let no = &tokens[1];
+    assert_eq!(no.pos_alternatives.len(), 2);
+    assert_eq!(no.pos_alternatives[0], no.pos);
+    assert!(no.pos_alternatives.contains(&PartOfSpeech::PARTICLE));
+    assert!(no.pos_alternatives.contains(&PartOfSpeech::NOUN));
+    assert_eq!(tokens[0].pos_alternatives, [PartOfSpeech::NOUN]);
+
+    let tokens = tokenizer(true).tokenize("東京で働く");
+    assert_eq!(surfaces(&tokens), ["東京", "で", "働く"]);
+    let de = &tokens[1].pos_alternatives;
+    assert_eq!(de.len(), 2);
+    assert!(de.contains(&PartOfSpeech::PARTICLE));
+    assert!(de.contains(&PartOfSpeech::COPULA));
+}
+
+#[test]
+fn alternatives_are_only_collected_when_asked_for() {
+    let tokens = tokenizer(false).tokenize("猫の方");
+    assert!(tokens.iter().all(|token| token.pos_alternatives.is_empty()));
+
+    let tokens =
+        Tokenizer::with_options(Arc::new(dictionary()), TokenizerOptions::analysis())
+            .tokenize("猫の方");
+    assert_eq!(tokens[1].pos_alternatives.len(), 2);
+}
+
+#[test]
+fn unknown_tokens_have_no_alternatives() {
+    let tokens = tokenizer(true).tokenize("猫、ＸＹＺ");
+    assert!(tokens[1..]
+        .iter()
+        .all(|token| token.pos_alternatives.is_empty()));
+}
@@ -0,0 +1,146 @@
+mod common;
+
+use common::{fixture_dictionary, surfaces};
+use segmenter::tokenizer::{Tokenizer, TokenizerOptions};
+use std::sync::Arc;
+
+fn tokenizer(options: TokenizerOptions) -> Tokenizer {
+    Tokenizer::with_options(Arc::new(fixture_dictionary()), options)
+}
+
+fn presets() -> [(&'static str, Tokenizer); 3] {
+    [
+        ("reader", tokenizer(TokenizerOptions::reader())),
+        ("search", tokenizer(TokenizerOptions::search())),
+        ("analysis", tokenizer(TokenizerOptions::analysis())),
+    ]
+}
+
+#[test]
+fn conjugations_stay_whole_except_for_analysis() {
+    let text = "今日は天気が良かった";
+    for (name, tokenizer) in presets() {
+        let expected: &[&str] = match name {
+            "analysis" => &["今日", "は", "天気", "が", "良", "か", "った"],
+            "search" => &["今日", "天気", "良かった"],
+            _ => &["今日", "は", "天気", "が", "良かった"],
+        };
+        assert_eq!(surfaces(&tokenizer.tokenize(text)), expected, "{name}");
+    }
+}
+
+#[test]
+fn only_the_reader_passes_foreign_sentences_through() {
+    let text = "猫を見た。This is a pen.";
+    for (name, tokenizer) in presets() {
+        let tokens = tokenizer.tokenize(text);
+        let expected: &[&str] = match name {
+            "reader" => &["猫", "を", "見た", "。", "This is a pen."],
+            "search" => &["猫", "見た", "This", " ", "is", " ", "a", " ", "pen"],
+            _ => &[
+                "猫", "を", "見た", "。", "This", " ", "is", " ", "a", " ", "pen", ".",
+            ],
+        };
+        assert_eq!(surfaces(&tokens), expected, "{name}");
+    }
+}
+
+#[test]
+fn only_search_drops_punctuation_and_stopwords() {
+    let text = "「猫」を見た。";
+    for (name, tokenizer) in presets() {
+        let tokens = tokenizer.tokenize(text);
+        if name == "search" {
+            assert_eq!(surfaces(&tokens), ["猫", "見た"]);
+            let gaps = tokenizer.tokenize_checked(text).unwrap().gaps;
+            assert_eq!(gaps, [0..3, 6..12, 18..21]);
+        } else {
+            assert_eq!(
+                surfaces(&tokens),
+                ["「", "猫", "」", "を", "見た", "。"],
+                "{name}"
+            );
+        }
+    }
+}
+
+#[test]
+fn search_and_analysis_decompose_expressions() {
+    for (name, tokenizer) in presets() {
+        let tokens = tokenizer.tokenize("かもしれない");
+        assert_eq!(surfaces(&tokens), ["かもしれない"], "{name}");
+        let components = tokens[0].components.as_deref().map(surfaces);
+        if name == "reader" {
+            assert_eq!(components, None);
+        } else {
+            let parts = ["か", "も", "しれ", "ない"];
+            assert_eq!(components.as_deref(), Some(&parts[..]), "{name}");
+        }
+    }
+}
+
+#[test]
+fn analysis_keeps_the_text_as_it_is() {
+    for (name, tokenizer) in presets() {
+        let tokens = tokenizer.tokenize("田\u{200B}中さん");
+        let tokenization = tokenizer.tokenize_checked("東京都").unwrap();
+        assert_eq!(surfaces(&tokenization.tokens), ["東京", "都"], "{name}");
+        let covering = tokenization.covering_entries.len();
+        if name == "analysis" {
+            assert_eq!(surfaces(&tokens), ["田", "\u{200B}", "中", "さん"]);
+            assert!(covering > 0);
+        } else {
+            assert_eq!(surfaces(&tokens), ["田\u{200B}中", "さん"], "{name}");
+            assert_eq!(covering, 0, "{name}");
+        }
+    }
+}
+
+/// Lists every option, so a new one doesn't compile until it is added here, which is
+/// the reminder to decide what each preset sets it to.
+#[test]
+fn presets_cover_every_option() {
+    for options in [
+        TokenizerOptions::reader(),
+        TokenizerOptions::search(),
+        TokenizerOptions::analysis(),
+    ] {
+        let TokenizerOptions {
+            single_char_factor: _,
+            decompose_expressions: _,
+            emphasis_threshold: _,
+            deinflect: _,
+            stem_particle_penalty: _,
+            acronym_bonus: _,
+            katakana_seam_bonus: _,
+            other_script_penalty: _,
+            irregular_form_penalty: _,
+            ateji_bonus: _,
+            max_unknown_length: _,
+            word_length_power: _,
+            length_power: _,
+            unknown_words: _,
+            dictionary_bonus: _,
+            dialect_bonus: _,
+            domain_boost: _,
+            function_word_bonus: _,
+            particle_connection_bonus: _,
+            nominalizer_bonus: _,
+            unknown_coverage_penalty: _,
+            strict_coverage: _,
+            covering_entry_min_length: _,
+            skip_non_japanese_sentences: _,
+            japanese_thresholds: _,
+            sentence_rules: _,
+            skip_invisible_characters: _,
+            repair_trailing_fragments: _,
+            collapse_repeats: _,
+            fold_historical_kana: _,
+            cache_lookup_misses: _,
+            window: _,
+            pos_alternatives: _,
+            drop_punctuation: _,
+            drop_stopwords: _,
+        } = options;
+    }
+}
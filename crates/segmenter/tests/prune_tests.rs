@@ -0,0 +1,131 @@
+mod common;
+
+use common::fixture_dictionary;
+use segmenter::dictionary::{Dictionary, PartOfSpeech, Tag, TermEntry, TermId};
+use segmenter::tokenizer::Tokenizer;
+use std::sync::Arc;
+
+/// The fixture dictionary with 30 archaic nouns read は and 30 read はし.
+fn dictionary_with_homographs() -> Dictionary {
+    let mut dictionary = fixture_dictionary();
+    let mut term_id = 10_000;
+    for surface in ["は", "はし"] {
+        for _ in 0..30 {
+            dictionary.add_word(term_id, None, surface, PartOfSpeech::NOUN, Tag::ARCHAIC);
+            term_id += 1;
+        }
+    }
+    dictionary
+}
+
+fn parts_of_speech(dictionary: &Dictionary, surface: &str) -> Vec<PartOfSpeech> {
+    dictionary.kana[surface]
+        .iter()
+        .map(|term_entry| dictionary.entries[term_entry.entry_index as usize].pos)
+        .collect()
+}
+
+fn term_ids(dictionary: &Dictionary, term_entries: &[TermEntry]) -> Vec<TermId> {
+    term_entries
+        .iter()
+        .map(|term_entry| dictionary.entries[term_entry.entry_index as usize].term_id)
+        .collect()
+}
+
+#[test]
+fn pruning_keeps_the_best_entries() {
+    let mut dictionary = dictionary_with_homographs();
+    let before = dictionary.kana["はし"].clone();
+    assert_eq!(dictionary.prune(3), 27 + 28);
+    assert_eq!(dictionary.kana["はし"], before[..3]);
+}
+
+#[test]
+fn pruning_keeps_every_part_of_speech() {
+    let mut dictionary = dictionary_with_homographs();
+    dictionary.add_word(20_000, None, "は", PartOfSpeech::PREFIX, Tag::NONE);
+    dictionary.prune(2);
+
+    // Homograph order puts the particle first and the archaic nouns last.
+    assert_eq!(
+        parts_of_speech(&dictionary, "は"),
+        [
+            PartOfSpeech::PARTICLE,
+            PartOfSpeech::PREFIX,
+            PartOfSpeech::NOUN
+        ]
+    );
+    assert_eq!(
+        parts_of_speech(&dictionary, "はし"),
+        [PartOfSpeech::NOUN, PartOfSpeech::NOUN]
+    );
+
+    // Surfaces within the limit are left alone.
+    assert_eq!(dictionary.kana["きょう"].len(), 1);
+}
+
+#[test]
+fn generous_pruning_keeps_the_tokenization() {
+    const CORPUS: &[&str] = &[
+        "私は学生です",
+        "今日は良い天気ですね",
+        "はしを見た",
+        "田中さんはお茶を飲みました",
+        "東京には橋がある",
+        "ははははは",
+    ];
+
+    let full = Tokenizer::new(Arc::new(dictionary_with_homographs()));
+    let mut dictionary = dictionary_with_homographs();
+    assert!(dictionary.prune(5) > 0);
+    let pruned = Tokenizer::new(Arc::new(dictionary));
+    for text in CORPUS {
+        assert_eq!(
+            format!("{:?}", pruned.tokenize(text)),
+            format!("{:?}", full.tokenize(text)),
+            "{text}"
+        );
+    }
+}
+
+#[test]
+fn compacting_drops_the_entries_pruning_orphaned() {
+    let full = dictionary_with_homographs();
+    let mut dictionary = dictionary_with_homographs();
+    dictionary.prune(3);
+    // A surface emptied by hand goes as well, and with it the entry only it had.
+    dictionary.kana.get_mut("すごい").unwrap().clear();
+
+    let report = dictionary.compact();
+    assert_eq!(report.entries, 27 + 28 + 1);
+    assert_eq!(report.keys, 1);
+    assert_eq!(
+        dictionary.entries.len(),
+        full.entries.len() - report.entries
+    );
+    dictionary.validate().unwrap();
+    assert!(!dictionary.kana.contains_key("すごい"));
+    assert!(dictionary
+        .entries
+        .iter()
+        .all(|entry| entry.term_id != 10_029));
+
+    // Every remaining surface still finds what it found before pruning, as far as
+    // pruning kept it.
+    for (surface, term_entries) in dictionary.kanji.iter() {
+        let before = term_ids(&full, &full.kanji[surface]);
+        assert_eq!(term_ids(&dictionary, term_entries), before);
+    }
+    for (surface, term_entries) in dictionary.kana.iter() {
+        let before = term_ids(&full, &full.kana[surface]);
+        assert_eq!(
+            term_ids(&dictionary, term_entries),
+            before[..term_entries.len()]
+        );
+    }
+    let tokens = Tokenizer::new(Arc::new(dictionary.clone())).tokenize("はしを見た");
+    assert_eq!(tokens[0].term_id, Some(10_030));
+
+    // Compacting again finds nothing left to do.
+    assert_eq!(dictionary.compact(), Default::default());
+}
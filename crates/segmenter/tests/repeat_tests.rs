@@ -0,0 +1,65 @@
+mod common;
+
+use common::{fixture_dictionary, surfaces};
+use segmenter::dictionary::{Dictionary, PartOfSpeech, Tag};
+use segmenter::tokenizer::{Tokenizer, TokenizerOptions};
+use std::sync::Arc;
+
+/// The fixture dictionary with a word spelled with the kanji iteration mark.
+fn dictionary() -> Dictionary {
+    let mut dictionary = fixture_dictionary();
+    dictionary.add_word(
+        1000,
+        Some("人々"),
+        "ひとびと",
+        PartOfSpeech::NOUN,
+        Tag::NONE,
+    );
+    dictionary
+}
+
+/// A tokenizer without emphasis tokens, so the repeats reach the filter one by one.
+fn tokenizer(collapse_repeats: Option<usize>) -> Tokenizer {
+    let options = TokenizerOptions {
+        emphasis_threshold: 0,
+        collapse_repeats,
+        ..TokenizerOptions::default()
+    };
+    Tokenizer::with_options(Arc::new(dictionary()), options)
+}
+
+#[test]
+fn repeated_tokens_collapse_into_one() {
+    let text = "猫ののの見た";
+    assert_eq!(
+        surfaces(&tokenizer(None).tokenize(text)),
+        ["猫", "の", "の", "の", "見た"]
+    );
+
+    let tokens = tokenizer(Some(3)).tokenize(text);
+    assert_eq!(surfaces(&tokens), ["猫", "ののの", "見た"]);
+    assert_eq!(tokens[1].repeat_count, Some(3));
+    assert_eq!(tokens[1].char_start, 1);
+    assert_eq!(tokens[1].byte_start, "猫".len());
+    assert_eq!(tokens[1].pos, PartOfSpeech::PARTICLE);
+    let components = tokens[1].components.as_ref().unwrap();
+    assert_eq!(surfaces(components), ["の", "の", "の"]);
+    assert_eq!(tokens[0].repeat_count, None);
+    assert_eq!(tokens[2].repeat_count, None);
+    assert_eq!(surfaces(&tokens).concat(), text);
+}
+
+#[test]
+fn shorter_runs_are_kept() {
+    let tokens = tokenizer(Some(4)).tokenize("猫ののの見た");
+    assert_eq!(surfaces(&tokens), ["猫", "の", "の", "の", "見た"]);
+    assert!(tokens.iter().all(|token| token.repeat_count.is_none()));
+}
+
+#[test]
+fn words_spelled_with_a_repeat_stay_whole() {
+    let tokens = tokenizer(Some(2)).tokenize("人々を見た");
+    assert_eq!(surfaces(&tokens), ["人々", "を", "見た"]);
+    assert_eq!(tokens[0].term_id, Some(1000));
+    assert!(tokens.iter().all(|token| token.repeat_count.is_none()));
+}
@@ -0,0 +1,72 @@
+mod common;
+
+use common::{fixture_dictionary, surfaces};
+use segmenter::dictionary::{DictionaryBuilder, PartOfSpeech, Tag};
+use segmenter::tokenizer::{Token, Tokenizer, TokenizerOptions};
+use std::cell::RefCell;
+use std::sync::Arc;
+
+/// Takes 国際 and 空港 over the 国際空港 entry, since scores stop growing at two
+/// characters.
+fn compound_tokenizer() -> Tokenizer {
+    let mut builder = DictionaryBuilder::new();
+    for (kanji, kana) in [
+        ("国際", "こくさい"),
+        ("空港", "くうこう"),
+        ("国際空港", "こくさいくうこう"),
+    ] {
+        builder.add(Some(kanji), kana, PartOfSpeech::NOUN, Tag::NONE);
+    }
+    builder.add(None, "に", PartOfSpeech::PARTICLE, Tag::NONE);
+    let options = TokenizerOptions {
+        max_unknown_length: 2,
+        ..Default::default()
+    };
+    Tokenizer::with_options(Arc::new(builder.build()), options)
+}
+
+fn fewer_tokens(tokens: &[Token]) -> f32 {
+    -(tokens.len() as f32)
+}
+
+#[test]
+fn rescoring_flips_the_choice() {
+    let tokenizer = compound_tokenizer();
+    let text = "国際空港に";
+    assert_eq!(surfaces(&tokenizer.tokenize(text)), ["国際", "空港", "に"]);
+
+    let tokens = tokenizer.tokenize_rescored(text, 4, fewer_tokens);
+    assert_eq!(surfaces(&tokens), ["国際空港", "に"]);
+    assert_eq!(tokens[0].term_id, Some(2));
+    assert_eq!(tokens[0].pos, PartOfSpeech::NOUN);
+    assert_eq!((tokens[1].char_start, tokens[1].byte_start), (4, 12));
+
+    // Only the best path is a candidate with n = 1.
+    let tokens = tokenizer.tokenize_rescored(text, 1, fewer_tokens);
+    assert_eq!(surfaces(&tokens), ["国際", "空港", "に"]);
+}
+
+#[test]
+fn ties_keep_the_lattice_order() {
+    let tokenizer = Tokenizer::new(Arc::new(fixture_dictionary()));
+    for text in ["東京都", "私は学生です", "今日は天気が良い"] {
+        let tokens = tokenizer.tokenize_rescored(text, 8, |_| 0.0);
+        assert_eq!(
+            surfaces(&tokens),
+            surfaces(&tokenizer.tokenize(text)),
+            "{text}"
+        );
+    }
+    assert!(tokenizer.tokenize_rescored("", 8, |_| 0.0).is_empty());
+}
+
+#[test]
+fn candidates_are_the_n_best_paths() {
+    let tokenizer = Tokenizer::new(Arc::new(fixture_dictionary()));
+    let candidates = RefCell::new(Vec::new());
+    tokenizer.tokenize_rescored("東京都", 8, |tokens| {
+        candidates.borrow_mut().push(surfaces(tokens).join("|"));
+        0.0
+    });
+    assert_eq!(candidates.into_inner(), ["東京|都", "東|京都"]);
+}
@@ -0,0 +1,173 @@
+#![cfg(feature = "romaji")]
+
+mod common;
+
+use common::fixture_dictionary;
+use segmenter::dictionary::{
+    Dictionary, DictionaryBuilder, MatchSource, PartOfSpeech, Tag, TermId,
+};
+use segmenter::romaji::{fold_romaji, katakana_to_romaji};
+use segmenter::tokenizer::Tokenizer;
+use std::sync::Arc;
+
+const WORDS: &[(&str, &str)] = &[
+    ("プロダクティビティ", "purodakutibiti"),
+    ("コンピューター", "konpyuutaa"),
+    ("スマートフォン", "sumaatofon"),
+    ("パーティー", "paatii"),
+    ("ファイル", "fairu"),
+    ("フォルダ", "foruda"),
+    ("ディスク", "disuku"),
+    ("ベッド", "beddo"),
+    ("キッチン", "kitchin"),
+    ("ヴァイオリン", "vaiorin"),
+    ("ウィキペディア", "wikipedia"),
+    ("チェック", "chekku"),
+    ("シェア", "shea"),
+    ("ジェット", "jetto"),
+    ("ツァー", "tsaa"),
+    ("デュエット", "dyuetto"),
+    ("カップ", "kappu"),
+    ("ショッピング", "shoppingu"),
+    ("コーヒー", "koohii"),
+    ("ソフトウェア", "sofutowea"),
+    ("インターネット", "intaanetto"),
+    ("メール", "meeru"),
+    ("アプリ", "apuri"),
+    ("ゲーム", "geemu"),
+    ("ニュース", "nyuusu"),
+    ("テレビ", "terebi"),
+    ("カメラ", "kamera"),
+    ("デザイン", "dezain"),
+    ("サッカー", "sakkaa"),
+    ("バッグ", "baggu"),
+    ("ホテル", "hoteru"),
+    ("レストラン", "resutoran"),
+    ("チョコレート", "chokoreeto"),
+    ("ジュース", "juusu"),
+    ("ミュージック", "myuujikku"),
+    ("フィルム", "firumu"),
+    ("フェスティバル", "fesutibaru"),
+    ("ヴィーナス", "viinasu"),
+    ("トゥデイ", "tudei"),
+    ("ドゥー", "duu"),
+    ("ウォッチ", "wotchi"),
+    ("ウェブ", "webu"),
+    ("マッチ", "matchi"),
+    ("ピッツァ", "pittsa"),
+    ("ラーメン", "raamen"),
+    ("キャンプ", "kyanpu"),
+    ("ギャラリー", "gyararii"),
+    ("オレンジ", "orenji"),
+    ("シャツ", "shatsu"),
+    ("ティッシュ", "tisshu"),
+];
+
+#[test]
+fn loanwords() {
+    for (katakana, romaji) in WORDS {
+        assert_eq!(
+            katakana_to_romaji(katakana).as_deref(),
+            Some(*romaji),
+            "{katakana}"
+        );
+    }
+}
+
+#[test]
+fn non_katakana_has_no_romaji() {
+    for text in ["", "猫", "ねこ", "ABC", "パン・ダ", "パンを"] {
+        assert_eq!(katakana_to_romaji(text), None, "{text}");
+    }
+}
+
+#[test]
+fn only_unknown_tokens_get_hints() {
+    let tokenizer = Tokenizer::new(Arc::new(fixture_dictionary()));
+    let tokens = tokenizer.tokenize("パンとスマートフォン");
+    assert_eq!(tokens[0].token, "パン");
+    assert_eq!(tokens[0].katakana_romaji(), None);
+    assert_eq!(tokens[2].token, "スマートフォン");
+    assert_eq!(tokens[2].katakana_romaji().as_deref(), Some("sumaatofon"));
+}
+
+#[test]
+fn spelling_variants_fold_together() {
+    for variants in [
+        &["toukyou", "tōkyō", "Tokyo", "tookyoo"][..],
+        &["shinbun", "sinbun"],
+        &["tsukue", "tukue"],
+        &["chizu", "tizu"],
+        &["fuji", "huzi"],
+        &["matcha", "mattya"],
+        &["raamen", "ramen", "rāmen", "ra-men"],
+    ] {
+        let folded = fold_romaji(variants[0]);
+        for variant in variants[1..].iter() {
+            assert_eq!(fold_romaji(variant), folded, "{variant}");
+        }
+    }
+    assert_ne!(fold_romaji("kite"), fold_romaji("kitte"));
+}
+
+/// 東京, ラーメン and 寿司 with their term ids, without the romaji index.
+fn romaji_dictionary() -> (Dictionary, [TermId; 3]) {
+    let mut builder = DictionaryBuilder::new();
+    let tokyo = builder.add(
+        Some("東京"),
+        "とうきょう",
+        PartOfSpeech::NOUN_PROPER,
+        Tag::NONE,
+    );
+    let ramen = builder.add(None, "ラーメン", PartOfSpeech::NOUN, Tag::NONE);
+    let sushi = builder.add(Some("寿司"), "すし", PartOfSpeech::NOUN, Tag::NONE);
+    (builder.build(), [tokyo, ramen, sushi])
+}
+
+fn term_ids(dictionary: &Dictionary, romaji: &str) -> Vec<TermId> {
+    let hits = dictionary.lookup_romaji(romaji);
+    hits.iter().map(|hit| hit.term_id).collect()
+}
+
+#[test]
+fn romaji_finds_kana_surfaces() {
+    let (mut dictionary, [tokyo, ramen, sushi]) = romaji_dictionary();
+    assert!(dictionary.lookup_romaji("tokyo").is_empty());
+
+    dictionary.build_romaji_index();
+    assert_eq!(term_ids(&dictionary, "toukyou"), [tokyo]);
+    assert_eq!(term_ids(&dictionary, "tokyo"), [tokyo]);
+    assert_eq!(term_ids(&dictionary, "ramen"), [ramen]);
+    assert_eq!(term_ids(&dictionary, "susi"), [sushi]);
+    assert!(dictionary.lookup_romaji("kyoto").is_empty());
+
+    let hit = &dictionary.lookup_romaji("tokyo")[0];
+    assert_eq!(
+        hit.matched_via,
+        MatchSource::Romaji {
+            kana: "とうきょう".to_string()
+        }
+    );
+}
+
+#[test]
+#[cfg(feature = "serde")]
+fn romaji_index_is_an_optional_section() {
+    let (mut dictionary, [tokyo, ..]) = romaji_dictionary();
+    let mut bytes = Vec::new();
+    let header = dictionary.write_to(&mut bytes).unwrap();
+    assert_eq!(header.section_lengths[5], 0);
+    assert!(Dictionary::from_bytes(&bytes).unwrap().romaji.is_empty());
+
+    dictionary.build_romaji_index();
+    let mut bytes = Vec::new();
+    let header = dictionary.write_to(&mut bytes).unwrap();
+    assert!(header.section_lengths[5] > 0);
+    for loaded in [
+        Dictionary::from_bytes(&bytes).unwrap(),
+        Dictionary::from_reader(&bytes[..]).unwrap(),
+    ] {
+        assert_eq!(loaded.romaji, dictionary.romaji);
+        assert_eq!(term_ids(&loaded, "tokyo"), [tokyo]);
+    }
+}
@@ -0,0 +1,64 @@
+mod common;
+
+use common::fixture_dictionary;
+use segmenter::{
+    dictionary::{Dictionary, PartOfSpeech},
+    ruby::RubyRenderer,
+    tokenizer::{Token, Tokenizer},
+};
+use std::sync::Arc;
+
+#[cfg(test)]
+lazy_static::lazy_static! {
+    static ref DICTIONARY: Arc<Dictionary> = Arc::new(fixture_dictionary());
+    static ref TOKENIZER: Tokenizer = Tokenizer::new(DICTIONARY.clone());
+}
+
+/// Reads 方 as かた after a verb stem (飲み方) and as ほう otherwise.
+fn by_neighbour_pos(
+    _token: &Token,
+    prev: Option<&Token>,
+    _next: Option<&Token>,
+    candidates: &[&str],
+) -> usize {
+    let after_verb = prev.is_some_and(|prev| {
+        prev.pos
+            .intersects(PartOfSpeech::GODAN_VERB | PartOfSpeech::ICHIDAN_VERB)
+    });
+    let wanted = if after_verb { "かた" } else { "ほう" };
+    candidates.iter().position(|c| *c == wanted).unwrap_or(0)
+}
+
+#[test]
+fn candidates_are_ordered_by_priority() {
+    assert_eq!(DICTIONARY.readings_for("今日"), ["きょう", "こんにち"]);
+    assert_eq!(DICTIONARY.readings_for("方"), ["ほう", "かた"]);
+    assert!(DICTIONARY.readings_for("を").is_empty());
+}
+
+#[test]
+fn default_selector_picks_highest_priority() {
+    let renderer = RubyRenderer::new(&DICTIONARY);
+
+    let tokens = TOKENIZER.tokenize("今日は天気が良い");
+    assert_eq!(
+        renderer.html(&tokens),
+        "<ruby>今日<rt>きょう</rt></ruby>は<ruby>天気<rt>てんき</rt></ruby>が\
+         <ruby>良<rt>よ</rt></ruby>い"
+    );
+
+    let tokens = TOKENIZER.tokenize("飲み方");
+    assert_eq!(renderer.furigana(&tokens), "飲[の]み方[ほう]");
+}
+
+#[test]
+fn custom_selector_uses_context() {
+    let renderer = RubyRenderer::new(&DICTIONARY).with_selector(by_neighbour_pos);
+
+    let tokens = TOKENIZER.tokenize("飲み方");
+    assert_eq!(renderer.readings(&tokens), [Some("のみ"), Some("かた")]);
+    assert_eq!(renderer.furigana(&tokens), "飲[の]み方[かた]");
+
+    let tokens = TOKENIZER.tokenize("猫の方が");
+    assert_eq!(renderer.furigana(&tokens), "猫[ねこ]の方[ほう]が");
+}
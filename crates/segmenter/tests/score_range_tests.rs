@@ -0,0 +1,77 @@
+mod common;
+
+use common::{fixture_dictionary, surfaces};
+use segmenter::dictionary::PartOfSpeech;
+use segmenter::lattice::{Lattice, LatticeNode};
+use segmenter::tokenizer::{TokenKind, Tokenizer, TokenizerOptions, MAX_NODE_SCORE};
+use std::sync::Arc;
+
+/// An unknown katakana run far longer than any word, which the high powers below score
+/// at [`MAX_NODE_SCORE`].
+const LONG_RUN: &str =
+    "アイウエオカキクケコサシスセソタチツテトナニヌネノハヒフヘホマミムメモヤユヨラリ";
+
+fn steep_tokenizer(power: f32) -> Tokenizer {
+    let options = TokenizerOptions {
+        word_length_power: power,
+        length_power: power,
+        ..TokenizerOptions::default()
+    };
+    Tokenizer::with_options(Arc::new(fixture_dictionary()), options)
+}
+
+fn node(start: usize, end: usize, score: f32) -> LatticeNode {
+    LatticeNode {
+        term_id: None,
+        term_entry: None,
+        pos: PartOfSpeech::NONE,
+        start,
+        end,
+        score,
+        kind: TokenKind::Word,
+    }
+}
+
+#[test]
+fn small_scores_count_after_a_huge_one() {
+    // In f32, 3e9 + 1 and 3e9 + 4 are both 3e9, and the first ending would win the tie.
+    let mut lattice = Lattice::new(3, 2);
+    for (start, end, score) in [(0, 1, 3.0e9), (1, 2, 1.0), (1, 2, 4.0)] {
+        lattice.add_node(node(start, end, score));
+    }
+
+    let path = lattice.find_path();
+    assert_eq!(path.node_ids(), [0, 2]);
+    assert_eq!(path.score(), 3.0e9 + 4.0);
+    assert_eq!(lattice.find_paths(2)[0].node_ids(), [0, 2]);
+}
+
+#[test]
+fn particles_still_decide_after_a_long_run() {
+    for power in [6.0, 8.0] {
+        let tokenizer = steep_tokenizer(power);
+        for (tail, expected) in [
+            ("ねこをみた", vec!["ねこ", "を", "みた"]),
+            ("私はがくせいです", vec!["私", "は", "がくせい", "です"]),
+        ] {
+            assert_eq!(surfaces(&tokenizer.tokenize(tail)), expected);
+
+            let text = format!("{LONG_RUN}{tail}");
+            let tokens = tokenizer.tokenize(&text);
+            let surfaces = surfaces(&tokens);
+            assert_eq!(surfaces[0], LONG_RUN, "power {power}");
+            assert_eq!(surfaces[1..], expected, "power {power}");
+        }
+    }
+}
+
+#[test]
+fn node_scores_are_capped() {
+    let snapshot = steep_tokenizer(12.0).export_lattice(&format!("{LONG_RUN}ねこ"));
+    let highest = snapshot
+        .nodes
+        .iter()
+        .map(|node| node.node.score)
+        .fold(0.0, f32::max);
+    assert_eq!(highest, MAX_NODE_SCORE);
+}
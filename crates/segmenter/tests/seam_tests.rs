@@ -0,0 +1,62 @@
+use segmenter::dictionary::{Dictionary, DictionaryBuilder, PartOfSpeech, Tag};
+use segmenter::tokenizer::{Tokenizer, TokenizerOptions};
+use std::sync::Arc;
+
+/// Kanji nouns, and katakana fragments of brand names that aren't entries themselves.
+fn dictionary() -> Dictionary {
+    let mut builder = DictionaryBuilder::new();
+    for (kanji, kana) in [("自動車", "じどうしゃ"), ("銀行", "ぎんこう")] {
+        builder.add(Some(kanji), kana, PartOfSpeech::NOUN, Tag::NONE);
+    }
+    for kana in ["トヨ", "タ", "ミズ", "ホ"] {
+        builder.add(None, kana, PartOfSpeech::NOUN, Tag::NONE);
+    }
+    builder.build()
+}
+
+fn surfaces(options: TokenizerOptions, text: &str) -> Vec<String> {
+    let tokenizer = Tokenizer::with_options(Arc::new(dictionary()), options);
+    tokenizer
+        .tokenize(text)
+        .iter()
+        .map(|token| token.token.to_string())
+        .collect()
+}
+
+#[test]
+fn unknown_brand_splits_at_the_script_seam() {
+    let options = TokenizerOptions::default;
+    assert_eq!(surfaces(options(), "トヨタ自動車"), ["トヨタ", "自動車"]);
+    assert_eq!(surfaces(options(), "ミズホ銀行"), ["ミズホ", "銀行"]);
+}
+
+#[test]
+fn without_the_bonus_fragments_win() {
+    let options = TokenizerOptions {
+        katakana_seam_bonus: 0.0,
+        ..TokenizerOptions::default()
+    };
+    assert_eq!(surfaces(options, "トヨタ自動車"), ["トヨ", "タ", "自動車"]);
+}
+
+#[test]
+fn only_runs_before_kanji_get_the_bonus() {
+    let options = TokenizerOptions::default;
+    assert_eq!(surfaces(options(), "トヨタ"), ["トヨ", "タ"]);
+    assert_eq!(
+        surfaces(options(), "トヨタの車"),
+        ["トヨ", "タ", "の", "車"]
+    );
+}
+
+#[test]
+fn runs_longer_than_any_grouping_stay_whole() {
+    let options = TokenizerOptions {
+        max_unknown_length: 3,
+        ..TokenizerOptions::default()
+    };
+    assert_eq!(
+        surfaces(options, "アイウエオ自動車"),
+        ["アイウエオ", "自動車"]
+    );
+}
@@ -0,0 +1,72 @@
+mod common;
+
+use common::fixture_dictionary;
+use segmenter::dictionary::Dictionary;
+use segmenter::error::SegmenterError;
+use segmenter::health::{SelfTestError, SelfTestThresholds, CANARY_SENTENCES};
+use segmenter::tokenizer::Tokenizer;
+use std::sync::Arc;
+
+fn self_test(dictionary: Dictionary) -> Result<(), SegmenterError> {
+    Tokenizer::new(Arc::new(dictionary)).self_test().map(|_| ())
+}
+
+#[test]
+fn fixture_dictionary_passes() {
+    let tokenizer = Tokenizer::new(Arc::new(fixture_dictionary()));
+    let report = tokenizer.self_test().unwrap();
+    assert_eq!(report.entries, fixture_dictionary().entries.len());
+    assert_eq!(report.indexed_fraction, 1.0);
+    assert!(report.canary_tokens >= CANARY_SENTENCES.len());
+    assert!(report.known_fraction >= SelfTestThresholds::default().min_known_fraction);
+}
+
+#[test]
+fn dictionary_without_kanji_surfaces_fails() {
+    let mut dictionary = fixture_dictionary();
+    dictionary.kanji.clear();
+
+    let error = self_test(dictionary).unwrap_err();
+    let SegmenterError::SelfTest(SelfTestError::UnknownCanaries { unknown, .. }) = &error
+    else {
+        panic!("{error}");
+    };
+    assert!(unknown.iter().any(|surface| surface == "猫"));
+}
+
+#[test]
+fn dictionary_without_surfaces_fails() {
+    let mut dictionary = fixture_dictionary();
+    dictionary.kanji.clear();
+    dictionary.kana.clear();
+
+    let error = self_test(dictionary).unwrap_err();
+    let SegmenterError::SelfTest(SelfTestError::Unindexed { fraction, .. }) = error
+    else {
+        panic!("{error}");
+    };
+    assert_eq!(fraction, 0.0);
+}
+
+#[test]
+fn truncated_dictionary_fails() {
+    let mut dictionary = fixture_dictionary();
+    dictionary.entries.truncate(dictionary.entries.len() / 2);
+
+    let error = self_test(dictionary).unwrap_err();
+    assert!(matches!(error, SegmenterError::Dictionary(_)), "{error}");
+}
+
+#[test]
+fn thresholds_are_configurable() {
+    let tokenizer = Tokenizer::new(Arc::new(fixture_dictionary()));
+    let thresholds = SelfTestThresholds {
+        min_entries: 1_000_000,
+        ..Default::default()
+    };
+    let error = tokenizer.self_test_with(&thresholds).unwrap_err();
+    assert!(matches!(
+        error,
+        SegmenterError::SelfTest(SelfTestError::TooFewEntries { min: 1_000_000, .. })
+    ));
+}
@@ -0,0 +1,82 @@
+#![cfg(feature = "serde")]
+
+mod common;
+
+use common::fixture_dictionary;
+use segmenter::candidate::CandidateSource;
+use segmenter::lattice::LatticeSnapshot;
+use segmenter::tokenizer::{Tokenizer, TokenizerOptions};
+use std::sync::Arc;
+
+/// Exported from "私は猫を見た。" with the fixture dictionary and default options.
+const SNAPSHOT: &str = include_str!("fixtures/lattice_snapshot.json");
+
+fn path_surfaces(snapshot: &LatticeSnapshot, path: &[usize]) -> Vec<String> {
+    path.iter()
+        .map(|node_id| snapshot.nodes[*node_id].surface.clone())
+        .collect()
+}
+
+#[test]
+fn fixture_replays_to_its_path() {
+    let snapshot: LatticeSnapshot = serde_json::from_str(SNAPSHOT).unwrap();
+    assert_eq!(snapshot.replay(), snapshot.path);
+    assert_eq!(
+        path_surfaces(&snapshot, &snapshot.path),
+        ["私", "は", "猫", "を", "見た", "。"]
+    );
+
+    // Path finding only depends on the stored nodes.
+    let tokenizer = Tokenizer::new(Arc::new(fixture_dictionary()));
+    assert_eq!(tokenizer.export_lattice(&snapshot.text), snapshot);
+}
+
+#[test]
+fn replay_follows_stored_nodes() {
+    let mut snapshot: LatticeSnapshot = serde_json::from_str(SNAPSHOT).unwrap();
+
+    // An edited snapshot: a high-scoring 見 makes 見|た win over 見た.
+    let mut node = snapshot.nodes[snapshot.path[4]].clone();
+    node.surface = "見".to_string();
+    node.source = CandidateSource::Unknown;
+    node.node.term_id = None;
+    node.node.term_entry = None;
+    node.node.end = node.node.start + 1;
+    node.node.score = 100.0;
+    snapshot.nodes.push(node);
+
+    let replayed = snapshot.replay();
+    assert_ne!(replayed, snapshot.path);
+    assert_eq!(
+        path_surfaces(&snapshot, &replayed),
+        ["私", "は", "猫", "を", "見", "た", "。"]
+    );
+}
+
+#[test]
+fn exported_snapshots_round_trip_and_match_tokenize() {
+    let tokenizer = Tokenizer::with_options(
+        Arc::new(fixture_dictionary()),
+        TokenizerOptions {
+            deinflect: true,
+            ..Default::default()
+        },
+    );
+    for text in [
+        "猫を見た",
+        "東京に行けば",
+        "面白いｗｗｗ",
+        "佐藤さんが会社に行く",
+        "",
+    ] {
+        let snapshot = tokenizer.export_lattice(text);
+        let json = serde_json::to_string(&snapshot).unwrap();
+        let restored: LatticeSnapshot = serde_json::from_str(&json).unwrap();
+        assert_eq!(restored, snapshot, "{text}");
+        assert_eq!(restored.replay(), snapshot.path, "{text}");
+
+        let tokens = tokenizer.tokenize(text);
+        let surfaces: Vec<&str> = tokens.iter().map(|token| token.token).collect();
+        assert_eq!(path_surfaces(&snapshot, &snapshot.path), surfaces, "{text}");
+    }
+}
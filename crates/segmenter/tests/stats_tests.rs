@@ -0,0 +1,65 @@
+mod common;
+
+use common::fixture_dictionary;
+use segmenter::dictionary::PartOfSpeech;
+use segmenter::pipeline::Segmenter;
+use segmenter::stats::{self, UNKNOWN};
+use segmenter::tokenizer::Tokenizer;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+const CORPUS: &[&str] = &[
+    "猫を見た",
+    "私は学生です",
+    "東京に行く",
+    "猫を見た。",
+    "田中さんは会社に行く",
+    "ぴよぴよを見た",
+];
+
+fn ngrams(n: usize, num_threads: usize) -> HashMap<Vec<PartOfSpeech>, u64> {
+    let tokenizer = Arc::new(Tokenizer::new(Arc::new(fixture_dictionary())));
+    let segmenter = Segmenter::new(tokenizer, num_threads);
+    stats::pos_ngrams(&segmenter, CORPUS.iter().map(|text| text.to_string()), n)
+}
+
+fn count(counts: &HashMap<Vec<PartOfSpeech>, u64>, ngram: &[PartOfSpeech]) -> u64 {
+    counts.get(ngram).copied().unwrap_or(0)
+}
+
+#[test]
+fn particle_bigrams_are_counted() {
+    let counts = ngrams(2, 4);
+
+    use PartOfSpeech as P;
+    assert_eq!(count(&counts, &[P::NOUN, P::PARTICLE]), 3);
+    assert_eq!(count(&counts, &[P::PARTICLE, P::ICHIDAN_VERB]), 3);
+    assert_eq!(count(&counts, &[P::PARTICLE, P::GODAN_VERB]), 2);
+    assert_eq!(count(&counts, &[P::ICHIDAN_VERB, P::SYMBOL]), 1);
+    // Bigrams don't span texts, so nothing follows the verb ending a line.
+    assert_eq!(count(&counts, &[P::ICHIDAN_VERB, P::PRONOUN]), 0);
+    assert_eq!(counts.values().sum::<u64>(), 18);
+}
+
+#[test]
+fn unknown_tokens_count_as_unknown() {
+    let counts = ngrams(2, 1);
+
+    // ぴよぴ is unknown, the よ after it a built-in particle.
+    assert_eq!(count(&counts, &[UNKNOWN, PartOfSpeech::PARTICLE]), 1);
+    assert_eq!(
+        stats::ngram_name(&[UNKNOWN, PartOfSpeech::PARTICLE]),
+        "UNKNOWN PARTICLE"
+    );
+}
+
+#[test]
+fn trigrams_and_the_sorted_table() {
+    let counts = ngrams(3, 2);
+    let table = stats::sorted_ngrams(counts);
+
+    use PartOfSpeech as P;
+    assert_eq!(table[0], (vec![P::NOUN, P::PARTICLE, P::ICHIDAN_VERB], 2));
+    assert!(table.windows(2).all(|pair| pair[0].1 >= pair[1].1));
+    assert_eq!(table.iter().map(|(_, count)| count).sum::<u64>(), 12);
+}
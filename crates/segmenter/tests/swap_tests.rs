@@ -0,0 +1,110 @@
+mod common;
+
+use common::{fixture_dictionary, surfaces};
+use segmenter::dictionary::Dictionary;
+use segmenter::incremental::IncrementalTokenizer;
+use segmenter::tokenizer::Tokenizer;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+const TEXTS: &[&str] = &[
+    "私は学生です",
+    "今日は天気が良い",
+    "田中さんの方がコーヒーを飲みました",
+    "トヨタ自動車の会社",
+];
+
+/// The tokens of every text of [`TEXTS`] with `dictionary`, as compared in the tests.
+fn expected(dictionary: &Arc<Dictionary>) -> Vec<String> {
+    let tokenizer = Tokenizer::new(dictionary.clone());
+    TEXTS
+        .iter()
+        .map(|text| format!("{:?}", tokenizer.tokenize(text)))
+        .collect()
+}
+
+#[test]
+fn swapping_changes_later_results() {
+    let empty = Arc::new(Dictionary::new());
+    let tokenizer = Tokenizer::new(Arc::new(fixture_dictionary()));
+    assert_eq!(
+        surfaces(&tokenizer.tokenize("私は学生")),
+        ["私", "は", "学生"]
+    );
+
+    tokenizer.swap_dictionary(empty.clone());
+    assert!(Arc::ptr_eq(&tokenizer.dictionary(), &empty));
+    assert_eq!(
+        format!("{:?}", tokenizer.tokenize("私は学生")),
+        format!("{:?}", Tokenizer::new(empty).tokenize("私は学生"))
+    );
+    let iterated: Vec<_> = tokenizer.tokenize_iter("私は学生").collect();
+    assert_eq!(
+        format!("{iterated:?}"),
+        format!("{:?}", tokenizer.tokenize("私は学生"))
+    );
+
+    tokenizer.swap_dictionary(Arc::new(fixture_dictionary()));
+    assert_eq!(
+        surfaces(&tokenizer.tokenize("私は学生")),
+        ["私", "は", "学生"]
+    );
+}
+
+#[test]
+fn incremental_tokenizers_pick_up_a_swap() {
+    let tokenizer = Tokenizer::new(Arc::new(fixture_dictionary()));
+    let mut incremental = IncrementalTokenizer::new(&tokenizer);
+    incremental.push_str("私は学");
+    tokenizer.swap_dictionary(Arc::new(Dictionary::new()));
+    incremental.push_str("生です");
+    assert_eq!(
+        format!("{:?}", incremental.tokens()),
+        format!("{:?}", tokenizer.tokenize("私は学生です"))
+    );
+}
+
+#[test]
+fn calls_during_swaps_see_one_dictionary() {
+    let dictionaries = [Arc::new(fixture_dictionary()), Arc::new(Dictionary::new())];
+    let expected = dictionaries.each_ref().map(expected);
+    assert_ne!(expected[0], expected[1]);
+
+    let tokenizer = Tokenizer::new(dictionaries[0].clone());
+    let done = AtomicBool::new(false);
+    std::thread::scope(|scope| {
+        for reader in 0..4 {
+            let (tokenizer, expected, done) = (&tokenizer, &expected, &done);
+            scope.spawn(move || {
+                let mut calls = 0;
+                while !done.load(Ordering::Relaxed) || calls < 100 {
+                    let index = (reader + calls) % TEXTS.len();
+                    let tokens = if calls % 2 == 0 {
+                        tokenizer.tokenize(TEXTS[index])
+                    } else {
+                        tokenizer.tokenize_iter(TEXTS[index]).collect()
+                    };
+                    let tokens = format!("{tokens:?}");
+                    assert!(
+                        expected.iter().any(|expected| expected[index] == tokens),
+                        "{tokens}"
+                    );
+                    calls += 1;
+                }
+            });
+        }
+
+        for swap in 0..200 {
+            tokenizer.swap_dictionary(dictionaries[swap % 2].clone());
+            std::thread::yield_now();
+        }
+        done.store(true, Ordering::Relaxed);
+    });
+
+    // The last swap put in the empty dictionary.
+    let after: Vec<String> = TEXTS
+        .iter()
+        .map(|text| format!("{:?}", tokenizer.tokenize(text)))
+        .collect();
+    assert_eq!(after, expected[1]);
+}
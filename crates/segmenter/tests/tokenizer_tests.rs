@@ -1,7 +1,14 @@
-use segmenter::tokenizer::Tokenizer;
+mod common;
+
+use common::{fixture_dictionary, surfaces};
+use segmenter::dictionary::PartOfSpeech;
+use segmenter::tokenizer::{Token, TokenKind, Tokenizer, TokenizerOptions};
+use std::sync::Arc;
 
 #[cfg(test)]
-lazy_static::lazy_static! {}
+lazy_static::lazy_static! {
+    static ref TOKENIZER: Tokenizer = Tokenizer::new(Arc::new(fixture_dictionary()));
+}
 
 #[test]
 fn じいちゃん() {
@@ -10,3 +17,345 @@ fn じいちゃん() {
 
 #[test]
 fn この() {}
+
+#[test]
+fn greedy_agrees_with_lattice() {
+    for text in ["猫を見た", "私は学生です", "東京に行く"] {
+        let lattice = TOKENIZER.tokenize(text);
+        let greedy = TOKENIZER.tokenize_greedy(text);
+        assert_eq!(surfaces(&lattice), surfaces(&greedy), "{text}");
+        assert_eq!(
+            lattice.iter().map(|t| t.term_id).collect::<Vec<_>>(),
+            greedy.iter().map(|t| t.term_id).collect::<Vec<_>>(),
+            "{text}"
+        );
+    }
+}
+
+#[test]
+fn greedy_prefers_dictionary_over_unknown_grouping() {
+    // The lattice keeps the unknown katakana run whole, while greedy commits to the
+    // longest dictionary match and leaves the remainder as an unknown token.
+    assert_eq!(surfaces(&TOKENIZER.tokenize("パンダ")), ["パンダ"]);
+    assert_eq!(
+        surfaces(&TOKENIZER.tokenize_greedy("パンダ")),
+        ["パン", "ダ"]
+    );
+}
+
+#[test]
+fn greedy_covers_whole_input() {
+    for text in ["", "猫", "ABCと猫🐈!?", "  東京  ", "ｘｙｚ１２３"] {
+        let tokens = TOKENIZER.tokenize_greedy(text);
+        assert_eq!(surfaces(&tokens).concat(), text);
+        assert!(tokens.iter().all(|token| !token.token.is_empty()));
+    }
+}
+
+#[test]
+fn final_character_is_kept() {
+    for (text, expected) in [
+        ("猫", &["猫"][..]),
+        ("を", &["を"]),
+        ("!", &["!"]),
+        ("猫を見た", &["猫", "を", "見た"]),
+        ("私は猫", &["私", "は", "猫"]),
+        ("東京", &["東京"]),
+        ("猫を見た!", &["猫", "を", "見た", "!"]),
+        ("猫?", &["猫", "?"]),
+    ] {
+        assert_eq!(surfaces(&TOKENIZER.tokenize(text)), expected, "{text}");
+    }
+}
+
+#[test]
+fn degenerate_input_gives_no_or_plain_tokens() {
+    for text in ["", " ", "   \t\n", "\u{0}\u{1}", "\u{E000}\u{F8FF}"] {
+        let tokens = TOKENIZER.tokenize(text);
+        assert_eq!(surfaces(&tokens).concat(), text, "{text:?}");
+        assert_eq!(
+            TOKENIZER.tokenize_iter(text).count(),
+            tokens.len(),
+            "{text:?}"
+        );
+        let tokenization = TOKENIZER.tokenize_checked(text).unwrap();
+        assert!(tokenization.gaps.is_empty(), "{text:?}");
+        assert_eq!(TOKENIZER.tokenize_granular(text).len(), tokens.len());
+    }
+    assert!(TOKENIZER.tokenize("").is_empty());
+}
+
+#[test]
+fn uncategorized_characters_are_covered() {
+    for text in ["猫🐈", "e\u{301}と\u{0}", "🐈\u{200d}⬛"] {
+        let tokens = TOKENIZER.tokenize(text);
+        assert_eq!(surfaces(&tokens).concat(), text);
+        assert_eq!(
+            surfaces(&tokens),
+            surfaces(&TOKENIZER.tokenize_greedy(text))
+        );
+    }
+}
+
+#[test]
+fn punctuation_is_tagged_as_symbol() {
+    let tokens = TOKENIZER.tokenize("「猫」を見た。");
+    assert_eq!(surfaces(&tokens), ["「", "猫", "」", "を", "見た", "。"]);
+
+    for token in tokens
+        .iter()
+        .filter(|token| ["「", "」", "。"].contains(&token.token))
+    {
+        assert_eq!(token.pos, PartOfSpeech::SYMBOL, "{}", token.token);
+    }
+    assert!(!tokens[1].pos.is_symbol());
+}
+
+#[test]
+fn punctuation_filter_drops_symbols() {
+    let mut tokens = TOKENIZER.tokenize("「猫」を見た。");
+    segmenter::filter::drop_punctuation(&mut tokens);
+    assert_eq!(surfaces(&tokens), ["猫", "を", "見た"]);
+
+    let mut tokens = TOKENIZER.tokenize_greedy("猫・パン!");
+    segmenter::filter::drop_punctuation(&mut tokens);
+    assert_eq!(surfaces(&tokens), ["猫", "パン"]);
+}
+
+#[test]
+fn boundaries_match_token_offsets() {
+    for text in [
+        "猫を見た",
+        "私は学生です。",
+        "「猫」を見た、東京に行く。",
+        "",
+        "猫",
+    ] {
+        let tokens = TOKENIZER.tokenize(text);
+        let mut expected = Vec::new();
+        let mut offset = 0;
+        for pair in tokens.windows(2) {
+            offset += pair[0].token.len();
+            if !(["。", "、", "」"].contains(&pair[1].token) || pair[0].token == "「")
+            {
+                expected.push(offset);
+            }
+        }
+
+        assert_eq!(TOKENIZER.boundaries(text), expected, "{text}");
+    }
+}
+
+#[test]
+fn boundaries_skip_punctuation() {
+    let text = "「猫」を見た。";
+    // 「|猫|」|を|見た|。 with the breaks after 「 and before 」/。 removed.
+    assert_eq!(
+        TOKENIZER.boundaries(text),
+        ["「猫」".len(), "「猫」を".len()]
+    );
+}
+
+fn term_ids(tokens: &[Token]) -> Vec<Option<u32>> {
+    tokens.iter().map(|token| token.term_id).collect()
+}
+
+fn term_id(surface: &str) -> Option<u32> {
+    TOKENIZER.tokenize_greedy(surface)[0].term_id
+}
+
+#[test]
+fn particles_are_split_from_nouns() {
+    let tokens = TOKENIZER.tokenize("私は猫を見た");
+    assert_eq!(surfaces(&tokens), ["私", "は", "猫", "を", "見た"]);
+    assert!(tokens[1].pos.is_particle() && tokens[3].pos.is_particle());
+
+    let tokens = TOKENIZER.tokenize("猫と私");
+    assert_eq!(surfaces(&tokens), ["猫", "と", "私"]);
+    assert_eq!(
+        term_ids(&tokens),
+        [term_id("猫"), term_id("と"), term_id("私")]
+    );
+}
+
+#[test]
+fn katakana_words() {
+    let tokens = TOKENIZER.tokenize("パンを見た");
+    assert_eq!(surfaces(&tokens), ["パン", "を", "見た"]);
+    assert_eq!(tokens[0].term_id, term_id("パン"));
+
+    // Unknown katakana runs stay together.
+    let tokens = TOKENIZER.tokenize("スマートフォンを見た");
+    assert_eq!(surfaces(&tokens), ["スマートフォン", "を", "見た"]);
+    assert_eq!(tokens[0].term_id, None);
+}
+
+#[test]
+fn unknown_kanji_names() {
+    let tokens = TOKENIZER.tokenize("佐藤さんが会社に行く");
+    assert_eq!(surfaces(&tokens).concat(), "佐藤さんが会社に行く");
+    assert!(tokens
+        .iter()
+        .take_while(|token| token.token != "さん")
+        .all(|token| token.term_id.is_none()));
+    assert_eq!(
+        surfaces(&tokens[tokens.len() - 5..]),
+        ["さん", "が", "会社", "に", "行く"]
+    );
+}
+
+#[test]
+fn mixed_ascii() {
+    let tokens = TOKENIZER.tokenize("私はiPhoneを見た");
+    assert_eq!(surfaces(&tokens), ["私", "は", "iPhone", "を", "見た"]);
+    assert_eq!(tokens[2].term_id, None);
+
+    let tokens = TOKENIZER.tokenize("会社でABC123を見た");
+    assert_eq!(
+        surfaces(&tokens),
+        ["会社", "で", "ABC", "123", "を", "見た"]
+    );
+}
+
+#[test]
+fn dominated_single_char_terms_are_discounted() {
+    let dictionary = Arc::new(fixture_dictionary());
+    let disabled = Tokenizer::with_options(
+        dictionary.clone(),
+        TokenizerOptions {
+            single_char_factor: 1.0,
+            ..Default::default()
+        },
+    );
+
+    // Without the penalty the one-character match ties and wins on insertion order.
+    assert_eq!(surfaces(&disabled.tokenize("日本人")), ["日", "本人"]);
+    assert_eq!(surfaces(&disabled.tokenize("東京都")), ["東", "京都"]);
+
+    assert_eq!(surfaces(&TOKENIZER.tokenize("日本人")), ["日本", "人"]);
+    assert_eq!(surfaces(&TOKENIZER.tokenize("東京都")), ["東京", "都"]);
+    assert_eq!(surfaces(&TOKENIZER.tokenize("気になる")), ["気になる"]);
+    assert_eq!(surfaces(&TOKENIZER.tokenize("手伝う")), ["手伝う"]);
+}
+
+#[test]
+fn single_char_particles_are_not_discounted() {
+    for text in ["私は猫を見た", "東京に行く", "猫と私"] {
+        let disabled = Tokenizer::with_options(
+            Arc::new(fixture_dictionary()),
+            TokenizerOptions {
+                single_char_factor: 1.0,
+                ..Default::default()
+            },
+        );
+        assert_eq!(
+            surfaces(&TOKENIZER.tokenize(text)),
+            surfaces(&disabled.tokenize(text))
+        );
+    }
+    assert_eq!(
+        surfaces(&TOKENIZER.tokenize("東京に行く")),
+        ["東京", "に", "行く"]
+    );
+}
+
+#[test]
+fn expressions_can_be_decomposed() {
+    let decomposing = Tokenizer::with_options(
+        Arc::new(fixture_dictionary()),
+        TokenizerOptions {
+            decompose_expressions: true,
+            ..Default::default()
+        },
+    );
+
+    let tokens = decomposing.tokenize("猫かもしれない");
+    assert_eq!(surfaces(&tokens), ["猫", "かもしれない"]);
+    assert!(tokens[0].components.is_none());
+    let components = tokens[1].components.as_ref().unwrap();
+    assert_eq!(surfaces(components), ["か", "も", "しれ", "ない"]);
+    assert_eq!(
+        term_ids(components),
+        [
+            term_id("か"),
+            term_id("も"),
+            term_id("しれ"),
+            term_id("ない")
+        ]
+    );
+
+    let tokens = TOKENIZER.tokenize("猫かもしれない");
+    assert_eq!(surfaces(&tokens), ["猫", "かもしれない"]);
+    assert!(tokens.iter().all(|token| token.components.is_none()));
+}
+
+#[test]
+fn emphasis_runs_are_single_tokens() {
+    for (text, expected) in [
+        ("面白いｗｗｗ", &["面白い", "ｗｗｗ"][..]),
+        ("すごい！！！！", &["すごい", "！！！！"]),
+        ("また今度〜〜〜", &["また", "今度", "〜〜〜"]),
+    ] {
+        for tokens in [TOKENIZER.tokenize(text), TOKENIZER.tokenize_greedy(text)] {
+            assert_eq!(surfaces(&tokens), expected, "{text}");
+            let (last, words) = tokens.split_last().unwrap();
+            assert_eq!(last.kind, TokenKind::Emphasis, "{text}");
+            assert!(words.iter().all(|token| token.kind == TokenKind::Word));
+            assert!(words.iter().all(|token| token.term_id.is_some()));
+        }
+    }
+}
+
+#[test]
+fn emphasis_threshold_is_configurable() {
+    let tokenizer = Tokenizer::with_options(
+        Arc::new(fixture_dictionary()),
+        TokenizerOptions {
+            emphasis_threshold: 5,
+            ..Default::default()
+        },
+    );
+    let tokens = tokenizer.tokenize("面白いｗｗｗ");
+    assert!(tokens.iter().all(|token| token.kind == TokenKind::Word));
+
+    let tokens = tokenizer.tokenize("面白いｗｗｗｗｗ");
+    assert_eq!(surfaces(&tokens), ["面白い", "ｗｗｗｗｗ"]);
+    assert_eq!(tokens[1].kind, TokenKind::Emphasis);
+}
+
+#[test]
+fn small_kana_stay_with_their_mora() {
+    for (text, expected) in [
+        ("ちょっと待って", &["ちょっと", "待", "って"][..]),
+        // き is in the dictionary, but can't be split from its ゃ.
+        ("きゃあ", &["きゃあ"]),
+        ("猫がャ", &["猫", "がャ"]),
+        // Unknown kana keep a sokuon with the kana it doubles.
+        ("ドキっとした", &["ドキっと", "した"]),
+        ("ニャっと", &["ニャっと"]),
+    ] {
+        assert_eq!(surfaces(&TOKENIZER.tokenize(text)), expected, "{text}");
+        assert_eq!(
+            surfaces(&TOKENIZER.tokenize_greedy(text)),
+            expected,
+            "{text}"
+        );
+    }
+}
+
+#[test]
+fn truncated_katakana_fragments() {
+    // Without a kana before them, small kana and ッ start a cut-off fragment.
+    for (text, expected) in [
+        ("ャーナル", &["ャーナル"][..]),
+        ("ッターを見た", &["ッター", "を", "見た"]),
+        ("猫ャーナル", &["猫", "ャーナル"]),
+    ] {
+        assert_eq!(surfaces(&TOKENIZER.tokenize(text)), expected, "{text}");
+        assert_eq!(
+            surfaces(&TOKENIZER.tokenize_greedy(text)),
+            expected,
+            "{text}"
+        );
+    }
+}
@@ -0,0 +1,96 @@
+mod common;
+
+use common::{fixture_dictionary, surfaces};
+use segmenter::tokenizer::{Tokenizer, TokenizerOptions};
+use segmenter::unknown::{UnknownCategory, UnknownWordModel, MAX_MODEL_LENGTH};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// Unknown katakana words of three to six characters.
+const WORDS: &[&str] = &["トヨタ", "メルカリ", "バルセロナ", "アンドロメダ"];
+
+/// Three six-character place names in a row.
+const LONG_RUN: &str = "アンドロメダシンシナティミネアポリス";
+
+/// A model trained on a handful of words, most of them six characters long.
+fn trained() -> UnknownWordModel {
+    let corpus = [
+        "エスプレッソ",
+        "ペペロンチノ",
+        "フィレンツェ",
+        "エスプレッソ",
+        "フィレンツェ",
+        "マカロニ",
+        "ミラノ",
+        "ナポリタン",
+        "アムステルダム",
+    ];
+    let mut counts = HashMap::new();
+    for word in corpus {
+        let key = (UnknownCategory::of(word), word.chars().count());
+        *counts.entry(key).or_insert(0) += 1;
+    }
+    UnknownWordModel::train(&counts)
+}
+
+fn tokenizer(model: UnknownWordModel) -> Tokenizer {
+    let options = TokenizerOptions {
+        unknown_words: Arc::new(model),
+        ..TokenizerOptions::default()
+    };
+    Tokenizer::with_options(Arc::new(fixture_dictionary()), options)
+}
+
+#[test]
+fn default_model_keeps_runs_whole() {
+    let model = UnknownWordModel::default();
+    assert!(!model.has_statistics(UnknownCategory::Katakana));
+    assert_eq!(model.weight(UnknownCategory::Katakana, 18), 1.0);
+
+    let tokenizer = tokenizer(model);
+    for word in WORDS.iter().chain([&LONG_RUN]) {
+        assert_eq!(surfaces(&tokenizer.tokenize(word)), [*word]);
+    }
+}
+
+#[test]
+fn trained_model_splits_long_runs_into_likely_lengths() {
+    let tokenizer = tokenizer(trained());
+    for word in WORDS {
+        assert_eq!(surfaces(&tokenizer.tokenize(word)), [*word]);
+    }
+    assert_eq!(
+        surfaces(&tokenizer.tokenize(LONG_RUN)),
+        ["アンドロメダ", "シンシナティ", "ミネアポリス"]
+    );
+    assert_eq!(
+        surfaces(&tokenizer.tokenize(&format!("{LONG_RUN}を見た"))),
+        ["アンドロメダ", "シンシナティ", "ミネアポリス", "を", "見た"]
+    );
+}
+
+#[test]
+fn training_smooths_the_counted_lengths() {
+    let model = trained();
+    let katakana = UnknownCategory::Katakana;
+    assert!(model.has_statistics(katakana));
+    assert!(!model.has_statistics(UnknownCategory::Kanji));
+    assert_eq!(model.probability(UnknownCategory::Kanji, 2), None);
+
+    // Nine words and one more occurrence of every length.
+    let total = (9 + MAX_MODEL_LENGTH) as f64;
+    assert_eq!(model.probability(katakana, 6), Some(6.0 / total));
+    assert_eq!(model.probability(katakana, 20), Some(1.0 / total));
+    assert_eq!(
+        model.probability(katakana, 100),
+        model.probability(katakana, 32)
+    );
+    let sum: f64 = (1..=MAX_MODEL_LENGTH)
+        .filter_map(|length| model.probability(katakana, length))
+        .sum();
+    assert!((sum - 1.0).abs() < 1e-9);
+
+    assert_eq!(model.weight(katakana, 6), 1.0);
+    assert_eq!(model.weight(katakana, 20), 1.0 / 6.0);
+    assert_eq!(model.weight(UnknownCategory::Kanji, 20), 1.0);
+}
@@ -0,0 +1,90 @@
+mod common;
+
+use common::{fixture_dictionary, surfaces};
+use segmenter::dictionary::{PartOfSpeech, Tag};
+use segmenter::symbols;
+use segmenter::tokenizer::Tokenizer;
+use std::sync::Arc;
+
+/// Sentences as vertical layouts write them, and as horizontal ones do.
+const PAIRS: &[(&str, &str)] = &[
+    (
+        "今日は天気が良い︑猫を見た︒",
+        "今日は天気が良い、猫を見た。",
+    ),
+    ("﹁猫﹂を見た︒", "「猫」を見た。"),
+    ("私は学生です︖︕", "私は学生です？！"),
+    ("︵東京︶に行く︙", "（東京）に行く…"),
+];
+
+#[test]
+fn vertical_punctuation_segments_like_horizontal() {
+    let tokenizer = Tokenizer::new(Arc::new(fixture_dictionary()));
+    for (vertical, horizontal) in PAIRS {
+        let vertical_tokens = tokenizer.tokenize(vertical);
+        let horizontal_tokens = tokenizer.tokenize(horizontal);
+        let spans = |tokens: &[segmenter::tokenizer::Token]| -> Vec<_> {
+            tokens
+                .iter()
+                .map(|token| (token.char_start, token.term_id, token.pos))
+                .collect()
+        };
+        assert_eq!(
+            spans(&vertical_tokens),
+            spans(&horizontal_tokens),
+            "{vertical}"
+        );
+
+        // Surfaces are kept as they were written.
+        assert_eq!(surfaces(&vertical_tokens).concat(), *vertical);
+    }
+}
+
+#[test]
+fn vertical_punctuation_is_a_symbol() {
+    let tokenizer = Tokenizer::new(Arc::new(fixture_dictionary()));
+    let tokens = tokenizer.tokenize("猫を見た︒");
+    assert_eq!(surfaces(&tokens), ["猫", "を", "見た", "︒"]);
+    assert_eq!(tokens[3].pos, PartOfSpeech::SYMBOL);
+
+    assert_eq!(symbols::horizontal_form('︑'), '、');
+    assert_eq!(symbols::horizontal_form('﹃'), '『');
+    assert_eq!(symbols::horizontal_form('猫'), '猫');
+    assert_eq!(symbols::to_horizontal("猫を見た"), None);
+}
+
+#[test]
+fn middle_dot_variants_find_the_entry() {
+    let mut dictionary = fixture_dictionary();
+    dictionary.add_word(
+        1000,
+        Some("ジョン・スミス"),
+        "じょんすみす",
+        PartOfSpeech::NOUN_PROPER,
+        Tag::NONE,
+    );
+    let tokenizer = Tokenizer::new(Arc::new(dictionary));
+
+    for text in [
+        "ジョン・スミス",
+        "ジョン·スミス",
+        "ジョン‧スミス",
+        "ジョン･スミス",
+    ] {
+        let tokens = tokenizer.tokenize(text);
+        assert_eq!(surfaces(&tokens), [text], "{text}");
+        assert_eq!(tokens[0].term_id, Some(1000), "{text}");
+    }
+}
+
+#[test]
+fn no_line_break_before_vertical_punctuation() {
+    let tokenizer = Tokenizer::new(Arc::new(fixture_dictionary()));
+    for (vertical, horizontal) in PAIRS {
+        assert_eq!(
+            tokenizer.boundaries(vertical),
+            tokenizer.boundaries(horizontal),
+            "{vertical}"
+        );
+    }
+}
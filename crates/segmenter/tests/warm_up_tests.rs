@@ -0,0 +1,26 @@
+mod common;
+
+use common::fixture_dictionary;
+use segmenter::tokenizer::{initialization_count, Tokenizer, TokenizerOptions};
+use std::sync::Arc;
+
+#[test]
+fn nothing_is_initialized_after_warm_up() {
+    let options = TokenizerOptions {
+        deinflect: true,
+        ..TokenizerOptions::default()
+    };
+    let tokenizer = Tokenizer::with_options(Arc::new(fixture_dictionary()), options);
+    tokenizer.warm_up();
+
+    // At least the word categories and the deinflection rules.
+    let count = initialization_count();
+    assert!(count >= 2);
+    tokenizer.tokenize("学生です。今日は良い天気で、猫を見た");
+    tokenizer.tokenize_checked("食べました").unwrap();
+    assert_eq!(initialization_count(), count);
+
+    // Warming up again finds nothing left to do.
+    tokenizer.warm_up();
+    assert_eq!(initialization_count(), count);
+}
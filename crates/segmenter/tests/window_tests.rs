@@ -0,0 +1,83 @@
+#[path = "common/corpus.rs"]
+mod corpus;
+
+use segmenter::tokenizer::{Token, Tokenizer, TokenizerOptions, DEFAULT_WINDOW};
+use std::sync::Arc;
+
+fn tokenizers(window: usize) -> (Tokenizer, Tokenizer) {
+    let dictionary = Arc::new(corpus::bench_dictionary());
+    let windowed = TokenizerOptions {
+        window: Some(window),
+        ..TokenizerOptions::default()
+    };
+    (
+        Tokenizer::new(dictionary.clone()),
+        Tokenizer::with_options(dictionary, windowed),
+    )
+}
+
+fn spans<'a>(tokens: &[Token<'a>]) -> Vec<(&'a str, usize, usize, Option<u32>)> {
+    tokens
+        .iter()
+        .map(|token| {
+            (
+                token.token,
+                token.char_start,
+                token.byte_start,
+                token.term_id,
+            )
+        })
+        .collect()
+}
+
+#[test]
+fn windows_give_the_tokens_of_the_whole_text() {
+    // The smallest window there is, so most texts take several.
+    let (whole, windowed) = tokenizers(0);
+    for seed in 1..=40 {
+        for text in [
+            corpus::generate_text(seed, 300 + seed as usize * 37),
+            corpus::generate_kana_text(seed, 300 + seed as usize * 37),
+        ] {
+            let text = text.replace(['。', '、'], "");
+            assert_eq!(
+                spans(&windowed.tokenize(&text)),
+                spans(&whole.tokenize(&text)),
+                "seed {seed}: {text}"
+            );
+        }
+    }
+}
+
+#[test]
+fn one_long_line_is_covered_in_order() {
+    let (_, windowed) = tokenizers(DEFAULT_WINDOW);
+    let text = corpus::sample_sentences()
+        .concat()
+        .replace('。', "")
+        .repeat(20);
+    let tokens = windowed.tokenize(&text);
+
+    assert_eq!(
+        tokens.iter().map(|token| token.token).collect::<String>(),
+        text
+    );
+    let mut char_start = 0;
+    for token in tokens.iter() {
+        assert_eq!(token.char_start, char_start);
+        assert_eq!(&text[token.byte_start..][..token.token.len()], token.token);
+        char_start += token.token.chars().count();
+    }
+}
+
+#[test]
+fn short_texts_take_one_window() {
+    let (whole, windowed) = tokenizers(DEFAULT_WINDOW);
+    for sentence in corpus::sample_sentences() {
+        assert_eq!(
+            spans(&windowed.tokenize(sentence)),
+            spans(&whole.tokenize(sentence))
+        );
+    }
+    assert!(windowed.tokenize("").is_empty());
+}
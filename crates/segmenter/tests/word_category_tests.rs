@@ -0,0 +1,91 @@
+mod common;
+
+use common::fixture_dictionary;
+use segmenter::dictionary::{Dictionary, PartOfSpeech, Tag};
+use segmenter::tokenizer::Tokenizer;
+use segmenter::unknown::UnknownCategory;
+use std::sync::Arc;
+
+/// Characters from the edges of every block the unknown-term tables know, and the
+/// category a word of them gets.
+const MATRIX: &[(char, UnknownCategory)] = &[
+    ('ァ', UnknownCategory::Katakana),
+    ('ヶ', UnknownCategory::Katakana),
+    ('ヺ', UnknownCategory::Katakana),
+    ('ー', UnknownCategory::Katakana),
+    ('ヽ', UnknownCategory::Katakana),
+    ('ヾ', UnknownCategory::Katakana),
+    ('ヿ', UnknownCategory::Katakana),
+    ('ㇰ', UnknownCategory::Katakana),
+    ('ｦ', UnknownCategory::Katakana),
+    ('ﾞ', UnknownCategory::Katakana),
+    ('ぁ', UnknownCategory::Kana),
+    ('ゔ', UnknownCategory::Kana),
+    ('ゖ', UnknownCategory::Kana),
+    ('ゝ', UnknownCategory::Kana),
+    ('ゞ', UnknownCategory::Kana),
+    ('ゟ', UnknownCategory::Kana),
+    ('々', UnknownCategory::Kanji),
+    ('〇', UnknownCategory::Kanji),
+    ('⺀', UnknownCategory::Kanji),
+    ('⼀', UnknownCategory::Kanji),
+    ('㐀', UnknownCategory::Kanji),
+    ('一', UnknownCategory::Kanji),
+    ('龥', UnknownCategory::Kanji),
+    ('豈', UnknownCategory::Kanji),
+    ('龯', UnknownCategory::Other),
+    ('・', UnknownCategory::Other),
+    ('。', UnknownCategory::Other),
+    ('！', UnknownCategory::Other),
+    ('a', UnknownCategory::Other),
+    ('Ａ', UnknownCategory::Other),
+    ('1', UnknownCategory::Other),
+    ('１', UnknownCategory::Other),
+    ('α', UnknownCategory::Other),
+    ('д', UnknownCategory::Other),
+    ('😀', UnknownCategory::Other),
+];
+
+#[test]
+fn categories_follow_the_unknown_term_tables() {
+    for (c, category) in MATRIX {
+        assert_eq!(UnknownCategory::of(&c.to_string()), *category, "{c}");
+        assert_eq!(UnknownCategory::of(&format!("{c}{c}{c}")), *category, "{c}");
+    }
+}
+
+#[test]
+fn words_with_a_japanese_character_are_looked_up() {
+    for (c, category) in MATRIX {
+        let word = format!("{c}{c}");
+        let mut dictionary = Dictionary::new();
+        dictionary.add_word(0, Some(&word), &word, PartOfSpeech::NOUN, Tag::NONE);
+        let tokenizer = Tokenizer::new(Arc::new(dictionary));
+
+        let tokens = tokenizer.tokenize(&word);
+        let found = tokens.len() == 1 && tokens[0].term_id == Some(0);
+        assert_eq!(found, *category != UnknownCategory::Other, "{c}");
+    }
+}
+
+#[test]
+fn mixed_scripts_are_looked_up_as_written() {
+    let mut dictionary = fixture_dictionary();
+    dictionary.add_word(
+        1000,
+        Some("Tシャツ"),
+        "ティーシャツ",
+        PartOfSpeech::NOUN,
+        Tag::NONE,
+    );
+    let tokenizer = Tokenizer::new(Arc::new(dictionary));
+    for (text, surface, term_id) in [
+        ("Tシャツを見た", "Tシャツ", 1000),
+        ("Tシャツ", "Tシャツ", 1000),
+        ("コーヒーを見た", "コーヒー", 24),
+    ] {
+        let tokens = tokenizer.tokenize(text);
+        assert_eq!(tokens[0].token, surface, "{text}");
+        assert_eq!(tokens[0].term_id, Some(term_id), "{text}");
+    }
+}
@@ -0,0 +1,74 @@
+use segmenter::dictionary::{Dictionary, TermId};
+use std::collections::BTreeMap;
+
+/// What an entry is recognized by across builds: its first kanji form, if it has one,
+/// and its first reading.
+pub type EntryKey = (Option<String>, String);
+
+/// How the term ids of a rebuilt dictionary compare to the previous build's.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct IdReport {
+    /// Entries of the old build found with the same term id in the new one.
+    pub kept: usize,
+    /// Entries only in the new build.
+    pub added: usize,
+    /// Entries only in the old build.
+    pub removed: usize,
+    /// Entries of both builds whose term ids differ, with the old and the new ids.
+    /// Entries sharing a key are compared as a group.
+    pub changed: Vec<(EntryKey, Vec<TermId>, Vec<TermId>)>,
+    /// Term ids several entries of the new build share.
+    pub duplicates: Vec<TermId>,
+}
+
+impl IdReport {
+    /// Whether every entry kept its term id and the new build's ids are unique.
+    pub fn is_stable(&self) -> bool {
+        self.changed.is_empty() && self.duplicates.is_empty()
+    }
+}
+
+/// Sorted term ids of the entries of `dictionary`, by key.
+fn term_ids_by_key(dictionary: &Dictionary) -> BTreeMap<EntryKey, Vec<TermId>> {
+    let mut term_ids: BTreeMap<EntryKey, Vec<TermId>> = BTreeMap::new();
+    for (index, entry) in dictionary.entries.iter().enumerate() {
+        let kanji = dictionary.kanji_forms[index].first().cloned();
+        let reading = dictionary.readings[index].first().cloned();
+        term_ids
+            .entry((kanji, reading.unwrap_or_default()))
+            .or_default()
+            .push(entry.term_id);
+    }
+    for ids in term_ids.values_mut() {
+        ids.sort_unstable();
+    }
+    term_ids
+}
+
+/// Matches the entries of `old` and `new` by [`EntryKey`] and compares their term ids.
+/// A key keeps its ids when every old one is still there; new ids next to them count
+/// as added entries.
+pub fn check_ids(old: &Dictionary, new: &Dictionary) -> IdReport {
+    let old_ids = term_ids_by_key(old);
+    let mut new_ids = term_ids_by_key(new);
+
+    let mut report = IdReport {
+        duplicates: new.duplicate_term_ids(),
+        ..Default::default()
+    };
+    for (key, old_ids) in old_ids {
+        let Some(new_ids) = new_ids.remove(&key) else {
+            report.removed += old_ids.len();
+            continue;
+        };
+        if old_ids.iter().all(|term_id| new_ids.contains(term_id)) {
+            report.kept += old_ids.len();
+            report.added += new_ids.len() - old_ids.len();
+        } else {
+            report.changed.push((key, old_ids, new_ids));
+        }
+    }
+    report.added += new_ids.values().map(Vec::len).sum::<usize>();
+
+    report
+}
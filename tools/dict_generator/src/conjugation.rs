@@ -0,0 +1,222 @@
+use segmenter::dictionary::{Dictionary, DictionaryId, InflectionType, TermEntry};
+
+/// A form [`add_conjugations`] could not derive from a verb.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConjugationFailure {
+    /// Name of the form, like `te` or `negative_past_long`, or `verb` when the word
+    /// couldn't be conjugated at all.
+    pub form: &'static str,
+    /// The `jp_inflections` error, such as `NotAVerb`.
+    pub error: String,
+}
+
+impl ConjugationFailure {
+    /// `error` is a `jp_inflections` error, whose type the crate doesn't export.
+    fn new(form: &'static str, error: impl std::fmt::Debug) -> Self {
+        Self {
+            form,
+            error: format!("{error:?}"),
+        }
+    }
+}
+
+/// Godan verbs JMdict marks as conjugating irregularly, which `jp_inflections`
+/// conjugates like every other godan verb.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GodanIrregular {
+    /// `v5k-s`: 行く and ゆく, with 行って and 行った.
+    Iku,
+    /// `v5u-s`: 問う and 請う, with 問うて and 問うた.
+    Tou,
+    /// `v5r-i`: ある, whose negative is ない.
+    Aru,
+    /// `v5aru`: honorifics like 下さる, with 下さい and 下さいます.
+    Honorific,
+}
+
+impl GodanIrregular {
+    /// The irregular class among the JMdict part-of-speech codes of an entry, if any.
+    pub fn from_pos(part_of_speeches: &[&str]) -> Option<Self> {
+        part_of_speeches.iter().find_map(|pos| match *pos {
+            "v5k-s" => Some(Self::Iku),
+            "v5u-s" => Some(Self::Tou),
+            "v5r-i" => Some(Self::Aru),
+            "v5aru" => Some(Self::Honorific),
+            _ => None,
+        })
+    }
+
+    /// The surface the verb `kana`/`kanji` takes for `form` instead of the regular godan
+    /// one, `None` where it is regular. The ending replaces the last kana of the
+    /// dictionary form, except for the forms of ある built on ない, which has no kanji.
+    fn form(
+        self,
+        form: &str,
+        kana: &str,
+        kanji: Option<&str>,
+    ) -> Option<jp_inflections::Word> {
+        let (ending, suppletive) = match (self, form) {
+            (Self::Iku, "te") => ("って", false),
+            (Self::Iku, "past") => ("った", false),
+            (Self::Tou, "te") => ("うて", false),
+            (Self::Tou, "past") => ("うた", false),
+            (Self::Aru, "negative") => ("ない", true),
+            (Self::Aru, "negative_te") => ("なくて", true),
+            (Self::Aru, "negative_past") => ("なかった", true),
+            (Self::Honorific, "imperative") => ("い", false),
+            (Self::Honorific, "negative_long") => ("いません", false),
+            (Self::Honorific, "past_long") => ("いました", false),
+            (Self::Honorific, "negative_past_long") => ("いませんでした", false),
+            _ => return None,
+        };
+        if suppletive {
+            return Some(jp_inflections::Word::new(ending, None::<&str>));
+        }
+
+        let stem = |surface: &str| {
+            let last = surface.chars().next_back().map_or(0, char::len_utf8);
+            format!("{}{ending}", &surface[..surface.len() - last])
+        };
+        let (kana, kanji) = (stem(kana), kanji.map(stem));
+        Some(jp_inflections::Word::new(kana.as_str(), kanji.as_deref()))
+    }
+}
+
+/// Indexes the conjugated forms of a verb under `entry_index`, each with its
+/// [`InflectionType`], skipping surfaces that are already present in the dictionary.
+/// Polite forms share the type of the plain ones, so 食べました is a past like 食べた.
+/// The forms spelled with `kanji`, or with `kana` for a verb without one, carry
+/// `form_penalty`, see `TermEntry::form_penalty`. The forms `irregular` conjugates
+/// differently are replaced by its own.
+///
+/// Forms that can't be derived are left out and returned, so one odd reading doesn't
+/// cost the others.
+pub fn add_conjugations(
+    dictionary: &mut Dictionary,
+    verb_type: jp_inflections::VerbType,
+    kana: &str,
+    kanji: Option<&str>,
+    entry_index: u32,
+    form_penalty: u8,
+    irregular: Option<GodanIrregular>,
+) -> Vec<ConjugationFailure> {
+    let kana_penalty = if kanji.is_some() { 0 } else { form_penalty };
+    use jp_inflections::*;
+    let verb = match Word::new(kana, kanji).into_verb(verb_type) {
+        Ok(verb) => verb,
+        Err(error) => return vec![ConjugationFailure::new("verb", error)],
+    };
+
+    let forms = [
+        (
+            "negative",
+            InflectionType::Negative,
+            verb.negative(WordForm::Short),
+        ),
+        (
+            "negative_long",
+            InflectionType::Negative,
+            verb.negative(WordForm::Long),
+        ),
+        ("te", InflectionType::Te, verb.te_form()),
+        (
+            "negative_te",
+            InflectionType::NegativeTe,
+            verb.negative_te_form(),
+        ),
+        ("past", InflectionType::Past, verb.past(WordForm::Short)),
+        ("past_long", InflectionType::Past, verb.past(WordForm::Long)),
+        (
+            "negative_past",
+            InflectionType::NegativePast,
+            verb.negative_past(WordForm::Short),
+        ),
+        (
+            "negative_past_long",
+            InflectionType::NegativePast,
+            verb.negative_past(WordForm::Long),
+        ),
+        (
+            "potential",
+            InflectionType::Potential,
+            verb.potential(WordForm::Short),
+        ),
+        (
+            "potential_long",
+            InflectionType::Potential,
+            verb.potential(WordForm::Long),
+        ),
+        (
+            "negative_potential",
+            InflectionType::NegativePotential,
+            verb.negative_potential(WordForm::Short),
+        ),
+        (
+            "negative_potential_long",
+            InflectionType::NegativePotential,
+            verb.negative_potential(WordForm::Long),
+        ),
+        ("imperative", InflectionType::Imperative, verb.imperative()),
+        (
+            "imperative_negative",
+            InflectionType::ImperativeNegative,
+            verb.imperative_negative(),
+        ),
+        ("causative", InflectionType::Causative, verb.causative()),
+        (
+            "causative_passive",
+            InflectionType::CausativePassive,
+            verb.causative_passive(),
+        ),
+        (
+            "negative_causative_passive",
+            InflectionType::NegativeCausativePassive,
+            verb.negative_causative_passive(),
+        ),
+        (
+            "negative_causative",
+            InflectionType::NegativeCausative,
+            verb.negative_causative(),
+        ),
+        ("passive", InflectionType::Passive, verb.passive()),
+        (
+            "negative_passive",
+            InflectionType::NegativePassive,
+            verb.negative_passive(),
+        ),
+    ];
+
+    let mut failures = Vec::new();
+    let mut words = Vec::new();
+    for (form, inflection_type, word) in forms {
+        let irregular_word =
+            irregular.and_then(|irregular| irregular.form(form, kana, kanji));
+        match irregular_word.map_or(word, Ok) {
+            Ok(word) => words.push((inflection_type, word)),
+            Err(error) => failures.push(ConjugationFailure::new(form, error)),
+        }
+    }
+
+    let term_entry = |inflection_type, form_penalty| TermEntry {
+        entry_index,
+        inflection_type,
+        other_script: false,
+        form_penalty,
+        folded: false,
+        dictionary: DictionaryId::MAIN,
+    };
+    for (inflection_type, word) in words {
+        if let Some(kanji) = word.kanji {
+            dictionary
+                .kanji
+                .entry(kanji)
+                .or_insert_with(|| vec![term_entry(inflection_type, form_penalty)]);
+        }
+        dictionary
+            .kana
+            .entry(word.kana)
+            .or_insert_with(|| vec![term_entry(inflection_type, kana_penalty)]);
+    }
+
+    failures
+}
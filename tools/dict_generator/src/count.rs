@@ -0,0 +1,184 @@
+use segmenter::analysis::OwnedToken;
+use segmenter::dictionary::PartOfSpeech;
+use segmenter::frequency::FrequencyTable;
+use segmenter::pipeline::Segmenter;
+use segmenter::tokenizer::{TokenKind, Tokenizer};
+use segmenter::unknown::{UnknownCategory, UnknownWordModel};
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::io::BufRead;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+/// Settings of [`count`].
+#[derive(Debug, Clone)]
+pub struct CountOptions {
+    /// Worker threads tokenizing lines.
+    pub num_threads: usize,
+    /// Number of unknown surfaces kept in the table.
+    pub top_unknown: usize,
+}
+
+impl Default for CountOptions {
+    fn default() -> Self {
+        Self {
+            num_threads: std::thread::available_parallelism().map_or(1, usize::from),
+            top_unknown: 10_000,
+        }
+    }
+}
+
+/// Tally of unknown surfaces that never holds more than `capacity` of them.
+///
+/// Once it is full, the less frequent half is dropped, so a surface's count only covers
+/// the occurrences since it was last dropped and rare surfaces may be missing.
+/// Surfaces more frequent than the pruned ones survive every pruning.
+#[derive(Debug)]
+pub struct UnknownTally {
+    counts: HashMap<String, u64>,
+    capacity: usize,
+}
+
+impl UnknownTally {
+    /// `capacity` is clamped to at least two surfaces.
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            counts: HashMap::new(),
+            capacity: capacity.max(2),
+        }
+    }
+
+    ///
+    pub fn add(&mut self, surface: &str) {
+        if let Some(count) = self.counts.get_mut(surface) {
+            *count += 1;
+            return;
+        }
+
+        if self.counts.len() >= self.capacity {
+            self.prune();
+        }
+        self.counts.insert(surface.to_string(), 1);
+    }
+
+    /// Keeps the more frequent half of the surfaces.
+    fn prune(&mut self) {
+        let mut counts: Vec<u64> = self.counts.values().copied().collect();
+        let keep = self.capacity / 2;
+        let (_, threshold, _) = counts.select_nth_unstable_by(keep, |a, b| b.cmp(a));
+        let threshold = *threshold;
+
+        // Which of the surfaces tied at the threshold are kept is arbitrary.
+        let mut above = counts.iter().filter(|count| **count > threshold).count();
+        self.counts.retain(|_, count| {
+            if *count > threshold {
+                true
+            } else if *count == threshold && above < keep {
+                above += 1;
+                true
+            } else {
+                false
+            }
+        });
+    }
+
+    /// The `k` most frequent surfaces, most frequent first and ties in surface order.
+    pub fn top(self, k: usize) -> Vec<(String, u64)> {
+        let mut counts: Vec<(String, u64)> = self.counts.into_iter().collect();
+        counts.sort_by(|(a, a_count), (b, b_count)| b_count.cmp(a_count).then(a.cmp(b)));
+        counts.truncate(k);
+        counts
+    }
+}
+
+/// Whether an unknown token is worth tallying: not a symbol, emphasis run or blank.
+fn is_unknown_word(token: &OwnedToken) -> bool {
+    token.term_id.is_none()
+        && token.kind == TokenKind::Word
+        && !token.pos.contains(PartOfSpeech::SYMBOL)
+        && !token.surface.trim().is_empty()
+}
+
+/// Every file below `dir`, in path order.
+pub fn corpus_files(dir: &Path) -> std::io::Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    let mut pending = vec![dir.to_path_buf()];
+    while let Some(dir) = pending.pop() {
+        for entry in std::fs::read_dir(&dir)? {
+            let path = entry?.path();
+            if path.is_dir() {
+                pending.push(path);
+            } else {
+                files.push(path);
+            }
+        }
+    }
+    files.sort();
+
+    Ok(files)
+}
+
+/// The lines of `files`, read as they are pulled. The first error ends them and is
+/// kept in `error`. Files have to be UTF-8.
+pub fn corpus_lines<'a>(
+    files: &'a [PathBuf],
+    error: &'a RefCell<Option<std::io::Error>>,
+) -> impl Iterator<Item = String> + 'a {
+    files
+        .iter()
+        .flat_map(|path| match std::fs::File::open(path) {
+            Ok(file) => Some(std::io::BufReader::new(file).lines()),
+            Err(open_error) => {
+                error.borrow_mut().get_or_insert(open_error);
+                None
+            }
+        })
+        .flatten()
+        .map_while(|line| match line {
+            Ok(line) => Some(line),
+            Err(read_error) => {
+                error.borrow_mut().get_or_insert(read_error);
+                None
+            }
+        })
+}
+
+/// Tokenizes every line of `files` and counts the terms and unknown surfaces, and
+/// trains the unknown-word model on the lengths of the unknown surfaces.
+///
+/// Files are read line by line as the workers need them, so the corpus is never held
+/// in memory, and the unknown-surface tally keeps at most a few times
+/// [`CountOptions::top_unknown`] surfaces. Files have to be UTF-8.
+pub fn count(
+    tokenizer: Arc<Tokenizer>,
+    files: &[PathBuf],
+    options: &CountOptions,
+) -> std::io::Result<FrequencyTable> {
+    let error = RefCell::new(None);
+    let lines = corpus_lines(files, &error);
+
+    let mut table = FrequencyTable::default();
+    let mut unknown = UnknownTally::new(options.top_unknown.saturating_mul(4));
+    let mut lengths: HashMap<(UnknownCategory, usize), u64> = HashMap::new();
+    let segmenter = Segmenter::new(tokenizer, options.num_threads);
+    for (_, tokens) in segmenter.process(lines) {
+        for token in tokens.iter() {
+            table.total_tokens += 1;
+            if let Some(term_id) = token.term_id {
+                *table.terms.entry(term_id).or_insert(0) += 1;
+            } else if is_unknown_word(token) {
+                unknown.add(&token.surface);
+                let category = UnknownCategory::of(&token.surface);
+                let length = token.surface.chars().count();
+                *lengths.entry((category, length)).or_insert(0) += 1;
+            }
+        }
+    }
+
+    if let Some(error) = error.into_inner() {
+        return Err(error);
+    }
+    table.unknown = unknown.top(options.top_unknown);
+    table.unknown_lengths = UnknownWordModel::train(&lengths);
+    Ok(table)
+}
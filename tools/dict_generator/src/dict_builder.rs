@@ -0,0 +1,637 @@
+use crate::conjugation::{add_conjugations, ConjugationFailure, GodanIrregular};
+use crate::jmdict::{self, Entry};
+use quick_xml::de::DeError;
+use segmenter::dictionary::{
+    Dialect, Dictionary, DictionaryEntry, DictionaryId, Domain, InflectionType,
+    PartOfSpeech, Tag, TermEntry, TermId, IMPORTED_TERM_IDS,
+};
+use std::io::Read;
+
+/// Settings of [`build_dictionary`].
+#[derive(Debug, Clone)]
+pub struct BuildOptions {
+    /// Index the conjugated forms of godan and ichidan verbs. Without them only
+    /// dictionary forms are indexed, for use with the tokenizer's deinflection lookup.
+    pub expand_conjugations: bool,
+    /// Index every kana surface in both scripts, see
+    /// `Dictionary::index_both_kana_scripts`.
+    pub index_both_kana_scripts: bool,
+    /// Build the index behind `Dictionary::lookup_romaji`.
+    pub with_romaji_index: bool,
+    /// Index the spellings JMdict marks as search-only (`sK`, `sk`) too, at the
+    /// highest [`FORM_PENALTIES`] penalty. Without it they are left out of the index.
+    pub index_search_only_forms: bool,
+    /// Resolve the `xref`s of every entry to term ids, for `Dictionary::xrefs`. Those
+    /// matching no entry are reported as [`BuildWarning::UnresolvedXref`].
+    pub with_xrefs: bool,
+}
+
+impl Default for BuildOptions {
+    fn default() -> Self {
+        Self {
+            expand_conjugations: true,
+            index_both_kana_scripts: false,
+            with_romaji_index: false,
+            index_search_only_forms: false,
+            with_xrefs: false,
+        }
+    }
+}
+
+/// Something the build skipped or couldn't make sense of, which doesn't stop it.
+#[derive(Debug, Clone, PartialEq)]
+pub enum BuildWarning {
+    /// A sense uses a part of speech that isn't in the JMdict DTD, reported for the first
+    /// entry using it only. The entries are kept, without flags for it.
+    UnknownPartOfSpeech { ent_seq: i64, pos: String },
+    /// The entry has no reading element and was skipped.
+    MissingReading { ent_seq: i64 },
+    /// The `ent_seq` can't be a term id, being negative or in `IMPORTED_TERM_IDS`, so
+    /// the entry was skipped.
+    InvalidEntSeq { ent_seq: i64 },
+    /// A conjugated form of the verb couldn't be derived and was left out.
+    ConjugationFailed {
+        ent_seq: i64,
+        kana: String,
+        kanji: Option<String>,
+        failure: ConjugationFailure,
+    },
+    /// An `xref` names no entry in the document, or only a skipped one, and was left out.
+    UnresolvedXref { ent_seq: i64, xref: String },
+}
+
+impl std::fmt::Display for BuildWarning {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::UnknownPartOfSpeech { ent_seq, pos } => {
+                write!(f, "entry {ent_seq}: unknown part of speech `{pos}`")
+            }
+            Self::MissingReading { ent_seq } => {
+                write!(f, "entry {ent_seq}: no reading, skipped")
+            }
+            Self::InvalidEntSeq { ent_seq } => {
+                write!(
+                    f,
+                    "entry {ent_seq}: ent_seq out of the term id range, skipped"
+                )
+            }
+            Self::ConjugationFailed {
+                ent_seq,
+                kana,
+                kanji,
+                failure,
+            } => {
+                let surface = kanji.as_deref().unwrap_or(kana);
+                write!(
+                    f,
+                    "entry {ent_seq}: no {} form of {surface} ({kana}): {}",
+                    failure.form, failure.error
+                )
+            }
+            Self::UnresolvedXref { ent_seq, xref } => {
+                write!(f, "entry {ent_seq}: no entry for xref `{xref}`")
+            }
+        }
+    }
+}
+
+///
+#[derive(Debug)]
+pub enum BuildError {
+    /// The source isn't a JMdict document.
+    Parse(DeError),
+    /// Several entries share an `ent_seq`, which is their term id.
+    TermIdCollision(TermId),
+}
+
+impl std::fmt::Display for BuildError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Parse(error) => write!(f, "invalid JMdict document: {error}"),
+            Self::TermIdCollision(term_id) => {
+                write!(f, "ent_seq {term_id} is used by several entries")
+            }
+        }
+    }
+}
+
+impl std::error::Error for BuildError {}
+
+impl From<DeError> for BuildError {
+    fn from(error: DeError) -> Self {
+        Self::Parse(error)
+    }
+}
+
+/// Flags of the parts of speech declared by the JMdict DTD, by entity name. Those
+/// without a flag of their own map to none: na-, taru- and the classical adjectives,
+/// nidan, yodan and the other irregular verb classes, and `unc` and `v-unspec`. A name
+/// missing from the table is a [`BuildWarning::UnknownPartOfSpeech`].
+pub const PARTS_OF_SPEECH: &[(&str, PartOfSpeech)] = &[
+    ("adj-f", PartOfSpeech::ADJECTIVE_PRENOMINAL),
+    ("adj-i", PartOfSpeech::ADJECTIVE),
+    ("adj-ix", PartOfSpeech::ADJECTIVE),
+    ("adj-kari", PartOfSpeech::NONE),
+    ("adj-ku", PartOfSpeech::NONE),
+    ("adj-na", PartOfSpeech::NONE),
+    ("adj-nari", PartOfSpeech::NONE),
+    ("adj-no", PartOfSpeech::ADJECTIVE_NO),
+    ("adj-pn", PartOfSpeech::PRE_NOUN_ADJECTIVAL),
+    ("adj-shiku", PartOfSpeech::NONE),
+    ("adj-t", PartOfSpeech::NONE),
+    ("adv", PartOfSpeech::ADVERB),
+    ("adv-to", PartOfSpeech::ADVERB_TO),
+    ("aux", PartOfSpeech::AUXILIARY),
+    ("aux-adj", PartOfSpeech::AUXILIARY_ADJECTIVE),
+    ("aux-v", PartOfSpeech::AUXILIARY_VERB),
+    ("conj", PartOfSpeech::CONJUNCTION),
+    ("cop", PartOfSpeech::COPULA),
+    ("ctr", PartOfSpeech::COUNTER),
+    ("exp", PartOfSpeech::EXPRESSION),
+    ("int", PartOfSpeech::INTERJECTION),
+    ("n", PartOfSpeech::NOUN),
+    ("n-adv", PartOfSpeech::NOUN_ADVERB),
+    ("n-pr", PartOfSpeech::NOUN_PROPER),
+    ("n-pref", PartOfSpeech::NOUN_PREFIX),
+    ("n-suf", PartOfSpeech::NOUN_SUFFIX),
+    ("n-t", PartOfSpeech::NOUN_TEMPORAL),
+    ("num", PartOfSpeech::NUMERIC),
+    ("pn", PartOfSpeech::PRONOUN),
+    ("pref", PartOfSpeech::PREFIX),
+    ("prt", PartOfSpeech::PARTICLE),
+    ("suf", PartOfSpeech::SUFFIX),
+    ("unc", PartOfSpeech::NONE),
+    ("v-unspec", PartOfSpeech::NONE),
+    ("v1", PartOfSpeech::ICHIDAN_VERB),
+    ("v1-s", PartOfSpeech::ICHIDAN_VERB),
+    ("v2a-s", PartOfSpeech::NONE),
+    ("v2b-k", PartOfSpeech::NONE),
+    ("v2b-s", PartOfSpeech::NONE),
+    ("v2d-k", PartOfSpeech::NONE),
+    ("v2d-s", PartOfSpeech::NONE),
+    ("v2g-k", PartOfSpeech::NONE),
+    ("v2g-s", PartOfSpeech::NONE),
+    ("v2h-k", PartOfSpeech::NONE),
+    ("v2h-s", PartOfSpeech::NONE),
+    ("v2k-k", PartOfSpeech::NONE),
+    ("v2k-s", PartOfSpeech::NONE),
+    ("v2m-k", PartOfSpeech::NONE),
+    ("v2m-s", PartOfSpeech::NONE),
+    ("v2n-s", PartOfSpeech::NONE),
+    ("v2r-k", PartOfSpeech::NONE),
+    ("v2r-s", PartOfSpeech::NONE),
+    ("v2s-s", PartOfSpeech::NONE),
+    ("v2t-k", PartOfSpeech::NONE),
+    ("v2t-s", PartOfSpeech::NONE),
+    ("v2w-s", PartOfSpeech::NONE),
+    ("v2y-k", PartOfSpeech::NONE),
+    ("v2y-s", PartOfSpeech::NONE),
+    ("v2z-s", PartOfSpeech::NONE),
+    ("v4b", PartOfSpeech::NONE),
+    ("v4g", PartOfSpeech::NONE),
+    ("v4h", PartOfSpeech::NONE),
+    ("v4k", PartOfSpeech::NONE),
+    ("v4m", PartOfSpeech::NONE),
+    ("v4n", PartOfSpeech::NONE),
+    ("v4r", PartOfSpeech::NONE),
+    ("v4s", PartOfSpeech::NONE),
+    ("v4t", PartOfSpeech::NONE),
+    ("v5aru", PartOfSpeech::GODAN_VERB),
+    ("v5b", PartOfSpeech::GODAN_VERB),
+    ("v5g", PartOfSpeech::GODAN_VERB),
+    ("v5k", PartOfSpeech::GODAN_VERB),
+    ("v5k-s", PartOfSpeech::GODAN_VERB),
+    ("v5m", PartOfSpeech::GODAN_VERB),
+    ("v5n", PartOfSpeech::GODAN_VERB),
+    ("v5r", PartOfSpeech::GODAN_VERB),
+    ("v5r-i", PartOfSpeech::GODAN_VERB),
+    ("v5s", PartOfSpeech::GODAN_VERB),
+    ("v5t", PartOfSpeech::GODAN_VERB),
+    ("v5u", PartOfSpeech::GODAN_VERB),
+    ("v5u-s", PartOfSpeech::GODAN_VERB),
+    ("v5uru", PartOfSpeech::GODAN_VERB),
+    ("vi", PartOfSpeech::INTRANSITIVE_VERB),
+    ("vk", PartOfSpeech::KURU_VERB),
+    ("vn", PartOfSpeech::NONE),
+    ("vr", PartOfSpeech::NONE),
+    ("vs", PartOfSpeech::SURU_VERB),
+    ("vs-c", PartOfSpeech::SURU_VERB),
+    ("vs-i", PartOfSpeech::SURU_VERB),
+    ("vs-s", PartOfSpeech::SURU_VERB),
+    ("vt", PartOfSpeech::TRANSITIVE_VERB),
+    ("vz", PartOfSpeech::ICHIDAN_VERB),
+];
+
+/// `TermEntry::form_penalty` points of the JMdict markers for irregular (`iK`, `ik`,
+/// `io`), outdated (`oK`, `ok`), rarely used (`rK`, `rk`) and search-only (`sK`, `sk`)
+/// spellings, by entity name. A surface with several markers takes the highest.
+pub const FORM_PENALTIES: &[(&str, u8)] = &[
+    ("io", 1),
+    ("oK", 1),
+    ("ok", 1),
+    ("iK", 2),
+    ("ik", 2),
+    ("rK", 2),
+    ("rk", 2),
+    ("sK", 3),
+    ("sk", 3),
+];
+
+/// Dialects of the JMdict `dial` markers, by entity name.
+pub const DIALECTS: &[(&str, Dialect)] = &[
+    ("bra", Dialect::BRAZILIAN),
+    ("hob", Dialect::HOKKAIDO),
+    ("ksb", Dialect::KANSAI),
+    ("ktb", Dialect::KANTOU),
+    ("kyb", Dialect::KYOTO),
+    ("kyu", Dialect::KYUUSHUU),
+    ("nab", Dialect::NAGANO),
+    ("osb", Dialect::OSAKA),
+    ("rkb", Dialect::RYUUKYUU),
+    ("thb", Dialect::TOUHOKU),
+    ("tsb", Dialect::TOSA),
+    ("tsug", Dialect::TSUGARU),
+];
+
+/// Tags of the JMdict `misc` markers, by entity name. Other markers are left out.
+pub const TAGS: &[(&str, Tag)] = &[
+    ("abbr", Tag::ABBREVIATION),
+    ("arch", Tag::ARCHAIC),
+    ("dated", Tag::DATED_TERM),
+    ("hist", Tag::HISTORICAL_TERM),
+    ("hon", Tag::SONKEIGO),
+    ("hum", Tag::KENJOUGO),
+    ("id", Tag::IDIOMATIC_EXPRESSION),
+    ("obs", Tag::OBSOLETE_TERM),
+    ("pol", Tag::TEINEIGO),
+    ("rare", Tag::RARE),
+    ("uk", Tag::USUALLY_KANA),
+    ("yoji", Tag::YOJIJUKUGO),
+];
+
+/// Domains of the JMdict `field` markers, by entity name. Fields without a [`Domain`]
+/// are left out.
+pub const DOMAINS: &[(&str, Domain)] = &[
+    ("agric", Domain::AGRICULTURE),
+    ("anat", Domain::ANATOMY),
+    ("archit", Domain::ARCHITECTURE),
+    ("astron", Domain::ASTRONOMY),
+    ("baseb", Domain::BASEBALL),
+    ("biol", Domain::BIOLOGY),
+    ("bot", Domain::BOTANY),
+    ("Buddh", Domain::BUDDHISM),
+    ("bus", Domain::BUSINESS),
+    ("chem", Domain::CHEMISTRY),
+    ("comp", Domain::COMPUTING),
+    ("econ", Domain::ECONOMICS),
+    ("elec", Domain::ELECTRICITY),
+    ("engr", Domain::ENGINEERING),
+    ("finc", Domain::FINANCE),
+    ("food", Domain::FOOD),
+    ("geol", Domain::GEOLOGY),
+    ("gramm", Domain::GRAMMAR),
+    ("law", Domain::LAW),
+    ("ling", Domain::LINGUISTICS),
+    ("math", Domain::MATHEMATICS),
+    ("med", Domain::MEDICINE),
+    ("mil", Domain::MILITARY),
+    ("music", Domain::MUSIC),
+    ("physics", Domain::PHYSICS),
+    ("sports", Domain::SPORTS),
+    ("sumo", Domain::SUMO),
+    ("zool", Domain::ZOOLOGY),
+];
+
+/// The form penalty of a kanji or reading element with the `ke_inf` or `re_inf`
+/// markers `infos`, `None` for a search-only form that isn't to be indexed.
+fn form_penalty(infos: Option<&[String]>, options: &BuildOptions) -> Option<u8> {
+    let infos = infos.unwrap_or_default();
+    let search_only = infos.iter().any(|info| info == "sK" || info == "sk");
+    if search_only && !options.index_search_only_forms {
+        return None;
+    }
+
+    let penalty = FORM_PENALTIES
+        .iter()
+        .filter(|(name, _)| infos.iter().any(|info| info == name))
+        .map(|(_, penalty)| *penalty)
+        .max();
+    Some(penalty.unwrap_or(0))
+}
+
+/// Builds a dictionary from the JMdict document in `source`.
+///
+/// Entries are added in `ent_seq` order, with their `ent_seq` as term id. JMdict
+/// releases list them in that order anyway, and it makes the dictionary independent of
+/// the order of the document. Conjugations are indexed once every entry has its own
+/// surfaces, so they only take surfaces no entry has, like the potential of 開く, which
+/// is spelled like 開ける. Problems with single entries are returned as warnings, in the
+/// order they were found, and only a document that can't be parsed or repeats an
+/// `ent_seq` fails the build.
+pub fn build_dictionary(
+    source: impl Read,
+    options: &BuildOptions,
+) -> Result<(Dictionary, Vec<BuildWarning>), BuildError> {
+    let reader = std::io::BufReader::with_capacity(1024 * 1024 * 128, source);
+    let mut dict = jmdict::parse(reader)?;
+    dict.entries.sort_by_key(|entry| entry.ent_seq);
+
+    let mut dictionary = Dictionary::new();
+    let mut warnings = Vec::new();
+    // Dictionary entry of every JMdict entry, `None` for skipped ones.
+    let mut entry_indices = Vec::with_capacity(dict.entries.len());
+    let mut conjugations = Vec::new();
+    for entry in dict.entries.iter() {
+        let entry_index = add_entry(
+            &mut dictionary,
+            entry,
+            options,
+            &mut conjugations,
+            &mut warnings,
+        );
+        entry_indices.push(entry_index);
+    }
+    for conjugation in conjugations {
+        let failures = add_conjugations(
+            &mut dictionary,
+            conjugation.verb_type,
+            &conjugation.kana,
+            conjugation.kanji.as_deref(),
+            conjugation.entry_index,
+            conjugation.form_penalty,
+            conjugation.irregular,
+        );
+        warnings.extend(failures.into_iter().map(|failure| {
+            BuildWarning::ConjugationFailed {
+                ent_seq: conjugation.ent_seq,
+                kana: conjugation.kana.clone(),
+                kanji: conjugation.kanji.clone(),
+                failure,
+            }
+        }));
+    }
+    if let Some(&term_id) = dictionary.duplicate_term_ids().first() {
+        return Err(BuildError::TermIdCollision(term_id));
+    }
+
+    for (index, partner) in jmdict::transitivity_pairs(&dict.entries) {
+        if let (Some(index), Some(partner)) =
+            (entry_indices[index], entry_indices[partner])
+        {
+            let partner_term_id = dictionary.entries[partner as usize].term_id;
+            dictionary.entries[index as usize].pair_term_id = Some(partner_term_id);
+        }
+    }
+
+    if options.with_xrefs {
+        dictionary.xrefs = vec![Vec::new(); dictionary.entries.len()];
+        let resolved = jmdict::resolve_xrefs(&dict.entries);
+        for (index, xrefs) in resolved.into_iter().enumerate() {
+            let Some(entry_index) = entry_indices[index] else {
+                continue;
+            };
+            for (xref, other) in xrefs {
+                match other.and_then(|other| entry_indices[other]) {
+                    Some(other) => {
+                        let term_id = dictionary.entries[other as usize].term_id;
+                        let targets = &mut dictionary.xrefs[entry_index as usize];
+                        if !targets.contains(&term_id) {
+                            targets.push(term_id);
+                        }
+                    }
+                    None => warnings.push(BuildWarning::UnresolvedXref {
+                        ent_seq: dict.entries[index].ent_seq,
+                        xref: xref.to_string(),
+                    }),
+                }
+            }
+        }
+    }
+
+    dictionary.sort_term_entries();
+    if options.index_both_kana_scripts {
+        dictionary.index_both_kana_scripts();
+    }
+    if options.with_romaji_index {
+        dictionary.build_romaji_index();
+    }
+    Ok((dictionary, warnings))
+}
+
+/// A spelling of a verb whose conjugations are still to be indexed.
+struct Conjugation {
+    ent_seq: i64,
+    verb_type: jp_inflections::VerbType,
+    kana: String,
+    kanji: Option<String>,
+    entry_index: u32,
+    form_penalty: u8,
+    irregular: Option<GodanIrregular>,
+}
+
+/// Warns about the part of speech `pos` missing from [`PARTS_OF_SPEECH`], unless an
+/// earlier entry used it already.
+fn report_unknown_pos(warnings: &mut Vec<BuildWarning>, ent_seq: i64, pos: &str) {
+    let reported = warnings.iter().any(|warning| {
+        matches!(
+            warning,
+            BuildWarning::UnknownPartOfSpeech { pos: name, .. } if name == pos
+        )
+    });
+    if !reported {
+        warnings.push(BuildWarning::UnknownPartOfSpeech {
+            ent_seq,
+            pos: pos.to_string(),
+        });
+    }
+}
+
+/// Adds the surfaces of `entry` and, if it is a verb, queues their conjugations onto
+/// `conjugations`. Returns the index of the new dictionary entry, `None` if the entry
+/// was skipped.
+fn add_entry(
+    dictionary: &mut Dictionary,
+    entry: &Entry,
+    options: &BuildOptions,
+    conjugations: &mut Vec<Conjugation>,
+    warnings: &mut Vec<BuildWarning>,
+) -> Option<u32> {
+    use wana_kana::ConvertJapanese;
+
+    let ent_seq = entry.ent_seq;
+    let term_id = match TermId::try_from(ent_seq) {
+        Ok(term_id) if !IMPORTED_TERM_IDS.contains(&term_id) => term_id,
+        _ => {
+            warnings.push(BuildWarning::InvalidEntSeq { ent_seq });
+            return None;
+        }
+    };
+    let kanji_words: Vec<String> = entry
+        .kanji_elements
+        .iter()
+        .flatten()
+        .filter_map(|kanji_element| kanji_element.keb.clone())
+        .collect();
+    let kana_words: Vec<String> = entry
+        .reading_elements
+        .iter()
+        .flatten()
+        .filter_map(|reading_element| reading_element.reb.clone())
+        .collect();
+    // The surfaces to index, with their form penalties.
+    let indexed_kanji: Vec<(&str, u8)> = entry
+        .kanji_elements
+        .iter()
+        .flatten()
+        .filter_map(|kanji_element| {
+            let penalty = form_penalty(kanji_element.ke_inf.as_deref(), options)?;
+            Some((kanji_element.keb.as_deref()?, penalty))
+        })
+        .collect();
+    let indexed_kana: Vec<(&str, u8)> = entry
+        .reading_elements
+        .iter()
+        .flatten()
+        .filter_map(|reading_element| {
+            let penalty = form_penalty(reading_element.re_inf.as_deref(), options)?;
+            Some((reading_element.reb.as_deref()?, penalty))
+        })
+        .collect();
+    if kana_words.is_empty() {
+        warnings.push(BuildWarning::MissingReading { ent_seq });
+        return None;
+    }
+
+    // Readings carrying a priority marker come first, otherwise JMdict order is kept.
+    let mut readings: Vec<(bool, String)> = entry
+        .reading_elements
+        .iter()
+        .flatten()
+        .filter_map(|reading_element| {
+            let reb = reading_element.reb.clone()?;
+            Some((reading_element.re_pri.is_some(), reb))
+        })
+        .collect();
+    readings.sort_by_key(|(has_priority, _)| !has_priority);
+
+    let mut part_of_speeches: Vec<&str> = Vec::new();
+    let mut pos_flags = PartOfSpeech::NONE;
+    for sense in entry.senses.iter().flatten() {
+        for pos in sense.pos.iter() {
+            if part_of_speeches.contains(&pos.as_str()) {
+                continue;
+            }
+            match PARTS_OF_SPEECH.iter().find(|(name, _)| name == pos) {
+                Some((_, flags)) => pos_flags |= *flags,
+                None => report_unknown_pos(warnings, ent_seq, pos),
+            }
+            part_of_speeches.push(pos);
+        }
+    }
+
+    let verb_type = if part_of_speeches.iter().any(|pos| pos.starts_with("v5")) {
+        Some(jp_inflections::VerbType::Godan)
+    } else if part_of_speeches.iter().any(|pos| pos.starts_with("v1")) {
+        Some(jp_inflections::VerbType::Ichidan)
+    } else {
+        None
+    };
+    let verb_type = verb_type.filter(|_| options.expand_conjugations);
+    let irregular = GodanIrregular::from_pos(&part_of_speeches);
+
+    // Ateji and gikun spellings have readings that don't split up per kanji.
+    let ateji = entry
+        .kanji_elements
+        .iter()
+        .flatten()
+        .flat_map(|kanji_element| kanji_element.ke_inf.iter().flatten())
+        .any(|info| info == "ateji" || info == "gikun");
+    let tag = entry
+        .senses
+        .iter()
+        .flatten()
+        .flat_map(|sense| sense.misc.iter().flatten())
+        .filter_map(|misc| TAGS.iter().find(|(name, _)| name == misc))
+        .fold(Tag::NONE, |tag, (_, flag)| tag | *flag);
+    let dialect = entry
+        .senses
+        .iter()
+        .flatten()
+        .flat_map(|sense| sense.dial.iter().flatten())
+        .filter_map(|dial| DIALECTS.iter().find(|(name, _)| name == dial))
+        .fold(Dialect::NONE, |dialect, (_, flag)| dialect | *flag);
+    let domain = entry
+        .senses
+        .iter()
+        .flatten()
+        .flat_map(|sense| sense.field.iter().flatten())
+        .filter_map(|field| DOMAINS.iter().find(|(name, _)| name == field))
+        .fold(Domain::NONE, |domain, (_, flag)| domain | *flag);
+
+    let entry_index = dictionary.entries.len() as u32;
+    dictionary.entries.push(DictionaryEntry {
+        term_id,
+        pos: pos_flags,
+        tag: if ateji { tag | Tag::ATEJI } else { tag },
+        pair_term_id: None,
+        dialect,
+        domain,
+    });
+    dictionary
+        .readings
+        .push(readings.into_iter().map(|(_, reb)| reb).collect());
+    dictionary.kanji_forms.push(kanji_words.clone());
+
+    let term_entry = TermEntry {
+        entry_index,
+        inflection_type: InflectionType::DictionaryForm,
+        other_script: false,
+        form_penalty: 0,
+        folded: false,
+        dictionary: DictionaryId::MAIN,
+    };
+    let mut conjugate = |kana: &str, kanji: Option<&str>, form_penalty: u8| {
+        let Some(verb_type) = verb_type else {
+            return;
+        };
+        conjugations.push(Conjugation {
+            ent_seq,
+            verb_type,
+            kana: kana.to_hiragana(),
+            kanji: kanji.map(str::to_string),
+            entry_index,
+            form_penalty,
+            irregular,
+        });
+    };
+
+    for &(kanji, form_penalty) in indexed_kanji.iter() {
+        dictionary
+            .kanji
+            .entry(kanji.to_string())
+            .or_default()
+            .push(TermEntry {
+                form_penalty,
+                ..term_entry
+            });
+        for &(kana, _) in indexed_kana.iter() {
+            conjugate(kana, Some(kanji), form_penalty);
+        }
+    }
+
+    for &(kana, form_penalty) in indexed_kana.iter() {
+        dictionary
+            .kana
+            .entry(kana.to_string())
+            .or_default()
+            .push(TermEntry {
+                form_penalty,
+                ..term_entry
+            });
+        if kanji_words.is_empty() {
+            conjugate(kana, None, form_penalty);
+        }
+    }
+
+    Some(entry_index)
+}
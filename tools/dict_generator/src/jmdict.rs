@@ -0,0 +1,411 @@
+use quick_xml::de::{DeError, Deserializer, EntityResolver};
+use quick_xml::events::BytesText;
+use regex::bytes::Regex;
+use segmenter::dictionary::Transitivity;
+use serde::Deserialize;
+use std::collections::{BTreeMap, HashMap};
+use std::string::FromUtf8Error;
+
+/// Resolves the entities declared in the DOCTYPE, which JMdict uses for the markers of
+/// its elements, such as `&n;` in `pos`.
+///
+/// A reference resolves to the entity's name rather than its description, so `&n;`
+/// reads as `n`. The builder's marker tables key off the names, which stay the same
+/// across releases while the descriptions get reworded. The descriptions are collected
+/// into `descriptions`, by name.
+struct DocTypeEntityResolver<'d> {
+    re: Regex,
+    descriptions: &'d mut BTreeMap<String, String>,
+}
+
+impl<'d> DocTypeEntityResolver<'d> {
+    fn new(descriptions: &'d mut BTreeMap<String, String>) -> Self {
+        Self {
+            // We do not focus on true parsing in this example
+            // You should use special libraries to parse DTD
+            re: Regex::new(r#"<!ENTITY\s+([^ \t\r\n]+)\s+"([^"]*)"\s*>"#).unwrap(),
+            descriptions,
+        }
+    }
+}
+
+impl EntityResolver for DocTypeEntityResolver<'_> {
+    type Error = FromUtf8Error;
+
+    fn capture(&mut self, doctype: BytesText) -> Result<(), Self::Error> {
+        for cap in self.re.captures_iter(&doctype) {
+            self.descriptions.insert(
+                String::from_utf8(cap[1].to_vec())?,
+                String::from_utf8(cap[2].to_vec())?,
+            );
+        }
+        Ok(())
+    }
+
+    fn resolve(&self, entity: &str) -> Option<&str> {
+        self.descriptions
+            .get_key_value(entity)
+            .map(|(name, _)| name.as_str())
+    }
+}
+
+// Every element and attribute of the JMdict DTD has a field, even the ones the generator
+// doesn't use yet, and unknown ones are rejected so format changes can't go unnoticed.
+// Missing elements are left out when serializing, so written documents parse back to the
+// same entries.
+
+#[derive(Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct JMdict {
+    #[serde(rename = "entry")]
+    pub entries: Vec<Entry>,
+}
+
+#[derive(Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(rename = "entry", deny_unknown_fields)]
+pub struct Entry {
+    pub ent_seq: i64,
+    #[serde(rename = "k_ele", skip_serializing_if = "Option::is_none")]
+    pub kanji_elements: Option<Vec<KanjiElement>>,
+    #[serde(rename = "r_ele", skip_serializing_if = "Option::is_none")]
+    pub reading_elements: Option<Vec<ReadingElement>>,
+    #[serde(rename = "sense", skip_serializing_if = "Option::is_none")]
+    pub senses: Option<Vec<Sense>>,
+}
+
+#[allow(dead_code)]
+#[derive(Debug, serde::Serialize, serde::Deserialize)]
+#[serde(rename = "ent_seq")]
+pub struct EntSeq {
+    #[serde(rename = "$value")]
+    pub body: i64,
+}
+
+#[derive(Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(rename = "k_ele", deny_unknown_fields)]
+pub struct KanjiElement {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub keb: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ke_inf: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ke_pri: Option<Vec<String>>,
+}
+
+#[derive(Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(rename = "r_ele", deny_unknown_fields)]
+pub struct ReadingElement {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reb: Option<String>,
+    /// Present, and empty, when the reading isn't a true reading of the kanji, like
+    /// those of gairaigo written with ateji.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub re_nokanji: Option<String>,
+    /// The kanji elements the reading applies to, all of them if `None`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub re_restr: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub re_inf: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub re_pri: Option<Vec<String>>,
+}
+
+#[allow(dead_code)]
+#[derive(Debug, serde::Serialize, serde::Deserialize)]
+#[serde(rename = "pos")]
+pub struct PoS {
+    #[serde(rename = "$value")]
+    pub body: String,
+}
+
+#[derive(Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(rename = "sense", deny_unknown_fields)]
+pub struct Sense {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stagk: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stagr: Option<Vec<String>>,
+    /// Empty where the parts of speech of the previous sense carry over.
+    #[serde(default)]
+    pub pos: Vec<String>,
+    /// Related entries, see [`CrossReference`].
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub xref: Option<Vec<String>>,
+    /// Entries of opposite meaning, in the format of `xref`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ant: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub field: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub misc: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub s_inf: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub lsource: Option<Vec<LoanSource>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dial: Option<Vec<String>>,
+    #[serde(default)]
+    pub gloss: Vec<Gloss>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub example: Option<Vec<Example>>,
+}
+
+/// The language and word a loanword came from.
+#[derive(Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(rename = "lsource", deny_unknown_fields)]
+pub struct LoanSource {
+    /// ISO 639-2 code, English if `None`.
+    #[serde(
+        rename(serialize = "@xml:lang", deserialize = "@lang"),
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub lang: Option<String>,
+    /// `part` if the word is only partly taken from the source.
+    #[serde(rename = "@ls_type", skip_serializing_if = "Option::is_none")]
+    pub ls_type: Option<String>,
+    /// `y` for wasei-eigo style words made up from the source language.
+    #[serde(rename = "@ls_wasei", skip_serializing_if = "Option::is_none")]
+    pub ls_wasei: Option<String>,
+    /// The source word, `None` where only the language is known.
+    #[serde(rename = "$text", skip_serializing_if = "Option::is_none")]
+    pub text: Option<String>,
+}
+
+impl LoanSource {
+    ///
+    pub fn language(&self) -> &str {
+        self.lang.as_deref().unwrap_or("eng")
+    }
+}
+
+/// A translation of a sense.
+#[derive(Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(rename = "gloss", deny_unknown_fields)]
+pub struct Gloss {
+    /// ISO 639-2 code, English if `None`.
+    #[serde(
+        rename(serialize = "@xml:lang", deserialize = "@lang"),
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub lang: Option<String>,
+    #[serde(rename = "@g_gend", skip_serializing_if = "Option::is_none")]
+    pub g_gend: Option<String>,
+    /// `lit`, `fig`, `expl`, `tm` for literal, figurative or explanatory translations
+    /// and trademarks.
+    #[serde(rename = "@g_type", skip_serializing_if = "Option::is_none")]
+    pub g_type: Option<String>,
+    #[serde(rename = "$text")]
+    pub text: String,
+}
+
+impl Gloss {
+    ///
+    pub fn language(&self) -> &str {
+        self.lang.as_deref().unwrap_or("eng")
+    }
+}
+
+/// An example sentence using the entry.
+#[derive(Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(rename = "example", deny_unknown_fields)]
+pub struct Example {
+    pub ex_srce: ExampleSource,
+    /// The form of the entry the sentence uses.
+    pub ex_text: String,
+    pub ex_sent: Vec<ExampleSentence>,
+}
+
+/// Where an [`Example`] comes from, like a Tatoeba sentence id.
+#[derive(Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(rename = "ex_srce", deny_unknown_fields)]
+pub struct ExampleSource {
+    #[serde(rename = "@exsrc_type", skip_serializing_if = "Option::is_none")]
+    pub exsrc_type: Option<String>,
+    #[serde(rename = "$text")]
+    pub text: String,
+}
+
+///
+#[derive(Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(rename = "ex_sent", deny_unknown_fields)]
+pub struct ExampleSentence {
+    #[serde(
+        rename(serialize = "@xml:lang", deserialize = "@lang"),
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub lang: Option<String>,
+    #[serde(rename = "$text")]
+    pub text: String,
+}
+
+/// A parsed `xref` or `ant`: `surface`, `surface・reading` or either followed by
+/// `・sense number`. The surface may be kanji or kana.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CrossReference<'a> {
+    pub surface: &'a str,
+    pub reading: Option<&'a str>,
+    /// 1-based index of the sense referred to.
+    pub sense: Option<u32>,
+}
+
+impl<'a> CrossReference<'a> {
+    ///
+    pub fn parse(xref: &'a str) -> Self {
+        let mut parts = xref.split('・');
+        let surface = parts.next().unwrap_or_default();
+        let mut reading = None;
+        let mut sense = None;
+        for part in parts {
+            match part.parse() {
+                Ok(number) => sense = Some(number),
+                Err(_) => reading = Some(part),
+            }
+        }
+
+        Self {
+            surface,
+            reading,
+            sense,
+        }
+    }
+}
+
+/// Parses a JMdict XML document. Entity references read as the entity's name, like
+/// `n` for `&n;`, see [`parse_with_entities`] for their descriptions.
+pub fn parse(reader: impl std::io::BufRead) -> Result<JMdict, DeError> {
+    parse_with_entities(reader).map(|(dict, _)| dict)
+}
+
+/// [`parse`], along with the description of every entity the DOCTYPE declares, by
+/// name, such as `noun (common) (futsuumeishi)` for `n`.
+pub fn parse_with_entities(
+    reader: impl std::io::BufRead,
+) -> Result<(JMdict, BTreeMap<String, String>), DeError> {
+    let mut descriptions = BTreeMap::new();
+    let dict = {
+        let resolver = DocTypeEntityResolver::new(&mut descriptions);
+        let mut de = Deserializer::with_resolver(reader, resolver);
+        JMdict::deserialize(&mut de)?
+    };
+    Ok((dict, descriptions))
+}
+
+impl Entry {
+    /// `Some` if the senses mark the verb only `vt` or only `vi`.
+    pub fn transitivity(&self) -> Option<Transitivity> {
+        let has = |name: &str| {
+            self.senses
+                .iter()
+                .flatten()
+                .any(|sense| sense.pos.iter().any(|pos| pos == name))
+        };
+        match (has("vt"), has("vi")) {
+            (true, false) => Some(Transitivity::Transitive),
+            (false, true) => Some(Transitivity::Intransitive),
+            _ => None,
+        }
+    }
+
+    /// Kanji elements followed by reading elements.
+    pub fn surfaces(&self) -> impl Iterator<Item = &str> {
+        let kanji = self.kanji_elements.iter().flatten();
+        let readings = self.reading_elements.iter().flatten();
+        kanji
+            .filter_map(|element| element.keb.as_deref())
+            .chain(readings.filter_map(|element| element.reb.as_deref()))
+    }
+}
+
+/// Indices of the entries having each kanji or kana surface, in document order.
+fn entries_by_surface(entries: &[Entry]) -> HashMap<&str, Vec<usize>> {
+    let mut by_surface: HashMap<&str, Vec<usize>> = HashMap::new();
+    for (index, entry) in entries.iter().enumerate() {
+        for surface in entry.surfaces() {
+            let indices = by_surface.entry(surface).or_default();
+            if !indices.contains(&index) {
+                indices.push(index);
+            }
+        }
+    }
+    by_surface
+}
+
+/// Second pass over parsed entries: the index of the transitive or intransitive partner
+/// of every verb whose `xref` points at an entry of the opposite transitivity, such as
+/// 開ける and 開く. Links are made in both directions even if only one side has the xref.
+///
+/// The reading of an xref, when present, has to match too.
+pub fn transitivity_pairs(entries: &[Entry]) -> HashMap<usize, usize> {
+    let by_surface = entries_by_surface(entries);
+    let mut pairs = HashMap::new();
+    for (index, entry) in entries.iter().enumerate() {
+        let Some(transitivity) = entry.transitivity() else {
+            continue;
+        };
+
+        let xrefs = entry
+            .senses
+            .iter()
+            .flatten()
+            .flat_map(|sense| sense.xref.iter().flatten());
+        for xref in xrefs {
+            let CrossReference {
+                surface, reading, ..
+            } = CrossReference::parse(xref);
+
+            let is_partner = |other: usize| {
+                let candidate = &entries[other];
+                other != index
+                    && candidate
+                        .transitivity()
+                        .is_some_and(|other| other != transitivity)
+                    && reading.is_none_or(|reading| {
+                        candidate.surfaces().any(|surface| surface == reading)
+                    })
+            };
+            let mut candidates = by_surface.get(surface).into_iter().flatten().copied();
+            if let Some(partner) = candidates.find(|other| is_partner(*other)) {
+                pairs.entry(index).or_insert(partner);
+                pairs.entry(partner).or_insert(index);
+            }
+        }
+    }
+
+    pairs
+}
+
+/// Second pass over parsed entries: the `xref`s of every entry, in document order, each
+/// with the index of the first other entry having its surface, `None` when there is none.
+///
+/// The reading of an xref, when present, has to match too. Sense numbers are ignored,
+/// since entries are indexed as a whole.
+pub fn resolve_xrefs(entries: &[Entry]) -> Vec<Vec<(&str, Option<usize>)>> {
+    let by_surface = entries_by_surface(entries);
+    let resolve = |index: usize, xref: &str| {
+        let CrossReference {
+            surface, reading, ..
+        } = CrossReference::parse(xref);
+        let mut candidates = by_surface.get(surface).into_iter().flatten().copied();
+        candidates.find(|other| {
+            *other != index
+                && reading.is_none_or(|reading| {
+                    entries[*other].surfaces().any(|surface| surface == reading)
+                })
+        })
+    };
+
+    entries
+        .iter()
+        .enumerate()
+        .map(|(index, entry)| {
+            let xrefs = entry
+                .senses
+                .iter()
+                .flatten()
+                .flat_map(|sense| sense.xref.iter().flatten());
+            xrefs
+                .map(|xref| (xref.as_str(), resolve(index, xref)))
+                .collect()
+        })
+        .collect()
+}
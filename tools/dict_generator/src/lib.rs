@@ -0,0 +1,10 @@
+pub mod check_ids;
+pub mod conjugation;
+pub mod count;
+pub mod dict_builder;
+mod jis0208;
+pub mod jmdict;
+pub mod manifest;
+pub mod mecab_csv;
+pub mod sha256;
+pub mod user_csv;
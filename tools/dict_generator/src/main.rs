@@ -1,389 +1,548 @@
-use quick_xml::de::{Deserializer, EntityResolver};
-use quick_xml::events::BytesText;
-use regex::bytes::Regex;
-use segmenter::dictionary::{Dictionary, DictionaryEntry, InflectionType, TermEntry};
-use serde::Deserialize;
-use std::collections::{HashMap, HashSet};
+use dict_generator::check_ids::check_ids;
+use dict_generator::dict_builder::{build_dictionary, BuildOptions, BuildWarning};
+use dict_generator::manifest::BuildManifest;
+use dict_generator::{count, mecab_csv, user_csv};
+use segmenter::dictionary::{Dictionary, DictionaryError, DictionaryHeader};
+use segmenter::diff;
+use segmenter::pipeline::Segmenter;
+use segmenter::stats;
+use segmenter::tokenizer::Tokenizer;
+use serde_json::Value;
+use std::collections::BTreeMap;
 use std::fs;
-use std::string::FromUtf8Error;
+use std::path::{Path, PathBuf};
 
-struct DocTypeEntityResolver {
-    re: Regex,
-    map: HashMap<String, String>,
-}
+// http://ftp.usf.edu/pub/ftp.monash.edu.au/pub/nihongo/00INDEX.html
 
-impl DocTypeEntityResolver {
-    fn new() -> Self {
-        Self {
-            // We do not focus on true parsing in this example
-            // You should use special libraries to parse DTD
-            re: Regex::new(r#"<!ENTITY\s+([^ \t\r\n]+)\s+"([^"]*)"\s*>"#).unwrap(),
-            map: HashMap::new(),
+fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    match args.first().map(String::as_str) {
+        None | Some("build") => build(&args[args.len().min(1)..]),
+        Some(flag) if flag.starts_with("--") => build(&args),
+        Some("verify") => {
+            let path = args
+                .get(1)
+                .map_or("dictionary_test_sg_jp.bin", String::as_str);
+            verify(path);
+        }
+        Some("append") => append(&args[1..]),
+        Some("count") => count_corpus(&args[1..]),
+        Some("stats") => print_stats(&args[1..]),
+        Some("diff") => print_diff(&args[1..]),
+        Some("check-ids") => check_term_ids(&args[1..]),
+        Some("self-test") => match flag_value(&args[1..], "--dict") {
+            Some(path) => self_test(path),
+            None => {
+                eprintln!("self-test requires --dict");
+                std::process::exit(2);
+            }
+        },
+        Some(command) => {
+            eprintln!("unknown command `{command}`");
+            eprintln!(
+                "usage: dict_generator [build] [--no-conjugation-expansion] \
+                 [--import-mecab-csv <dir>] [--index-both-kana-scripts] \
+                 [--with-romaji-index] [--index-search-only-forms] [--prune <k>] \
+                 [--with-xrefs] [--compact] [--manifest <out.json>]"
+            );
+            eprintln!("       dict_generator verify <dictionary.bin>");
+            eprintln!(
+                "       dict_generator append --dict <in.bin> --user-csv <words.csv> \
+                 --output <out.bin>"
+            );
+            eprintln!(
+                "       dict_generator count --dict <dict.bin> --corpus <dir> \
+                 --out <counts.bin> [--threads <n>] [--top-unknown <k>]"
+            );
+            eprintln!(
+                "       dict_generator stats --dict <dict.bin> --corpus <dir> \
+                 [--ngrams <n>] [--threads <n>]"
+            );
+            eprintln!(
+                "       dict_generator diff --dict-a <a.bin> --dict-b <b.bin> \
+                 --corpus <dir> [--top <n>] [--json]"
+            );
+            eprintln!("       dict_generator check-ids <old.bin> <new.bin>");
+            eprintln!("       dict_generator self-test --dict <dictionary.bin>");
+            std::process::exit(2);
         }
     }
 }
 
-impl EntityResolver for DocTypeEntityResolver {
-    type Error = FromUtf8Error;
+/// With `--no-conjugation-expansion` only dictionary forms are indexed, for use with the
+/// tokenizer's deinflection lookup. `--import-mecab-csv <dir>` adds the words of every
+/// MeCab CSV lexicon in `dir` after the JMdict entries. `--index-both-kana-scripts` also
+/// indexes every kana surface in the other script. `--with-romaji-index` adds the index
+/// behind `Dictionary::lookup_romaji`. `--index-search-only-forms` indexes the spellings
+/// JMdict marks as search-only too. `--prune <k>` keeps the `k` best entries of
+/// every surface, see `Dictionary::prune`. `--compact` then drops the entries left
+/// without surfaces, see `Dictionary::compact`. `--with-xrefs` resolves the JMdict
+/// xrefs behind `Dictionary::xrefs`, warning about those naming no entry.
+/// `--manifest <out.json>` writes a `BuildManifest` of the build: the digests of the
+/// inputs and the output, every option of `BUILD_SWITCHES` and `BUILD_VALUES`, and the
+/// crate versions.
+fn build(args: &[String]) {
+    let index_both_kana_scripts =
+        args.iter().any(|arg| arg == "--index-both-kana-scripts");
+    let with_romaji_index = args.iter().any(|arg| arg == "--with-romaji-index");
+    let mecab_dir = flag_value(args, "--import-mecab-csv");
+    // With MeCab words, both indices are built once they are imported.
+    let options = BuildOptions {
+        expand_conjugations: !args.iter().any(|arg| arg == "--no-conjugation-expansion"),
+        index_both_kana_scripts: index_both_kana_scripts && mecab_dir.is_none(),
+        with_romaji_index: with_romaji_index && mecab_dir.is_none(),
+        index_search_only_forms: args
+            .iter()
+            .any(|arg| arg == "--index-search-only-forms"),
+        with_xrefs: args.iter().any(|arg| arg == "--with-xrefs"),
+    };
+
+    let jmdict_path = Path::new("JMdict_e/JMdict_e.xml");
+    let f = fs::File::open(jmdict_path).unwrap();
+    let (mut dictionary, warnings) = match build_dictionary(f, &options) {
+        Ok(result) => result,
+        Err(error) => {
+            eprintln!("{}: {error}", jmdict_path.display());
+            std::process::exit(1);
+        }
+    };
+    for warning in warnings.iter() {
+        eprintln!("warning: {warning}");
+    }
 
-    fn capture(&mut self, doctype: BytesText) -> Result<(), Self::Error> {
-        for cap in self.re.captures_iter(&doctype) {
-            self.map.insert(
-                String::from_utf8(cap[1].to_vec())?,
-                String::from_utf8(cap[1].to_vec())?,
-            );
+    let mut inputs = vec![jmdict_path.to_path_buf()];
+    if let Some(dir) = mecab_dir {
+        inputs.extend(import_mecab_csv(&mut dictionary, Path::new(dir)));
+        if index_both_kana_scripts {
+            dictionary.index_both_kana_scripts();
+        }
+        if with_romaji_index {
+            dictionary.build_romaji_index();
         }
-        Ok(())
     }
 
-    fn resolve(&self, entity: &str) -> Option<&str> {
-        self.map.get(entity).map(|s| s.as_str())
+    if let Some(text) = flag_value(args, "--prune") {
+        let Ok(max_per_surface) = text.parse() else {
+            eprintln!("--prune expects a number, found `{text}`");
+            std::process::exit(2);
+        };
+        let removed = dictionary.prune(max_per_surface);
+        println!("pruned term entries: {removed}");
+    }
+    if args.iter().any(|arg| arg == "--compact") {
+        let report = dictionary.compact();
+        println!(
+            "compacted: {} entries and {} keys removed",
+            report.entries, report.keys
+        );
     }
-}
 
-#[derive(Debug, serde::Serialize, serde::Deserialize)]
-struct JMdict {
-    #[serde(rename = "entry")]
-    entries: Vec<Entry>,
+    let kanji_len = dictionary.kanji.len();
+    let kana_len = dictionary.kana.len();
+    let entries_len = dictionary.entries.len();
+    println!("kanji len: {kanji_len}");
+    println!("kana len: {kana_len}");
+    println!("entries len: {entries_len}");
+    let unmapped_pos = warnings
+        .iter()
+        .filter(|warning| matches!(warning, BuildWarning::UnknownPartOfSpeech { .. }))
+        .count();
+    println!("unmapped parts of speech: {unmapped_pos}");
+    dictionary.shrink_to_fit();
+    println!("memory usage:\n{}", dictionary.memory_report());
+    let output = Path::new("dictionary_test_sg_jp.bin");
+    let header = dictionary.save(output).unwrap();
+    print_section_lengths(&header);
+
+    if let Some(path) = flag_value(args, "--manifest") {
+        let inputs: Vec<&Path> = inputs.iter().map(PathBuf::as_path).collect();
+        let written = BuildManifest::new(&inputs, recorded_options(args), output)
+            .and_then(|manifest| {
+                let json = serde_json::to_string_pretty(&manifest)?;
+                fs::write(path, json + "\n")
+            });
+        if let Err(error) = written {
+            eprintln!("{path}: {error}");
+            std::process::exit(1);
+        }
+    }
 }
 
-#[derive(Debug, serde::Serialize, serde::Deserialize)]
-#[serde(rename = "entry")]
-struct Entry {
-    ent_seq: i64,
-    #[serde(rename = "k_ele")]
-    kanji_elements: Option<Vec<KanjiElement>>,
-    #[serde(rename = "r_ele")]
-    reading_elements: Option<Vec<ReadingElement>>,
-    #[serde(rename = "sense")]
-    senses: Option<Vec<Sense>>,
+/// Switches of `build`, recorded in its manifest whether they are given or not.
+const BUILD_SWITCHES: &[&str] = &[
+    "--no-conjugation-expansion",
+    "--index-both-kana-scripts",
+    "--with-romaji-index",
+    "--index-search-only-forms",
+    "--with-xrefs",
+    "--compact",
+];
+
+/// Flags of `build` taking a value, recorded in its manifest as `null` when not given.
+const BUILD_VALUES: &[&str] = &["--import-mecab-csv", "--prune"];
+
+/// Every option of a `build` with `args`, for its manifest.
+fn recorded_options(args: &[String]) -> BTreeMap<String, Value> {
+    let switches = BUILD_SWITCHES.iter().map(|flag| {
+        let given = args.iter().any(|arg| arg == flag);
+        (flag.to_string(), Value::Bool(given))
+    });
+    let values = BUILD_VALUES.iter().map(|flag| {
+        let value = flag_value(args, flag).map_or(Value::Null, Value::from);
+        (flag.to_string(), value)
+    });
+    switches.chain(values).collect()
 }
 
-#[derive(Debug, serde::Serialize, serde::Deserialize)]
-#[serde(rename = "ent_seq")]
-struct EntSeq {
-    #[serde(rename = "$value")]
-    pub body: i64,
-}
+/// Imports every `.csv` file in `dir`, in file name order, exiting on the first error.
+/// Returns the paths of the files.
+fn import_mecab_csv(dictionary: &mut Dictionary, dir: &Path) -> Vec<PathBuf> {
+    let fail = |path: &Path, error: &dyn std::fmt::Display| -> ! {
+        eprintln!("{}: {error}", path.display());
+        std::process::exit(1);
+    };
+
+    let mut paths: Vec<_> = match fs::read_dir(dir) {
+        Ok(entries) => entries
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .filter(|path| path.extension().is_some_and(|extension| extension == "csv"))
+            .collect(),
+        Err(error) => fail(dir, &error),
+    };
+    paths.sort();
+
+    let mut words = Vec::new();
+    for path in paths.iter() {
+        let parsed = fs::read(path)
+            .map_err(|error| user_csv::AppendError::Dictionary(error.into()))
+            .and_then(|bytes| mecab_csv::decode(&bytes))
+            .and_then(|(text, encoding)| {
+                let parsed = mecab_csv::parse(&text)?;
+                println!("{}: {} rows ({encoding:?})", path.display(), parsed.len());
+                Ok(parsed)
+            });
+        match parsed {
+            Ok(parsed) => words.extend(parsed),
+            Err(error) => fail(path, &error),
+        }
+    }
 
-#[derive(Debug, serde::Serialize, serde::Deserialize)]
-#[serde(rename = "k_ele")]
-struct KanjiElement {
-    keb: Option<String>,
-    ke_pri: Option<Vec<String>>,
-    ke_inf: Option<Vec<String>>,
+    match mecab_csv::import(dictionary, &words) {
+        Ok(count) => println!("imported {count} MeCab entries"),
+        Err(error) => fail(dir, &error),
+    }
+    paths
 }
 
-#[derive(Debug, serde::Serialize, serde::Deserialize)]
-#[serde(rename = "r_ele")]
-struct ReadingElement {
-    reb: Option<String>,
-    keb: Option<String>,
-    re_inf: Option<Vec<String>>,
-    re_pri: Option<Vec<String>>,
+fn verify(path: &str) {
+    let header = fs::File::open(path)
+        .map_err(DictionaryError::from)
+        .and_then(|mut f| DictionaryHeader::read(&mut f));
+    let dictionary = header.and_then(|header| Ok((header, Dictionary::load(path)?)));
+    let (header, dictionary) = match dictionary {
+        Ok(result) => result,
+        Err(error) => {
+            eprintln!("{path}: {error}");
+            std::process::exit(1);
+        }
+    };
+
+    println!("{path}: format version {}", header.version);
+    println!("kanji len: {}", dictionary.kanji.len());
+    println!("kana len: {}", dictionary.kana.len());
+    println!("entries len: {}", dictionary.entries.len());
+    println!("memory usage:\n{}", dictionary.memory_report());
+    print_section_lengths(&header);
+    print_self_test(&Tokenizer::new(dictionary.into()));
 }
 
-#[derive(Debug, serde::Serialize, serde::Deserialize)]
-#[serde(rename = "pos")]
-struct PoS {
-    #[serde(rename = "$value")]
-    pub body: String,
+/// Loads the dictionary at `path` and runs `Tokenizer::self_test` on it, exiting with 1
+/// when it fails.
+fn self_test(path: &str) {
+    let dictionary = match Dictionary::load(path) {
+        Ok(dictionary) => dictionary,
+        Err(error) => {
+            eprintln!("{path}: {error}");
+            std::process::exit(1);
+        }
+    };
+    print_self_test(&Tokenizer::new(dictionary.into()));
 }
 
-#[derive(Debug, serde::Serialize, serde::Deserialize)]
-#[serde(rename = "sense")]
-struct Sense {
-    pos: Vec<String>,
-    stagk: Option<Vec<String>>,
-    stagr: Option<Vec<String>>,
-    xref: Option<Vec<String>>,
-    ant: Option<Vec<String>>,
-    field: Option<Vec<String>>,
-    misc: Option<Vec<String>>,
-    s_inf: Option<String>,
-    dial: Option<Vec<String>>,
-    gloss: Vec<String>,
+/// Prints the `Tokenizer::self_test` report, exiting with 1 when the check fails.
+fn print_self_test(tokenizer: &Tokenizer) {
+    match tokenizer.self_test() {
+        Ok(report) => println!(
+            "self-test passed in {:?}: {:.1}% of {} entries indexed, {:.1}% of {} \
+             canary tokens known",
+            report.elapsed,
+            report.indexed_fraction * 100.0,
+            report.entries,
+            report.known_fraction * 100.0,
+            report.canary_tokens
+        ),
+        Err(error) => {
+            eprintln!("self-test failed: {error}");
+            std::process::exit(1);
+        }
+    }
 }
 
-// http://ftp.usf.edu/pub/ftp.monash.edu.au/pub/nihongo/00INDEX.html
+/// Value following `--name` in `args`.
+fn flag_value<'a>(args: &'a [String], name: &str) -> Option<&'a str> {
+    let position = args.iter().position(|arg| arg == name)?;
+    args.get(position + 1).map(String::as_str)
+}
 
-fn main() {
-    let f = fs::File::open("JMdict_e/JMdict_e.xml").unwrap();
-    let reader = std::io::BufReader::with_capacity(1024 * 1024 * 128, f);
-    let mut de = Deserializer::with_resolver(reader, DocTypeEntityResolver::new());
-    let dict = JMdict::deserialize(&mut de).unwrap();
-
-    let mut dictionary = Dictionary::new();
-
-    for entry in dict.entries.iter() {
-        let mut kanji_words = Vec::new();
-        let mut kana_words = Vec::new();
-        let mut part_of_speeches = HashSet::new();
-        let mut tags = HashSet::new();
-
-        if let Some(ref kanji_elements) = entry.kanji_elements {
-            for kanji_element in kanji_elements.iter() {
-                if let Some(ref keb) = kanji_element.keb {
-                    kanji_words.push(keb.clone());
-                }
+fn append(args: &[String]) {
+    let (Some(dict), Some(csv), Some(output)) = (
+        flag_value(args, "--dict"),
+        flag_value(args, "--user-csv"),
+        flag_value(args, "--output"),
+    ) else {
+        eprintln!("append requires --dict, --user-csv and --output");
+        std::process::exit(2);
+    };
+
+    let result = Dictionary::load(dict)
+        .map_err(user_csv::AppendError::from)
+        .and_then(|mut dictionary| {
+            let text = fs::read_to_string(csv).map_err(DictionaryError::from)?;
+            let words = user_csv::parse(&text)?;
+            user_csv::append(&mut dictionary, &words)?;
+            // Keep an existing romaji index covering the new words.
+            if !dictionary.romaji.is_empty() {
+                dictionary.build_romaji_index();
             }
+            dictionary.shrink_to_fit();
+            dictionary.save(output)?;
+            Ok(words.len())
+        });
+
+    match result {
+        Ok(count) => println!("appended {count} words to {output}"),
+        Err(error) => {
+            eprintln!("append failed: {error}");
+            std::process::exit(1);
         }
+    }
+}
 
-        if let Some(ref reading_elements) = entry.reading_elements {
-            for reading_element in reading_elements.iter() {
-                if let Some(ref reb) = reading_element.reb {
-                    kana_words.push(reb.clone());
-                }
-            }
+/// Counts term frequencies over every file below `--corpus`.
+fn count_corpus(args: &[String]) {
+    let (Some(dict), Some(corpus), Some(output)) = (
+        flag_value(args, "--dict"),
+        flag_value(args, "--corpus"),
+        flag_value(args, "--out"),
+    ) else {
+        eprintln!("count requires --dict, --corpus and --out");
+        std::process::exit(2);
+    };
+
+    let mut options = count::CountOptions::default();
+    for (flag, value) in [
+        ("--threads", &mut options.num_threads),
+        ("--top-unknown", &mut options.top_unknown),
+    ] {
+        if let Some(text) = flag_value(args, flag) {
+            *value = text.parse().unwrap_or_else(|_| {
+                eprintln!("{flag} expects a number, found `{text}`");
+                std::process::exit(2);
+            });
         }
-        // <!ENTITY rK "rarely-used kanji form">
-        if let Some(ref senses) = entry.senses {
-            for sense in senses.iter() {
-                for pos in sense.pos.iter() {
-                    part_of_speeches.insert(pos.clone());
-                }
-
-                if let Some(ref miscs) = sense.misc {
-                    for misc in miscs.iter() {
-                        tags.insert(misc.clone());
-                    }
-                }
-            }
+    }
+
+    let result = Dictionary::load(dict)
+        .map_err(|error| error.to_string())
+        .and_then(|dictionary| {
+            let tokenizer = std::sync::Arc::new(Tokenizer::new(dictionary.into()));
+            let files = count::corpus_files(Path::new(corpus))
+                .map_err(|error| format!("{corpus}: {error}"))?;
+            let table = count::count(tokenizer, &files, &options)
+                .map_err(|error| format!("{corpus}: {error}"))?;
+            table
+                .save(output)
+                .map_err(|error| format!("{output}: {error}"))?;
+            Ok((files.len(), table))
+        });
+
+    match result {
+        Ok((files, table)) => println!(
+            "counted {} tokens of {} terms in {files} files, {} unknown surfaces kept",
+            table.total_tokens,
+            table.terms.len(),
+            table.unknown.len()
+        ),
+        Err(error) => {
+            eprintln!("count failed: {error}");
+            std::process::exit(1);
         }
+    }
+}
 
-        let is_godan = part_of_speeches.iter().any(|pos| pos.starts_with("v5"));
-        let is_ichidan = part_of_speeches.iter().any(|pos| pos.starts_with("v1"));
-        let dictionary_entry_index = dictionary.entries.len() as u32;
+/// Prints the part-of-speech n-grams of a corpus, `--ngrams 2` by default, most
+/// frequent first.
+fn print_stats(args: &[String]) {
+    let (Some(dict), Some(corpus)) =
+        (flag_value(args, "--dict"), flag_value(args, "--corpus"))
+    else {
+        eprintln!("stats requires --dict and --corpus");
+        std::process::exit(2);
+    };
+
+    let mut n = 2;
+    let mut num_threads = count::CountOptions::default().num_threads;
+    for (flag, value) in [("--ngrams", &mut n), ("--threads", &mut num_threads)] {
+        if let Some(text) = flag_value(args, flag) {
+            *value = text.parse().unwrap_or_else(|_| {
+                eprintln!("{flag} expects a number, found `{text}`");
+                std::process::exit(2);
+            });
+        }
+    }
 
-        dictionary.entries.push(DictionaryEntry {
-            term_id: 0,
-            pos: segmenter::dictionary::PartOfSpeech::empty(),
-            tag: segmenter::dictionary::Tag::empty(),
+    let result = Dictionary::load(dict)
+        .map_err(|error| error.to_string())
+        .and_then(|dictionary| {
+            let tokenizer = std::sync::Arc::new(Tokenizer::new(dictionary.into()));
+            let files = count::corpus_files(Path::new(corpus))
+                .map_err(|error| format!("{corpus}: {error}"))?;
+            let error = std::cell::RefCell::new(None);
+            let segmenter = Segmenter::new(tokenizer, num_threads);
+            let counts =
+                stats::pos_ngrams(&segmenter, count::corpus_lines(&files, &error), n);
+            match error.into_inner() {
+                Some(error) => Err(format!("{corpus}: {error}")),
+                None => Ok(counts),
+            }
         });
 
-        use wana_kana::ConvertJapanese;
-
-        for kanji in kanji_words.iter() {
-            dictionary
-                .kanji
-                .entry(kanji.clone())
-                .and_modify(|v| {
-                    v.push(TermEntry {
-                        entry_index: dictionary_entry_index,
-                        inflection_type: InflectionType::DictionaryForm,
-                    })
-                })
-                .or_insert_with(|| {
-                    vec![segmenter::dictionary::TermEntry {
-                        entry_index: dictionary_entry_index,
-                        inflection_type:
-                            segmenter::dictionary::InflectionType::DictionaryForm,
-                    }]
-                });
-
-            if is_godan || is_ichidan {
-                for kana in kana_words.iter() {
-                    let kana = kana.to_hiragana();
-                    if is_godan {
-                        add_conjugations(
-                            &mut dictionary,
-                            jp_inflections::VerbType::Godan,
-                            &kana,
-                            Some(kanji),
-                            dictionary_entry_index,
-                        );
-                    } else if is_ichidan {
-                        add_conjugations(
-                            &mut dictionary,
-                            jp_inflections::VerbType::Ichidan,
-                            &kana,
-                            Some(kanji),
-                            dictionary_entry_index,
-                        );
-                    }
-                }
+    match result {
+        Ok(counts) => {
+            for (ngram, count) in stats::sorted_ngrams(counts) {
+                println!("{count:>10}  {}", stats::ngram_name(&ngram));
             }
         }
-
-        for kana in kana_words.iter() {
-            dictionary
-                .kana
-                .entry(kana.clone())
-                .and_modify(|v| {
-                    v.push(TermEntry {
-                        entry_index: dictionary_entry_index,
-                        inflection_type: InflectionType::DictionaryForm,
-                    })
-                })
-                .or_insert(vec![segmenter::dictionary::TermEntry {
-                    entry_index: dictionary_entry_index,
-                    inflection_type: InflectionType::DictionaryForm,
-                }]);
-
-            let kana = kana.to_hiragana();
-            #[allow(clippy::collapsible_else_if)]
-            if is_godan || is_ichidan {
-                if !kanji_words.is_empty() {
-                    for kanji in kanji_words.iter() {
-                        if is_godan {
-                            add_conjugations(
-                                &mut dictionary,
-                                jp_inflections::VerbType::Godan,
-                                &kana,
-                                Some(kanji),
-                                dictionary_entry_index,
-                            );
-                        } else if is_ichidan {
-                            add_conjugations(
-                                &mut dictionary,
-                                jp_inflections::VerbType::Ichidan,
-                                &kana,
-                                Some(kanji),
-                                dictionary_entry_index,
-                            );
-                        }
-                    }
-                } else {
-                    if is_godan {
-                        add_conjugations(
-                            &mut dictionary,
-                            jp_inflections::VerbType::Godan,
-                            &kana,
-                            None,
-                            dictionary_entry_index,
-                        );
-                    } else if is_ichidan {
-                        add_conjugations(
-                            &mut dictionary,
-                            jp_inflections::VerbType::Ichidan,
-                            &kana,
-                            None,
-                            dictionary_entry_index,
-                        );
-                    }
-                }
-            }
+        Err(error) => {
+            eprintln!("stats failed: {error}");
+            std::process::exit(1);
         }
     }
-
-    let kanji_len = dictionary.kanji.len();
-    let kana_len = dictionary.kana.len();
-    let entries_len = dictionary.entries.len();
-    println!("kanji len: {kanji_len}");
-    println!("kana len: {kana_len}");
-    println!("entries len: {entries_len}");
-    let encoded: Vec<u8> = bincode::serialize(&dictionary).unwrap();
-    std::fs::write("dictionary_test_sg_jp.bin", encoded).unwrap();
 }
 
-fn add_conjugations(
-    dictionary: &mut Dictionary,
-    verb_type: jp_inflections::VerbType,
-    kana: &str,
-    kanji: Option<&str>,
-    entry_index: u32,
-) {
-    use jp_inflections::*;
-    let verb = Word::new(kana, kanji).into_verb(verb_type).unwrap();
-
-    let negative = verb.negative(WordForm::Short).unwrap();
-    let negative_long = verb.negative(WordForm::Long).unwrap();
-
-    let te = verb.te_form().unwrap();
-
-    let negative_te = verb.negative_te_form().unwrap();
-
-    let past = verb.past(WordForm::Short).unwrap();
-    let past_long = verb.past(WordForm::Long).unwrap();
-
-    let negative_past = verb.negative_past(WordForm::Short).unwrap();
-    let negative_past_long = verb.negative_past(WordForm::Long).unwrap();
-
-    let potential = verb.potential(WordForm::Short).unwrap();
-    let potential_long = verb.potential(WordForm::Long).unwrap();
-
-    let negative_potential = verb.negative_potential(WordForm::Short).unwrap();
-    let negative_potential_long = verb.negative_potential(WordForm::Long).unwrap();
-
-    let imperative = verb.imperative().unwrap();
-
-    let imperative_negative = verb.imperative_negative().unwrap();
-
-    let causative = verb.causative().unwrap();
-
-    let causative_passive = verb.causative_passive().unwrap();
-
-    let negative_causative_passive = verb.negative_causative_passive().unwrap();
-
-    let negative_causative = verb.negative_causative().unwrap();
-
-    let passive = verb.passive().unwrap();
+/// Segments the corpus with both dictionaries and prints where they disagree, or the
+/// whole report as JSON with `--json`.
+fn print_diff(args: &[String]) {
+    let (Some(dict_a), Some(dict_b), Some(corpus)) = (
+        flag_value(args, "--dict-a"),
+        flag_value(args, "--dict-b"),
+        flag_value(args, "--corpus"),
+    ) else {
+        eprintln!("diff requires --dict-a, --dict-b and --corpus");
+        std::process::exit(2);
+    };
+    let top = flag_value(args, "--top").map_or(20, |text| {
+        text.parse().unwrap_or_else(|_| {
+            eprintln!("--top expects a number, found `{text}`");
+            std::process::exit(2);
+        })
+    });
+
+    let load = |path: &str| {
+        Dictionary::load(path)
+            .map(|dictionary| Tokenizer::new(dictionary.into()))
+            .map_err(|error| format!("{path}: {error}"))
+    };
+    let result = load(dict_a).and_then(|a| {
+        let b = load(dict_b)?;
+        let files = count::corpus_files(Path::new(corpus))
+            .map_err(|error| format!("{corpus}: {error}"))?;
+        let error = std::cell::RefCell::new(None);
+        let report = diff::compare(&a, &b, count::corpus_lines(&files, &error));
+        match error.into_inner() {
+            Some(error) => Err(format!("{corpus}: {error}")),
+            None => Ok(report),
+        }
+    });
 
-    let negative_passive = verb.negative_passive().unwrap();
+    let report = match result {
+        Ok(report) => report,
+        Err(error) => {
+            eprintln!("diff failed: {error}");
+            std::process::exit(1);
+        }
+    };
+    if args.iter().any(|arg| arg == "--json") {
+        println!("{}", serde_json::to_string_pretty(&report).unwrap());
+        return;
+    }
 
-    let words = [
-        negative,
-        negative_long,
-        te,
-        negative_te,
-        past,
-        past_long,
-        negative_past,
-        negative_past_long,
-        potential,
-        potential_long,
-        negative_potential,
-        negative_potential_long,
-        imperative,
-        imperative_negative,
-        causative,
-        causative_passive,
-        negative_causative_passive,
-        negative_causative,
-        passive,
-        negative_passive,
-    ];
+    println!("{} of {} texts changed", report.changed.len(), report.texts);
+    println!("boundary precision: {:.4}", report.precision);
+    println!("boundary recall:    {:.4}", report.recall);
+    for pattern in report.top_patterns(top) {
+        println!(
+            "{:>10}  {} -> {}",
+            pattern.count,
+            pattern.before.join("|"),
+            pattern.after.join("|")
+        );
+    }
+}
 
-    for word in words {
-        if let Some(kanji) = word.kanji {
-            if !dictionary.kanji.contains_key(&kanji) {
-                dictionary
-                    .kanji
-                    .entry(kanji.clone())
-                    .and_modify(|v| {
-                        v.push(TermEntry {
-                            entry_index,
-                            inflection_type: InflectionType::DictionaryForm,
-                        })
-                    })
-                    .or_insert_with(|| {
-                        vec![segmenter::dictionary::TermEntry {
-                            entry_index,
-                            inflection_type:
-                                segmenter::dictionary::InflectionType::DictionaryForm,
-                        }]
-                    });
-            }
+/// Compares the term ids of a rebuilt dictionary to the previous build's, exiting with
+/// 1 when an entry got another id or the new build repeats one.
+fn check_term_ids(args: &[String]) {
+    let [old, new] = args else {
+        eprintln!("check-ids requires <old.bin> and <new.bin>");
+        std::process::exit(2);
+    };
+
+    let load =
+        |path: &str| Dictionary::load(path).map_err(|error| format!("{path}: {error}"));
+    let result = load(old.as_str()).and_then(|old| {
+        let new = load(new.as_str())?;
+        Ok(check_ids(&old, &new))
+    });
+    let report = match result {
+        Ok(report) => report,
+        Err(error) => {
+            eprintln!("check-ids failed: {error}");
+            std::process::exit(1);
         }
+    };
+
+    println!(
+        "{} entries kept their term ids, {} added, {} removed",
+        report.kept, report.added, report.removed
+    );
+    for ((kanji, reading), old_ids, new_ids) in report.changed.iter() {
+        let surface = kanji.as_deref().unwrap_or(reading);
+        println!("changed: {surface} ({reading}): {old_ids:?} -> {new_ids:?}");
+    }
+    for term_id in report.duplicates.iter() {
+        println!("duplicate: {term_id}");
+    }
+    if !report.is_stable() {
+        std::process::exit(1);
+    }
+}
 
-        let kana = word.kana;
-        if !dictionary.kana.contains_key(&kana) {
-            dictionary
-                .kana
-                .entry(kana.clone())
-                .and_modify(|v| {
-                    v.push(TermEntry {
-                        entry_index,
-                        inflection_type: InflectionType::DictionaryForm,
-                    })
-                })
-                .or_insert_with(|| {
-                    vec![segmenter::dictionary::TermEntry {
-                        entry_index,
-                        inflection_type:
-                            segmenter::dictionary::InflectionType::DictionaryForm,
-                    }]
-                });
-        }
+fn print_section_lengths(header: &DictionaryHeader) {
+    println!("serialized sections:");
+    let names = [
+        "entries",
+        "readings",
+        "kanji",
+        "kana",
+        "kanji forms",
+        "romaji index",
+        "xrefs",
+    ];
+    for (name, length) in names.iter().zip(header.section_lengths) {
+        println!(
+            "{name:>12}: {:>10.2} MiB",
+            length as f64 / (1024.0 * 1024.0)
+        );
     }
 }
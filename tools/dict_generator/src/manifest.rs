@@ -0,0 +1,106 @@
+use crate::sha256::Sha256;
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::io::Read;
+use std::path::Path;
+
+/// Version of `jp_inflections` the conjugations are built with. This crate's `Cargo.toml`
+/// requires exactly this version, since Cargo tells a crate its own version only.
+pub const JP_INFLECTIONS_VERSION: &str = "0.1.3";
+
+/// A file by its path, size and SHA-256.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct FileDigest {
+    pub path: String,
+    pub size: u64,
+    /// Lowercase hex.
+    pub sha256: String,
+}
+
+impl FileDigest {
+    /// Digest of the file at `path` as it is now, read a chunk at a time.
+    pub fn of(path: &Path) -> std::io::Result<Self> {
+        let mut file = std::fs::File::open(path)?;
+        let mut hasher = Sha256::new();
+        let mut buffer = vec![0; 1 << 16];
+        let mut size = 0;
+        loop {
+            let read = file.read(&mut buffer)?;
+            if read == 0 {
+                break;
+            }
+            hasher.update(&buffer[..read]);
+            size += read as u64;
+        }
+
+        Ok(Self {
+            path: path.display().to_string(),
+            size,
+            sha256: hasher.finish_hex(),
+        })
+    }
+}
+
+/// What went into a dictionary build and what came out, for certifying where a
+/// dictionary file comes from. The build has no randomized choices and writes its maps
+/// in key order, so the same inputs and options always give the same output, and two
+/// manifests of such builds only differ in [`BuildManifest::created_at`].
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct BuildManifest {
+    /// Seconds since the Unix epoch when the manifest was made.
+    pub created_at: u64,
+    /// Versions of the crates doing the build, by crate name, see [`versions`].
+    pub versions: BTreeMap<String, String>,
+    /// Source files, in the order they were read.
+    pub inputs: Vec<FileDigest>,
+    /// Value of every build option by its flag, defaults included.
+    pub options: BTreeMap<String, Value>,
+    pub output: FileDigest,
+}
+
+impl BuildManifest {
+    /// Manifest of a build of `output` from `inputs` with `options`, with the digests
+    /// of the files as they are now.
+    pub fn new(
+        inputs: &[&Path],
+        options: BTreeMap<String, Value>,
+        output: &Path,
+    ) -> std::io::Result<Self> {
+        let created_at = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map_or(0, |elapsed| elapsed.as_secs());
+        let inputs = inputs
+            .iter()
+            .map(|path| FileDigest::of(path))
+            .collect::<std::io::Result<_>>()?;
+
+        Ok(Self {
+            created_at,
+            versions: versions(),
+            inputs,
+            options,
+            output: FileDigest::of(output)?,
+        })
+    }
+
+    /// Whether both manifests record the same build, whenever they were made.
+    pub fn is_equivalent(&self, other: &Self) -> bool {
+        let other = Self {
+            created_at: self.created_at,
+            ..other.clone()
+        };
+        *self == other
+    }
+}
+
+/// Versions of `segmenter`, `dict_generator` and `jp_inflections`, by crate name.
+pub fn versions() -> BTreeMap<String, String> {
+    [
+        ("dict_generator", env!("CARGO_PKG_VERSION")),
+        ("jp_inflections", JP_INFLECTIONS_VERSION),
+        ("segmenter", segmenter::VERSION),
+    ]
+    .into_iter()
+    .map(|(name, version)| (name.to_string(), version.to_string()))
+    .collect()
+}
@@ -0,0 +1,340 @@
+use crate::conjugation::add_conjugations;
+use crate::jis0208::JIS0208;
+use crate::user_csv::AppendError;
+use segmenter::dictionary::{imported_term_ids, Dictionary, PartOfSpeech, Tag, TermId};
+use std::collections::{HashMap, HashSet};
+
+/// One base-form row of a MeCab lexicon in IPADIC format:
+/// `surface,left-id,right-id,cost,pos1,pos2,pos3,pos4,conjugation type,conjugation
+/// form,base form,reading,pronunciation`.
+///
+/// The connection ids and the pronunciation are not used.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MecabWord {
+    pub surface: String,
+    /// Katakana reading, `None` where the row has none or `*`.
+    pub reading: Option<String>,
+    pub pos: PartOfSpeech,
+    pub cost: i32,
+}
+
+/// Text encoding a lexicon file was read as.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Encoding {
+    Utf8,
+    EucJp,
+}
+
+const NOUN: PartOfSpeech = PartOfSpeech::NOUN;
+
+/// IPADIC part-of-speech hierarchy prefixes and the flags they map to; the longest
+/// matching prefix wins and anything else (e.g. `その他`) maps to no flags.
+///
+/// | IPADIC                  | `PartOfSpeech`           |
+/// |-------------------------|--------------------------|
+/// | 名詞                    | `NOUN`                   |
+/// | 名詞,固有名詞           | `NOUN_PROPER`            |
+/// | 名詞,代名詞             | `PRONOUN`                |
+/// | 名詞,数                 | `NUMERIC`                |
+/// | 名詞,副詞可能           | `NOUN`, `NOUN_ADVERB`    |
+/// | 名詞,サ変接続           | `NOUN`, `SURU_VERB`      |
+/// | 名詞,接尾               | `NOUN_SUFFIX`            |
+/// | 名詞,接尾,助数詞        | `COUNTER`                |
+/// | 動詞                    | from the conjugation type, see [`verb_class`] |
+/// | 形容詞                  | `ADJECTIVE`              |
+/// | 副詞                    | `ADVERB`                 |
+/// | 連体詞                  | `PRE_NOUN_ADJECTIVAL`    |
+/// | 接続詞                  | `CONJUNCTION`            |
+/// | 助詞                    | `PARTICLE`               |
+/// | 助動詞                  | `AUXILIARY_VERB`         |
+/// | 感動詞, フィラー        | `INTERJECTION`           |
+/// | 接頭詞                  | `PREFIX`                 |
+/// | 記号                    | `SYMBOL`                 |
+pub const POS_TABLE: &[(&[&str], PartOfSpeech)] = &[
+    (&["名詞"], NOUN),
+    (&["名詞", "固有名詞"], PartOfSpeech::NOUN_PROPER),
+    (&["名詞", "代名詞"], PartOfSpeech::PRONOUN),
+    (&["名詞", "数"], PartOfSpeech::NUMERIC),
+    (&["名詞", "副詞可能"], NOUN.union(PartOfSpeech::NOUN_ADVERB)),
+    (&["名詞", "サ変接続"], NOUN.union(PartOfSpeech::SURU_VERB)),
+    (&["名詞", "接尾"], PartOfSpeech::NOUN_SUFFIX),
+    (&["名詞", "接尾", "助数詞"], PartOfSpeech::COUNTER),
+    (&["動詞"], PartOfSpeech::NONE),
+    (&["形容詞"], PartOfSpeech::ADJECTIVE),
+    (&["副詞"], PartOfSpeech::ADVERB),
+    (&["連体詞"], PartOfSpeech::PRE_NOUN_ADJECTIVAL),
+    (&["接続詞"], PartOfSpeech::CONJUNCTION),
+    (&["助詞"], PartOfSpeech::PARTICLE),
+    (&["助動詞"], PartOfSpeech::AUXILIARY_VERB),
+    (&["感動詞"], PartOfSpeech::INTERJECTION),
+    (&["フィラー"], PartOfSpeech::INTERJECTION),
+    (&["接頭詞"], PartOfSpeech::PREFIX),
+    (&["記号"], PartOfSpeech::SYMBOL),
+];
+
+/// Flags for the `pos1..pos4` columns of a row, see [`POS_TABLE`].
+pub fn map_pos(hierarchy: &[&str]) -> PartOfSpeech {
+    POS_TABLE
+        .iter()
+        .filter(|(prefix, _)| hierarchy.starts_with(prefix))
+        .max_by_key(|(prefix, _)| prefix.len())
+        .map_or(PartOfSpeech::NONE, |(_, pos)| *pos)
+}
+
+/// Verb class of an IPADIC conjugation type such as `五段・カ行イ音便` or `一段`.
+pub fn verb_class(conjugation_type: &str) -> PartOfSpeech {
+    if conjugation_type.starts_with("五段") {
+        PartOfSpeech::GODAN_VERB
+    } else if conjugation_type.starts_with("一段") {
+        PartOfSpeech::ICHIDAN_VERB
+    } else if conjugation_type.starts_with("サ変") {
+        PartOfSpeech::SURU_VERB
+    } else if conjugation_type.starts_with("カ変") {
+        PartOfSpeech::KURU_VERB
+    } else {
+        PartOfSpeech::NONE
+    }
+}
+
+/// Priority bucket of a MeCab word cost, `0` for the most likely words. Lower costs
+/// are more likely; IPADIC's costs mostly fall between a few thousand below zero and
+/// about 15000.
+pub fn priority_bucket(cost: i32) -> u8 {
+    match cost {
+        ..=2000 => 0,
+        2001..=5000 => 1,
+        5001..=8000 => 2,
+        _ => 3,
+    }
+}
+
+/// Decodes a lexicon file as UTF-8 (with or without a byte order mark), or as EUC-JP
+/// when it isn't valid UTF-8.
+///
+/// Only the JIS X 0208 and half-width katakana parts of EUC-JP are supported; JIS X
+/// 0212 characters are reported as errors.
+pub fn decode(bytes: &[u8]) -> Result<(String, Encoding), AppendError> {
+    let bytes = bytes.strip_prefix(b"\xEF\xBB\xBF").unwrap_or(bytes);
+    if let Ok(text) = std::str::from_utf8(bytes) {
+        return Ok((text.to_string(), Encoding::Utf8));
+    }
+
+    let mut text = String::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let trail = bytes.get(i + 1).copied();
+        let (c, length) = match (bytes[i], trail) {
+            (lead @ 0x00..=0x7F, _) => (Some(lead as char), 1),
+            (0x8E, Some(trail @ 0xA1..=0xDF)) => {
+                (char::from_u32(0xFF61 + (trail - 0xA1) as u32), 2)
+            }
+            (lead @ 0xA1..=0xFE, Some(trail @ 0xA1..=0xFE)) => {
+                let index = (lead - 0xA1) as usize * 94 + (trail - 0xA1) as usize;
+                let code_point = JIS0208[index];
+                (
+                    (code_point != 0)
+                        .then(|| char::from_u32(code_point as u32))
+                        .flatten(),
+                    2,
+                )
+            }
+            _ => (None, 1),
+        };
+
+        let Some(c) = c else {
+            let line = bytes[..i].iter().filter(|b| **b == b'\n').count() + 1;
+            return Err(AppendError::Parse {
+                line,
+                message: "neither valid UTF-8 nor EUC-JP".to_string(),
+            });
+        };
+        text.push(c);
+        i += length;
+    }
+
+    Ok((text, Encoding::EucJp))
+}
+
+/// Splits a CSV line, allowing `"`-quoted fields with `""` escapes as written for
+/// surfaces that contain commas.
+fn split_fields(line: &str) -> Vec<String> {
+    let mut fields = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut chars = line.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                field.push('"');
+                chars.next();
+            }
+            '"' => quoted = !quoted,
+            ',' if !quoted => fields.push(std::mem::take(&mut field)),
+            _ => field.push(c),
+        }
+    }
+    fields.push(field);
+
+    fields
+}
+
+///
+fn is_kana(c: char) -> bool {
+    matches!(c, 'ぁ'..='ゖ' | 'ァ'..='ヺ' | 'ー')
+}
+
+/// Parses the base-form rows of a MeCab CSV lexicon.
+///
+/// Rows whose conjugation form is neither `*` nor `基本形` are skipped, since
+/// [`import`] generates the conjugations of godan and ichidan verbs itself. Empty lines
+/// are ignored.
+pub fn parse(text: &str) -> Result<Vec<MecabWord>, AppendError> {
+    let mut words = Vec::new();
+    for (i, line) in text.lines().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let error = |message: String| AppendError::Parse {
+            line: i + 1,
+            message,
+        };
+        let fields = split_fields(line);
+        if fields.len() < 11 {
+            return Err(error(format!(
+                "expected at least 11 fields, found {}",
+                fields.len()
+            )));
+        }
+
+        let surface = fields[0].as_str();
+        if surface.is_empty() {
+            return Err(error("missing surface".to_string()));
+        }
+        let cost = fields[3]
+            .parse()
+            .map_err(|_| error(format!("invalid cost `{}`", fields[3])))?;
+        let (conjugation_type, conjugation_form) = (&fields[8], &fields[9]);
+        if !matches!(conjugation_form.as_str(), "*" | "基本形") {
+            continue;
+        }
+
+        let hierarchy: Vec<&str> = fields[4..8]
+            .iter()
+            .map(String::as_str)
+            .take_while(|field| *field != "*")
+            .collect();
+        let mut pos = map_pos(&hierarchy);
+        if hierarchy.first() == Some(&"動詞") {
+            pos |= verb_class(conjugation_type);
+        }
+
+        let reading = fields
+            .get(11)
+            .filter(|reading| !reading.is_empty() && *reading != "*")
+            .cloned();
+        if reading.is_none() && !surface.chars().all(is_kana) {
+            return Err(error(format!("missing reading for `{surface}`")));
+        }
+
+        words.push(MecabWord {
+            surface: surface.to_string(),
+            reading,
+            pos,
+            cost,
+        });
+    }
+
+    Ok(words)
+}
+
+/// Adds `words` to `dictionary` and returns how many entries were added.
+///
+/// Term ids come from `imported_term_ids` with the source `mecab`, so an entry keeps its
+/// id when the lexicon is imported again. They are handed out in surface and reading
+/// order, skipping ids the dictionary or an earlier word already has, so they don't
+/// depend on the order of the rows.
+///
+/// Rows with the same surface and reading become one entry with the union of their
+/// parts of speech. The dictionary has no priority field and treats earlier entries as
+/// more likely, so entries are added in [`priority_bucket`] order of their lowest cost,
+/// and by surface and reading within a bucket, so the dictionary doesn't depend on the
+/// order of the rows either.
+/// Kana surfaces are indexed as they are; other surfaces are indexed as kanji along
+/// with their reading in hiragana. Godan and ichidan verbs get their conjugations.
+pub fn import(
+    dictionary: &mut Dictionary,
+    words: &[MecabWord],
+) -> Result<usize, AppendError> {
+    use wana_kana::ConvertJapanese;
+
+    let mut merged: Vec<MecabWord> = Vec::new();
+    let mut positions: HashMap<(&str, Option<&str>), usize> = HashMap::new();
+    for word in words.iter() {
+        let key = (word.surface.as_str(), word.reading.as_deref());
+        match positions.get(&key) {
+            Some(&position) => {
+                merged[position].pos |= word.pos;
+                merged[position].cost = merged[position].cost.min(word.cost);
+            }
+            None => {
+                positions.insert(key, merged.len());
+                merged.push(word.clone());
+            }
+        }
+    }
+    merged.sort_by(|a, b| {
+        (priority_bucket(a.cost), &a.surface, &a.reading).cmp(&(
+            priority_bucket(b.cost),
+            &b.surface,
+            &b.reading,
+        ))
+    });
+
+    let surfaces: Vec<(Option<&str>, String)> = merged
+        .iter()
+        .map(|word| {
+            if word.surface.chars().all(is_kana) {
+                (None, word.surface.clone())
+            } else {
+                // `parse` only lets kana surfaces through without a reading.
+                let reading = word.reading.as_deref().unwrap_or_default();
+                (Some(word.surface.as_str()), reading.to_hiragana())
+            }
+        })
+        .collect();
+    let mut order: Vec<usize> = (0..merged.len()).collect();
+    order.sort_by_key(|&i| (&merged[i].surface, &surfaces[i].1, &merged[i].reading));
+    let mut taken: HashSet<TermId> = dictionary
+        .entries
+        .iter()
+        .map(|entry| entry.term_id)
+        .collect();
+    let mut term_ids = vec![0; merged.len()];
+    for i in order {
+        term_ids[i] = imported_term_ids("mecab", &merged[i].surface, &surfaces[i].1)
+            .find(|term_id| taken.insert(*term_id))
+            .expect("imported term ids never run out");
+    }
+
+    for ((word, (kanji, kana)), term_id) in merged.iter().zip(surfaces).zip(term_ids) {
+        let entry_index = dictionary.add_word(term_id, kanji, &kana, word.pos, Tag::NONE);
+
+        let verb_type = if word.pos.contains(PartOfSpeech::GODAN_VERB) {
+            Some(jp_inflections::VerbType::Godan)
+        } else if word.pos.contains(PartOfSpeech::ICHIDAN_VERB) {
+            Some(jp_inflections::VerbType::Ichidan)
+        } else {
+            None
+        };
+        if let Some(verb_type) = verb_type {
+            let kana = kana.to_hiragana();
+            add_conjugations(dictionary, verb_type, &kana, kanji, entry_index, 0, None);
+        }
+    }
+
+    // Conjugated forms are pushed as they come.
+    dictionary.sort_term_entries();
+    dictionary.validate()?;
+    Ok(merged.len())
+}
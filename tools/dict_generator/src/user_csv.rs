@@ -0,0 +1,135 @@
+use crate::conjugation::add_conjugations;
+use segmenter::dictionary::{
+    Dictionary, DictionaryError, PartOfSpeech, Tag, TermId, UnknownFlagName,
+};
+use std::collections::HashSet;
+
+/// One row of a user dictionary CSV: `term_id,kanji,kana,pos`.
+///
+/// `kanji` may be empty for kana-only words and `pos` holds `PartOfSpeech` flag names
+/// separated by `|` (e.g. `NOUN|SURU_VERB`). Empty lines and lines starting with `#`
+/// are ignored.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UserWord {
+    pub term_id: TermId,
+    pub kanji: Option<String>,
+    pub kana: String,
+    pub pos: PartOfSpeech,
+}
+
+///
+#[derive(Debug)]
+pub enum AppendError {
+    /// A CSV line could not be parsed (1-based line number).
+    Parse {
+        line: usize,
+        message: String,
+    },
+    /// The term id is already used by the dictionary or an earlier row.
+    TermIdCollision(TermId),
+    Dictionary(DictionaryError),
+}
+
+impl std::fmt::Display for AppendError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Parse { line, message } => write!(f, "line {line}: {message}"),
+            Self::TermIdCollision(term_id) => {
+                write!(f, "term_id {term_id} is already used by another entry")
+            }
+            Self::Dictionary(error) => write!(f, "{error}"),
+        }
+    }
+}
+
+impl std::error::Error for AppendError {}
+
+impl From<DictionaryError> for AppendError {
+    fn from(error: DictionaryError) -> Self {
+        Self::Dictionary(error)
+    }
+}
+
+///
+pub fn parse(text: &str) -> Result<Vec<UserWord>, AppendError> {
+    let mut words = Vec::new();
+    for (i, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let error = |message: String| AppendError::Parse {
+            line: i + 1,
+            message,
+        };
+        let fields: Vec<&str> = line.split(',').map(str::trim).collect();
+        let [term_id, kanji, kana, pos] = fields[..] else {
+            return Err(error(format!("expected 4 fields, found {}", fields.len())));
+        };
+
+        let term_id = term_id
+            .parse()
+            .map_err(|_| error(format!("invalid term_id `{term_id}`")))?;
+        if kana.is_empty() {
+            return Err(error("missing kana surface".to_string()));
+        }
+        let flags: PartOfSpeech = pos.parse().map_err(|UnknownFlagName(name)| {
+            error(format!("unknown part of speech `{name}`"))
+        })?;
+
+        words.push(UserWord {
+            term_id,
+            kanji: (!kanji.is_empty()).then(|| kanji.to_string()),
+            kana: kana.to_string(),
+            pos: flags,
+        });
+    }
+
+    Ok(words)
+}
+
+/// Adds `words` to `dictionary`, expanding conjugations of godan and ichidan verbs.
+///
+/// Nothing is added if any term id collides with an existing entry or another row.
+pub fn append(
+    dictionary: &mut Dictionary,
+    words: &[UserWord],
+) -> Result<(), AppendError> {
+    use wana_kana::ConvertJapanese;
+
+    let mut term_ids: HashSet<TermId> = dictionary
+        .entries
+        .iter()
+        .map(|entry| entry.term_id)
+        .collect();
+    for word in words.iter() {
+        if !term_ids.insert(word.term_id) {
+            return Err(AppendError::TermIdCollision(word.term_id));
+        }
+    }
+
+    for word in words.iter() {
+        let kanji = word.kanji.as_deref();
+        let entry_index =
+            dictionary.add_word(word.term_id, kanji, &word.kana, word.pos, Tag::NONE);
+
+        let verb_type = if word.pos.contains(PartOfSpeech::GODAN_VERB) {
+            Some(jp_inflections::VerbType::Godan)
+        } else if word.pos.contains(PartOfSpeech::ICHIDAN_VERB) {
+            Some(jp_inflections::VerbType::Ichidan)
+        } else {
+            None
+        };
+        if let Some(verb_type) = verb_type {
+            let kana = word.kana.to_hiragana();
+            // Forms that fail to conjugate are left out, as in the JMdict build.
+            add_conjugations(dictionary, verb_type, &kana, kanji, entry_index, 0, None);
+        }
+    }
+
+    // Conjugated forms are pushed as they come.
+    dictionary.sort_term_entries();
+    dictionary.validate()?;
+    Ok(())
+}
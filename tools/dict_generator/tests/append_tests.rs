@@ -0,0 +1,102 @@
+use dict_generator::user_csv::{self, AppendError};
+use segmenter::dictionary::{
+    Dictionary, DictionaryBuilder, InflectionType, PartOfSpeech, Tag,
+};
+
+const EXTRA_CSV: &str = "\
+# term_id,kanji,kana,pos
+1000,株式会社,かぶしきがいしゃ,NOUN
+1001,,ソニー,NOUN_PROPER
+1002,走る,はしる,GODAN_VERB
+";
+
+fn base_fixture() -> Dictionary {
+    let mut builder = DictionaryBuilder::new();
+    builder.add(Some("猫"), "ねこ", PartOfSpeech::NOUN, Tag::NONE);
+    builder.add(None, "を", PartOfSpeech::PARTICLE, Tag::NONE);
+    builder.build()
+}
+
+fn temp_path(name: &str) -> std::path::PathBuf {
+    std::env::temp_dir().join(format!("dict-generator-{}-{name}", std::process::id()))
+}
+
+fn term_id(dictionary: &Dictionary, surface: &str) -> u32 {
+    let term_entries = dictionary
+        .kanji
+        .get(surface)
+        .or_else(|| dictionary.kana.get(surface))
+        .unwrap_or_else(|| panic!("`{surface}` is missing"));
+    dictionary.entries[term_entries[0].entry_index as usize].term_id
+}
+
+#[test]
+fn appended_words_and_old_words_resolve() {
+    let base_path = temp_path("base.bin");
+    let updated_path = temp_path("updated.bin");
+    base_fixture().save(&base_path).unwrap();
+
+    let mut dictionary = Dictionary::load(&base_path).unwrap();
+    let words = user_csv::parse(EXTRA_CSV).unwrap();
+    user_csv::append(&mut dictionary, &words).unwrap();
+    dictionary.save(&updated_path).unwrap();
+
+    let updated = Dictionary::load(&updated_path).unwrap();
+    std::fs::remove_file(&base_path).unwrap();
+    std::fs::remove_file(&updated_path).unwrap();
+
+    assert_eq!(term_id(&updated, "猫"), 0);
+    assert_eq!(term_id(&updated, "を"), 1);
+    assert_eq!(term_id(&updated, "株式会社"), 1000);
+    assert_eq!(term_id(&updated, "かぶしきがいしゃ"), 1000);
+    assert_eq!(term_id(&updated, "ソニー"), 1001);
+    assert!(!updated.kanji.contains_key(""));
+
+    // Verb rows get their conjugations.
+    assert_eq!(term_id(&updated, "走る"), 1002);
+    assert_eq!(term_id(&updated, "走った"), 1002);
+    assert_eq!(term_id(&updated, "はしらない"), 1002);
+    assert_eq!(
+        updated.kanji["走る"][0].inflection_type,
+        InflectionType::DictionaryForm
+    );
+    assert_eq!(
+        updated.kanji["走った"][0].inflection_type,
+        InflectionType::Past
+    );
+    assert_eq!(
+        updated.kana["はしらない"][0].inflection_type,
+        InflectionType::Negative
+    );
+}
+
+#[test]
+fn term_id_collisions_are_rejected() {
+    let mut dictionary = base_fixture();
+    let words = user_csv::parse("1,犬,いぬ,NOUN").unwrap();
+    let error = user_csv::append(&mut dictionary, &words).unwrap_err();
+    assert!(matches!(error, AppendError::TermIdCollision(1)));
+    assert_eq!(
+        error.to_string(),
+        "term_id 1 is already used by another entry"
+    );
+    // Nothing was added.
+    assert_eq!(dictionary.entries.len(), 2);
+    assert!(!dictionary.kanji.contains_key("犬"));
+
+    let words = user_csv::parse("50,犬,いぬ,NOUN\n50,猿,さる,NOUN").unwrap();
+    let error = user_csv::append(&mut dictionary, &words).unwrap_err();
+    assert!(matches!(error, AppendError::TermIdCollision(50)));
+}
+
+#[test]
+fn malformed_rows_report_their_line() {
+    let error = user_csv::parse("1,猫,ねこ,NOUN\n2,犬,いぬ").unwrap_err();
+    assert!(matches!(error, AppendError::Parse { line: 2, .. }));
+
+    let error = user_csv::parse("x,猫,ねこ,NOUN").unwrap_err();
+    assert_eq!(error.to_string(), "line 1: invalid term_id `x`");
+
+    let error = user_csv::parse("1,猫,ねこ,NOUNS").unwrap_err();
+    assert_eq!(error.to_string(), "line 1: unknown part of speech `NOUNS`");
+}
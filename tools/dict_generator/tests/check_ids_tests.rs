@@ -0,0 +1,100 @@
+use dict_generator::check_ids::check_ids;
+use dict_generator::dict_builder::{build_dictionary, BuildError, BuildOptions};
+use segmenter::dictionary::{Dictionary, TERM_ID_SCHEME};
+use segmenter::tokenizer::Tokenizer;
+use std::sync::Arc;
+
+fn jmdict(entries: &[(u32, &str, &str)]) -> String {
+    let mut document = String::from(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+         <!DOCTYPE JMdict [\n<!ENTITY n \"noun (common) (futsuumeishi)\">\n]>\n<JMdict>\n",
+    );
+    for (ent_seq, keb, reb) in entries {
+        document.push_str(&format!(
+            "<entry><ent_seq>{ent_seq}</ent_seq><k_ele><keb>{keb}</keb></k_ele>\
+             <r_ele><reb>{reb}</reb></r_ele><sense><pos>&n;</pos><gloss>x</gloss></sense>\
+             </entry>\n"
+        ));
+    }
+    document.push_str("</JMdict>\n");
+    document
+}
+
+fn build(entries: &[(u32, &str, &str)]) -> Result<Dictionary, BuildError> {
+    let document = jmdict(entries);
+    let (dictionary, _) =
+        build_dictionary(document.as_bytes(), &BuildOptions::default())?;
+    Ok(dictionary)
+}
+
+const OLD: &[(u32, &str, &str)] = &[
+    (1000010, "猫", "ねこ"),
+    (1000020, "犬", "いぬ"),
+    (1000030, "魚", "さかな"),
+    (1000040, "鳥", "とり"),
+];
+
+/// The next release drops 魚 and adds 馬 between the others.
+const NEW: &[(u32, &str, &str)] = &[
+    (1000010, "猫", "ねこ"),
+    (1000020, "犬", "いぬ"),
+    (1000035, "馬", "うま"),
+    (1000040, "鳥", "とり"),
+];
+
+#[test]
+fn term_ids_are_the_ent_seqs() {
+    let dictionary = build(OLD).unwrap();
+    let term_ids: Vec<u32> = dictionary
+        .entries
+        .iter()
+        .map(|entry| entry.term_id)
+        .collect();
+    assert_eq!(term_ids, [1000010, 1000020, 1000030, 1000040]);
+    assert_eq!(dictionary.metadata().term_id_scheme, TERM_ID_SCHEME);
+}
+
+#[test]
+fn tokens_carry_the_ent_seq_of_their_entry() {
+    let tokenizer = Tokenizer::new(Arc::new(build(OLD).unwrap()));
+    let term_ids: Vec<(&str, u32)> = tokenizer
+        .tokenize("猫と犬")
+        .iter()
+        .filter_map(|token| Some((token.token, token.term_id?)))
+        .collect();
+    assert_eq!(term_ids, [("猫", 1000010), ("犬", 1000020)]);
+}
+
+#[test]
+fn rebuild_keeps_term_ids() {
+    let report = check_ids(&build(OLD).unwrap(), &build(NEW).unwrap());
+    assert_eq!((report.kept, report.added, report.removed), (3, 1, 1));
+    assert!(report.is_stable(), "{report:?}");
+}
+
+#[test]
+fn renumbered_entries_are_reported() {
+    let old = build(OLD).unwrap();
+    let mut new = build(NEW).unwrap();
+    new.entries[1].term_id = 2;
+
+    let report = check_ids(&old, &new);
+    assert!(!report.is_stable());
+    assert_eq!(
+        report.changed,
+        [(
+            (Some("犬".to_string()), "いぬ".to_string()),
+            vec![1000020],
+            vec![2]
+        )]
+    );
+
+    new.entries[1].term_id = 1000010;
+    assert_eq!(check_ids(&old, &new).duplicates, [1000010]);
+}
+
+#[test]
+fn repeated_ent_seqs_fail_the_build() {
+    let error = build(&[(1000010, "猫", "ねこ"), (1000010, "犬", "いぬ")]).unwrap_err();
+    assert!(matches!(error, BuildError::TermIdCollision(1000010)));
+}
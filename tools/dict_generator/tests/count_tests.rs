@@ -0,0 +1,140 @@
+use dict_generator::count::{self, CountOptions, UnknownTally};
+use segmenter::dictionary::{Dictionary, DictionaryBuilder, PartOfSpeech, Tag};
+use segmenter::frequency::FrequencyTable;
+use segmenter::tokenizer::Tokenizer;
+use segmenter::unknown::UnknownCategory;
+use std::path::Path;
+use std::sync::Arc;
+
+const WORDS: &[(Option<&str>, &str, PartOfSpeech)] = &[
+    (Some("猫"), "ねこ", PartOfSpeech::NOUN),
+    (Some("犬"), "いぬ", PartOfSpeech::NOUN),
+    (Some("魚"), "さかな", PartOfSpeech::NOUN),
+    (Some("好き"), "すき", PartOfSpeech::NOUN),
+    (None, "は", PartOfSpeech::PARTICLE),
+    (None, "を", PartOfSpeech::PARTICLE),
+    (None, "が", PartOfSpeech::PARTICLE),
+    (None, "と", PartOfSpeech::PARTICLE),
+];
+
+fn dictionary() -> Dictionary {
+    let mut builder = DictionaryBuilder::new();
+    for (kanji, kana, pos) in WORDS {
+        builder.add(*kanji, kana, *pos, Tag::NONE);
+    }
+    builder.build()
+}
+
+fn term_id(dictionary: &Dictionary, kanji: &str) -> u32 {
+    let term_entry = dictionary
+        .kanji
+        .get(kanji)
+        .or_else(|| dictionary.kana.get(kanji));
+    dictionary.entries[term_entry.unwrap()[0].entry_index as usize].term_id
+}
+
+fn count_fixture(options: &CountOptions) -> (Dictionary, FrequencyTable) {
+    let dictionary = dictionary();
+    let tokenizer = Arc::new(Tokenizer::new(Arc::new(dictionary.clone())));
+    let files = count::corpus_files(Path::new("tests/fixtures/corpus")).unwrap();
+    let table = count::count(tokenizer, &files, options).unwrap();
+    (dictionary, table)
+}
+
+fn temp_path(name: &str) -> std::path::PathBuf {
+    std::env::temp_dir().join(format!("dict-generator-{}-{name}", std::process::id()))
+}
+
+#[test]
+fn known_words_are_counted_across_nested_files() {
+    for num_threads in [1, 4] {
+        let options = CountOptions {
+            num_threads,
+            ..Default::default()
+        };
+        let (dictionary, table) = count_fixture(&options);
+        for (word, expected) in [("猫", 4), ("犬", 2), ("魚", 1), ("好き", 1), ("は", 4)]
+        {
+            let term_id = term_id(&dictionary, word);
+            assert_eq!(table.term_count(term_id), expected, "{word}");
+        }
+        assert_eq!(table.total_tokens, 30);
+        assert_eq!(
+            table.relative_frequency(term_id(&dictionary, "猫")),
+            4.0 / 30.0
+        );
+    }
+}
+
+#[test]
+fn unknown_surfaces_exclude_symbols_and_emphasis() {
+    let (_, table) = count_fixture(&CountOptions::default());
+    assert_eq!(
+        table.unknown[..2],
+        [("だ".to_string(), 2), ("ポチ".to_string(), 2)]
+    );
+    assert!(table.unknown.iter().any(|(surface, _)| surface == "タマ"));
+    assert!(table
+        .unknown
+        .iter()
+        .all(|(surface, _)| !surface.contains(['。', '！'])));
+
+    let options = CountOptions {
+        top_unknown: 1,
+        ..Default::default()
+    };
+    let (_, table) = count_fixture(&options);
+    assert_eq!(table.unknown.len(), 1);
+}
+
+#[test]
+fn unknown_lengths_are_learned() {
+    let (_, table) = count_fixture(&CountOptions::default());
+    let model = &table.unknown_lengths;
+    // ポチ twice and タマ once are the only unknown katakana words.
+    assert!(model.has_statistics(UnknownCategory::Katakana));
+    assert_eq!(model.weight(UnknownCategory::Katakana, 2), 1.0);
+    assert_eq!(model.weight(UnknownCategory::Katakana, 4), 0.25);
+    assert!(!model.has_statistics(UnknownCategory::Other));
+}
+
+#[test]
+fn tally_keeps_frequent_surfaces_within_capacity() {
+    let mut tally = UnknownTally::new(4);
+    for surface in ["ポチ", "タマ", "ポチ", "タマ"] {
+        tally.add(surface);
+    }
+    // Surfaces counted more often than those pruned are never pruned themselves.
+    for i in 0..100 {
+        tally.add("ポチ");
+        tally.add(&format!("rare{i}"));
+        if i % 2 == 0 {
+            tally.add("タマ");
+        }
+    }
+
+    let top = tally.top(10);
+    assert!(top.len() <= 4);
+    assert_eq!(top[0], ("ポチ".to_string(), 102));
+    assert_eq!(top[1], ("タマ".to_string(), 52));
+}
+
+#[test]
+fn tables_round_trip_through_a_file() {
+    let (_, table) = count_fixture(&CountOptions::default());
+    let path = temp_path("counts.bin");
+    table.save(&path).unwrap();
+    assert_eq!(FrequencyTable::load(&path).unwrap(), table);
+    std::fs::remove_file(&path).unwrap();
+
+    let error = FrequencyTable::read(&b"\xFF"[..]).unwrap_err();
+    assert_eq!(error.kind(), std::io::ErrorKind::UnexpectedEof);
+}
+
+#[test]
+fn unreadable_corpus_files_are_errors() {
+    let tokenizer = Arc::new(Tokenizer::new(Arc::new(dictionary())));
+    let missing = vec![temp_path("missing.txt")];
+    let error = count::count(tokenizer, &missing, &CountOptions::default()).unwrap_err();
+    assert_eq!(error.kind(), std::io::ErrorKind::NotFound);
+}
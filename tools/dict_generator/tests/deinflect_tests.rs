@@ -0,0 +1,90 @@
+use dict_generator::user_csv::{self, UserWord};
+use segmenter::dictionary::{Dictionary, DictionaryBuilder, Tag};
+use segmenter::tokenizer::{Tokenizer, TokenizerOptions};
+use std::sync::Arc;
+
+const WORDS_CSV: &str = "\
+0,猫,ねこ,NOUN
+1,,を,PARTICLE
+2,,が,PARTICLE
+3,,に,PARTICLE
+4,本,ほん,NOUN
+5,魚,さかな,NOUN
+6,手紙,てがみ,NOUN
+7,食べる,たべる,ICHIDAN_VERB|TRANSITIVE_VERB
+8,読む,よむ,GODAN_VERB|TRANSITIVE_VERB
+9,書く,かく,GODAN_VERB|TRANSITIVE_VERB
+10,走る,はしる,GODAN_VERB|INTRANSITIVE_VERB
+11,見る,みる,ICHIDAN_VERB|TRANSITIVE_VERB
+12,待つ,まつ,GODAN_VERB|TRANSITIVE_VERB
+";
+
+/// Known differences are left out: deinflection also reaches forms the generator never
+/// produces (conditionals, する and 来る).
+const CORPUS: &[&str] = &[
+    "猫が魚を食べなかった",
+    "本を読んだ",
+    "手紙を書かない",
+    "猫が走って",
+    "魚を見ました",
+    "猫を待たせる",
+    "本が読まれる",
+    "手紙を書きませんでした",
+    "猫に食べられない",
+];
+
+fn words() -> Vec<UserWord> {
+    user_csv::parse(WORDS_CSV).unwrap()
+}
+
+/// Every conjugation pre-generated, as `dict_generator build` does by default.
+fn expanded_dictionary() -> Dictionary {
+    let mut dictionary = Dictionary::new();
+    user_csv::append(&mut dictionary, &words()).unwrap();
+    dictionary
+}
+
+/// Dictionary forms only, as `dict_generator build --no-conjugation-expansion` does.
+fn dictionary_forms_only() -> Dictionary {
+    let mut builder = DictionaryBuilder::new();
+    for word in words() {
+        builder.add(word.kanji.as_deref(), &word.kana, word.pos, Tag::NONE);
+    }
+    builder.build()
+}
+
+fn segment(tokenizer: &Tokenizer, text: &str) -> Vec<(String, Option<u32>)> {
+    tokenizer
+        .tokenize(text)
+        .iter()
+        .map(|token| (token.token.to_string(), token.term_id))
+        .collect()
+}
+
+#[test]
+fn deinflection_matches_expanded_dictionary() {
+    let expanded = Tokenizer::new(Arc::new(expanded_dictionary()));
+    let small = Tokenizer::with_options(
+        Arc::new(dictionary_forms_only()),
+        TokenizerOptions {
+            deinflect: true,
+            ..Default::default()
+        },
+    );
+
+    for text in CORPUS {
+        assert_eq!(segment(&small, text), segment(&expanded, text), "{text}");
+    }
+}
+
+#[test]
+fn small_dictionary_needs_deinflection() {
+    let dictionary = Arc::new(dictionary_forms_only());
+    assert!(dictionary.kanji.len() < expanded_dictionary().kanji.len());
+
+    let plain = Tokenizer::new(dictionary);
+    assert!(plain
+        .tokenize("本を読んだ")
+        .iter()
+        .any(|token| token.term_id.is_none()));
+}
@@ -0,0 +1,417 @@
+use dict_generator::dict_builder::{
+    build_dictionary, BuildOptions, BuildWarning, PARTS_OF_SPEECH,
+};
+use segmenter::dictionary::{
+    Dialect, Dictionary, Domain, InflectionType, PartOfSpeech, Tag,
+};
+
+/// Entries the build warns about, between ones it takes as they are.
+const FIXTURE: &str = include_str!("fixtures/jmdict/warnings.xml");
+
+fn build(options: &BuildOptions) -> (Dictionary, Vec<BuildWarning>) {
+    build_dictionary(FIXTURE.as_bytes(), options).unwrap()
+}
+
+fn entry_index(dictionary: &Dictionary, surface: &str) -> Option<u32> {
+    let term_entries = dictionary
+        .kanji
+        .get(surface)
+        .or_else(|| dictionary.kana.get(surface))?;
+    Some(term_entries[0].entry_index)
+}
+
+#[test]
+fn warnings_name_the_entries() {
+    let (_, warnings) = build(&BuildOptions::default());
+
+    let conjugation_failures = |ent_seq| {
+        warnings
+            .iter()
+            .filter(|warning| {
+                matches!(warning, BuildWarning::ConjugationFailed { ent_seq: seq, .. } if *seq == ent_seq)
+            })
+            .count()
+    };
+    // かけ can't be read as a verb at all, ぴぷ only loses the forms built on its ending.
+    assert_eq!(conjugation_failures(1000030), 1);
+    assert_eq!(conjugation_failures(1000040), 19);
+
+    let others: Vec<&BuildWarning> = warnings
+        .iter()
+        .filter(|warning| !matches!(warning, BuildWarning::ConjugationFailed { .. }))
+        .collect();
+    assert_eq!(
+        others,
+        [
+            &BuildWarning::MissingReading { ent_seq: 1000020 },
+            &BuildWarning::UnknownPartOfSpeech {
+                ent_seq: 1000050,
+                pos: "n-x".to_string(),
+            },
+        ]
+    );
+    assert!(warnings.contains(&BuildWarning::ConjugationFailed {
+        ent_seq: 1000030,
+        kana: "かけ".to_string(),
+        kanji: None,
+        failure: dict_generator::conjugation::ConjugationFailure {
+            form: "verb",
+            error: "NotAVerb".to_string(),
+        },
+    }));
+}
+
+#[test]
+fn failed_forms_are_skipped_not_fatal() {
+    let (dictionary, _) = build(&BuildOptions::default());
+
+    // The entry without a reading is the only one left out.
+    assert_eq!(dictionary.entries.len(), 6);
+    assert_eq!(entry_index(&dictionary, "謎"), None);
+    // Entries come in ent_seq order, whatever the order of the document.
+    assert_eq!(entry_index(&dictionary, "かけ"), Some(1));
+    assert_eq!(entry_index(&dictionary, "ぴぷ"), Some(2));
+    assert_eq!(entry_index(&dictionary, "ぴぷな"), Some(2));
+    assert_eq!(entry_index(&dictionary, "開いた"), Some(4));
+}
+
+#[test]
+fn transitivity_pairs_survive_skipped_entries() {
+    let (dictionary, _) = build(&BuildOptions::default());
+
+    let paired = |surface| {
+        let index = entry_index(&dictionary, surface).unwrap();
+        dictionary.entries[index as usize].pair_term_id.is_some()
+    };
+    assert!(paired("開ける"));
+    assert!(paired("開く"));
+    assert!(!paired("猫"));
+}
+
+#[test]
+fn without_expansion_nothing_is_conjugated() {
+    let options = BuildOptions {
+        expand_conjugations: false,
+        ..BuildOptions::default()
+    };
+    let (dictionary, warnings) = build(&options);
+
+    assert_eq!(entry_index(&dictionary, "開いた"), None);
+    assert!(!warnings
+        .iter()
+        .any(|warning| matches!(warning, BuildWarning::ConjugationFailed { .. })));
+}
+
+#[test]
+fn malformed_documents_fail_the_build() {
+    let error = build_dictionary("<JMdict><entry>".as_bytes(), &BuildOptions::default());
+    assert!(error.is_err());
+}
+
+#[test]
+fn kana_surfaces_can_be_indexed_in_both_scripts() {
+    let (dictionary, _) = build(&BuildOptions::default());
+    assert_eq!(entry_index(&dictionary, "ネコ"), None);
+
+    let options = BuildOptions {
+        index_both_kana_scripts: true,
+        ..BuildOptions::default()
+    };
+    let (dictionary, _) = build(&options);
+    assert_eq!(
+        entry_index(&dictionary, "ネコ"),
+        entry_index(&dictionary, "ねこ")
+    );
+    // Conjugated forms too.
+    assert_eq!(
+        entry_index(&dictionary, "アケタ"),
+        entry_index(&dictionary, "開ける")
+    );
+}
+
+#[test]
+fn dictionary_forms_outrank_conjugations_spelled_the_same() {
+    // 開く comes first by ent_seq, and its potential is spelled 開ける.
+    let (dictionary, _) = build(&BuildOptions::default());
+    let hit = dictionary.best_entry_for("開ける").unwrap();
+    assert_eq!(hit.term_id, 1202440);
+    assert_eq!(hit.inflection_type, InflectionType::DictionaryForm);
+}
+
+/// Words with irregular spellings, marked with `ke_inf` and `re_inf`.
+const IRREGULAR_FORMS: &str = include_str!("fixtures/jmdict/irregular_forms.xml");
+
+/// Form penalties of the entries indexed under `surface`, in homograph order.
+fn form_penalties(dictionary: &Dictionary, surface: &str) -> Vec<(u32, u8)> {
+    let term_entries = dictionary
+        .kanji
+        .get(surface)
+        .or_else(|| dictionary.kana.get(surface));
+    term_entries
+        .into_iter()
+        .flatten()
+        .map(|term_entry| (term_entry.entry_index, term_entry.form_penalty))
+        .collect()
+}
+
+#[test]
+fn irregular_forms_carry_penalties() {
+    let (dictionary, _) =
+        build_dictionary(IRREGULAR_FORMS.as_bytes(), &BuildOptions::default()).unwrap();
+
+    assert_eq!(form_penalties(&dictionary, "匂い"), [(0, 0)]);
+    // The standard spelling of the later entry comes first.
+    assert_eq!(form_penalties(&dictionary, "臭い"), [(1, 0), (0, 2)]);
+    assert_eq!(form_penalties(&dictionary, "におい"), [(0, 0)]);
+    // Conjugated forms of a rare spelling are just as rare, those of its kana aren't.
+    assert_eq!(form_penalties(&dictionary, "呟いた"), [(2, 2)]);
+    assert_eq!(form_penalties(&dictionary, "つぶやいた"), [(2, 0)]);
+    assert_eq!(form_penalties(&dictionary, "つぶやくぅ"), [(2, 2)]);
+}
+
+#[test]
+fn search_only_forms_are_indexed_on_request() {
+    let (dictionary, _) =
+        build_dictionary(IRREGULAR_FORMS.as_bytes(), &BuildOptions::default()).unwrap();
+    assert_eq!(form_penalties(&dictionary, "匂"), []);
+    assert!(dictionary.kanji_forms[0].contains(&"匂".to_string()));
+
+    let options = BuildOptions {
+        index_search_only_forms: true,
+        ..BuildOptions::default()
+    };
+    let (dictionary, _) = build_dictionary(IRREGULAR_FORMS.as_bytes(), &options).unwrap();
+    assert_eq!(form_penalties(&dictionary, "匂"), [(0, 3)]);
+}
+
+#[test]
+fn ateji_and_gikun_spellings_tag_their_entries() {
+    let (dictionary, _) =
+        build_dictionary(IRREGULAR_FORMS.as_bytes(), &BuildOptions::default()).unwrap();
+
+    let tag = |surface| {
+        dictionary.entries[entry_index(&dictionary, surface).unwrap() as usize].tag
+    };
+    assert_eq!(tag("流石"), Tag::ATEJI);
+    assert_eq!(tag("大人しい"), Tag::ATEJI);
+    assert_eq!(tag("匂い"), Tag::empty());
+}
+
+#[test]
+fn misc_markers_of_every_sense_tag_the_entry() {
+    let fixture = include_str!("fixtures/jmdict/misc_tags.xml");
+    let (dictionary, _) =
+        build_dictionary(fixture.as_bytes(), &BuildOptions::default()).unwrap();
+
+    let tag = |surface| {
+        dictionary.entries[entry_index(&dictionary, surface).unwrap() as usize].tag
+    };
+    assert_eq!(
+        tag("御座る"),
+        Tag::USUALLY_KANA | Tag::TEINEIGO | Tag::ARCHAIC
+    );
+    assert_eq!(tag("ござる"), tag("御座る"));
+    assert_eq!(tag("猫に小判"), Tag::IDIOMATIC_EXPRESSION);
+    assert_eq!(tag("一期一会"), Tag::YOJIJUKUGO);
+    assert_eq!(tag("召し上がる"), Tag::SONKEIGO);
+    assert_eq!(tag("申す"), Tag::KENJOUGO | Tag::DATED_TERM);
+    assert_eq!(tag("パソコン"), Tag::ABBREVIATION);
+    assert_eq!(
+        tag("仮字"),
+        Tag::OBSOLETE_TERM | Tag::RARE | Tag::HISTORICAL_TERM
+    );
+    assert_eq!(tag("流石"), Tag::ATEJI | Tag::USUALLY_KANA);
+    assert_eq!(tag("マジ"), Tag::NONE);
+}
+
+#[test]
+fn dialect_and_field_markers_are_kept_on_the_entry() {
+    let fixture = include_str!("fixtures/jmdict/every_element.xml");
+    let (dictionary, _) =
+        build_dictionary(fixture.as_bytes(), &BuildOptions::default()).unwrap();
+
+    let dialect = |surface| {
+        dictionary.entries[entry_index(&dictionary, surface).unwrap() as usize].dialect
+    };
+    assert_eq!(dialect("開ける"), Dialect::KANSAI);
+    assert_eq!(dialect("コーヒー"), Dialect::NONE);
+
+    let domain = |surface| {
+        dictionary.entries[entry_index(&dictionary, surface).unwrap() as usize].domain
+    };
+    assert_eq!(domain("コーヒー"), Domain::FOOD);
+    assert_eq!(domain("開ける"), Domain::NONE);
+}
+
+#[test]
+fn parts_of_speech_become_flags() {
+    let fixture = include_str!("fixtures/jmdict/parts_of_speech.xml");
+    let (dictionary, warnings) =
+        build_dictionary(fixture.as_bytes(), &BuildOptions::default()).unwrap();
+
+    let pos = |surface| {
+        dictionary.entries[entry_index(&dictionary, surface).unwrap() as usize].pos
+    };
+    assert_eq!(pos("猫"), PartOfSpeech::NOUN);
+    assert!(pos("を").is_particle());
+    assert_eq!(
+        pos("ありがとう"),
+        PartOfSpeech::EXPRESSION | PartOfSpeech::INTERJECTION
+    );
+    assert_eq!(pos("高い"), PartOfSpeech::ADJECTIVE);
+    // Na-adjectives have no flag, the noun sense still counts.
+    assert_eq!(pos("静か"), PartOfSpeech::NOUN);
+    assert_eq!(
+        pos("食べる"),
+        PartOfSpeech::ICHIDAN_VERB | PartOfSpeech::TRANSITIVE_VERB
+    );
+    assert_eq!(
+        pos("帰る"),
+        PartOfSpeech::GODAN_VERB | PartOfSpeech::INTRANSITIVE_VERB
+    );
+    assert_eq!(
+        pos("来る"),
+        PartOfSpeech::KURU_VERB | PartOfSpeech::INTRANSITIVE_VERB
+    );
+    assert_eq!(pos("する"), PartOfSpeech::SURU_VERB);
+    assert_eq!(pos("なにか"), PartOfSpeech::NOUN);
+    assert_eq!(pos("どこか"), PartOfSpeech::NONE);
+
+    // Conjugated forms point at the same entry.
+    assert_eq!(pos("食べた"), pos("食べる"));
+
+    // Two entries use n-x, the first one is reported.
+    let unknown: Vec<&BuildWarning> = warnings
+        .iter()
+        .filter(|warning| matches!(warning, BuildWarning::UnknownPartOfSpeech { .. }))
+        .collect();
+    assert_eq!(
+        unknown,
+        [&BuildWarning::UnknownPartOfSpeech {
+            ent_seq: 1000100,
+            pos: "n-x".to_string(),
+        }]
+    );
+}
+
+#[test]
+fn conjugations_record_their_inflection() {
+    let fixture = include_str!("fixtures/jmdict/parts_of_speech.xml");
+    let (dictionary, _) =
+        build_dictionary(fixture.as_bytes(), &BuildOptions::default()).unwrap();
+
+    let taberu = entry_index(&dictionary, "食べる").unwrap();
+    for (surface, inflection_type) in [
+        ("食べる", InflectionType::DictionaryForm),
+        ("食べて", InflectionType::Te),
+        ("たべて", InflectionType::Te),
+        ("食べた", InflectionType::Past),
+        ("食べました", InflectionType::Past),
+        ("食べない", InflectionType::Negative),
+        ("食べなかった", InflectionType::NegativePast),
+        ("食べられる", InflectionType::Potential),
+        ("食べさせる", InflectionType::Causative),
+        ("食べろ", InflectionType::Imperative),
+    ] {
+        let term_entries = dictionary
+            .kanji
+            .get(surface)
+            .or_else(|| dictionary.kana.get(surface))
+            .unwrap();
+        assert_eq!(term_entries[0].entry_index, taberu, "{surface}");
+        assert_eq!(
+            term_entries[0].inflection_type, inflection_type,
+            "{surface}"
+        );
+    }
+    assert_eq!(
+        dictionary.kanji["帰らない"][0].inflection_type,
+        InflectionType::Negative
+    );
+}
+
+#[test]
+fn every_part_of_speech_is_listed_once() {
+    for (index, (name, _)) in PARTS_OF_SPEECH.iter().enumerate() {
+        assert!(
+            PARTS_OF_SPEECH[..index]
+                .iter()
+                .all(|(other, _)| other != name),
+            "{name}"
+        );
+    }
+}
+
+/// One godan verb of every irregular class.
+const GODAN_IRREGULAR: &str = include_str!("fixtures/jmdict/godan_irregular.xml");
+
+#[test]
+fn irregular_godan_verbs_get_their_own_forms() {
+    let (dictionary, _) =
+        build_dictionary(GODAN_IRREGULAR.as_bytes(), &BuildOptions::default()).unwrap();
+
+    for (surfaces, base) in [
+        (&["行った", "行って", "いった", "いって"][..], "行く"),
+        (&["問うた", "問うて", "とうた", "とうて"], "問う"),
+        (&["ない", "なかった", "なくて", "ありません"], "有る"),
+        (
+            &["下さい", "ください", "下さいました", "下さいません"],
+            "下さる",
+        ),
+    ] {
+        let expected = entry_index(&dictionary, base);
+        assert!(expected.is_some(), "{base}");
+        for surface in surfaces {
+            assert_eq!(entry_index(&dictionary, surface), expected, "{surface}");
+        }
+    }
+
+    // The regular godan forms are wrong for these verbs.
+    for surface in [
+        "行いた",
+        "行いて",
+        "問った",
+        "問って",
+        "有らない",
+        "あらない",
+        "下され",
+        "下さりました",
+    ] {
+        assert_eq!(entry_index(&dictionary, surface), None, "{surface}");
+    }
+}
+
+/// Two entries referring to each other and one referring to a missing entry.
+const XREFS: &str = include_str!("fixtures/jmdict/xrefs.xml");
+
+#[test]
+fn xrefs_are_resolved_to_term_ids() {
+    let (dictionary, warnings) =
+        build_dictionary(XREFS.as_bytes(), &BuildOptions::default()).unwrap();
+    assert!(dictionary.xrefs.is_empty());
+    assert!(warnings.is_empty());
+
+    let options = BuildOptions {
+        with_xrefs: true,
+        ..BuildOptions::default()
+    };
+    let (dictionary, warnings) = build_dictionary(XREFS.as_bytes(), &options).unwrap();
+    let toru = entry_index(&dictionary, "取る").unwrap() as usize;
+    let tori = entry_index(&dictionary, "取り").unwrap() as usize;
+    let neko = entry_index(&dictionary, "猫").unwrap() as usize;
+    assert_eq!(dictionary.xrefs[toru], [dictionary.entries[tori].term_id]);
+    assert_eq!(dictionary.xrefs[tori], [dictionary.entries[toru].term_id]);
+    assert!(dictionary.xrefs[neko].is_empty());
+    assert_eq!(
+        dictionary.xrefs(dictionary.entries[toru].term_id),
+        [dictionary.entries[tori].term_id]
+    );
+
+    assert_eq!(
+        warnings,
+        [BuildWarning::UnresolvedXref {
+            ent_seq: 1000030,
+            xref: "子猫".to_string(),
+        }]
+    );
+}
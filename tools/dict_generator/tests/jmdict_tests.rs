@@ -0,0 +1,209 @@
+use dict_generator::jmdict::{self, CrossReference, Entry, JMdict};
+use segmenter::dictionary::{
+    Dialect, DictionaryEntry, Domain, PartOfSpeech, Tag, Transitivity,
+};
+
+const FIXTURE: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE JMdict [
+<!ENTITY n "noun (common) (futsuumeishi)">
+<!ENTITY v1 "Ichidan verb">
+<!ENTITY v5k "Godan verb with 'ku' ending">
+<!ENTITY vt "transitive verb">
+<!ENTITY vi "intransitive verb">
+]>
+<JMdict>
+<entry>
+<ent_seq>1202440</ent_seq>
+<k_ele><keb>開ける</keb></k_ele>
+<r_ele><reb>あける</reb></r_ele>
+<sense>
+<pos>&v1;</pos>
+<pos>&vt;</pos>
+<xref>開く・あく・1</xref>
+<gloss>to open (a door, etc.)</gloss>
+</sense>
+</entry>
+<entry>
+<ent_seq>1202410</ent_seq>
+<k_ele><keb>開く</keb></k_ele>
+<r_ele><reb>あく</reb></r_ele>
+<sense>
+<pos>&v5k;</pos>
+<pos>&vi;</pos>
+<gloss>to open (e.g. doors)</gloss>
+</sense>
+</entry>
+<entry>
+<ent_seq>1202400</ent_seq>
+<k_ele><keb>開く</keb></k_ele>
+<r_ele><reb>ひらく</reb></r_ele>
+<sense>
+<pos>&v5k;</pos>
+<pos>&vt;</pos>
+<gloss>to open (a book, etc.)</gloss>
+</sense>
+</entry>
+<entry>
+<ent_seq>1000001</ent_seq>
+<k_ele><keb>扉</keb></k_ele>
+<r_ele><reb>とびら</reb></r_ele>
+<sense>
+<pos>&n;</pos>
+<xref>開ける</xref>
+<gloss>door</gloss>
+</sense>
+</entry>
+</JMdict>
+"#;
+
+/// Uses every element and attribute of the JMdict DTD.
+const EVERY_ELEMENT: &str = include_str!("fixtures/jmdict/every_element.xml");
+
+fn entries() -> Vec<Entry> {
+    jmdict::parse(FIXTURE.as_bytes()).unwrap().entries
+}
+
+#[test]
+fn transitivity_comes_from_senses() {
+    let entries = entries();
+    assert_eq!(entries[0].transitivity(), Some(Transitivity::Transitive));
+    assert_eq!(entries[1].transitivity(), Some(Transitivity::Intransitive));
+    assert_eq!(entries[3].transitivity(), None);
+}
+
+#[test]
+fn xref_pairs_are_linked_both_ways() {
+    let entries = entries();
+    let pairs = jmdict::transitivity_pairs(&entries);
+
+    // The xref's reading picks あく over ひらく, which is transitive anyway.
+    assert_eq!(pairs.get(&0), Some(&1));
+    assert_eq!(pairs.get(&1), Some(&0));
+    // Neither ひらく nor the noun pointing at 開ける are verb pairs.
+    assert_eq!(pairs.len(), 2);
+}
+
+#[test]
+fn dictionary_entries_report_transitivity() {
+    let entry = |pos| DictionaryEntry {
+        term_id: 1,
+        pos,
+        tag: Tag::NONE,
+        pair_term_id: Some(2),
+        dialect: Dialect::NONE,
+        domain: Domain::NONE,
+    };
+    let transitive = entry(PartOfSpeech::ICHIDAN_VERB | PartOfSpeech::TRANSITIVE_VERB);
+    let intransitive = entry(PartOfSpeech::GODAN_VERB | PartOfSpeech::INTRANSITIVE_VERB);
+    let both = entry(PartOfSpeech::TRANSITIVE_VERB | PartOfSpeech::INTRANSITIVE_VERB);
+
+    assert_eq!(transitive.transitivity(), Some(Transitivity::Transitive));
+    assert_eq!(
+        intransitive.transitivity(),
+        Some(Transitivity::Intransitive)
+    );
+    assert_eq!(both.transitivity(), None);
+    assert_eq!(entry(PartOfSpeech::NOUN).transitivity(), None);
+}
+
+#[test]
+fn every_element_is_kept() {
+    let dict = jmdict::parse(EVERY_ELEMENT.as_bytes()).unwrap();
+    let [open, coffee] = &dict.entries[..] else {
+        panic!("{dict:?}");
+    };
+
+    let kanji = open.kanji_elements.as_ref().unwrap();
+    assert_eq!(kanji[0].ke_pri, Some(vec!["ichi1".into(), "news1".into()]));
+    assert_eq!(kanji[1].ke_inf, Some(vec!["ateji".into()]));
+    let readings = open.reading_elements.as_ref().unwrap();
+    assert_eq!(readings[1].re_restr, Some(vec!["開ける".into()]));
+    assert_eq!(readings[1].re_inf, Some(vec!["ok".into()]));
+
+    let senses = open.senses.as_ref().unwrap();
+    let sense = &senses[0];
+    assert_eq!(sense.stagk, Some(vec!["開ける".into()]));
+    assert_eq!(sense.stagr, Some(vec!["あける".into()]));
+    assert_eq!(sense.pos, ["v1", "vt"]);
+    assert_eq!(sense.xref.as_ref().unwrap().len(), 2);
+    assert_eq!(sense.ant, Some(vec!["閉める".into()]));
+    assert_eq!(sense.misc, Some(vec!["uk".into()]));
+    assert_eq!(sense.s_inf, Some(vec!["of a door or window".into()]));
+    assert_eq!(sense.dial, Some(vec!["ksb".into()]));
+    assert_eq!(sense.gloss[0].language(), "eng");
+    assert_eq!(sense.gloss[1].g_type.as_deref(), Some("lit"));
+    assert_eq!(sense.gloss[2].language(), "ger");
+    assert_eq!(sense.gloss[2].g_gend.as_deref(), Some("masc"));
+    assert_eq!(sense.gloss[2].text, "öffnen");
+    let example = &sense.example.as_ref().unwrap()[0];
+    assert_eq!(example.ex_srce.exsrc_type.as_deref(), Some("tat"));
+    assert_eq!(example.ex_srce.text, "154232");
+    assert_eq!(example.ex_text, "開ける");
+    assert_eq!(example.ex_sent[1].lang.as_deref(), Some("eng"));
+    // Parts of speech carry over from the previous sense.
+    assert!(senses[1].pos.is_empty());
+
+    let readings = coffee.reading_elements.as_ref().unwrap();
+    assert_eq!(readings[0].re_nokanji, None);
+    assert_eq!(readings[1].re_nokanji.as_deref(), Some(""));
+    let sense = &coffee.senses.as_ref().unwrap()[0];
+    assert_eq!(sense.field, Some(vec!["food".into()]));
+    let sources = sense.lsource.as_ref().unwrap();
+    assert_eq!(sources[0].language(), "dut");
+    assert_eq!(sources[0].text.as_deref(), Some("koffie"));
+    assert_eq!(sources[1].language(), "eng");
+    assert_eq!(sources[1].ls_type.as_deref(), Some("part"));
+    assert_eq!(sources[1].ls_wasei.as_deref(), Some("y"));
+    assert_eq!(sources[2].language(), "fre");
+    assert_eq!(sources[2].text, None);
+}
+
+#[test]
+fn entities_read_as_their_names() {
+    let (dict, descriptions) = jmdict::parse_with_entities(FIXTURE.as_bytes()).unwrap();
+
+    let names: Vec<&str> = descriptions.keys().map(String::as_str).collect();
+    assert_eq!(names, ["n", "v1", "v5k", "vi", "vt"]);
+    assert_eq!(descriptions["n"], "noun (common) (futsuumeishi)");
+    assert_eq!(descriptions["v5k"], "Godan verb with 'ku' ending");
+
+    let senses = dict.entries[1].senses.as_ref().unwrap();
+    assert_eq!(senses[0].pos, ["v5k", "vi"]);
+    for pos in senses[0].pos.iter() {
+        assert!(descriptions.contains_key(pos), "{pos}");
+    }
+    assert_eq!(jmdict::parse(FIXTURE.as_bytes()).unwrap(), dict);
+}
+
+#[test]
+fn every_element_round_trips() {
+    let dict = jmdict::parse(EVERY_ELEMENT.as_bytes()).unwrap();
+    let xml = quick_xml::se::to_string(&dict).unwrap();
+    let reparsed: JMdict = quick_xml::de::from_str(&xml).unwrap();
+    assert_eq!(reparsed, dict);
+}
+
+#[test]
+fn unknown_elements_are_rejected() {
+    let xml = EVERY_ELEMENT.replace("<ke_pri>news1</ke_pri>", "<ke_freq>1</ke_freq>");
+    assert!(jmdict::parse(xml.as_bytes()).is_err());
+    let xml = EVERY_ELEMENT.replace("g_type=\"lit\"", "g_kind=\"lit\"");
+    assert!(jmdict::parse(xml.as_bytes()).is_err());
+}
+
+#[test]
+fn cross_references_parse() {
+    let parse = CrossReference::parse;
+    assert_eq!(
+        parse("開く・あく・1"),
+        CrossReference {
+            surface: "開く",
+            reading: Some("あく"),
+            sense: Some(1),
+        }
+    );
+    assert_eq!(parse("開く・2").sense, Some(2));
+    assert_eq!(parse("開く・2").reading, None);
+    assert_eq!(parse("あく").reading, None);
+    assert_eq!(parse("あく").surface, "あく");
+}
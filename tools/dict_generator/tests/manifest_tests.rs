@@ -0,0 +1,111 @@
+use dict_generator::dict_builder::{build_dictionary, BuildOptions};
+use dict_generator::manifest::{BuildManifest, JP_INFLECTIONS_VERSION};
+use dict_generator::sha256::sha256_hex;
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+/// Nouns and verbs, so the build conjugates some of them.
+const ENTRIES: &[(u32, &str, &str, &str)] = &[
+    (1000010, "猫", "ねこ", "n"),
+    (1000020, "犬", "いぬ", "n"),
+    (1000030, "書く", "かく", "v5k"),
+    (1000040, "食べる", "たべる", "v1"),
+    (1000050, "鳥", "とり", "n"),
+    (1000060, "読む", "よむ", "v5m"),
+];
+
+fn jmdict(entries: &[(u32, &str, &str, &str)]) -> String {
+    let mut document = String::from(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+         <!DOCTYPE JMdict [\n<!ENTITY n \"noun (common) (futsuumeishi)\">\n\
+         <!ENTITY v5k \"Godan verb with 'ku' ending\">\n\
+         <!ENTITY v5m \"Godan verb with 'mu' ending\">\n\
+         <!ENTITY v1 \"Ichidan verb\">\n]>\n<JMdict>\n",
+    );
+    for (ent_seq, keb, reb, pos) in entries {
+        document.push_str(&format!(
+            "<entry><ent_seq>{ent_seq}</ent_seq><k_ele><keb>{keb}</keb></k_ele>\
+             <r_ele><reb>{reb}</reb></r_ele><sense><pos>&{pos};</pos><gloss>x</gloss>\
+             </sense></entry>\n"
+        ));
+    }
+    document.push_str("</JMdict>\n");
+    document
+}
+
+fn temp_path(name: &str) -> PathBuf {
+    std::env::temp_dir().join(format!("dict-generator-{}-{name}", std::process::id()))
+}
+
+/// Builds the document at `input` into `output` with both kana scripts and the romaji
+/// index, the steps iterating over the surface maps, and returns the manifest.
+fn build(input: &Path, output: &Path) -> BuildManifest {
+    let options = BuildOptions {
+        index_both_kana_scripts: true,
+        with_romaji_index: true,
+        ..BuildOptions::default()
+    };
+    let source = std::fs::File::open(input).unwrap();
+    let (dictionary, _) = build_dictionary(source, &options).unwrap();
+    dictionary.save(output).unwrap();
+
+    let recorded = BTreeMap::from([
+        ("--index-both-kana-scripts".to_string(), Value::Bool(true)),
+        ("--with-romaji-index".to_string(), Value::Bool(true)),
+    ]);
+    BuildManifest::new(&[input], recorded, output).unwrap()
+}
+
+#[test]
+fn sha256_matches_known_digests() {
+    assert_eq!(
+        sha256_hex(b""),
+        "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855"
+    );
+    assert_eq!(
+        sha256_hex(b"abc"),
+        "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+    );
+    // Longer than a block, with the padding spilling into another one.
+    assert_eq!(
+        sha256_hex(&[b'x'; 119]),
+        "000b48d4edf0fa7bee3c6236ecd2785baa5db4eeb8bb54341b029e0d9fa5fb0c"
+    );
+}
+
+#[test]
+fn jp_inflections_version_is_the_pinned_one() {
+    let manifest = include_str!("../Cargo.toml");
+    let pinned = format!("jp_inflections = \"={JP_INFLECTIONS_VERSION}\"");
+    assert!(manifest.contains(&pinned), "{pinned}");
+}
+
+#[test]
+fn shuffled_entries_build_identical_dictionaries() {
+    let forward = temp_path("manifest-forward.xml");
+    let shuffled = temp_path("manifest-shuffled.xml");
+    let output = temp_path("manifest.bin");
+    let mut entries = ENTRIES.to_vec();
+    std::fs::write(&forward, jmdict(&entries)).unwrap();
+    entries.swap(0, 4);
+    entries.reverse();
+    std::fs::write(&shuffled, jmdict(&entries)).unwrap();
+
+    let first = build(&forward, &output);
+    let again = build(&forward, &output);
+    let other_order = build(&shuffled, &output);
+    for path in [&forward, &shuffled, &output] {
+        std::fs::remove_file(path).unwrap();
+    }
+
+    assert!(first.is_equivalent(&again), "{first:?}\n{again:?}");
+    assert_eq!(other_order.output, first.output);
+    assert_ne!(other_order.inputs, first.inputs);
+    assert_eq!(first.versions["jp_inflections"], JP_INFLECTIONS_VERSION);
+    assert_eq!(first.versions["segmenter"], segmenter::VERSION);
+
+    let written = serde_json::to_string(&first).unwrap();
+    let read: BuildManifest = serde_json::from_str(&written).unwrap();
+    assert_eq!(read, first);
+}
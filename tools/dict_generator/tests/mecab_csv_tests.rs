@@ -0,0 +1,191 @@
+use dict_generator::mecab_csv::{self, Encoding, MecabWord};
+use dict_generator::user_csv::AppendError;
+use segmenter::dictionary::{
+    imported_term_ids, Dictionary, DictionaryBuilder, PartOfSpeech, Tag, TermId,
+    IMPORTED_TERM_IDS,
+};
+use segmenter::tokenizer::Tokenizer;
+use std::sync::Arc;
+
+const NEOLOGD: &[u8] = include_bytes!("fixtures/mecab/neologd.csv");
+const TERMS_EUC_JP: &[u8] = include_bytes!("fixtures/mecab/terms_eucjp.csv");
+
+fn parse(bytes: &[u8]) -> (Vec<MecabWord>, Encoding) {
+    let (text, encoding) = mecab_csv::decode(bytes).unwrap();
+    (mecab_csv::parse(&text).unwrap(), encoding)
+}
+
+fn word<'a>(words: &'a [MecabWord], surface: &str) -> &'a MecabWord {
+    words
+        .iter()
+        .find(|word| word.surface == surface)
+        .unwrap_or_else(|| panic!("`{surface}` is missing"))
+}
+
+fn base_fixture() -> Dictionary {
+    let mut builder = DictionaryBuilder::new();
+    builder.add(Some("国"), "くに", PartOfSpeech::NOUN, Tag::NONE);
+    builder.add(Some("図書館"), "としょかん", PartOfSpeech::NOUN, Tag::NONE);
+    builder.add(None, "に", PartOfSpeech::PARTICLE, Tag::NONE);
+    builder.add(None, "を", PartOfSpeech::PARTICLE, Tag::NONE);
+    builder.build()
+}
+
+#[test]
+fn encodings_are_detected() {
+    let (words, encoding) = parse(NEOLOGD);
+    assert_eq!(encoding, Encoding::Utf8);
+    assert_eq!(
+        word(&words, "国立国会図書館").reading.as_deref(),
+        Some("コクリツコッカイトショカン")
+    );
+
+    let (words, encoding) = parse(TERMS_EUC_JP);
+    assert_eq!(encoding, Encoding::EucJp);
+    assert_eq!(
+        word(&words, "半導体").reading.as_deref(),
+        Some("ハンドウタイ")
+    );
+    // Half-width katakana use the single-shift prefix.
+    assert_eq!(word(&words, "ｶﾞｲｼ").cost, 8000);
+
+    let with_bom = [b"\xEF\xBB\xBF".as_slice(), NEOLOGD].concat();
+    assert_eq!(parse(&with_bom).0, parse(NEOLOGD).0);
+}
+
+#[test]
+fn invalid_bytes_are_reported_with_their_line() {
+    let error = mecab_csv::decode(b"a,b\n\xA1\xA1,\xFF\n").unwrap_err();
+    assert!(matches!(error, AppendError::Parse { line: 2, .. }));
+}
+
+#[test]
+fn pos_hierarchy_is_mapped() {
+    let (words, _) = parse(NEOLOGD);
+    assert_eq!(
+        word(&words, "国立国会図書館").pos,
+        PartOfSpeech::NOUN_PROPER
+    );
+    assert_eq!(word(&words, "呟く").pos, PartOfSpeech::GODAN_VERB);
+    assert_eq!(word(&words, "1,000円").pos, PartOfSpeech::NUMERIC);
+    assert_eq!(word(&words, "ところで").pos, PartOfSpeech::CONJUNCTION);
+
+    let (words, _) = parse(TERMS_EUC_JP);
+    assert_eq!(word(&words, "半導体").pos, PartOfSpeech::NOUN);
+    assert_eq!(
+        word(&words, "受注").pos,
+        PartOfSpeech::NOUN | PartOfSpeech::SURU_VERB
+    );
+
+    assert_eq!(
+        mecab_csv::map_pos(&["名詞", "接尾", "助数詞"]),
+        PartOfSpeech::COUNTER
+    );
+    assert_eq!(
+        mecab_csv::map_pos(&["名詞", "接尾", "一般"]),
+        PartOfSpeech::NOUN_SUFFIX
+    );
+    assert_eq!(mecab_csv::map_pos(&["その他", "間投"]), PartOfSpeech::NONE);
+    assert_eq!(mecab_csv::verb_class("一段"), PartOfSpeech::ICHIDAN_VERB);
+}
+
+#[test]
+fn inflected_rows_are_skipped() {
+    let (words, _) = parse(NEOLOGD);
+    assert!(words.iter().all(|word| word.surface != "呟か"));
+    assert_eq!(words.len(), 5);
+}
+
+#[test]
+fn malformed_rows_are_rejected() {
+    let error = mecab_csv::parse("猫,1,1,10,名詞").unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "line 1: expected at least 11 fields, found 5"
+    );
+
+    let error =
+        mecab_csv::parse("\n猫,1,1,x,名詞,一般,*,*,*,*,猫,ネコ,ネコ").unwrap_err();
+    assert_eq!(error.to_string(), "line 2: invalid cost `x`");
+
+    let error = mecab_csv::parse("猫,1,1,10,名詞,一般,*,*,*,*,猫").unwrap_err();
+    assert_eq!(error.to_string(), "line 1: missing reading for `猫`");
+}
+
+#[test]
+fn cost_buckets_order_entries() {
+    assert!(mecab_csv::priority_bucket(-3000) < mecab_csv::priority_bucket(4000));
+    assert!(mecab_csv::priority_bucket(4000) < mecab_csv::priority_bucket(12000));
+
+    let mut dictionary = Dictionary::new();
+    let (words, _) = parse(TERMS_EUC_JP);
+    // 受注 appears twice and becomes one entry.
+    assert_eq!(mecab_csv::import(&mut dictionary, &words).unwrap(), 3);
+
+    let order: Vec<&str> = dictionary
+        .readings
+        .iter()
+        .map(|readings| readings[0].as_str())
+        .collect();
+    assert_eq!(order, ["はんどうたい", "じゅちゅう", "がいし"]);
+}
+
+#[test]
+fn term_ids_do_not_depend_on_row_order() {
+    let term_ids = |words: &[MecabWord]| {
+        let mut dictionary = Dictionary::new();
+        mecab_csv::import(&mut dictionary, words).unwrap();
+        let mut term_ids: Vec<(String, TermId)> = dictionary
+            .entries
+            .iter()
+            .zip(dictionary.readings.iter())
+            .map(|(entry, readings)| (readings[0].clone(), entry.term_id))
+            .collect();
+        term_ids.sort();
+        term_ids
+    };
+
+    let (mut words, _) = parse(NEOLOGD);
+    let forward = term_ids(&words);
+    words.reverse();
+    assert_eq!(term_ids(&words), forward);
+    assert!(forward
+        .iter()
+        .all(|(_, term_id)| IMPORTED_TERM_IDS.contains(term_id)));
+}
+
+#[test]
+fn imported_words_win_in_tokenization() {
+    let tokenize =
+        |dictionary: &Arc<Dictionary>, text: &str| -> Vec<(String, Option<u32>)> {
+            Tokenizer::new(dictionary.clone())
+                .tokenize(text)
+                .iter()
+                .map(|token| (token.token.to_string(), token.term_id))
+                .collect()
+        };
+    let text = "国立国会図書館に呟いた";
+
+    let base = Arc::new(base_fixture());
+    assert_ne!(tokenize(&base, text)[0].0, "国立国会図書館");
+
+    let mut dictionary = base_fixture();
+    let (words, _) = parse(NEOLOGD);
+    mecab_csv::import(&mut dictionary, &words).unwrap();
+    let dictionary = Arc::new(dictionary);
+
+    let tokens = tokenize(&dictionary, text);
+    let surfaces: Vec<&str> =
+        tokens.iter().map(|(surface, _)| surface.as_str()).collect();
+    assert_eq!(surfaces, ["国立国会図書館", "に", "呟いた"]);
+    // Term ids are hashed from the surface and reading, above JMdict's range.
+    let term_id =
+        imported_term_ids("mecab", "国立国会図書館", "こくりつこっかいとしょかん").next();
+    assert_eq!(tokens[0].1, term_id);
+    assert!(IMPORTED_TERM_IDS.contains(&term_id.unwrap()));
+    assert!(tokens[2].1.is_some());
+
+    // Kanji surfaces are indexed under their reading too.
+    assert!(dictionary.kana.contains_key("こくりつこっかいとしょかん"));
+    assert!(dictionary.kanji.contains_key("1,000円"));
+}